@@ -0,0 +1,28 @@
+use axum::{extract::State, response::IntoResponse, Json};
+
+use crate::auth::middleware::RequireAdmin;
+use crate::error::AppError;
+use crate::AppState;
+
+/* ============================================================================================== */
+/*                                            Handlers                                            */
+/* ============================================================================================== */
+
+/// GET /api/admin/reconcile — reports drift between the portal DB, NocoDB and Grafana (admin only).
+pub async fn reconcile_report(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Result<impl IntoResponse, AppError> {
+    let report = state.orchestrator.reconcile_report().await?;
+    Ok(Json(report))
+}
+
+/* ============================================================================================== */
+/// POST /api/admin/reconcile — repairs or removes the orphans found by the report (admin only).
+pub async fn reconcile_repair(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+) -> Result<impl IntoResponse, AppError> {
+    let outcome = state.orchestrator.reconcile_repair().await?;
+    Ok(Json(outcome))
+}
\ No newline at end of file
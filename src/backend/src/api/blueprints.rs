@@ -0,0 +1,194 @@
+use axum::{
+    body::Bytes,
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor};
+use crate::auth::permissions::TemplatePermission;
+use crate::db::repos::blueprint_repo::CreateBlueprint;
+use crate::error::AppError;
+use crate::orchestrator::blueprint::{builtin_blueprint, builtin_blueprints, Blueprint, BlueprintEncoding};
+use crate::orchestrator::CreateTemplateInput;
+use crate::AppState;
+
+use super::records::file_stem;
+use super::templates::{authorize_template, validate_fields};
+
+/* ============================================================================================== */
+/*                                              DTOs                                              */
+/* ============================================================================================== */
+
+#[derive(Serialize)]
+pub struct BlueprintEntry {
+    /// Catalogue key of a built-in blueprint, or the UUID of a saved one.
+    pub id:          String,
+    pub builtin:     bool,
+    pub created_by:  Option<Uuid>,
+    pub created_at:  Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub blueprint:   Blueprint,
+}
+
+#[derive(Deserialize)]
+pub struct EncodingQuery {
+    #[serde(default)]
+    pub format: BlueprintEncoding,
+}
+
+/// Overrides for the new dataset, e.g. "Blood pressure (Sam)" for one family member.
+#[derive(Deserialize, Default)]
+pub struct InstantiateRequest {
+    pub name:        Option<String>,
+    pub description: Option<String>,
+}
+
+/* ============================================================================================== */
+/*                                            Handlers                                            */
+/* ============================================================================================== */
+
+/// GET /api/templates/blueprints — the built-in catalogue, then every saved version
+pub async fn list_blueprints(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let mut entries: Vec<BlueprintEntry> = builtin_blueprints().iter()
+        .map(|(key, blueprint)| BlueprintEntry {
+            id:         key.to_string(),
+            builtin:    true,
+            created_by: None,
+            created_at: None,
+            blueprint:  blueprint.clone(),
+        })
+        .collect();
+
+    for stored in state.blueprints.list_all().await? {
+        entries.push(BlueprintEntry {
+            id:         stored.id.to_string(),
+            builtin:    false,
+            created_by: stored.created_by,
+            created_at: Some(stored.created_at),
+            blueprint:  Blueprint::from_stored(&stored)?,
+        });
+    }
+    Ok(Json(entries))
+}
+
+/* ============================================================================================== */
+/// POST /api/templates/blueprints — save a JSON or YAML blueprint (by `Content-Type`) as the
+/// next version of its name
+pub async fn import_blueprint(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let blueprint = Blueprint::decode(&body, BlueprintEncoding::from_content_type(content_type))?;
+    validate_fields(&blueprint.fields)?;
+
+    let stored = state.blueprints.create(CreateBlueprint {
+        name:        blueprint.name.trim().to_string(),
+        description: blueprint.description,
+        fields:      serde_json::to_value(&blueprint.fields).map_err(|e| AppError::Internal(e.into()))?,
+        created_by:  user.user_id,
+    }).await?;
+
+    let entry = BlueprintEntry {
+        id:         stored.id.to_string(),
+        builtin:    false,
+        created_by: stored.created_by,
+        created_at: Some(stored.created_at),
+        blueprint:  Blueprint::from_stored(&stored)?,
+    };
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/* ============================================================================================== */
+/// GET /api/templates/blueprints/:id?format=json|yaml — download a blueprint
+pub async fn get_blueprint(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+    Path(id): Path<String>,
+    Query(query): Query<EncodingQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let blueprint = find_blueprint(&state, &id).await?;
+    download(&blueprint, query.format)
+}
+
+/* ============================================================================================== */
+/// GET /api/templates/:id/blueprint?format=json|yaml — an existing template as a blueprint
+pub async fn export_blueprint(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<EncodingQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Read).await?;
+    download(&Blueprint::from_template(&template)?, query.format)
+}
+
+/* ============================================================================================== */
+/// POST /api/templates/blueprints/:id/instantiate — provision a new dataset from a blueprint;
+/// poll `GET /api/templates/jobs/:id` like a regular template creation
+pub async fn instantiate_blueprint(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<String>,
+    req: Result<Json<InstantiateRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    // An empty body keeps the blueprint's own name and description.
+    let req = match req {
+        Ok(Json(req)) => req,
+        Err(JsonRejection::MissingJsonContentType(_)) => InstantiateRequest::default(),
+        Err(e) => return Err(e.into()),
+    };
+    if req.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err(AppError::Validation("name cannot be empty".into()));
+    }
+
+    let blueprint = find_blueprint(&state, &id).await?;
+    // Saved blueprints were validated on import, but validation may have tightened since.
+    validate_fields(&blueprint.fields)?;
+
+    let job = state.orchestrator.enqueue_provisioning(
+        CreateTemplateInput {
+            name:           req.name.unwrap_or(blueprint.name),
+            description:    req.description.or(blueprint.description),
+            fields:         blueprint.fields,
+        },
+        user.user_id,
+    ).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// A saved blueprint by UUID, else a built-in one by key.
+async fn find_blueprint(state: &AppState, id: &str) -> Result<Blueprint, AppError> {
+    if let Ok(uuid) = id.parse::<Uuid>() {
+        return Blueprint::from_stored(&state.blueprints.get_by_id(uuid).await?);
+    }
+    builtin_blueprint(id)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("blueprint '{id}' not found")))
+}
+
+fn download(blueprint: &Blueprint, encoding: BlueprintEncoding) -> Result<impl IntoResponse + use<>, AppError> {
+    let filename = format!("{}.blueprint.{}", file_stem(&blueprint.name), encoding.extension());
+    Ok((
+        [
+            (header::CONTENT_TYPE, encoding.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        blueprint.encode(encoding)?,
+    ))
+}
@@ -1,23 +1,27 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use uuid::Uuid;
 
-use crate::auth::middleware::{AuthenticatedUser, Role};
-use crate::db::repos::dashboard_repo::{CreateDashboard, UpdateDashboard};
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor, Role};
+use crate::auth::permissions::{dashboard_permission, SharePermission};
+use crate::db::repos::dashboard_repo::{CreateDashboard, Dashboard, UpdateDashboard};
+use crate::db::repos::panel_repo::Panel;
 use crate::error::AppError;
 use crate::AppState;
 
+use super::trash::DeleteQuery;
+
 /* ============================================================================================== */
-/// GET /api/dashboards - lists dashboards owned by the caller or marked shared. 
+/// GET /api/dashboards - lists dashboards owned by the caller, marked shared or shared with them.
 pub async fn list_dashboards(
     State(state): State<AppState>,
     user: AuthenticatedUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let dashboards = state.dashboards.list_for_user(user.user_id).await?;
+    let dashboards = state.dashboards.list_for_user(user.user_id, &user.group_ids).await?;
     Ok(Json(dashboards))
 }
 
@@ -29,14 +33,7 @@ pub async fn get_dashboard(
     Path(slug): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let dashboard = state.dashboards.get_by_slug(&slug).await?;
-    
-    // Viewers can only access shared dashboards or their own.
-    if !dashboard.is_shared
-        && dashboard.owner_id != Some(user.user_id)
-        && user.role != Role::Admin
-    {
-        return Err(AppError::Forbidden);
-    }
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::View).await?;
 
     let panels = state.panels.list_for_dashboard(dashboard.id).await?;
     Ok(Json(serde_json::json!({ "dashboard": dashboard, "panels": panels })))
@@ -46,7 +43,7 @@ pub async fn get_dashboard(
 /// POST   /api/dashboards
 pub async fn create_dashboard(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Json(input): Json<CreateDashboard>,
 ) -> Result<impl IntoResponse, AppError> {
     if input.title.trim().is_empty() {
@@ -60,7 +57,7 @@ pub async fn create_dashboard(
 /// PUT    /api/dashboards/:id
 pub async fn update_dashboard(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(dasboard_id): Path<Uuid>,
     Json(input): Json<UpdateDashboard>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -71,15 +68,24 @@ pub async fn update_dashboard(
 }
 
 /* ============================================================================================== */
-/// DELETE /api/dashboards/:id
+/// DELETE /api/dashboards/:id — moves the dashboard and its panels to the trash;
+/// `?permanent=true` (admin) deletes them for good
 pub async fn delete_dashboard(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let dashboard = state.dashboards.get_by_id(id).await?;
     require_owner_or_admin(&dashboard.owner_id, &user)?;
-    state.dashboards.delete(id).await?;
+    if query.permanent {
+        if user.role != Role::Admin {
+            return Err(AppError::Forbidden);
+        }
+        state.dashboards.delete(id).await?;
+    } else {
+        state.dashboards.trash(id).await?;
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -87,6 +93,36 @@ pub async fn delete_dashboard(
 /*                                             Helpers                                            */
 /* ============================================================================================== */
 
+/// Fails with 403 unless the caller holds at least `needed` on the dashboard, through
+/// ownership, the `is_shared` flag or a share to them or one of their groups.
+pub(crate) async fn require_dashboard_permission(
+    state: &AppState,
+    dashboard: &Dashboard,
+    user: &AuthenticatedUser,
+    needed: SharePermission,
+) -> Result<(), AppError> {
+    let share = state.dashboard_shares.get_for_user(dashboard.id, user.user_id, &user.group_ids).await?
+        .iter()
+        .map(|s| SharePermission::try_from(s.permission.as_str()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .max();
+
+    match dashboard_permission(dashboard, share, user) {
+        Some(granted) if granted >= needed => Ok(()),
+        _ => Err(AppError::Forbidden),
+    }
+}
+
+/// The live panels of every dashboard the user can see.
+pub(crate) async fn viewable_panels(state: &AppState, user: &AuthenticatedUser) -> Result<Vec<Panel>, AppError> {
+    let ids: Vec<Uuid> = state.dashboards.list_for_user(user.user_id, &user.group_ids).await?
+        .iter()
+        .map(|d| d.id)
+        .collect();
+    state.panels.list_for_dashboards(&ids).await
+}
+
 pub(crate) fn require_owner_or_admin(
     owner_id: &Option<Uuid>,
     user: &AuthenticatedUser,
@@ -0,0 +1,197 @@
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireAdmin};
+use crate::db::repos::group_repo::{CreateGroup, UpdateGroup};
+use crate::error::AppError;
+use crate::AppState;
+
+/* ============================================================================================== */
+/// GET    /api/groups — every group; open to all users so they can share with groups.
+pub async fn list_groups(
+    State(state): State<AppState>,
+    _user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let groups = state.groups.list().await?;
+    Ok(Json(groups))
+}
+
+/* ============================================================================================== */
+/// POST   /api/groups — creates a group (admin only).
+pub async fn create_group(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    input: Result<Json<CreateGroup>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(mut input) = input?;
+    input.name = input.name.trim().to_string();
+    if input.name.is_empty() {
+        return Err(AppError::Validation("name is required".into()));
+    }
+    let group = state.groups.create(input).await?;
+    Ok((StatusCode::CREATED, Json(group)))
+}
+
+/* ============================================================================================== */
+/// GET    /api/groups/:id — a group with its members (admin only).
+pub async fn get_group(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let group = state.groups.get_by_id(id).await?;
+    let members = state.groups.list_members(id).await?;
+    Ok(Json(serde_json::json!({ "group": group, "members": members })))
+}
+
+/* ============================================================================================== */
+/// PUT    /api/groups/:id — renames a group or changes its description (admin only).
+pub async fn update_group(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+    input: Result<Json<UpdateGroup>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(mut input) = input?;
+    input.name = input.name.map(|n| n.trim().to_string());
+    if input.name.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::Validation("name cannot be empty".into()));
+    }
+    let group = state.groups.update(id, input).await?;
+    Ok(Json(group))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/groups/:id — deletes a group and every grant made to it (admin only).
+pub async fn delete_group(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    state.groups.delete(id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/// PUT    /api/groups/:id/members/:user_id — adds a user to a group (admin only).
+///
+/// Membership is read when a session is validated, so it applies from the user's next request.
+pub async fn add_member(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    state.groups.add_member(id, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/// DELETE /api/groups/:id/members/:user_id — removes a user from a group (admin only).
+pub async fn remove_member(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    state.groups.remove_member(id, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::api::tests::{send, signed_in, state_on, template, user};
+    use crate::auth::middleware::Role;
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn group_grants_reach_members_until_they_leave(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let admin = user(&pool, "root", Role::Admin).await;
+        let alice = user(&pool, "alice", Role::Editor).await;
+        let bob = user(&pool, "bob", Role::Editor).await;
+
+        let (_, group) = send(&state, &admin, "POST", "/api/groups", json!({ "name": "family" })).await;
+        let group_id = group["id"].as_str().unwrap();
+        let membership = format!("/api/groups/{group_id}/members/{}", bob.user_id);
+
+        let template = template(&state, &alice, "weight").await;
+        let template_path = format!("/api/templates/{}", template.id);
+        let grant = format!("/api/templates/{}/acl/groups/{group_id}", template.id);
+        assert_eq!(send(&state, &alice, "PUT", &grant, json!({ "permission": "read" })).await.0, StatusCode::OK);
+        let (_, dashboard) = send(&state, &alice, "POST", "/api/dashboards", json!({ "title": "Health" })).await;
+        let shares = format!("/api/dashboards/{}/shares", dashboard["id"].as_str().unwrap());
+        let share = json!([{ "group_id": group_id, "permission": "view" }]);
+        assert_eq!(send(&state, &alice, "PUT", &shares, share).await.0, StatusCode::OK);
+
+        let reach = |bob| {
+            let (state, template_path) = (&state, &template_path);
+            async move {
+                let template = send(state, &bob, "GET", template_path, json!({})).await.0;
+                let dashboard = send(state, &bob, "GET", "/api/dashboards/health", json!({})).await.0;
+                (template, dashboard)
+            }
+        };
+        assert_eq!(reach(signed_in(&pool, &bob).await).await, (StatusCode::NOT_FOUND, StatusCode::FORBIDDEN));
+
+        assert_eq!(send(&state, &admin, "PUT", &membership, json!({})).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(reach(signed_in(&pool, &bob).await).await, (StatusCode::OK, StatusCode::OK));
+
+        assert_eq!(send(&state, &admin, "DELETE", &membership, json!({})).await.0, StatusCode::NO_CONTENT);
+        assert_eq!(reach(signed_in(&pool, &bob).await).await, (StatusCode::NOT_FOUND, StatusCode::FORBIDDEN));
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn user_and_group_grants_combine_to_the_strongest(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let admin = user(&pool, "root", Role::Admin).await;
+        let alice = user(&pool, "alice", Role::Editor).await;
+        let bob = user(&pool, "bob", Role::Editor).await;
+
+        let (_, group) = send(&state, &admin, "POST", "/api/groups", json!({ "name": "family" })).await;
+        let group_id = group["id"].as_str().unwrap();
+        let membership = format!("/api/groups/{group_id}/members/{}", bob.user_id);
+        assert_eq!(send(&state, &admin, "PUT", &membership, json!({})).await.0, StatusCode::NO_CONTENT);
+        let bob = signed_in(&pool, &bob).await;
+
+        // Listing a template's grants takes `manage`, whichever grant carries it.
+        for (as_user, as_group) in [("read", "manage"), ("manage", "read")] {
+            let template = template(&state, &alice, &format!("{as_user}-{as_group}")).await;
+            let to_bob = format!("/api/templates/{}/acl/{}", template.id, bob.user_id);
+            let to_group = format!("/api/templates/{}/acl/groups/{group_id}", template.id);
+            assert_eq!(send(&state, &alice, "PUT", &to_bob, json!({ "permission": as_user })).await.0, StatusCode::OK);
+            assert_eq!(send(&state, &alice, "PUT", &to_group, json!({ "permission": as_group })).await.0, StatusCode::OK);
+
+            let grants = format!("/api/templates/{}/acl", template.id);
+            assert_eq!(send(&state, &bob, "GET", &grants, json!({})).await.0, StatusCode::OK, "user {as_user}, group {as_group}");
+        }
+
+        // Adding a panel takes `edit`, whichever share carries it.
+        for (as_user, as_group) in [("view", "edit"), ("edit", "view")] {
+            let title = format!("{as_user} {as_group}");
+            let (_, dashboard) = send(&state, &alice, "POST", "/api/dashboards", json!({ "title": title })).await;
+            let id = dashboard["id"].as_str().unwrap();
+            let shares = json!([
+                { "user_id": bob.user_id, "permission": as_user },
+                { "group_id": group_id, "permission": as_group },
+            ]);
+            assert_eq!(send(&state, &alice, "PUT", &format!("/api/dashboards/{id}/shares"), shares).await.0, StatusCode::OK);
+
+            let panel = json!({ "panel_type": "markdown", "grid_x": 0, "grid_y": 0 });
+            let status = send(&state, &bob, "POST", &format!("/api/dashboards/{id}/panels"), panel).await.0;
+            assert_eq!(status, StatusCode::CREATED, "user {as_user}, group {as_group}");
+        }
+    }
+}
@@ -1,12 +1,20 @@
-mod dashboards;
+mod admin;
+mod blueprints;
+pub(crate) mod dashboards;
+mod groups;
 mod panels;
-mod templates;
+pub(crate) mod public_links;
+mod records;
+mod sharing;
+pub(crate) mod templates;
+mod trash;
 mod users;
 
 use axum::{
+    extract::DefaultBodyLimit,
     http::Request,
     response::Response,
-    routing::{get, put},
+    routing::{get, patch, put},
     Json, Router,
 };
 use serde_json::{json, Value};
@@ -16,6 +24,9 @@ use tracing::{Level, Span};
 
 use crate::AppState;
 
+/// CSV imports carry years of history; everything else keeps Axum's 2 MB default.
+const IMPORT_BODY_LIMIT: usize = 32 * 1024 * 1024;
+
 pub fn router(state: AppState) -> Router {
     Router::new()
         .route("/api/health", axum::routing::get(health))
@@ -23,7 +34,12 @@ pub fn router(state: AppState) -> Router {
         .nest("/api/dashboards", dashboard_routes())
         .nest("/api/panels", panel_routes())
         .nest("/api/users", user_routes())
+        .nest("/api/groups", group_routes())
         .nest("/api/templates", template_routes())
+        .nest("/api/trash", trash_routes())
+        .nest("/api/admin", admin_routes())
+        // Unauthenticated: the token is the credential.
+        .route("/api/public/{token}", get(crate::api::public_links::get_public_dashboard))
         .merge(crate::proxy::router())
         .fallback(crate::spa::spa_handler)
         .layer(
@@ -65,14 +81,31 @@ pub fn router(state: AppState) -> Router {
 
 fn auth_routes() -> Router<AppState> {
     use axum::routing::post;
-    use crate::auth::handlers::{login, logout, me, register, change_password};
+    use crate::auth::handlers::{
+        change_password, create_api_token, list_api_tokens, list_sessions, login, login_totp, logout, me,
+        oidc_callback, oidc_login, oidc_status, register, revoke_api_token, revoke_other_sessions,
+        revoke_session, totp_disable, totp_enable, totp_recovery_codes, totp_setup, totp_status,
+    };
 
     Router::new()
         .route("/register", post(register))
         .route("/login", post(login))
+        .route("/login/totp", post(login_totp))
         .route("/logout", post(logout))
+        .route("/oidc", get(oidc_status))
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", get(oidc_callback))
         .route("/me", get(me))
         .route("/change-password", post(change_password))
+        .route("/sessions", get(list_sessions).delete(revoke_other_sessions))
+        .route("/sessions/{id}", axum::routing::delete(revoke_session))
+        .route("/tokens", get(list_api_tokens).post(create_api_token))
+        .route("/tokens/{id}", axum::routing::delete(revoke_api_token))
+        .route("/totp", get(totp_status))
+        .route("/totp/setup", post(totp_setup))
+        .route("/totp/enable", post(totp_enable))
+        .route("/totp/recovery-codes", post(totp_recovery_codes))
+        .route("/totp/disable", post(totp_disable))
 }
 
 /* ============================================================================================== */
@@ -80,6 +113,8 @@ fn dashboard_routes() -> Router<AppState> {
     use crate::api::{
         dashboards::{create_dashboard, delete_dashboard, get_dashboard, list_dashboards, update_dashboard},
         panels::{create_panel, list_panels},
+        public_links::{create_public_link, list_public_links, revoke_public_link},
+        sharing::{list_dashboard_shares, replace_dashboard_shares},
     };
 
     Router::new()
@@ -87,6 +122,9 @@ fn dashboard_routes() -> Router<AppState> {
         // GET uses slug, PUT/DELETE use UUID - both map to the same path segment.
         .route("/{id}", get(get_dashboard).put(update_dashboard).delete(delete_dashboard))
         .route("/{dashboard_id}/panels", get(list_panels).post(create_panel))
+        .route("/{id}/shares", get(list_dashboard_shares).put(replace_dashboard_shares))
+        .route("/{id}/public-links", get(list_public_links).post(create_public_link))
+        .route("/{id}/public-links/{link_id}", axum::routing::delete(revoke_public_link))
 }
 
 /* ============================================================================================== */
@@ -104,20 +142,89 @@ fn panel_routes() -> Router<AppState> {
 
 /* ============================================================================================== */
 fn user_routes() -> Router<AppState> {
-    use crate::api::users::{list_users, update_user_role, reset_user_password};
+    use crate::api::users::{
+        list_directory, list_users, reset_user_password, reset_user_totp, unlock_user, update_user_role,
+    };
 
     Router::new()
         .route("/", get(list_users))
+        // Literal segment - Axum routes this before the parameterised /{id} routes.
+        .route("/directory", get(list_directory))
         .route("/{id}/role", put(update_user_role))
         .route("/{id}/password", put(reset_user_password))
+        .route("/{id}/totp", axum::routing::delete(reset_user_totp))
+        .route("/{id}/lockout", axum::routing::delete(unlock_user))
+}
+
+/* ============================================================================================== */
+fn group_routes() -> Router<AppState> {
+    use crate::api::groups::{
+        add_member, create_group, delete_group, get_group, list_groups, remove_member, update_group,
+    };
+
+    Router::new()
+        .route("/", get(list_groups).post(create_group))
+        .route("/{id}", get(get_group).put(update_group).delete(delete_group))
+        .route("/{id}/members/{user_id}", put(add_member).delete(remove_member))
 }
 
 /* ============================================================================================== */
 fn template_routes() -> Router<AppState> {
-    use crate::api::templates::{create_template, delete_template, get_template, list_templates};
+    use axum::routing::post;
+    use crate::api::templates::{
+        clone_template, create_template, delete_template, get_job, get_template, list_templates,
+        update_template,
+    };
+    use crate::api::records::{
+        create_record, delete_record, export_records, import_csv, list_records, update_record,
+    };
+    use crate::api::blueprints::{
+        export_blueprint, get_blueprint, import_blueprint, instantiate_blueprint, list_blueprints,
+    };
+    use crate::api::sharing::{
+        grant_access, grant_group_access, list_grants, revoke_access, revoke_group_access, update_sharing,
+    };
+
     Router::new()
         .route("/", get(list_templates).post(create_template))
-        .route("/{id}", get(get_template).delete(delete_template))
+        // Literal segment - Axum routes this before the parameterised /{id} routes.
+        .route("/jobs/{id}", get(get_job))
+        .route("/blueprints", get(list_blueprints).post(import_blueprint))
+        .route("/blueprints/{id}", get(get_blueprint))
+        .route("/blueprints/{id}/instantiate", post(instantiate_blueprint))
+        .route("/{id}", get(get_template).put(update_template).delete(delete_template))
+        .route("/{id}/records", get(list_records).post(create_record))
+        .route("/{id}/records/{row_id}", patch(update_record).delete(delete_record))
+        .route("/{id}/export", get(export_records))
+        .route("/{id}/blueprint", get(export_blueprint))
+        .route("/{id}/clone", post(clone_template))
+        .route("/{id}/sharing", put(update_sharing))
+        .route("/{id}/acl", get(list_grants))
+        .route("/{id}/acl/{user_id}", put(grant_access).delete(revoke_access))
+        .route("/{id}/acl/groups/{group_id}", put(grant_group_access).delete(revoke_group_access))
+        .route(
+            "/{id}/import",
+            post(import_csv).layer(DefaultBodyLimit::max(IMPORT_BODY_LIMIT)),
+        )
+}
+
+/* ============================================================================================== */
+fn trash_routes() -> Router<AppState> {
+    use axum::routing::{delete, post};
+    use crate::api::trash::{list_trash, purge_item, restore_item};
+
+    Router::new()
+        .route("/", get(list_trash))
+        .route("/{kind}/{id}", delete(purge_item))
+        .route("/{kind}/{id}/restore", post(restore_item))
+}
+
+/* ============================================================================================== */
+fn admin_routes() -> Router<AppState> {
+    use crate::api::admin::{reconcile_repair, reconcile_report};
+
+    Router::new()
+        .route("/reconcile", get(reconcile_report).post(reconcile_repair))
 }
 
 /* ============================================================================================== */
@@ -126,4 +233,292 @@ fn template_routes() -> Router<AppState> {
 
 async fn health() -> Json<Value> {
     Json(json!({ "status": "ok" }))
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use axum::{body::Body, http::{Method, StatusCode}, Extension};
+    use sqlx::{postgres::PgPoolOptions, PgPool};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::auth::middleware::{AuthenticatedUser, Role};
+    use crate::config::AppConfig;
+    use crate::db::repos::{
+        blueprint_repo::PgBlueprintRepo, dashboard_repo::PgDashboardRepo,
+        dashboard_share_repo::PgDashboardShareRepo, group_repo::PgGroupRepo, job_repo::PgJobRepo,
+        panel_repo::PgPanelRepo, public_link_repo::PgPublicLinkRepo,
+        template_acl_repo::PgTemplateAclRepo, template_repo::{CreateTemplateRecord, PgTemplateRepo, Template},
+        user_repo::PgUserRepo,
+    };
+    use crate::orchestrator::{grafana_client::GrafanaClient, nocodb_client::NocodbClient, Orchestrator};
+
+    const ID: &str = "00000000-0000-0000-0000-000000000001";
+
+    /// Every route behind authentication, with the least role allowed through the role check.
+    /// Ownership and per-template permissions are checked later and are not covered here.
+    const MATRIX: &[(&str, &str, Role)] = &[
+        ("GET",    "/api/auth/sessions",                    Role::Viewer),
+        ("DELETE", "/api/auth/sessions",                    Role::Viewer),
+        ("DELETE", "/api/auth/sessions/{id}",               Role::Viewer),
+        ("GET",    "/api/auth/tokens",                      Role::Viewer),
+        ("POST",   "/api/auth/tokens",                      Role::Viewer),
+        ("DELETE", "/api/auth/tokens/{id}",                 Role::Viewer),
+        ("GET",    "/api/auth/totp",                        Role::Viewer),
+        ("POST",   "/api/auth/totp/setup",                  Role::Viewer),
+        ("POST",   "/api/auth/totp/enable",                 Role::Viewer),
+        ("POST",   "/api/auth/totp/recovery-codes",         Role::Viewer),
+        ("POST",   "/api/auth/totp/disable",                Role::Viewer),
+        ("GET",    "/api/dashboards",                       Role::Viewer),
+        ("POST",   "/api/dashboards",                       Role::Editor),
+        ("GET",    "/api/dashboards/home",                  Role::Viewer),
+        ("PUT",    "/api/dashboards/{id}",                  Role::Editor),
+        ("DELETE", "/api/dashboards/{id}",                  Role::Editor),
+        ("GET",    "/api/dashboards/{id}/panels",           Role::Viewer),
+        ("POST",   "/api/dashboards/{id}/panels",           Role::Editor),
+        ("GET",    "/api/dashboards/{id}/shares",           Role::Viewer),
+        ("PUT",    "/api/dashboards/{id}/shares",           Role::Editor),
+        ("GET",    "/api/dashboards/{id}/public-links",     Role::Viewer),
+        ("POST",   "/api/dashboards/{id}/public-links",     Role::Editor),
+        ("DELETE", "/api/dashboards/{id}/public-links/{id}", Role::Editor),
+        ("PUT",    "/api/panels/batch-position",            Role::Editor),
+        ("PUT",    "/api/panels/{id}",                      Role::Editor),
+        ("DELETE", "/api/panels/{id}",                      Role::Editor),
+        ("PUT",    "/api/panels/{id}/position",             Role::Editor),
+        ("GET",    "/api/templates",                        Role::Viewer),
+        ("POST",   "/api/templates",                        Role::Editor),
+        ("GET",    "/api/templates/jobs/{id}",              Role::Viewer),
+        ("GET",    "/api/templates/blueprints",             Role::Viewer),
+        ("POST",   "/api/templates/blueprints",             Role::Editor),
+        ("GET",    "/api/templates/blueprints/weight",      Role::Viewer),
+        ("POST",   "/api/templates/blueprints/weight/instantiate", Role::Editor),
+        ("GET",    "/api/templates/{id}",                   Role::Viewer),
+        ("PUT",    "/api/templates/{id}",                   Role::Editor),
+        ("DELETE", "/api/templates/{id}",                   Role::Editor),
+        ("GET",    "/api/templates/{id}/records",           Role::Viewer),
+        ("POST",   "/api/templates/{id}/records",           Role::Editor),
+        ("PATCH",  "/api/templates/{id}/records/1",         Role::Editor),
+        ("DELETE", "/api/templates/{id}/records/1",         Role::Editor),
+        ("GET",    "/api/templates/{id}/export",            Role::Viewer),
+        ("GET",    "/api/templates/{id}/blueprint",         Role::Viewer),
+        ("POST",   "/api/templates/{id}/clone",             Role::Editor),
+        ("PUT",    "/api/templates/{id}/sharing",           Role::Editor),
+        ("GET",    "/api/templates/{id}/acl",               Role::Editor),
+        ("PUT",    "/api/templates/{id}/acl/{id}",          Role::Editor),
+        ("DELETE", "/api/templates/{id}/acl/{id}",          Role::Editor),
+        ("PUT",    "/api/templates/{id}/acl/groups/{id}",   Role::Editor),
+        ("DELETE", "/api/templates/{id}/acl/groups/{id}",   Role::Editor),
+        ("POST",   "/api/templates/{id}/import",            Role::Editor),
+        ("GET",    "/api/trash",                            Role::Viewer),
+        ("POST",   "/api/trash/templates/{id}/restore",     Role::Editor),
+        ("DELETE", "/api/trash/templates/{id}",             Role::Admin),
+        ("GET",    "/api/users",                            Role::Admin),
+        ("GET",    "/api/users/directory",                  Role::Editor),
+        ("GET",    "/api/groups",                           Role::Viewer),
+        ("POST",   "/api/groups",                           Role::Admin),
+        ("GET",    "/api/groups/{id}",                      Role::Admin),
+        ("PUT",    "/api/groups/{id}",                      Role::Admin),
+        ("DELETE", "/api/groups/{id}",                      Role::Admin),
+        ("PUT",    "/api/groups/{id}/members/{id}",         Role::Admin),
+        ("DELETE", "/api/groups/{id}/members/{id}",         Role::Admin),
+        ("PUT",    "/api/users/{id}/role",                  Role::Admin),
+        ("PUT",    "/api/users/{id}/password",              Role::Admin),
+        ("DELETE", "/api/users/{id}/totp",                  Role::Admin),
+        ("DELETE", "/api/users/{id}/lockout",               Role::Admin),
+        ("GET",    "/api/admin/reconcile",                  Role::Admin),
+        ("POST",   "/api/admin/reconcile",                  Role::Admin),
+    ];
+
+    /// State whose database and upstreams are unreachable: any request that gets past the role
+    /// check fails with something other than 403.
+    fn state() -> AppState {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(200))
+            .connect_lazy("postgres://nobody@127.0.0.1:1/none")
+            .unwrap();
+        state_on(pool)
+    }
+
+    /// State on `pool`, with unreachable upstreams.
+    pub(crate) fn state_on(pool: PgPool) -> AppState {
+        let http_client = reqwest::Client::new();
+        let config = AppConfig {
+            database_url:                   String::new(),
+            grafana_internal_url:           "http://127.0.0.1:1".into(),
+            grafana_service_account_token:  String::new(),
+            grafana_datasource_uid:         String::new(),
+            nocodb_internal_url:            "http://127.0.0.1:1".into(),
+            nocodb_api_token:               String::new(),
+            session_secret:                 String::new(),
+            session_ttl_hours:              1,
+            session_max_days:               1,
+            trash_retention_days:           30,
+            bind_address:                   String::new(),
+            trusted_proxies:                Vec::new(),
+            oidc:                           None,
+        };
+        let orchestrator = Arc::new(Orchestrator {
+            nocodb:     NocodbClient::new(http_client.clone(), config.nocodb_internal_url.clone(), String::new()),
+            grafana:    GrafanaClient::new(
+                http_client.clone(),
+                config.grafana_internal_url.clone(),
+                String::new(),
+                String::new(),
+            ),
+            pool:       pool.clone(),
+            job_signal: tokio::sync::Notify::new(),
+        });
+
+        AppState {
+            pool:             pool.clone(),
+            config,
+            http_client,
+            dashboards:       Arc::new(PgDashboardRepo      { pool: pool.clone() }),
+            dashboard_shares: Arc::new(PgDashboardShareRepo { pool: pool.clone() }),
+            groups:           Arc::new(PgGroupRepo          { pool: pool.clone() }),
+            panels:           Arc::new(PgPanelRepo          { pool: pool.clone() }),
+            public_links:     Arc::new(PgPublicLinkRepo     { pool: pool.clone() }),
+            users:            Arc::new(PgUserRepo           { pool: pool.clone() }),
+            templates:        Arc::new(PgTemplateRepo       { pool: pool.clone() }),
+            template_acl:     Arc::new(PgTemplateAclRepo    { pool: pool.clone() }),
+            jobs:             Arc::new(PgJobRepo            { pool: pool.clone() }),
+            blueprints:       Arc::new(PgBlueprintRepo      { pool: pool.clone() }),
+            orchestrator,
+        }
+    }
+
+    /// A new account, in no group yet; see [`signed_in`].
+    pub(crate) async fn user(pool: &PgPool, username: &str, role: Role) -> AuthenticatedUser {
+        let user_id = sqlx::query_scalar(
+            "INSERT INTO portal.users (username, password_hash, role) VALUES ($1, '', $2) RETURNING id",
+        )
+        .bind(username)
+        .bind(role.to_string())
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        AuthenticatedUser { user_id, username: username.into(), role, group_ids: vec![] }
+    }
+
+    /// `user` as their next request sees them, with the groups they are in now.
+    pub(crate) async fn signed_in(pool: &PgPool, user: &AuthenticatedUser) -> AuthenticatedUser {
+        let group_ids = sqlx::query_scalar("SELECT group_id FROM portal.group_members WHERE user_id = $1")
+            .bind(user.user_id)
+            .fetch_all(pool)
+            .await
+            .unwrap();
+        AuthenticatedUser { group_ids, ..user.clone() }
+    }
+
+    /// A private template owned by `owner`, whose NocoDB form and Grafana dashboard are
+    /// `f-{name}` and `g-{name}`.
+    pub(crate) async fn template(state: &AppState, owner: &AuthenticatedUser, name: &str) -> Template {
+        state.templates.create(CreateTemplateRecord {
+            id:                    None,
+            name:                  name.into(),
+            description:           None,
+            fields:                json!([]),
+            created_by:            owner.user_id,
+            nocodb_table_id:       Some(format!("t-{name}")),
+            nocodb_form_id:        Some(format!("f-{name}")),
+            grafana_dashboard_uid: Some(format!("g-{name}")),
+        }).await.unwrap()
+    }
+
+    /// Send `body` as `user` and return the status and JSON response, if any.
+    pub(crate) async fn send(
+        state: &AppState,
+        user: &AuthenticatedUser,
+        method: &str,
+        path: &str,
+        body: Value,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(Method::from_bytes(method.as_bytes()).unwrap())
+            .uri(path)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(state.clone())
+            .layer(Extension(user.clone()))
+            .oneshot(request)
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    async fn status(role: Role, method: &str, path: &str) -> StatusCode {
+        let user = AuthenticatedUser {
+            user_id:   Uuid::new_v4(),
+            username:  "someone".into(),
+            role,
+            group_ids: vec![],
+        };
+        let request = Request::builder()
+            .method(Method::from_bytes(method.as_bytes()).unwrap())
+            .uri(path.replace("{id}", ID))
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        router(state())
+            .layer(Extension(user))
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn roles_reach_only_their_routes() {
+        let cases: Vec<_> = MATRIX.iter()
+            .flat_map(|case| [Role::Viewer, Role::Editor, Role::Admin].map(|role| (case, role)))
+            .collect();
+        // Requests that pass the role check wait for the pool to time out, so run them together.
+        let statuses = futures::future::join_all(
+            cases.iter().map(|((method, path, _), role)| status(role.clone(), method, path)),
+        ).await;
+
+        for (((method, path, least), role), got) in cases.iter().zip(statuses) {
+            if !role.at_least(least) {
+                assert_eq!(got, StatusCode::FORBIDDEN, "{role} {method} {path}");
+            } else {
+                assert_ne!(got, StatusCode::FORBIDDEN, "{role} {method} {path}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn viewers_cannot_list_the_user_directory() {
+        assert_eq!(status(Role::Viewer, "GET", "/api/users/directory").await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn anonymous_requests_are_rejected() {
+        let request = Request::builder().uri("/api/dashboards").body(Body::empty()).unwrap();
+        let response = router(state()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn forged_public_tokens_are_not_found() {
+        let request = Request::builder().uri("/api/public/garbage").body(Body::empty()).unwrap();
+        let response = router(state()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn grafana_proxy_requires_a_session_or_public_link() {
+        let request = Request::builder().uri("/proxy/grafana/d/abc").body(Body::empty()).unwrap();
+        let response = router(state()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -1,17 +1,25 @@
+use std::collections::HashSet;
+
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 use uuid::Uuid;
 
-use crate::auth::middleware::AuthenticatedUser;
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor, Role};
+use crate::auth::permissions::SharePermission;
 use crate::db::repos::panel_repo::{BatchPositionUpdate, CreatePanel, GridPosition, UpdatePanel};
 use crate::error::AppError;
 use crate::AppState;
 
-use super::dashboards::require_owner_or_admin;
+use crate::orchestrator::reconcile::grafana_uid;
+use crate::proxy::nocodb::shared_view_id;
+
+use super::dashboards::require_dashboard_permission;
+use super::templates::readable_templates;
+use super::trash::DeleteQuery;
 
 /* ============================================================================================== */
 /*                   Handlers mounted under /api/dashboards/:dashboard_id/panels                  */
@@ -24,13 +32,7 @@ pub async fn list_panels(
     Path(dashboard_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
     let dashboard = state.dashboards.get_by_id(dashboard_id).await?;
-    // Read access: shared dashboards are visible to all; private ones require ownership/admin.
-    if !dashboard.is_shared
-        && dashboard.owner_id != Some(user.user_id)
-        && user.role != crate::auth::middleware::Role::Admin
-    {
-        return Err(AppError::Forbidden);
-    }
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::View).await?;
     let panels = state.panels.list_for_dashboard(dashboard_id).await?;
     Ok(Json(panels))
 }
@@ -39,7 +41,7 @@ pub async fn list_panels(
 ///POST   /api/dashboards/:id/panels
 pub async fn create_panel(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(dashboard_id): Path<Uuid>,
     Json(input): Json<CreatePanel>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -47,7 +49,8 @@ pub async fn create_panel(
         return Err(AppError::Validation("panel_type is required".into()));
     }
     let dashboard = state.dashboards.get_by_id(dashboard_id).await?;
-    require_owner_or_admin(&dashboard.owner_id, &user)?;
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
+    require_source_access(&state, &user, input.source_url.as_deref()).await?;
     let panel = state.panels.create(dashboard_id, input).await?;
     Ok((StatusCode::CREATED, Json(panel)))
 }
@@ -59,12 +62,21 @@ pub async fn create_panel(
 /// PUT    /api/panels/batch-position — atomically update grid positions for multiple panels.
 pub async fn batch_update_positions(
     State(state): State<AppState>,
-    _user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Json(updates): Json<Vec<BatchPositionUpdate>>,
 ) -> Result<impl IntoResponse, AppError> {
     if updates.is_empty() {
         return Ok(StatusCode::NO_CONTENT);
     }
+    // Every panel must sit on a dashboard the caller may edit.
+    let mut checked = HashSet::new();
+    for update in &updates {
+        let panel = state.panels.get_by_id(update.id).await?;
+        if checked.insert(panel.dashboard_id) {
+            let dashboard = state.dashboards.get_by_id(panel.dashboard_id).await?;
+            require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
+        }
+    }
     state.panels.batch_update_positions(updates).await?;
     Ok(StatusCode::NO_CONTENT)
 }
@@ -73,13 +85,14 @@ pub async fn batch_update_positions(
 /// PUT    /api/panels/:id — updates panel metadata (title, type, source_url, config).
 pub async fn update_panel(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(panel_id): Path<Uuid>,
     Json(input): Json<UpdatePanel>,
 ) -> Result<impl IntoResponse, AppError> {
     let panel = state.panels.get_by_id(panel_id).await?;
     let dashboard = state.dashboards.get_by_id(panel.dashboard_id).await?;
-    require_owner_or_admin(&dashboard.owner_id, &user)?;
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
+    require_source_access(&state, &user, input.source_url.as_deref()).await?;
     let updated = state.panels.update(panel_id, input).await?;
     Ok(Json(updated))
 }
@@ -88,27 +101,117 @@ pub async fn update_panel(
 /// PUT    /api/panels/:id/position — updates a single panel's grid position.
 pub async fn update_position(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(panel_id): Path<Uuid>,
     Json(pos): Json<GridPosition>,
 ) -> Result<impl IntoResponse, AppError> {
     let panel = state.panels.get_by_id(panel_id).await?;
     let dashboard = state.dashboards.get_by_id(panel.dashboard_id).await?;
-    require_owner_or_admin(&dashboard.owner_id, &user)?;
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
     let updated = state.panels.update_position(panel_id, pos).await?;
     Ok(Json(updated))
 }
 
 /* ============================================================================================== */
-/// DELETE /api/panels/:id
+/// DELETE /api/panels/:id — moves the panel to the trash; `?permanent=true` (admin) deletes it
+/// for good
 pub async fn delete_panel(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
+    RequireEditor(user): RequireEditor,
     Path(panel_id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     let panel = state.panels.get_by_id(panel_id).await?;
     let dashboard = state.dashboards.get_by_id(panel.dashboard_id).await?;
-    require_owner_or_admin(&dashboard.owner_id, &user)?;
-    state.panels.delete(panel_id).await?;
+    require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
+    if query.permanent {
+        if user.role != Role::Admin {
+            return Err(AppError::Forbidden);
+        }
+        state.panels.delete(panel_id).await?;
+    } else {
+        state.panels.trash(panel_id).await?;
+    }
     Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Whoever can see a panel reaches the Grafana dashboard or NocoDB form it embeds through the
+/// proxies, so non-admins may only embed those of templates they can read themselves.
+async fn require_source_access(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    source_url: Option<&str>,
+) -> Result<(), AppError> {
+    let Some(url) = source_url else { return Ok(()) };
+    let (dashboard_uid, form_id) = (grafana_uid(url), shared_view_id(url));
+    if user.role == Role::Admin || (dashboard_uid.is_none() && form_id.is_none()) {
+        return Ok(());
+    }
+
+    let embeds_readable = readable_templates(state, user).await?.iter().any(|(template, _)| {
+        dashboard_uid.is_some_and(|uid| template.grafana_dashboard_uid.as_deref() == Some(uid))
+            || form_id.is_some_and(|id| template.nocodb_form_id.as_deref() == Some(id))
+    });
+    if embeds_readable { Ok(()) } else { Err(AppError::Forbidden) }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::api::tests::{send, state_on, template, user};
+    use crate::db::repos::{dashboard_repo::CreateDashboard, group_repo::Grantee};
+
+    const PRIVATE_CHARTS: &str = "/proxy/grafana/d/g-private/weight?kiosk";
+    const PRIVATE_FORM: &str = "/proxy/nocodb/dashboard/#/nc/form/f-private";
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn editors_embed_only_what_they_can_read(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let alice = user(&pool, "alice", Role::Editor).await;
+        let bob = user(&pool, "bob", Role::Editor).await;
+        let admin = user(&pool, "root", Role::Admin).await;
+
+        let template = template(&state, &alice, "private").await;
+        let dashboard = state.dashboards.create(bob.user_id, CreateDashboard {
+            title:      "Bob's".into(),
+            slug:       None,
+            icon:       None,
+            sort_order: None,
+            is_shared:  None,
+        }).await.unwrap();
+        let panels = format!("/api/dashboards/{}/panels", dashboard.id);
+        let panel = |url: &str| json!({ "panel_type": "grafana_dashboard", "source_url": url, "grid_x": 0, "grid_y": 0 });
+
+        // Alice's template is private, so Bob may not point his panels at it...
+        assert_eq!(send(&state, &bob, "POST", &panels, panel(PRIVATE_CHARTS)).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(send(&state, &bob, "POST", &panels, panel(PRIVATE_FORM)).await.0, StatusCode::FORBIDDEN);
+        let (status, created) = send(&state, &bob, "POST", &panels, panel("https://example.com/embed")).await;
+        assert_eq!(status, StatusCode::CREATED);
+        let update = format!("/api/panels/{}", created["id"].as_str().unwrap());
+        assert_eq!(
+            send(&state, &bob, "PUT", &update, json!({ "source_url": PRIVATE_CHARTS })).await.0,
+            StatusCode::FORBIDDEN
+        );
+
+        // ...until she lets him read it.
+        state.template_acl.upsert(template.id, Grantee::User(bob.user_id), "read").await.unwrap();
+        assert_eq!(send(&state, &bob, "POST", &panels, panel(PRIVATE_FORM)).await.0, StatusCode::CREATED);
+        assert_eq!(send(&state, &bob, "PUT", &update, json!({ "source_url": PRIVATE_CHARTS })).await.0, StatusCode::OK);
+
+        // Admins reach everything through the proxies anyway.
+        assert_eq!(send(&state, &admin, "POST", &panels, panel("/proxy/grafana/d/hand-made")).await.0, StatusCode::CREATED);
+    }
+}
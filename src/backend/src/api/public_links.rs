@@ -0,0 +1,180 @@
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor};
+use crate::auth::public_link::{self, PUBLIC_COOKIE};
+use crate::db::repos::{Dashboard, PublicLink};
+use crate::error::AppError;
+use crate::AppState;
+
+use super::dashboards::require_owner_or_admin;
+
+/// A week, unless the caller asks for something else.
+const DEFAULT_EXPIRY_HOURS: i64 = 7 * 24;
+const MAX_EXPIRY_HOURS:     i64 = 365 * 24;
+
+/* ============================================================================================== */
+/*                                              DTOs                                              */
+/* ============================================================================================== */
+
+#[derive(Deserialize)]
+pub struct CreatePublicLinkRequest {
+    #[serde(default)]
+    pub label:            String,
+    pub expires_in_hours: Option<i64>,
+}
+
+/// A link as its dashboard's owner sees it, with the token to hand out.
+#[derive(Serialize)]
+pub struct PublicLinkResponse {
+    #[serde(flatten)]
+    pub link:   PublicLink,
+    pub active: bool,
+    pub token:  String,
+    /// Path of the read-only page, relative to the portal origin.
+    pub url:    String,
+}
+
+impl PublicLinkResponse {
+    fn new(link: PublicLink, secret: &str) -> Self {
+        let token = public_link::sign(secret, link.id);
+        Self {
+            active: link.is_active(),
+            url:    format!("/public/{token}"),
+            token,
+            link,
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                          Handlers mounted under /api/dashboards/:id                            */
+/* ============================================================================================== */
+
+/// GET /api/dashboards/:id/public-links — every link of the dashboard, revoked and expired included
+pub async fn list_public_links(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let dashboard = state.dashboards.get_by_id(id).await?;
+    require_owner_or_admin(&dashboard.owner_id, &user)?;
+
+    let links = state.public_links.list_for_dashboard(id).await?;
+    let secret = &state.config.session_secret;
+    Ok(Json(links.into_iter().map(|l| PublicLinkResponse::new(l, secret)).collect::<Vec<_>>()))
+}
+
+/* ============================================================================================== */
+/// POST /api/dashboards/:id/public-links — `{label?, expires_in_hours?}`; links expire after a
+/// week by default and after a year at most
+pub async fn create_public_link(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    req: Result<Json<CreatePublicLinkRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    let dashboard = state.dashboards.get_by_id(id).await?;
+    require_owner_or_admin(&dashboard.owner_id, &user)?;
+
+    let hours = req.expires_in_hours.unwrap_or(DEFAULT_EXPIRY_HOURS);
+    if !(1..=MAX_EXPIRY_HOURS).contains(&hours) {
+        return Err(AppError::Validation(format!(
+            "expires_in_hours must be between 1 and {MAX_EXPIRY_HOURS}"
+        )));
+    }
+    let label = req.label.trim();
+    if label.chars().count() > 128 {
+        return Err(AppError::Validation("label must be at most 128 characters".into()));
+    }
+
+    let link = state.public_links
+        .create(id, user.user_id, label, Utc::now() + Duration::hours(hours))
+        .await?;
+    Ok((StatusCode::CREATED, Json(PublicLinkResponse::new(link, &state.config.session_secret))))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/dashboards/:id/public-links/:link_id — revokes the link; it stays listed
+pub async fn revoke_public_link(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, link_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let dashboard = state.dashboards.get_by_id(id).await?;
+    require_owner_or_admin(&dashboard.owner_id, &user)?;
+    state.public_links.revoke(id, link_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                 Unauthenticated public endpoint                                */
+/* ============================================================================================== */
+
+/// GET /api/public/:token — the dashboard and its panels, read-only, for anyone holding the token.
+///
+/// Also sets the `portal_public` cookie that lets the Grafana proxy serve the dashboard's
+/// embedded Grafana panels, and nothing else, until the link expires.
+pub async fn get_public_dashboard(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let (link, dashboard) = resolve_token(&state, &token).await?;
+    // NocoDB panels edit data and their proxy needs a session; the public page shows the rest.
+    let panels: Vec<_> = state.panels.list_for_dashboard(dashboard.id).await?
+        .into_iter()
+        .filter(|p| !p.panel_type.starts_with("nocodb_"))
+        .collect();
+
+    let max_age = (link.expires_at - Utc::now()).num_seconds().max(0);
+    let cookie = HeaderValue::from_str(&format!(
+        "{PUBLIC_COOKIE}={token}; HttpOnly; SameSite=Strict; Max-Age={max_age}; Path=/proxy/grafana"
+    ))
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("invalid cookie: {e}")))?;
+
+    Ok((
+        [(header::SET_COOKIE, cookie)],
+        Json(serde_json::json!({
+            "dashboard":  dashboard,
+            "panels":     panels,
+            "expires_at": link.expires_at,
+        })),
+    ))
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// The link and live dashboard behind a token. Forged, revoked and expired tokens, and links to
+/// trashed dashboards, all look the same: 404.
+pub(crate) async fn resolve_token(
+    state: &AppState,
+    token: &str,
+) -> Result<(PublicLink, Dashboard), AppError> {
+    let not_found = || AppError::NotFound("public link not found".into());
+
+    let id = public_link::verify(&state.config.session_secret, token).ok_or_else(not_found)?;
+    let link = state.public_links.get_by_id(id).await.map_err(|e| hide_missing(e, not_found))?;
+    if !link.is_active() {
+        return Err(not_found());
+    }
+    let dashboard = state.dashboards.get_by_id(link.dashboard_id).await
+        .map_err(|e| hide_missing(e, not_found))?;
+    Ok((link, dashboard))
+}
+
+fn hide_missing(e: AppError, not_found: impl Fn() -> AppError) -> AppError {
+    match e {
+        AppError::NotFound(_) => not_found(),
+        e => e,
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{FromRequest, Multipart, Path, Query, Request, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor};
+use crate::auth::permissions::TemplatePermission;
+use crate::error::AppError;
+use crate::orchestrator::{
+    export::{ExportFilter, ExportFormat},
+    import::ImportOptions,
+    records::RecordFilter,
+};
+use crate::AppState;
+
+use super::templates::authorize_template;
+
+/* ============================================================================================== */
+/*                                              DTOs                                              */
+/* ============================================================================================== */
+
+#[derive(Deserialize)]
+pub struct ListRecordsQuery {
+    pub limit:  Option<u32>,
+    pub offset: Option<u32>,
+    pub from:   Option<NaiveDate>,
+    pub to:     Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    pub from:   Option<NaiveDate>,
+    pub to:     Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+    /// JSON object of CSV header → field name.
+    pub mapping: Option<String>,
+}
+
+/* ============================================================================================== */
+/*                                            Handlers                                            */
+/* ============================================================================================== */
+
+/// GET /api/templates/:id/records?limit=&offset=&from=&to=
+pub async fn list_records(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ListRecordsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    check_range(query.from, query.to)?;
+
+    let template = authorize_template(&state, id, &user, TemplatePermission::Read).await?;
+    let records = state.orchestrator.list_records(&template, RecordFilter {
+        limit:  query.limit.unwrap_or(50),
+        offset: query.offset.unwrap_or(0),
+        from:   query.from,
+        to:     query.to,
+    }).await?;
+    Ok(Json(records))
+}
+
+/* ============================================================================================== */
+/// GET /api/templates/:id/export?format=csv|jsonl|parquet&from=&to=
+///
+/// Streams the whole dataset as a file download. Like listing records, this needs `read`.
+pub async fn export_records(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    check_range(query.from, query.to)?;
+
+    let template = authorize_template(&state, id, &user, TemplatePermission::Read).await?;
+    let stream = state.orchestrator.clone().export_records(&template, query.format, ExportFilter {
+        from: query.from,
+        to:   query.to,
+    })?;
+
+    let filename = format!("{}.{}", file_stem(&template.name), query.format.extension());
+    Ok((
+        [
+            (header::CONTENT_TYPE, query.format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{filename}\"")),
+        ],
+        Body::from_stream(stream),
+    ))
+}
+
+/* ============================================================================================== */
+/// POST /api/templates/:id/records
+pub async fn create_record(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    Json(values): Json<Map<String, Value>>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Enter).await?;
+    let record = state.orchestrator.create_record(&template, values).await?;
+    Ok((StatusCode::CREATED, Json(record)))
+}
+
+/* ============================================================================================== */
+/// PATCH /api/templates/:id/records/:row_id
+pub async fn update_record(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, row_id)): Path<(Uuid, i64)>,
+    Json(values): Json<Map<String, Value>>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Enter).await?;
+    let record = state.orchestrator.update_record(&template, row_id, values).await?;
+    Ok(Json(record))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/templates/:id/records/:row_id
+pub async fn delete_record(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, row_id)): Path<(Uuid, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Enter).await?;
+    state.orchestrator.delete_record(&template, row_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/// POST /api/templates/:id/import?dry_run=&mapping=
+///
+/// Accepts the CSV either as the raw request body or as the `file` part of a multipart form.
+/// A multipart form may also carry `mapping` (JSON) and `dry_run` parts, which take precedence
+/// over the query string. Responds 422 with the report when any row is invalid.
+pub async fn import_csv(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ImportQuery>,
+    request: Request,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Enter).await?;
+
+    let mut mapping = query.mapping;
+    let mut dry_run = query.dry_run;
+    let is_multipart = request.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("multipart/form-data"));
+
+    let csv = if is_multipart {
+        let mut multipart = Multipart::from_request(request, &state).await
+            .map_err(|e| AppError::Validation(format!("invalid multipart body: {e}")))?;
+        let mut file = None;
+        while let Some(part) = multipart.next_field().await
+            .map_err(|e| AppError::Validation(format!("invalid multipart body: {e}")))?
+        {
+            let name = part.name().unwrap_or_default().to_string();
+            let data = part.bytes().await
+                .map_err(|e| AppError::Validation(format!("could not read part '{name}': {e}")))?;
+            match name.as_str() {
+                "file"    => file = Some(data),
+                "mapping" => mapping = Some(String::from_utf8_lossy(&data).into_owned()),
+                "dry_run" => dry_run = matches!(data.as_ref(), b"true" | b"1" | b"on"),
+                _         => {}
+            }
+        }
+        file.ok_or_else(|| AppError::Validation("multipart body has no 'file' part".into()))?
+    } else {
+        Bytes::from_request(request, &state).await
+            .map_err(|e| AppError::Validation(format!("could not read body: {e}")))?
+    };
+
+    let mapping = mapping
+        .filter(|m| !m.trim().is_empty())
+        .map(|m| serde_json::from_str::<HashMap<String, String>>(&m))
+        .transpose()
+        .map_err(|e| AppError::Validation(format!("mapping must be a JSON object of strings: {e}")))?;
+
+    let report = state.orchestrator.import_csv(&template, &csv, ImportOptions { mapping, dry_run }).await?;
+    let status = if report.error_count > 0 { StatusCode::UNPROCESSABLE_ENTITY } else { StatusCode::OK };
+    Ok((status, Json(report)))
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+fn check_range(from: Option<NaiveDate>, to: Option<NaiveDate>) -> Result<(), AppError> {
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(AppError::Validation("'from' must not be after 'to'".into()));
+    }
+    Ok(())
+}
+
+/// Template name reduced to characters that are safe in a `Content-Disposition` filename.
+pub(super) fn file_stem(name: &str) -> String {
+    let stem: String = name.trim().chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if stem.is_empty() { "export".into() } else { stem }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor, Role};
+use crate::auth::permissions::{SharePermission, TemplatePermission, Visibility};
+use crate::db::repos::Grantee;
+use crate::error::AppError;
+use crate::AppState;
+
+use super::dashboards::require_owner_or_admin;
+use super::templates::authorize_template;
+
+/* ============================================================================================== */
+/*                                              DTOs                                              */
+/* ============================================================================================== */
+
+#[derive(Deserialize)]
+pub struct UpdateSharingRequest {
+    pub visibility: Option<Visibility>,
+    /// Hands the template over to another user. Only the current owner or an admin may.
+    pub owner_id:   Option<Uuid>,
+}
+
+#[derive(Deserialize)]
+pub struct GrantRequest {
+    pub permission: TemplatePermission,
+}
+
+/// Exactly one of `user_id` and `group_id`.
+#[derive(Deserialize)]
+pub struct DashboardShareRequest {
+    pub user_id:    Option<Uuid>,
+    pub group_id:   Option<Uuid>,
+    pub permission: SharePermission,
+}
+
+/* ============================================================================================== */
+/*                          Handlers mounted under /api/templates/:id                             */
+/* ============================================================================================== */
+
+/// PUT /api/templates/:id/sharing — change the visibility and/or owner
+pub async fn update_sharing(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    req: Result<Json<UpdateSharingRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    let template = authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    if req.owner_id.is_some() && template.owner_id != Some(user.user_id) && user.role != Role::Admin {
+        return Err(AppError::Forbidden);
+    }
+
+    let updated = state.templates.update_sharing(
+        id,
+        req.visibility.map(Visibility::as_str),
+        req.owner_id,
+    ).await?;
+    Ok(Json(updated))
+}
+
+/* ============================================================================================== */
+/// GET /api/templates/:id/acl — every per-user and per-group grant on the template
+pub async fn list_grants(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    let entries = state.template_acl.list_for_template(id).await?;
+    Ok(Json(entries))
+}
+
+/* ============================================================================================== */
+/// PUT /api/templates/:id/acl/:user_id — grant `read`, `enter` or `manage`, replacing any
+/// earlier grant to that user
+pub async fn grant_access(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    req: Result<Json<GrantRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    let entry = state.template_acl.upsert(id, Grantee::User(user_id), req.permission.as_str()).await?;
+    Ok(Json(entry))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/templates/:id/acl/:user_id
+pub async fn revoke_access(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    state.template_acl.delete(id, Grantee::User(user_id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/// PUT /api/templates/:id/acl/groups/:group_id — grant `read`, `enter` or `manage` to every
+/// member of a group, replacing any earlier grant to that group
+pub async fn grant_group_access(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, group_id)): Path<(Uuid, Uuid)>,
+    req: Result<Json<GrantRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    let entry = state.template_acl.upsert(id, Grantee::Group(group_id), req.permission.as_str()).await?;
+    Ok(Json(entry))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/templates/:id/acl/groups/:group_id
+pub async fn revoke_group_access(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((id, group_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    state.template_acl.delete(id, Grantee::Group(group_id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                          Handlers mounted under /api/dashboards/:id                            */
+/* ============================================================================================== */
+
+/// GET /api/dashboards/:id/shares — every per-user and per-group share of the dashboard
+pub async fn list_dashboard_shares(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let dashboard = state.dashboards.get_by_id(id).await?;
+    require_owner_or_admin(&dashboard.owner_id, &user)?;
+    let shares = state.dashboard_shares.list_for_dashboard(id).await?;
+    Ok(Json(shares))
+}
+
+/* ============================================================================================== */
+/// PUT /api/dashboards/:id/shares — replace the shares with `[{user_id | group_id, permission}]`;
+/// users and groups left out lose their access
+pub async fn replace_dashboard_shares(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    req: Result<Json<Vec<DashboardShareRequest>>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    let dashboard = state.dashboards.get_by_id(id).await?;
+    require_owner_or_admin(&dashboard.owner_id, &user)?;
+
+    let mut seen = HashSet::new();
+    let mut grants = Vec::with_capacity(req.len());
+    for share in &req {
+        let grantee = match (share.user_id, share.group_id) {
+            (Some(user_id), None) => Grantee::User(user_id),
+            (None, Some(group_id)) => Grantee::Group(group_id),
+            _ => return Err(AppError::Validation("each share needs either a user_id or a group_id".into())),
+        };
+        if grantee.user_id().is_some() && grantee.user_id() == dashboard.owner_id {
+            return Err(AppError::Validation("the owner cannot be given a share".into()));
+        }
+        if !seen.insert(grantee) {
+            return Err(AppError::Validation(format!("'{}' is listed twice", grantee.id())));
+        }
+        grants.push((grantee, share.permission.as_str().to_string()));
+    }
+
+    let shares = state.dashboard_shares.replace(id, &grants).await?;
+    Ok(Json(shares))
+}
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Path, State},
+    extract::{rejection::JsonRejection, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
@@ -7,12 +9,16 @@ use axum::{
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::auth::middleware::AuthenticatedUser;
-use crate::db::repos::template_repo::FieldDefinition;
+use crate::auth::middleware::{AuthenticatedUser, RequireEditor, Role};
+use crate::auth::permissions::{template_permission, TemplatePermission};
+use crate::db::repos::template_repo::{time_axis, ChartSettings, FieldDefinition, FieldType, Template, RATING_SCALE_LIMIT};
 use crate::error::AppError;
-use crate::orchestrator::CreateTemplateInput;
+use crate::orchestrator::{expression, records::validate_value, CreateTemplateInput, FieldUpdate, UpdateTemplateInput};
 use crate::AppState;
 
+use super::dashboards::require_owner_or_admin;
+use super::trash::DeleteQuery;
+
 /* ============================================================================================== */
 /*                                              DTOs                                              */
 /* ============================================================================================== */
@@ -24,16 +30,34 @@ pub struct CreateTemplateRequest {
     pub fields:         Vec<FieldDefinition>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateTemplateRequest {
+    pub name:           Option<String>,
+    pub description:    Option<String>,
+    pub fields:         Vec<FieldUpdate>,
+}
+
+#[derive(Deserialize)]
+pub struct CloneTemplateRequest {
+    /// Defaults to "<source name> (copy)".
+    pub name:           Option<String>,
+    #[serde(default)]
+    pub copy_rows:      bool,
+}
+
 /* ============================================================================================== */
 /*                                            Handlers                                            */
 /* ============================================================================================== */
 
-/// GET /api/templates
+/// GET /api/templates — the templates the caller may read
 pub async fn list_templates(
     State(state): State<AppState>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
 ) -> Result<impl IntoResponse, AppError> {
-    let templates = state.templates.list_all().await?;
+    let templates: Vec<Template> = readable_templates(&state, &user).await?
+        .into_iter()
+        .map(|(template, _)| template)
+        .collect();
     Ok(Json(templates))
 }
 
@@ -41,38 +65,28 @@ pub async fn list_templates(
 /// GET /api/templates/:id
 pub async fn get_template(
     State(state): State<AppState>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
-    let template = state.templates.get_by_id(id).await?;
+    let template = authorize_template(&state, id, &user, TemplatePermission::Read).await?;
     Ok(Json(template))
 }
 
 /* ============================================================================================== */
-/// POST /api/templates — triggers the full provisioning pipeline
+/// POST /api/templates — enqueues a provisioning job; poll `GET /api/templates/jobs/:id`
 pub async fn create_template(
     State(state): State<AppState>,
-    user: AuthenticatedUser,
-    Json(req): Json<CreateTemplateRequest>,
+    RequireEditor(user): RequireEditor,
+    req: Result<Json<CreateTemplateRequest>, JsonRejection>,
 ) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
     if req.name.trim().is_empty() {
         return Err(AppError::Validation("name is required".into()));
     }
 
-    if req.fields.is_empty() {
-        return Err(AppError::Validation("at least one field is required".into()));
-    }
+    validate_fields(&req.fields)?;
 
-    // Validate field names: lowercase alphanumeric + underscore
-    for field in &req.fields {
-        if !field.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
-            return Err(AppError::Validation(
-                format!("field name '{}' must be lowercase alphanumeric + underscore", field.name)
-            ));
-        }
-    }
-
-    let template = state.orchestrator.provision_dataset(
+    let job = state.orchestrator.enqueue_provisioning(
         CreateTemplateInput {
             name:           req.name,
             description:    req.description,
@@ -81,22 +95,289 @@ pub async fn create_template(
         user.user_id,
     ).await?;
 
-    Ok((StatusCode::CREATED, Json(template)))
+    Ok((StatusCode::ACCEPTED, Json(job)))
 }
 
 /* ============================================================================================== */
-/// DELETE /api/templates/:id — removes DB record + cleans up external resources
-pub async fn delete_template(
+/// POST /api/templates/:id/clone — provisions a new dataset with the same fields, optionally
+/// with a copy of every row; the source is never modified. Poll `GET /api/templates/jobs/:id`
+pub async fn clone_template(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    req: Result<Json<CloneTemplateRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    if req.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err(AppError::Validation("name cannot be empty".into()));
+    }
+
+    let source = authorize_template(&state, id, &user, TemplatePermission::Read).await?;
+    let name = req.name.unwrap_or_else(|| format!("{} (copy)", source.name));
+    let job = state.orchestrator.enqueue_clone(&source, name, req.copy_rows, user.user_id).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/* ============================================================================================== */
+/// GET /api/templates/jobs/:id — provisioning job status
+pub async fn get_job(
     State(state): State<AppState>,
     user: AuthenticatedUser,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, AppError> {
-    if user.role != crate::auth::middleware::Role::Admin {
-        return Err(AppError::Forbidden);
+    let job = state.jobs.get_by_id(id).await?;
+    require_owner_or_admin(&Some(job.user_id), &user)?;
+    Ok(Json(job))
+}
+
+/* ============================================================================================== */
+/// PUT /api/templates/:id — evolves the schema of a provisioned dataset without losing rows
+pub async fn update_template(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    req: Result<Json<UpdateTemplateRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let Json(req) = req?;
+    if req.name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err(AppError::Validation("name cannot be empty".into()));
+    }
+    let fields: Vec<FieldDefinition> = req.fields.iter().map(|u| u.field.clone()).collect();
+    validate_fields(&fields)?;
+
+    let template = authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+
+    let updated = state.orchestrator.update_dataset(
+        &template,
+        UpdateTemplateInput {
+            name:           req.name,
+            description:    req.description,
+            fields:         req.fields,
+        },
+    ).await?;
+
+    Ok(Json(updated))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/templates/:id — moves the template to the trash; its NocoDB table and Grafana
+/// dashboard are only removed when it is purged. `?permanent=true` (admin) purges immediately
+pub async fn delete_template(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let template = authorize_template(&state, id, &user, TemplatePermission::Manage).await?;
+    if query.permanent {
+        if user.role != Role::Admin {
+            return Err(AppError::Forbidden);
+        }
+        state.orchestrator.purge_template(&template).await?;
+    } else {
+        state.templates.trash(id).await?;
     }
-    let template = state.templates.get_by_id(id).await?;
-    // Best-effort cleanup of external resources
-    state.orchestrator.deprovision_dataset(&template).await;
-    state.templates.delete(id).await?;
     Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Every live template the user can read, with their permission on it.
+pub(crate) async fn readable_templates(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<Vec<(Template, TemplatePermission)>, AppError> {
+    let grants = template_grants(state, user).await?;
+    Ok(state.templates.list_all().await?
+        .into_iter()
+        .filter_map(|template| {
+            let acl = grants.get(&template.id).copied();
+            template_permission(&template, acl, user).map(|permission| (template, permission))
+        })
+        .collect())
+}
+
+/// The strongest ACL grant the user holds on each template, whether made to them or to one
+/// of their groups.
+pub(crate) async fn template_grants(
+    state: &AppState,
+    user: &AuthenticatedUser,
+) -> Result<HashMap<Uuid, TemplatePermission>, AppError> {
+    let mut grants: HashMap<Uuid, TemplatePermission> = HashMap::new();
+    for entry in state.template_acl.list_for_user(user.user_id, &user.group_ids).await? {
+        let permission = TemplatePermission::try_from(entry.permission.as_str())?;
+        let best = grants.entry(entry.template_id).or_insert(permission);
+        *best = (*best).max(permission);
+    }
+    Ok(grants)
+}
+
+/// What the user may do with `template`, live or in the trash; `None` if they cannot read it.
+pub(crate) async fn permission_on(
+    state: &AppState,
+    template: &Template,
+    user: &AuthenticatedUser,
+) -> Result<Option<TemplatePermission>, AppError> {
+    let acl = state.template_acl.get_for_user(template.id, user.user_id, &user.group_ids).await?
+        .iter()
+        .map(|e| TemplatePermission::try_from(e.permission.as_str()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .max();
+    Ok(template_permission(template, acl, user))
+}
+
+/// Load a template the caller holds at least `needed` on. Templates they cannot read at all
+/// are reported as missing rather than forbidden, so private ones do not leak.
+pub(crate) async fn authorize_template(
+    state: &AppState,
+    id: Uuid,
+    user: &AuthenticatedUser,
+    needed: TemplatePermission,
+) -> Result<Template, AppError> {
+    let template = state.templates.get_by_id(id).await?;
+    match permission_on(state, &template, user).await? {
+        Some(granted) if granted >= needed => Ok(template),
+        Some(_) => Err(AppError::Forbidden),
+        None => Err(AppError::NotFound(format!("template '{id}' not found"))),
+    }
+}
+
+pub(crate) fn validate_fields(fields: &[FieldDefinition]) -> Result<(), AppError> {
+    if !fields.iter().any(|f| f.field_type.is_stored()) {
+        return Err(AppError::Validation("at least one stored (non-computed) field is required".into()));
+    }
+
+    // Validate field names: lowercase alphanumeric + underscore
+    for field in fields {
+        if !field.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(AppError::Validation(
+                format!("field name '{}' must be lowercase alphanumeric + underscore", field.name)
+            ));
+        }
+        if field.field_type == FieldType::Currency
+            && field.unit.as_deref().is_some_and(|u| u.len() != 3 || !u.chars().all(|c| c.is_ascii_uppercase()))
+        {
+            return Err(AppError::Validation(
+                format!("currency field '{}' needs a three-letter currency code as its unit", field.name)
+            ));
+        }
+        validate_constraints(field)
+            .and_then(|()| validate_expression(field, fields))
+            .map_err(|e| AppError::Validation(format!("field '{}': {e}", field.name)))?;
+    }
+    validate_time_axis(fields).map_err(AppError::Validation)
+}
+
+/// At most one field is the time axis, and it must be a date or datetime. When several such
+/// fields exist, one has to be marked, since there is no sensible default among them.
+fn validate_time_axis(fields: &[FieldDefinition]) -> Result<(), String> {
+    let marked: Vec<&FieldDefinition> = fields.iter().filter(|f| f.time_axis).collect();
+    if marked.len() > 1 {
+        return Err("only one field can be the time axis".into());
+    }
+    if let Some(axis) = marked.first()
+        && !axis.field_type.is_temporal()
+    {
+        return Err(format!("time axis '{}' must be a date or datetime field", axis.name));
+    }
+
+    let temporal: Vec<&str> = fields.iter()
+        .filter(|f| f.field_type.is_temporal())
+        .map(|f| f.name.as_str())
+        .collect();
+    if marked.is_empty() && temporal.len() > 1 {
+        return Err(format!("mark one of {} as the time axis", temporal.join(", ")));
+    }
+
+    for field in fields.iter().filter(|f| f.timezone.is_some()) {
+        let tz = field.timezone.as_deref().unwrap_or_default();
+        if time_axis(fields).is_none_or(|axis| axis.name != field.name) {
+            return Err(format!("field '{}': only the time axis takes a timezone", field.name));
+        }
+        if tz.parse::<chrono_tz::Tz>().is_err() {
+            return Err(format!("field '{}': unknown timezone '{tz}'", field.name));
+        }
+    }
+    Ok(())
+}
+
+/// Computed fields need an expression over the stored numeric fields, and nothing that only
+/// makes sense for stored values.
+fn validate_expression(field: &FieldDefinition, fields: &[FieldDefinition]) -> Result<(), String> {
+    if field.field_type != FieldType::Computed {
+        return match field.expression {
+            Some(_) => Err("only computed fields take an expression".into()),
+            None => Ok(()),
+        };
+    }
+    if field.required || field.default.is_some() || field.min.is_some() || field.max.is_some() {
+        return Err("computed fields cannot be required or take a default, min or max".into());
+    }
+    let source = field.expression.as_deref().ok_or("computed fields need an expression")?;
+    expression::parse(source, fields).map(|_| ()).map_err(|e| format!("invalid expression: {e}"))
+}
+
+/// Options and bounds must suit the field type, and the default must satisfy them.
+fn validate_constraints(field: &FieldDefinition) -> Result<(), String> {
+    if !field.options.is_empty() {
+        if !field.field_type.has_options() {
+            return Err("only select and multi_select fields take options".into());
+        }
+        let mut seen = std::collections::HashSet::new();
+        for option in &field.options {
+            if option.trim().is_empty() || option.contains(',') {
+                return Err(format!("option '{option}' must be non-empty and contain no commas"));
+            }
+            if !seen.insert(option) {
+                return Err(format!("option '{option}' is listed twice"));
+            }
+        }
+    }
+
+    if field.min.is_some() || field.max.is_some() {
+        if !field.field_type.is_numeric() && !field.field_type.is_textual() {
+            return Err("min/max only apply to numeric and text fields".into());
+        }
+        if let (Some(min), Some(max)) = (field.min, field.max)
+            && min > max
+        {
+            return Err("min must not be greater than max".into());
+        }
+    }
+    if field.field_type == FieldType::Rating
+        && let Some(max) = field.max
+        && !(max.fract() == 0.0 && (1.0..=RATING_SCALE_LIMIT as f64).contains(&max))
+    {
+        return Err(format!("a rating's max must be a whole number from 1 to {RATING_SCALE_LIMIT}"));
+    }
+
+    if let Some(default) = &field.default {
+        validate_value(field, default.clone()).map_err(|e| format!("invalid default: {e}"))?;
+    }
+    if let Some(chart) = &field.chart {
+        validate_chart(field.field_type, chart)?;
+    }
+    Ok(())
+}
+
+fn validate_chart(field_type: FieldType, chart: &ChartSettings) -> Result<(), String> {
+    if !field_type.is_charted() {
+        return Err("only numeric and boolean fields are charted".into());
+    }
+    if let (Some(min), Some(max)) = (chart.y_min, chart.y_max)
+        && min > max
+    {
+        return Err("chart y_min must not be greater than y_max".into());
+    }
+    let colors = chart.color.iter().chain(chart.thresholds.iter().map(|t| &t.color));
+    for color in colors {
+        if color.is_empty() || !color.chars().all(|c| c.is_ascii_alphanumeric() || c == '#' || c == '-') {
+            return Err(format!("'{color}' is not a colour name or hex code"));
+        }
+    }
+    Ok(())
 }
\ No newline at end of file
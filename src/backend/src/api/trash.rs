@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::middleware::{AuthenticatedUser, RequireAdmin, RequireEditor, Role};
+use crate::auth::permissions::{template_permission, SharePermission, TemplatePermission};
+use crate::db::repos::{Dashboard, Panel, Template};
+use crate::error::AppError;
+use crate::AppState;
+
+use super::dashboards::{require_dashboard_permission, require_owner_or_admin};
+use super::templates::{permission_on, template_grants};
+
+/* ============================================================================================== */
+/*                                              DTOs                                              */
+/* ============================================================================================== */
+
+/// Query string of the `DELETE` endpoints for templates, dashboards and panels.
+#[derive(Deserialize)]
+pub struct DeleteQuery {
+    /// Admins only: skip the trash and delete for good.
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+#[derive(Serialize)]
+pub struct TrashListing {
+    /// Days an item stays in the trash before it is purged.
+    pub retention_days: u64,
+    pub templates:      Vec<Template>,
+    pub dashboards:     Vec<Dashboard>,
+    pub panels:         Vec<Panel>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Templates,
+    Dashboards,
+    Panels,
+}
+
+/* ============================================================================================== */
+/*                                            Handlers                                            */
+/* ============================================================================================== */
+
+/// GET /api/trash — what the caller may restore, i.e. what they could have trashed: everything
+/// for admins, otherwise the templates they manage, their own dashboards and the panels of
+/// dashboards they may edit
+pub async fn list_trash(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let is_admin = user.role == Role::Admin;
+    let owns = |owner_id: Option<Uuid>| is_admin || owner_id == Some(user.user_id);
+
+    let grants = template_grants(&state, &user).await?;
+    let templates: Vec<Template> = state.templates.list_trashed().await?
+        .into_iter()
+        .filter(|t| template_permission(t, grants.get(&t.id).copied(), &user) == Some(TemplatePermission::Manage))
+        .collect();
+    let dashboards: Vec<Dashboard> = state.dashboards.list_trashed().await?
+        .into_iter()
+        .filter(|d| owns(d.owner_id))
+        .collect();
+
+    // Panels of a trashed dashboard come back with it, so only its owner sees them.
+    let mut editable: HashSet<Uuid> = dashboards.iter().map(|d| d.id).collect();
+    for dashboard in state.dashboards.list_for_user(user.user_id, &user.group_ids).await? {
+        if can_edit(&state, &dashboard, &user).await? {
+            editable.insert(dashboard.id);
+        }
+    }
+    let panels = state.panels.list_trashed().await?
+        .into_iter()
+        .filter(|p| is_admin || editable.contains(&p.dashboard_id))
+        .collect();
+
+    Ok(Json(TrashListing {
+        retention_days: state.config.trash_retention_days,
+        templates,
+        dashboards,
+        panels,
+    }))
+}
+
+/* ============================================================================================== */
+/// POST /api/trash/:kind/:id/restore — `kind` is `templates`, `dashboards` or `panels`
+pub async fn restore_item(
+    State(state): State<AppState>,
+    RequireEditor(user): RequireEditor,
+    Path((kind, id)): Path<(TrashKind, Uuid)>,
+) -> Result<Response, AppError> {
+    match kind {
+        TrashKind::Templates => {
+            let template = state.templates.get_trashed(id).await?;
+            if permission_on(&state, &template, &user).await? != Some(TemplatePermission::Manage) {
+                return Err(AppError::Forbidden);
+            }
+            Ok(Json(state.templates.restore(id).await?).into_response())
+        }
+        TrashKind::Dashboards => {
+            let dashboard = state.dashboards.get_trashed(id).await?;
+            require_owner_or_admin(&dashboard.owner_id, &user)?;
+            Ok(Json(state.dashboards.restore(id).await?).into_response())
+        }
+        TrashKind::Panels => {
+            let panel = state.panels.get_trashed(id).await?;
+            let dashboard = match state.dashboards.get_by_id(panel.dashboard_id).await {
+                Err(AppError::NotFound(_)) => return Err(AppError::Validation(
+                    "the panel's dashboard is in the trash; restore the dashboard first".into(),
+                )),
+                other => other?,
+            };
+            require_dashboard_permission(&state, &dashboard, &user, SharePermission::Edit).await?;
+            Ok(Json(state.panels.restore(id).await?).into_response())
+        }
+    }
+}
+
+/* ============================================================================================== */
+/// DELETE /api/trash/:kind/:id — admin: purge a trashed item now instead of at the end of the
+/// retention period
+pub async fn purge_item(
+    State(state): State<AppState>,
+    _admin: RequireAdmin,
+    Path((kind, id)): Path<(TrashKind, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    match kind {
+        TrashKind::Templates => {
+            let template = state.templates.get_trashed(id).await?;
+            state.orchestrator.purge_template(&template).await?;
+        }
+        TrashKind::Dashboards => {
+            state.dashboards.get_trashed(id).await?;
+            state.dashboards.delete(id).await?;
+        }
+        TrashKind::Panels => {
+            state.panels.get_trashed(id).await?;
+            state.panels.delete(id).await?;
+        }
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Whether the user may change the panels of a live dashboard.
+async fn can_edit(state: &AppState, dashboard: &Dashboard, user: &AuthenticatedUser) -> Result<bool, AppError> {
+    match require_dashboard_permission(state, dashboard, user, SharePermission::Edit).await {
+        Ok(()) => Ok(true),
+        Err(AppError::Forbidden) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::api::tests::{send, state_on, template, user};
+
+    fn ids(listing: &Value, kind: &str) -> Vec<String> {
+        listing[kind].as_array().unwrap().iter().map(|item| item["id"].as_str().unwrap().to_string()).collect()
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn whoever_could_trash_an_item_may_restore_it(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let alice = user(&pool, "alice", Role::Editor).await;
+        let bob = user(&pool, "bob", Role::Editor).await;
+        let carol = user(&pool, "carol", Role::Editor).await;
+
+        // Bob manages Alice's template and Carol only reads it.
+        let template = template(&state, &alice, "weight").await;
+        let acl = |who: &AuthenticatedUser| format!("/api/templates/{}/acl/{}", template.id, who.user_id);
+        assert_eq!(send(&state, &alice, "PUT", &acl(&bob), json!({ "permission": "manage" })).await.0, StatusCode::OK);
+        assert_eq!(send(&state, &alice, "PUT", &acl(&carol), json!({ "permission": "read" })).await.0, StatusCode::OK);
+        let path = format!("/api/templates/{}", template.id);
+        assert_eq!(send(&state, &bob, "DELETE", &path, json!({})).await.0, StatusCode::NO_CONTENT);
+
+        let id = template.id.to_string();
+        assert_eq!(ids(&send(&state, &bob, "GET", "/api/trash", json!({})).await.1, "templates"), [id.as_str()]);
+        assert!(ids(&send(&state, &carol, "GET", "/api/trash", json!({})).await.1, "templates").is_empty());
+        let restore = format!("/api/trash/templates/{id}/restore");
+        assert_eq!(send(&state, &carol, "POST", &restore, json!({})).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(send(&state, &bob, "POST", &restore, json!({})).await.0, StatusCode::OK);
+        assert_eq!(send(&state, &carol, "GET", &path, json!({})).await.0, StatusCode::OK);
+
+        // Carol may edit Alice's dashboard, so she may restore the panels she trashed on it.
+        let (_, dashboard) = send(&state, &alice, "POST", "/api/dashboards", json!({ "title": "Health" })).await;
+        let dashboard_id = dashboard["id"].as_str().unwrap();
+        let shares = json!([{ "user_id": carol.user_id, "permission": "edit" }]);
+        assert_eq!(send(&state, &alice, "PUT", &format!("/api/dashboards/{dashboard_id}/shares"), shares).await.0, StatusCode::OK);
+        let panel = json!({ "panel_type": "markdown", "grid_x": 0, "grid_y": 0 });
+        let (_, panel) = send(&state, &carol, "POST", &format!("/api/dashboards/{dashboard_id}/panels"), panel).await;
+        let panel_id = panel["id"].as_str().unwrap().to_string();
+        assert_eq!(send(&state, &carol, "DELETE", &format!("/api/panels/{panel_id}"), json!({})).await.0, StatusCode::NO_CONTENT);
+
+        assert_eq!(ids(&send(&state, &carol, "GET", "/api/trash", json!({})).await.1, "panels"), [panel_id.as_str()]);
+        assert!(ids(&send(&state, &bob, "GET", "/api/trash", json!({})).await.1, "panels").is_empty());
+        let restore = format!("/api/trash/panels/{panel_id}/restore");
+        assert_eq!(send(&state, &bob, "POST", &restore, json!({})).await.0, StatusCode::FORBIDDEN);
+        assert_eq!(send(&state, &carol, "POST", &restore, json!({})).await.0, StatusCode::OK);
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_trashed_dashboard_frees_its_slug(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let alice = user(&pool, "alice", Role::Editor).await;
+
+        let (_, old) = send(&state, &alice, "POST", "/api/dashboards", json!({ "title": "Health" })).await;
+        assert_eq!(old["slug"], "health");
+        let old_id = old["id"].as_str().unwrap();
+        assert_eq!(send(&state, &alice, "DELETE", &format!("/api/dashboards/{old_id}"), json!({})).await.0, StatusCode::NO_CONTENT);
+
+        // The slug is free while the old dashboard is in the trash...
+        let (status, new) = send(&state, &alice, "POST", "/api/dashboards", json!({ "title": "Health" })).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(new["slug"], "health");
+
+        // ...so restoring it picks the next free one.
+        let restore = format!("/api/trash/dashboards/{old_id}/restore");
+        let (status, restored) = send(&state, &alice, "POST", &restore, json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(restored["slug"], "health-2");
+        assert_eq!(send(&state, &alice, "GET", "/api/dashboards/health", json!({})).await.1["dashboard"]["id"], new["id"]);
+        assert_eq!(send(&state, &alice, "GET", "/api/dashboards/health-2", json!({})).await.1["dashboard"]["id"], old["id"]);
+    }
+}
@@ -1,16 +1,24 @@
 use axum::{
     extract::{Path, State},
+    http::HeaderMap,
     response::IntoResponse,
     Json,
 };
 use reqwest::StatusCode;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::auth::{middleware::{AuthenticatedUser, RequireAdmin, Role}, password};
+use crate::auth::{api_token, middleware::{RequireAdmin, RequireEditor, Role}, password, session, throttle, totp};
 use crate::error::AppError;
 use crate::AppState;
 
+/// Just enough to pick someone to share with; `GET /api/users` stays admin-only.
+#[derive(Serialize)]
+pub struct UserSummary {
+    pub id:       Uuid,
+    pub username: String,
+}
+
 #[derive(Deserialize)]
 pub struct UpdateRoleInput {
     pub role: String,
@@ -32,17 +40,34 @@ pub async fn list_users(
     Ok(Json(users))
 }
 
+/* ============================================================================================== */
+///GET    /api/users/directory — ids and usernames of all users, for share dialogs (editor+).
+pub async fn list_directory(
+    State(state): State<AppState>,
+    _editor: RequireEditor,
+) -> Result<impl IntoResponse, AppError> {
+    let users: Vec<UserSummary> = state.users.list().await?
+        .into_iter()
+        .map(|u| UserSummary { id: u.id, username: u.username })
+        .collect();
+    Ok(Json(users))
+}
+
 /* ============================================================================================== */
 ///PUT    /api/users/:id/role — changes a user's role (admin only).
 pub async fn update_user_role(
     State(state): State<AppState>,
     _admin: RequireAdmin,
+    headers: HeaderMap,
     Path(user_id): Path<Uuid>,
     Json(input): Json<UpdateRoleInput>,
 ) -> Result<impl IntoResponse, AppError> {
     // Validate the role value before touching the DB.
     Role::try_from(input.role.as_str())?;
     let user = state.users.update_role(user_id, &input.role).await?;
+    // The user signs in again under the new role; an admin changing their own keeps this session.
+    session::revoke_user_sessions(&state.pool, user_id, session::extract_cookie(&headers).as_deref()).await?;
+    api_token::revoke_user_tokens(&state.pool, user_id).await?;
     Ok(Json(user))
 }
 
@@ -52,10 +77,12 @@ pub struct ResetPasswordInput {
     pub new_password: String,
 }
 
-/// PUT   /api/users/:id/password — admin resets a user's password.
+/// PUT   /api/users/:id/password — admin resets a user's password, signing them out everywhere
+/// and revoking their API tokens.
 pub async fn reset_user_password(
     State(state): State<AppState>,
     _admin: RequireAdmin,
+    headers: HeaderMap,
     Path(user_id): Path<Uuid>,
     Json(input): Json<ResetPasswordInput>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -71,6 +98,37 @@ pub async fn reset_user_password(
     )
     .execute(&state.pool)
     .await?;
+    session::revoke_user_sessions(&state.pool, user_id, session::extract_cookie(&headers).as_deref()).await?;
+    api_token::revoke_user_tokens(&state.pool, user_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// DELETE /api/users/:id/totp — turns off a user's 2FA, e.g. after a lost phone (admin only).
+pub async fn reset_user_totp(
+    State(state): State<AppState>,
+    RequireAdmin(admin): RequireAdmin,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    totp::disable(&state.pool, user_id).await?;
+    tracing::info!(target: "audit", admin = %admin.username, %user_id, "two-factor authentication reset");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/// DELETE /api/users/:id/lockout — lifts a lockout after failed sign-ins (admin only). Lockouts
+/// of client addresses expire on their own.
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    RequireAdmin(admin): RequireAdmin,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let username = sqlx::query_scalar!("SELECT username FROM portal.users WHERE id = $1", user_id)
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("user '{user_id}' not found")))?;
+    throttle::reset(&state.pool, &username).await?;
+    tracing::info!(target: "audit", admin = %admin.username, %user_id, "sign-in lockout lifted");
+    Ok(StatusCode::NO_CONTENT)
+}
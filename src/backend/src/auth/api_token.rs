@@ -0,0 +1,197 @@
+use axum::http::Method;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use super::middleware::Role;
+use super::session::{self, SessionUser};
+
+/// Prefix of every API token, so that leaked ones are easy to recognise.
+const TOKEN_PREFIX: &str = "mos_";
+
+/// What an API token may be used for, on top of the user's role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiScope {
+    /// List and read dashboards and their panels.
+    #[serde(rename = "dashboards:read")]
+    DashboardsRead,
+    /// List and read templates; read, create, change and import their records.
+    #[serde(rename = "records:write")]
+    RecordsWrite,
+    /// Everything the user may do, except managing sessions, passwords and tokens.
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl ApiScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DashboardsRead => "dashboards:read",
+            Self::RecordsWrite   => "records:write",
+            Self::Admin          => "admin",
+        }
+    }
+
+    /// The least role that may hold the scope.
+    pub fn least_role(self) -> Role {
+        match self {
+            Self::DashboardsRead => Role::Viewer,
+            Self::RecordsWrite   => Role::Editor,
+            Self::Admin          => Role::Admin,
+        }
+    }
+
+    /// True if a request to `path` (the full URI path) is within the scope.
+    pub fn allows(self, method: &Method, path: &str) -> bool {
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let read = *method == Method::GET;
+        match (self, segments.as_slice()) {
+            (_, ["api", "auth", "me"]) => read,
+            (Self::Admin, ["api", "auth", ..]) => false,
+            (Self::Admin, ["api", ..]) => true,
+
+            (Self::DashboardsRead, ["api", "dashboards", ..]) => read,
+
+            (Self::RecordsWrite, ["api", "templates"] | ["api", "templates", _]) => read,
+            (Self::RecordsWrite, ["api", "templates", _, "records", ..]) => true,
+            (Self::RecordsWrite, ["api", "templates", _, "import"]) => *method == Method::POST,
+
+            _ => false,
+        }
+    }
+}
+
+impl TryFrom<&str> for ApiScope {
+    type Error = AppError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "dashboards:read" => Ok(Self::DashboardsRead),
+            "records:write"   => Ok(Self::RecordsWrite),
+            "admin"           => Ok(Self::Admin),
+            _ => Err(AppError::Validation(format!("unknown scope: {s}"))),
+        }
+    }
+}
+
+/// A token as listed to its owner; the raw token is only returned once, on creation.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiToken {
+    pub id:           Uuid,
+    pub name:         String,
+    pub scopes:       Vec<String>,
+    pub expires_at:   DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at:   DateTime<Utc>,
+}
+
+/* ============================================================================================== */
+/// Generates a new raw API token: the prefix and 32 random bytes in hex.
+pub fn generate_api_token() -> String {
+    format!("{TOKEN_PREFIX}{}", session::generate_session_token())
+}
+
+/* ============================================================================================== */
+/// Extracts the token of an `Authorization: Bearer <token>` header (returns None if absent).
+pub fn extract_bearer(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_owned())
+}
+
+/* ============================================================================================== */
+/// Validates a raw API token, returning its user (with their group memberships) and scopes,
+/// or Unauthorized. Records when the token was last used.
+pub async fn validate_api_token(pool: &PgPool, token: &str) -> Result<(SessionUser, Vec<ApiScope>), AppError> {
+    let token_hash = session::hash_token(token);
+
+    let row = sqlx::query!(
+        r#"
+        WITH used AS (
+            UPDATE portal.api_tokens SET last_used_at = now()
+            WHERE token_hash = $1 AND expires_at > now()
+            RETURNING user_id, scopes
+        )
+        SELECT u.id AS user_id, u.username, u.role, used.scopes,
+               ARRAY(SELECT m.group_id FROM portal.group_members m WHERE m.user_id = u.id) AS "group_ids!"
+        FROM used
+        JOIN portal.users u ON u.id = used.user_id
+        "#,
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::Unauthorized)?;
+
+    let scopes = row.scopes.iter()
+        .map(|s| ApiScope::try_from(s.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let user = SessionUser {
+        user_id: row.user_id,
+        username: row.username,
+        role: row.role,
+        group_ids: row.group_ids,
+    };
+    Ok((user, scopes))
+}
+
+/* ============================================================================================== */
+/// Revokes every API token of a user. Tokens outlive sessions and act as their owner, so a
+/// password change or a new role takes them away too; the user makes new ones to suit.
+pub async fn revoke_user_tokens(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    sqlx::query!("DELETE FROM portal.api_tokens WHERE user_id = $1", user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_cover_only_their_endpoints() {
+        use ApiScope::*;
+        let get = Method::GET;
+        let post = Method::POST;
+        let id = "00000000-0000-0000-0000-000000000001";
+
+        assert!(DashboardsRead.allows(&get, "/api/dashboards"));
+        assert!(DashboardsRead.allows(&get, "/api/dashboards/home/"));
+        assert!(DashboardsRead.allows(&get, "/api/auth/me"));
+        assert!(!DashboardsRead.allows(&post, "/api/dashboards"));
+        assert!(!DashboardsRead.allows(&get, "/api/templates"));
+
+        assert!(RecordsWrite.allows(&get, "/api/templates"));
+        assert!(RecordsWrite.allows(&get, &format!("/api/templates/{id}")));
+        assert!(RecordsWrite.allows(&post, &format!("/api/templates/{id}/records")));
+        assert!(RecordsWrite.allows(&Method::PATCH, &format!("/api/templates/{id}/records/7")));
+        assert!(RecordsWrite.allows(&post, &format!("/api/templates/{id}/import")));
+        assert!(!RecordsWrite.allows(&Method::PUT, &format!("/api/templates/{id}")));
+        assert!(!RecordsWrite.allows(&get, &format!("/api/templates/{id}/acl")));
+        assert!(!RecordsWrite.allows(&get, "/proxy/nocodb/api/v2/tables/t1/records"));
+
+        assert!(Admin.allows(&Method::DELETE, &format!("/api/users/{id}/role")));
+        assert!(!Admin.allows(&post, "/api/auth/tokens"));
+        assert!(!Admin.allows(&post, "/api/auth/change-password"));
+        assert!(!Admin.allows(&get, "/proxy/grafana/api/search"));
+    }
+
+    #[test]
+    fn bearer_tokens_are_extracted() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(extract_bearer(&headers), None);
+        headers.insert(axum::http::header::AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(extract_bearer(&headers), None);
+        headers.insert(axum::http::header::AUTHORIZATION, "Bearer mos_abc".parse().unwrap());
+        assert_eq!(extract_bearer(&headers).as_deref(), Some("mos_abc"));
+    }
+}
@@ -1,11 +1,15 @@
 use axum::{
-    Json, extract::State, http::{HeaderMap, HeaderValue, StatusCode}, response::IntoResponse
+    Json, extract::{FromRef, Path, Query, State}, http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Redirect},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::auth::{middleware::AuthenticatedUser, password, session};
+use crate::auth::api_token::{self, ApiScope, ApiToken};
+use crate::auth::session::{ClientInfo, SessionPolicy};
+use crate::auth::{middleware::AuthenticatedUser, oidc, password, session, throttle, totp};
+use crate::config::OidcConfig;
 use crate::error::AppError;
 use crate::AppState;
 
@@ -26,6 +30,12 @@ pub struct LoginInput {
     pub password: String,
 }
 
+#[derive(Deserialize)]
+pub struct LoginTotpInput {
+    pub challenge: String,
+    pub code: String,
+}
+
 #[derive(Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -39,8 +49,9 @@ pub struct UserResponse {
 /*                                         Cookie helpers                                         */
 /* ============================================================================================== */
 
-fn build_session_cookie(token: &str, ttl_hours: u64) -> HeaderValue {
-    let max_age = ttl_hours * 3600;
+/// The cookie lives as long as the session could; the server expires idle sessions sooner.
+fn build_session_cookie(token: &str, policy: SessionPolicy) -> HeaderValue {
+    let max_age = policy.cookie_max_age_seconds();
     HeaderValue::from_str(&format!(
         "portal_session={token}; HttpOnly; SameSite=Strict; Max-Age={max_age}; Path=/"
     ))
@@ -49,6 +60,22 @@ fn build_session_cookie(token: &str, ttl_hours: u64) -> HeaderValue {
 
 const CLEAR_COOKIE: &str = "portal_session=; HttpOnly; SameSite=Strict; Max-Age=0; Path=/";
 
+/// Starts a session for `user` and returns them, with the session cookie.
+async fn signed_in(
+    state: &AppState,
+    client: &ClientInfo,
+    user: UserResponse,
+) -> Result<axum::response::Response, AppError> {
+    let policy = SessionPolicy::from_ref(state);
+    let token = session::create_session(&state.pool, user.id, policy, client).await?;
+    let mut response = Json(user).into_response();
+    response.headers_mut().insert(
+        axum::http::header::SET_COOKIE,
+        build_session_cookie(&token, policy),
+    );
+    Ok(response)
+}
+
 /* ============================================================================================== */
 /*                                            Handlers                                            */
 /* ============================================================================================== */
@@ -83,7 +110,7 @@ pub async fn register(
     } else {
         // Non-first user: Caller must be an authenticated admin.
         let token = session::extract_cookie(&headers).ok_or(AppError::Unauthorized)?;
-        let caller = session::validate_session(&state.pool, &token).await?;
+        let caller = session::validate_session(&state.pool, &token, SessionPolicy::from_ref(&state)).await?;
         if caller.role != "admin" {
             return Err(AppError::Forbidden);
         }
@@ -128,14 +155,24 @@ pub async fn register(
 
 /* ============================================================================================== */
 /// POST /api/auth/login
+///
+/// With two-factor authentication on, a correct password only earns 202 and a challenge token
+/// for `POST /api/auth/login/totp`; the session waits for the code.
+///
+/// Attempts are throttled per username and per address (see `auth::throttle`): 429 with
+/// Retry-After while either is locked out, whether or not the account exists.
 pub async fn login(
     State(state): State<AppState>,
+    client: ClientInfo,
     Json(input): Json<LoginInput>,
 ) -> Result<impl IntoResponse, AppError> {
+    throttle::charge(&state.pool, &input.username, client.ip.as_deref()).await?;
+
     // Fetch user - Generic error to prevent username enumeration.
-    let user = sqlx::query!(
+    let Some(user) = sqlx::query!(
         "
-        SELECT id, username, email, password_hash, role, created_at
+        SELECT id, username, email, password_hash, role, created_at,
+               totp_secret IS NOT NULL AS \"totp_enabled!\"
         FROM portal.users
         WHERE username = $1
         ",
@@ -143,29 +180,72 @@ pub async fn login(
     )
     .fetch_optional(&state.pool)
     .await?
-    .ok_or(AppError::Unauthorized)?;
+    else {
+        password::verify_nothing(&input.password);
+        return Err(AppError::Unauthorized);
+    };
 
-    if !password::verify_password(&input.password, &user.password_hash)? {
+    // Accounts provisioned by single sign-on have no password.
+    let Some(password_hash) = &user.password_hash else {
+        password::verify_nothing(&input.password);
+        return Err(AppError::Unauthorized);
+    };
+    if !password::verify_password(&input.password, password_hash)? {
         return Err(AppError::Unauthorized);
     }
+    throttle::refund_address(&state.pool, client.ip.as_deref()).await?;
 
-    let token = session::create_session(&state.pool, user.id, state.config.session_ttl_hours).await?;
+    // The attempt against the username stands until the second factor is in, so that guessing
+    // codes counts towards the lockout too.
+    if user.totp_enabled {
+        let challenge = totp::create_challenge(&state.pool, user.id).await?;
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({ "two_factor_required": true, "challenge": challenge })),
+        )
+            .into_response());
+    }
 
-    let mut response = Json(UserResponse {
+    throttle::reset(&state.pool, &user.username).await?;
+    signed_in(&state, &client, UserResponse {
         id: user.id,
         username: user.username,
         email: user.email,
         role: user.role,
         created_at: user.created_at,
     })
-    .into_response();
+    .await
+}
 
-    response.headers_mut().insert(
-        axum::http::header::SET_COOKIE,
-        build_session_cookie(&token, state.config.session_ttl_hours),
-    );
+/* ============================================================================================== */
+/// POST /api/auth/login/totp
+///
+/// Second login step: a code from the authenticator, or a recovery code, for the challenge
+/// `login` returned. A challenge allows a few attempts within a few minutes, and each counts
+/// towards the login throttle like a password.
+pub async fn login_totp(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    Json(input): Json<LoginTotpInput>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = totp::attempt_challenge(&state.pool, &input.challenge).await?;
+    let user = sqlx::query_as!(
+        UserResponse,
+        "SELECT id, username, email, role, created_at FROM portal.users WHERE id = $1",
+        user_id
+    )
+    .fetch_one(&state.pool)
+    .await?;
 
-    Ok(response)
+    throttle::charge(&state.pool, &user.username, client.ip.as_deref()).await?;
+    if !totp::verify(&state.pool, user_id, &input.code).await? {
+        return Err(AppError::Unauthorized);
+    }
+    totp::delete_challenge(&state.pool, &input.challenge).await?;
+    throttle::refund_address(&state.pool, client.ip.as_deref()).await?;
+    throttle::reset(&state.pool, &user.username).await?;
+
+    signed_in(&state, &client, user).await
 }
 
 /* ============================================================================================== */
@@ -206,6 +286,7 @@ pub struct ChangePasswordInput {
 pub async fn change_password(
     State(state): State<AppState>,
     user: AuthenticatedUser,
+    headers: HeaderMap,
     Json(input): Json<ChangePasswordInput>,
 ) -> Result<impl IntoResponse, AppError> {
     if input.new_password.len() < 8 {
@@ -222,7 +303,12 @@ pub async fn change_password(
     .await?
     .ok_or_else(|| AppError::NotFound("user not found".into()))?;
 
-    if !password::verify_password(&input.current_password, &row.password_hash)? {
+    let Some(password_hash) = &row.password_hash else {
+        return Err(AppError::Validation(
+            "this account signs in with single sign-on".into(),
+        ));
+    };
+    if !password::verify_password(&input.current_password, password_hash)? {
         return Err(AppError::Unauthorized);
     }
 
@@ -235,5 +321,417 @@ pub async fn change_password(
     .execute(&state.pool)
     .await?;
 
+    // Sign out everywhere else, in case the old password leaked.
+    session::revoke_user_sessions(&state.pool, user.user_id, session::extract_cookie(&headers).as_deref()).await?;
+    api_token::revoke_user_tokens(&state.pool, user.user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                            Sessions                                            */
+/* ============================================================================================== */
+
+/// GET /api/auth/sessions — the authenticated user's active sessions, the current one marked.
+pub async fn list_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let current = session::extract_cookie(&headers);
+    let sessions = session::list_sessions(&state.pool, user.user_id, current.as_deref()).await?;
+    Ok(Json(sessions))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/auth/sessions — signs the authenticated user out everywhere but here.
+pub async fn revoke_other_sessions(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let current = session::extract_cookie(&headers);
+    session::revoke_user_sessions(&state.pool, user.user_id, current.as_deref()).await?;
     Ok(StatusCode::NO_CONTENT)
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// DELETE /api/auth/sessions/{id} — signs out one of the authenticated user's sessions. Revoking
+/// the current one also clears its cookie.
+pub async fn revoke_session(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let current = session::extract_cookie(&headers);
+    let was_current = session::revoke_session(&state.pool, user.user_id, id, current.as_deref()).await?;
+
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    if was_current {
+        response.headers_mut().insert(
+            axum::http::header::SET_COOKIE,
+            HeaderValue::from_static(CLEAR_COOKIE),
+        );
+    }
+    Ok(response)
+}
+
+/* ============================================================================================== */
+/*                                           API tokens                                           */
+/* ============================================================================================== */
+
+/// Tokens last 90 days unless asked otherwise, and never more than a year.
+const DEFAULT_TOKEN_DAYS: i64 = 90;
+const MAX_TOKEN_DAYS: i64 = 365;
+
+#[derive(Deserialize)]
+pub struct CreateApiTokenInput {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub token: ApiToken,
+    /// The raw token; shown only in this response.
+    pub secret: String,
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/tokens — the authenticated user's API tokens, newest first.
+pub async fn list_api_tokens(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let tokens = sqlx::query_as!(
+        ApiToken,
+        "
+        SELECT id, name, scopes, expires_at, last_used_at, created_at
+        FROM portal.api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        ",
+        user.user_id
+    )
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(Json(tokens))
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/tokens — creates an API token for the authenticated user.
+///
+/// Each scope needs at least its role (`records:write` an editor, `admin` an admin). The raw
+/// token is returned once; only its hash is stored.
+pub async fn create_api_token(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(input): Json<CreateApiTokenInput>,
+) -> Result<impl IntoResponse, AppError> {
+    let name = input.name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(AppError::Validation(
+            "name must be between 1 and 64 characters".into(),
+        ));
+    }
+    if input.scopes.is_empty() {
+        return Err(AppError::Validation(
+            "at least one scope is required".into(),
+        ));
+    }
+    if input.scopes.iter().any(|scope| !user.role.at_least(&scope.least_role())) {
+        return Err(AppError::Forbidden);
+    }
+    let days = input.expires_in_days.unwrap_or(DEFAULT_TOKEN_DAYS);
+    if !(1..=MAX_TOKEN_DAYS).contains(&days) {
+        return Err(AppError::Validation(format!(
+            "expires_in_days must be between 1 and {MAX_TOKEN_DAYS}"
+        )));
+    }
+
+    let mut scopes: Vec<String> = input.scopes.iter().map(|s| s.as_str().to_string()).collect();
+    scopes.sort();
+    scopes.dedup();
+    let secret = api_token::generate_api_token();
+    let expires_at = Utc::now() + chrono::Duration::days(days);
+
+    let token = sqlx::query_as!(
+        ApiToken,
+        "
+        INSERT INTO portal.api_tokens (user_id, name, token_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name, scopes, expires_at, last_used_at, created_at
+        ",
+        user.user_id,
+        name,
+        session::hash_token(&secret),
+        &scopes,
+        expires_at,
+    )
+    .fetch_one(&state.pool)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(CreatedApiToken { token, secret })))
+}
+
+/* ============================================================================================== */
+/// DELETE /api/auth/tokens/{id} — revokes one of the authenticated user's API tokens.
+pub async fn revoke_api_token(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let result = sqlx::query!(
+        "DELETE FROM portal.api_tokens WHERE id = $1 AND user_id = $2",
+        id,
+        user.user_id
+    )
+    .execute(&state.pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("API token not found".into()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                          Single sign-on                                        */
+/* ============================================================================================== */
+
+/// Where the browser lands when single sign-on fails; the login page explains.
+const SSO_FAILED: &str = "/login?error=sso";
+
+#[derive(Deserialize)]
+pub struct OidcCallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+fn build_oidc_cookie(state: &str) -> HeaderValue {
+    let max_age = oidc::LOGIN_TTL_MINUTES * 60;
+    // Lax, not Strict: the provider's redirect back is a cross-site navigation.
+    HeaderValue::from_str(&format!(
+        "{}={state}; HttpOnly; SameSite=Lax; Max-Age={max_age}; Path=/api/auth/oidc",
+        oidc::OIDC_COOKIE
+    ))
+    .expect("cookie string is always valid ASCII")
+}
+
+fn extract_oidc_cookie(headers: &HeaderMap) -> Option<String> {
+    let prefix = format!("{}=", oidc::OIDC_COOKIE);
+    headers
+        .get(axum::http::header::COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.split(';').find_map(|seg| seg.trim().strip_prefix(prefix.as_str()).map(str::to_owned)))
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/oidc — whether single sign-on is configured, for the login page.
+pub async fn oidc_status(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({ "enabled": state.config.oidc.is_some() }))
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/oidc/login
+///
+/// Starts a sign-in: remembers its state, nonce and PKCE verifier, and redirects to the provider.
+pub async fn oidc_login(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+    let config = state.config.oidc.as_ref()
+        .ok_or_else(|| AppError::NotFound("single sign-on is not configured".into()))?;
+
+    let metadata = oidc::discover(&state.http_client, config).await?;
+    let login = oidc::new_login();
+    oidc::store_login(&state.pool, &login).await?;
+    let url = oidc::authorization_url(&metadata, config, &login)?;
+
+    let mut response = Redirect::to(&url).into_response();
+    response.headers_mut().insert(axum::http::header::SET_COOKIE, build_oidc_cookie(&login.state));
+    Ok(response)
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/oidc/callback
+///
+/// Completes a sign-in: checks the state against the cookie, redeems the code with the PKCE
+/// verifier, verifies the ID token, provisions the user and issues the session cookie. Failures
+/// are logged and land on the login page.
+pub async fn oidc_callback(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    headers: HeaderMap,
+    Query(params): Query<OidcCallbackParams>,
+) -> Result<impl IntoResponse, AppError> {
+    let config = state.config.oidc.as_ref()
+        .ok_or_else(|| AppError::NotFound("single sign-on is not configured".into()))?;
+
+    let mut response = match complete_oidc_login(&state, config, &headers, params).await {
+        Ok(user_id) => {
+            let policy = SessionPolicy::from_ref(&state);
+            let token = session::create_session(&state.pool, user_id, policy, &client).await?;
+            let mut response = Redirect::to("/").into_response();
+            response.headers_mut().append(
+                axum::http::header::SET_COOKIE,
+                build_session_cookie(&token, policy),
+            );
+            response
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "single sign-on failed");
+            Redirect::to(SSO_FAILED).into_response()
+        }
+    };
+    response.headers_mut().append(axum::http::header::SET_COOKIE, build_oidc_cookie(""));
+    Ok(response)
+}
+
+async fn complete_oidc_login(
+    state: &AppState,
+    config: &OidcConfig,
+    headers: &HeaderMap,
+    params: OidcCallbackParams,
+) -> Result<Uuid, AppError> {
+    if let Some(error) = params.error {
+        return Err(AppError::Validation(format!("provider returned {error}")));
+    }
+    let (Some(code), Some(login_state)) = (params.code, params.state) else {
+        return Err(AppError::Validation("code and state are required".into()));
+    };
+    // The state must come back to the browser that started the sign-in.
+    if extract_oidc_cookie(headers).as_deref() != Some(login_state.as_str()) {
+        return Err(AppError::Unauthorized);
+    }
+    let login = oidc::take_login(&state.pool, &login_state).await?.ok_or(AppError::Unauthorized)?;
+
+    let metadata = oidc::discover(&state.http_client, config).await?;
+    let id_token = oidc::exchange_code(&state.http_client, &metadata, config, &code, &login.code_verifier).await?;
+    let claims = oidc::verify_id_token(&state.http_client, &metadata, config, &id_token, &login.nonce).await?;
+    let role = oidc::map_role(config, &claims);
+    oidc::provision_user(&state.pool, &metadata.issuer, &claims, &role).await
+}
+
+/* ============================================================================================== */
+/*                                    Two-factor authentication                                   */
+/* ============================================================================================== */
+
+#[derive(Deserialize)]
+pub struct TotpCodeInput {
+    pub code: String,
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/totp — whether 2FA is on, and how many recovery codes are left.
+pub async fn totp_status(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let (enabled, recovery_codes_left) = totp::status(&state.pool, user.user_id).await?;
+    Ok(Json(serde_json::json!({ "enabled": enabled, "recovery_codes_left": recovery_codes_left })))
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/totp/setup — a new secret, with its provisioning URI and QR code. 2FA stays
+/// off until `enable` receives a code for it.
+pub async fn totp_setup(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AppError> {
+    let enrollment = totp::begin_enrollment(&state.pool, user.user_id).await?;
+    Ok(Json(serde_json::json!({
+        "secret": enrollment.secret,
+        "uri":    enrollment.uri,
+        "qr_svg": enrollment.qr_svg,
+    })))
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/totp/enable — turns 2FA on with a code for the new secret, and returns the
+/// recovery codes, which are not shown again.
+///
+/// Like `recovery-codes` and `disable`, allows a few wrong codes within a few minutes
+/// (see `totp::attempt_code`), then 429 with Retry-After.
+pub async fn totp_enable(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(input): Json<TotpCodeInput>,
+) -> Result<impl IntoResponse, AppError> {
+    totp::attempt_code(&state.pool, user.user_id).await?;
+    let codes = totp::confirm_enrollment(&state.pool, user.user_id, &input.code).await?;
+    totp::forget_attempts(&state.pool, user.user_id).await?;
+    Ok(Json(serde_json::json!({ "recovery_codes": codes })))
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/totp/recovery-codes — replaces the recovery codes; needs a current code.
+pub async fn totp_recovery_codes(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(input): Json<TotpCodeInput>,
+) -> Result<impl IntoResponse, AppError> {
+    totp::attempt_code(&state.pool, user.user_id).await?;
+    if !totp::verify(&state.pool, user.user_id, &input.code).await? {
+        return Err(AppError::Unauthorized);
+    }
+    totp::forget_attempts(&state.pool, user.user_id).await?;
+    let codes = totp::regenerate_recovery_codes(&state.pool, user.user_id).await?;
+    Ok(Json(serde_json::json!({ "recovery_codes": codes })))
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/totp/disable — turns 2FA off; needs a current or recovery code.
+pub async fn totp_disable(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+    Json(input): Json<TotpCodeInput>,
+) -> Result<impl IntoResponse, AppError> {
+    totp::attempt_code(&state.pool, user.user_id).await?;
+    if !totp::verify(&state.pool, user.user_id, &input.code).await? {
+        return Err(AppError::Unauthorized);
+    }
+    totp::forget_attempts(&state.pool, user.user_id).await?;
+    totp::disable(&state.pool, user.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::api::tests::{send, state_on, user};
+    use crate::auth::middleware::Role;
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_sixth_wrong_code_is_refused(pool: PgPool) {
+        let state = state_on(pool.clone());
+        let alice = user(&pool, "alice", Role::Viewer).await;
+        let (status, _) = send(&state, &alice, "POST", "/api/auth/totp/setup", json!({})).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Seven digits never match a code, nor a recovery code.
+        let wrong = json!({ "code": "0000000" });
+        for _ in 0..5 {
+            let (status, _) = send(&state, &alice, "POST", "/api/auth/totp/enable", wrong.clone()).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        }
+        let (status, _) = send(&state, &alice, "POST", "/api/auth/totp/enable", wrong.clone()).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // The other endpoints that take a code count against the same attempts.
+        for path in ["/api/auth/totp/recovery-codes", "/api/auth/totp/disable"] {
+            let (status, _) = send(&state, &alice, "POST", path, wrong.clone()).await;
+            assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{path}");
+        }
+    }
+}
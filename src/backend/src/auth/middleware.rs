@@ -1,11 +1,20 @@
-use axum::extract::{FromRef, FromRequestParts};
+use axum::extract::{FromRef, FromRequestParts, OriginalUri};
 use axum::http::request::Parts;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::auth::session;
+use crate::auth::{api_token, session::{self, SessionPolicy}};
 use crate::error::AppError;
 
+/// Permission matrix, enforced by the extractors below plus ownership checks in the handlers:
+///
+/// | Role   | Read                          | Create / modify                         |
+/// |--------|-------------------------------|-----------------------------------------|
+/// | Viewer | shared and own resources      | nothing                                 |
+/// | Editor | shared and own resources      | own dashboards, panels and templates    |
+/// | Admin  | everything                    | everything, plus users and maintenance  |
+///
+/// Template access is further narrowed per template; see `auth::permissions`.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
@@ -14,6 +23,21 @@ pub enum Role {
     Viewer,
 }
 
+impl Role {
+    /// Admin outranks editor, which outranks viewer.
+    pub fn at_least(&self, least: &Role) -> bool {
+        self.rank() >= least.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Role::Viewer => 0,
+            Role::Editor => 1,
+            Role::Admin  => 2,
+        }
+    }
+}
+
 impl std::fmt::Display for Role {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -39,31 +63,59 @@ impl TryFrom<&str> for Role {
 
 /* ============================================================================================== */
 /// Axum extractor that validates the session cookie and returns the authenticated user.
-/// Handlers add this to their signature to require authentication.
+/// Handlers add this to their signature to require authentication. Each use renews the session.
+///
+/// Without a session cookie, an `Authorization: Bearer` API token is accepted instead, for the
+/// requests its scopes cover; others are rejected with 403.
+///
+/// The user is cached in the request extensions, so a handler taking several of these
+/// extractors validates the session only once.
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub user_id: Uuid,
     pub username: String,
-    pub role: Role
+    pub role: Role,
+    /// Groups the user belongs to, loaded with the session so that permission checks on
+    /// group grants need no extra query.
+    pub group_ids: Vec<Uuid>,
 }
 
 impl<S> FromRequestParts<S> for AuthenticatedUser
 where
     S: Send + Sync,
     PgPool: FromRef<S>,
+    SessionPolicy: FromRef<S>,
 {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(user) = parts.extensions.get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+
         let pool = PgPool::from_ref(state);
-        let token = session::extract_cookie(&parts.headers).ok_or(AppError::Unauthorized)?;
-        let user = session::validate_session(&pool, &token).await?;
+        let user = match session::extract_cookie(&parts.headers) {
+            Some(token) => session::validate_session(&pool, &token, SessionPolicy::from_ref(state)).await?,
+            None => {
+                let token = api_token::extract_bearer(&parts.headers).ok_or(AppError::Unauthorized)?;
+                let (user, scopes) = api_token::validate_api_token(&pool, &token).await?;
+                // Nested routers see a stripped URI; scopes are written against the full path.
+                let path = parts.extensions.get::<OriginalUri>().map_or(parts.uri.path(), |uri| uri.path());
+                if !scopes.iter().any(|scope| scope.allows(&parts.method, path)) {
+                    return Err(AppError::Forbidden);
+                }
+                user
+            }
+        };
         let role = Role::try_from(user.role.as_str())?;
-        Ok(AuthenticatedUser {
+        let user = AuthenticatedUser {
             user_id: user.user_id,
             username: user.username,
             role,
-        })
+            group_ids: user.group_ids,
+        };
+        parts.extensions.insert(user.clone());
+        Ok(user)
     }
 }
 
@@ -75,6 +127,7 @@ impl<S> FromRequestParts<S> for RequireAdmin
 where
     S: Send + Sync,
     PgPool: FromRef<S>,
+    SessionPolicy: FromRef<S>,
 {
     type Rejection = AppError;
 
@@ -85,4 +138,25 @@ where
         }
         Ok(RequireAdmin(user))
     }
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// Extractor that requires the Editor or Admin role, rejecting viewers with 403.
+pub struct RequireEditor(pub AuthenticatedUser);
+
+impl<S> FromRequestParts<S> for RequireEditor
+where
+    S: Send + Sync,
+    PgPool: FromRef<S>,
+    SessionPolicy: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthenticatedUser::from_request_parts(parts, state).await?;
+        if user.role == Role::Viewer {
+            return Err(AppError::Forbidden);
+        }
+        Ok(RequireEditor(user))
+    }
+}
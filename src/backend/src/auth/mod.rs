@@ -1,6 +1,12 @@
+pub mod api_token;
 pub mod handlers;
 pub mod middleware;
+pub mod oidc;
 pub mod password;
+pub mod permissions;
+pub mod public_link;
 pub mod session;
+pub mod throttle;
+pub mod totp;
 
-pub use middleware::{AuthenticatedUser, RequireAdmin, Role};
\ No newline at end of file
+pub use middleware::{AuthenticatedUser, RequireAdmin, RequireEditor, Role};
\ No newline at end of file
@@ -1,3 +1,5 @@
+use std::sync::LazyLock;
+
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
@@ -22,6 +24,15 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
         .is_ok())
 }
 
+/// Checks `password` against a throwaway hash and discards the result. Logins for unknown
+/// usernames and password-less accounts call this, so that they take as long as a wrong
+/// password and timing does not tell which usernames exist.
+pub fn verify_nothing(password: &str) {
+    static DUMMY_HASH: LazyLock<String> =
+        LazyLock::new(|| hash_password("not anyone's password").expect("hashing a constant succeeds"));
+    let _ = verify_password(password, &DUMMY_HASH);
+}
+
 /* ============================================================================================== */
 /*                                              Test                                              */
 /* ============================================================================================== */
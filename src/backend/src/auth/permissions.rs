@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::repos::dashboard_repo::Dashboard;
+use crate::db::repos::template_repo::Template;
+use crate::error::AppError;
+use super::middleware::{AuthenticatedUser, Role};
+
+/* ============================================================================================== */
+/*                                      Template permissions                                      */
+/* ============================================================================================== */
+
+/// What a user may do with one template. Each level includes the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplatePermission {
+    /// See the template, list and export its records.
+    Read,
+    /// Also create, edit, delete and import records.
+    Enter,
+    /// Also change the schema and sharing, and move the template to the trash.
+    Manage,
+}
+
+impl TemplatePermission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Read   => "read",
+            Self::Enter  => "enter",
+            Self::Manage => "manage",
+        }
+    }
+}
+
+impl TryFrom<&str> for TemplatePermission {
+    type Error = AppError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "read"   => Ok(Self::Read),
+            "enter"  => Ok(Self::Enter),
+            "manage" => Ok(Self::Manage),
+            _ => Err(AppError::Validation(format!("unknown permission: {s}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Only the owner, admins and users with an ACL entry.
+    Private,
+    /// Every user may read; ACL entries can grant more.
+    Public,
+}
+
+impl Visibility {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Public  => "public",
+        }
+    }
+}
+
+/* ============================================================================================== */
+/// Effective permission of `user` on `template`, given their ACL entry if they have one.
+/// `None` means the template is invisible to them.
+///
+/// Admins and the owner manage the template. Viewers are capped at `read` whatever they were
+/// granted, so demoting a user takes away their write access everywhere at once.
+pub fn template_permission(
+    template: &Template,
+    acl: Option<TemplatePermission>,
+    user: &AuthenticatedUser,
+) -> Option<TemplatePermission> {
+    let granted = if user.role == Role::Admin || template.owner_id == Some(user.user_id) {
+        Some(TemplatePermission::Manage)
+    } else {
+        let public = (template.visibility == Visibility::Public.as_str()).then_some(TemplatePermission::Read);
+        acl.max(public)
+    };
+
+    if user.role == Role::Viewer {
+        granted.map(|p| p.min(TemplatePermission::Read))
+    } else {
+        granted
+    }
+}
+
+/* ============================================================================================== */
+/*                                      Dashboard permissions                                     */
+/* ============================================================================================== */
+
+/// What a user may do with one dashboard shared with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SharePermission {
+    /// See the dashboard and its panels.
+    View,
+    /// Also add, change, move and remove panels.
+    Edit,
+}
+
+impl SharePermission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Edit => "edit",
+        }
+    }
+}
+
+impl TryFrom<&str> for SharePermission {
+    type Error = AppError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "view" => Ok(Self::View),
+            "edit" => Ok(Self::Edit),
+            _ => Err(AppError::Validation(format!("unknown share permission: {s}"))),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/// Effective permission of `user` on `dashboard`, given the share they hold if any. `None`
+/// means they may not see it.
+///
+/// Admins and the owner edit the dashboard; everyone views an `is_shared` one. Viewers are
+/// capped at `view`, as for templates. Renaming, sharing and deleting the dashboard itself stay
+/// with the owner and admins whatever was granted.
+pub fn dashboard_permission(
+    dashboard: &Dashboard,
+    share: Option<SharePermission>,
+    user: &AuthenticatedUser,
+) -> Option<SharePermission> {
+    let granted = if user.role == Role::Admin || dashboard.owner_id == Some(user.user_id) {
+        Some(SharePermission::Edit)
+    } else {
+        share.max(dashboard.is_shared.then_some(SharePermission::View))
+    };
+
+    if user.role == Role::Viewer {
+        granted.map(|p| p.min(SharePermission::View))
+    } else {
+        granted
+    }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn template(owner_id: Uuid, visibility: Visibility) -> Template {
+        Template {
+            id:                    Uuid::new_v4(),
+            name:                  "weight".into(),
+            description:           None,
+            nocodb_table_id:       None,
+            nocodb_form_id:        None,
+            grafana_dashboard_uid: None,
+            fields:                serde_json::json!([]),
+            created_by:            Some(owner_id),
+            owner_id:              Some(owner_id),
+            visibility:            visibility.as_str().into(),
+            created_at:            Utc::now(),
+            updated_at:            Utc::now(),
+            deleted_at:            None,
+        }
+    }
+
+    fn dashboard(owner_id: Uuid, is_shared: bool) -> Dashboard {
+        Dashboard {
+            id:         Uuid::new_v4(),
+            owner_id:   Some(owner_id),
+            title:      "Health".into(),
+            slug:       "health".into(),
+            icon:       None,
+            sort_order: 0,
+            is_shared,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+
+    fn user(role: Role) -> AuthenticatedUser {
+        AuthenticatedUser { user_id: Uuid::new_v4(), username: "someone".into(), role, group_ids: vec![] }
+    }
+
+    #[test]
+    fn owner_and_admin_manage() {
+        let owner = user(Role::Editor);
+        let private = template(owner.user_id, Visibility::Private);
+        assert_eq!(template_permission(&private, None, &owner), Some(TemplatePermission::Manage));
+        assert_eq!(template_permission(&private, None, &user(Role::Admin)), Some(TemplatePermission::Manage));
+    }
+
+    #[test]
+    fn visibility_and_acl_combine() {
+        let editor = user(Role::Editor);
+        let private = template(Uuid::new_v4(), Visibility::Private);
+        let public = template(Uuid::new_v4(), Visibility::Public);
+
+        assert_eq!(template_permission(&private, None, &editor), None);
+        assert_eq!(template_permission(&public, None, &editor), Some(TemplatePermission::Read));
+        assert_eq!(
+            template_permission(&public, Some(TemplatePermission::Enter), &editor),
+            Some(TemplatePermission::Enter),
+        );
+    }
+
+    #[test]
+    fn viewers_are_read_only() {
+        let viewer = user(Role::Viewer);
+        let private = template(viewer.user_id, Visibility::Private);
+        assert_eq!(template_permission(&private, None, &viewer), Some(TemplatePermission::Read));
+
+        let other = template(Uuid::new_v4(), Visibility::Private);
+        assert_eq!(
+            template_permission(&other, Some(TemplatePermission::Manage), &viewer),
+            Some(TemplatePermission::Read),
+        );
+    }
+
+    #[test]
+    fn dashboard_shares_grant_view_or_edit() {
+        let editor = user(Role::Editor);
+        let private = dashboard(Uuid::new_v4(), false);
+        let shared = dashboard(Uuid::new_v4(), true);
+
+        assert_eq!(dashboard_permission(&private, None, &editor), None);
+        assert_eq!(dashboard_permission(&shared, None, &editor), Some(SharePermission::View));
+        assert_eq!(
+            dashboard_permission(&private, Some(SharePermission::Edit), &editor),
+            Some(SharePermission::Edit),
+        );
+        assert_eq!(dashboard_permission(&private, None, &user(Role::Admin)), Some(SharePermission::Edit));
+    }
+
+    #[test]
+    fn viewers_only_view_shared_dashboards() {
+        let viewer = user(Role::Viewer);
+        let private = dashboard(Uuid::new_v4(), false);
+        assert_eq!(
+            dashboard_permission(&private, Some(SharePermission::Edit), &viewer),
+            Some(SharePermission::View),
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Cookie that carries a public link token to the Grafana proxy.
+pub const PUBLIC_COOKIE: &str = "portal_public";
+
+/* ============================================================================================== */
+/// Signs a public link id: `<id as 32 hex chars>.<hex HMAC-SHA256(secret, id)>`.
+///
+/// The token can be re-derived at any time, so only the link row is stored.
+pub fn sign(secret: &str, link_id: Uuid) -> String {
+    let id = link_id.simple().to_string();
+    format!("{id}.{}", hex::encode(mac(secret, &id).finalize().into_bytes()))
+}
+
+/* ============================================================================================== */
+/// Returns the link id of a token signed with `secret`, or None if it is malformed or forged.
+/// Expiry and revocation live in the database and are checked by the caller.
+pub fn verify(secret: &str, token: &str) -> Option<Uuid> {
+    let (id, signature) = token.split_once('.')?;
+    let signature = hex::decode(signature).ok()?;
+    mac(secret, id).verify_slice(&signature).ok()?;
+    Uuid::try_parse(id).ok()
+}
+
+/* ============================================================================================== */
+/// Extracts the `portal_public` cookie value from request headers (returns None if absent).
+pub fn extract_cookie(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| {
+            s.split(';').find_map(|seg| {
+                seg.trim()
+                    .strip_prefix("portal_public=")
+                    .map(|v| v.to_owned())
+            })
+        })
+}
+
+fn mac(secret: &str, id: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(id.as_bytes());
+    mac
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_tokens_round_trip() {
+        let id = Uuid::new_v4();
+        assert_eq!(verify("secret", &sign("secret", id)), Some(id));
+    }
+
+    #[test]
+    fn tampered_tokens_are_rejected() {
+        let id = Uuid::new_v4();
+        let token = sign("secret", id);
+        let (_, signature) = token.split_once('.').unwrap();
+
+        // Another id under the same signature
+        let forged = format!("{}.{signature}", Uuid::new_v4().simple());
+        assert_eq!(verify("secret", &forged), None);
+        // Same token, other secret
+        assert_eq!(verify("other", &token), None);
+        // Truncated signature
+        assert_eq!(verify("secret", &token[..token.len() - 2]), None);
+        assert_eq!(verify("secret", "garbage"), None);
+        assert_eq!(verify("secret", ""), None);
+    }
+
+    #[test]
+    fn public_cookie_is_found_among_others() {
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert(
+            axum::http::header::COOKIE,
+            "portal_session=abc; portal_public=def.123".parse().unwrap(),
+        );
+        assert_eq!(extract_cookie(&headers).as_deref(), Some("def.123"));
+    }
+}
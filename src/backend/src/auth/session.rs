@@ -1,15 +1,123 @@
-use chrono::{Duration, Utc};
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use chrono::{DateTime, Duration, Utc};
+use ipnet::IpNet;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::error::AppError;
 
+/// A session seen again within this many seconds is not renewed, so that a dashboard firing a
+/// request per panel does not rewrite the row for each of them.
+const RENEW_AFTER_SECONDS: i64 = 60;
+
+/// How often the purger deletes expired sessions.
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// How long sessions last, from `SESSION_TTL_HOURS` and `SESSION_MAX_DAYS`.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionPolicy {
+    /// Idle timeout: each use pushes the expiry this far ahead.
+    pub ttl_hours: u64,
+    /// Absolute lifetime, counted from sign-in; also the cookie's Max-Age.
+    pub max_days: u64,
+}
+
+impl SessionPolicy {
+    /// Expiry of a session created at `created_at` and used at `now`.
+    pub fn expires_at(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let idle = now + Duration::hours(self.ttl_hours as i64);
+        let absolute = created_at + Duration::days(self.max_days as i64);
+        idle.min(absolute)
+    }
+
+    pub fn cookie_max_age_seconds(&self) -> u64 {
+        self.max_days * 24 * 3600
+    }
+}
+
+/// Reverse proxies whose `X-Forwarded-For` is believed, from `TRUSTED_PROXIES`. Empty by
+/// default, so that clients cannot pick their own address by sending the header.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(pub Vec<IpNet>);
+
+impl TrustedProxies {
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|net| net.contains(&ip))
+    }
+
+    /// The address a request comes from: the peer, unless that is a trusted proxy. Then the
+    /// `X-Forwarded-For` hops are walked from the right, each appended by the proxy before it,
+    /// up to the first one that is not itself a trusted proxy; anything left of it is whatever
+    /// the client chose to send.
+    fn client_ip(&self, peer: IpAddr, forwarded_for: &[&str]) -> IpAddr {
+        let mut client = peer;
+        let hops = forwarded_for.iter().flat_map(|header| header.split(',')).rev();
+        for hop in hops {
+            if !self.contains(client) {
+                break;
+            }
+            match hop.trim().parse() {
+                Ok(ip) => client = ip,
+                Err(_) => break,
+            }
+        }
+        client
+    }
+}
+
+/// The client a request comes from, recorded on the sessions it opens and counted by the login
+/// throttle. See `TrustedProxies` for how the address is found.
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+    TrustedProxies: FromRef<S>,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let forwarded_for: Vec<&str> = parts.headers.get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        let ip = parts.extensions.get::<ConnectInfo<SocketAddr>>()
+            .map(|ci| TrustedProxies::from_ref(state).client_ip(ci.0.ip(), &forwarded_for).to_string());
+        let user_agent = parts.headers.get(axum::http::header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.chars().take(256).collect());
+        Ok(Self { user_agent, ip })
+    }
+}
+
+/// A session as listed to its owner.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionInfo {
+    pub id:           Uuid,
+    pub user_agent:   Option<String>,
+    pub ip:           Option<String>,
+    pub created_at:   DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at:   DateTime<Utc>,
+    /// True for the session making the request.
+    pub current:      bool,
+}
+
 /// Returned by `validate_session` - raw strings so this module stays independent of middleware types.
 pub struct SessionUser {
     pub user_id: Uuid,
     pub username: String,
     pub role: String,
+    pub group_ids: Vec<Uuid>,
 }
 
 /* ============================================================================================== */
@@ -45,16 +153,27 @@ pub fn extract_cookie(headers: &axum::http::HeaderMap) -> Option<String> {
 
 /* ============================================================================================== */
 /// Inserts a new session row and returns the raw (unhashed) token to set as a cookie.
-pub async fn create_session(pool: &PgPool, user_id: Uuid, ttl_hours: u64) -> Result<String, AppError> {
+pub async fn create_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    policy: SessionPolicy,
+    client: &ClientInfo,
+) -> Result<String, AppError> {
     let token = generate_session_token();
     let token_hash = hash_token(&token);
-    let expires_at = Utc::now() + Duration::hours(ttl_hours as i64);
+    let now = Utc::now();
+    let expires_at = policy.expires_at(now, now);
 
     sqlx::query!(
-        "INSERT INTO portal.sessions (user_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+        "
+        INSERT INTO portal.sessions (user_id, token_hash, expires_at, user_agent, ip)
+        VALUES ($1, $2, $3, $4, $5)
+        ",
         user_id,
         token_hash,
-        expires_at
+        expires_at,
+        client.user_agent,
+        client.ip,
     )
     .execute(pool)
     .await?;
@@ -63,13 +182,17 @@ pub async fn create_session(pool: &PgPool, user_id: Uuid, ttl_hours: u64) -> Res
 }
 
 /* ============================================================================================== */
-/// Validates a raw token against the DB, returning the associated user or Unauthorized.
-pub async fn validate_session(pool: &PgPool, token: &str) -> Result<SessionUser, AppError> {
+/// Validates a raw token against the DB, returning the associated user (with their group
+/// memberships) or Unauthorized. Renews the session's expiry (sliding expiry), at most once
+/// every `RENEW_AFTER_SECONDS`.
+pub async fn validate_session(pool: &PgPool, token: &str, policy: SessionPolicy) -> Result<SessionUser, AppError> {
     let token_hash = hash_token(token);
 
     let row = sqlx::query!(
         r#"
-        SELECT u.id AS user_id, u.username, u.role
+        SELECT s.id AS session_id, s.created_at, s.last_seen_at,
+               u.id AS user_id, u.username, u.role,
+               ARRAY(SELECT m.group_id FROM portal.group_members m WHERE m.user_id = u.id) AS "group_ids!"
         FROM portal.sessions s
         JOIN portal.users u ON s.user_id = u.id
         WHERE s.token_hash = $1 AND s.expires_at > now()
@@ -80,10 +203,23 @@ pub async fn validate_session(pool: &PgPool, token: &str) -> Result<SessionUser,
     .await?
     .ok_or(AppError::Unauthorized)?;
 
+    let now = Utc::now();
+    if now - row.last_seen_at > Duration::seconds(RENEW_AFTER_SECONDS) {
+        sqlx::query!(
+            "UPDATE portal.sessions SET last_seen_at = $2, expires_at = $3 WHERE id = $1",
+            row.session_id,
+            now,
+            policy.expires_at(row.created_at, now),
+        )
+        .execute(pool)
+        .await?;
+    }
+
     Ok(SessionUser {
         user_id: row.user_id,
         username: row.username,
         role: row.role,
+        group_ids: row.group_ids,
     })
 }
 
@@ -99,4 +235,139 @@ pub async fn delete_session(pool: &PgPool, token: &str) -> Result<(), AppError>
     .execute(pool)
     .await?;
     Ok(())
+}
+
+/* ============================================================================================== */
+/// A user's unexpired sessions, most recently used first. `current_token` marks the caller's.
+pub async fn list_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    current_token: Option<&str>,
+) -> Result<Vec<SessionInfo>, AppError> {
+    let current_hash = current_token.map(hash_token);
+    let sessions = sqlx::query_as!(
+        SessionInfo,
+        r#"
+        SELECT id, user_agent, ip, created_at, last_seen_at, expires_at,
+               token_hash IS NOT DISTINCT FROM $2 AS "current!"
+        FROM portal.sessions
+        WHERE user_id = $1 AND expires_at > now()
+        ORDER BY last_seen_at DESC
+        "#,
+        user_id,
+        current_hash,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(sessions)
+}
+
+/* ============================================================================================== */
+/// Deletes one of a user's sessions, returning whether it was the one of `current_token`.
+pub async fn revoke_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    current_token: Option<&str>,
+) -> Result<bool, AppError> {
+    let token_hash = sqlx::query_scalar!(
+        "DELETE FROM portal.sessions WHERE id = $1 AND user_id = $2 RETURNING token_hash",
+        id,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("session not found".into()))?;
+
+    Ok(current_token.map(hash_token) == Some(token_hash))
+}
+
+/* ============================================================================================== */
+/// Deletes all of a user's sessions except the one of `keep_token`, if given (it is kept only
+/// if it belongs to the user). Returns how many were deleted.
+pub async fn revoke_user_sessions(pool: &PgPool, user_id: Uuid, keep_token: Option<&str>) -> Result<u64, AppError> {
+    let keep_hash = keep_token.map(hash_token);
+    let result = sqlx::query!(
+        "DELETE FROM portal.sessions WHERE user_id = $1 AND token_hash IS DISTINCT FROM $2",
+        user_id,
+        keep_hash,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/* ============================================================================================== */
+/// Background loop: deletes expired sessions. Runs once immediately, then every `PURGE_INTERVAL`.
+pub async fn run_session_purger(pool: PgPool) {
+    loop {
+        match sqlx::query!("DELETE FROM portal.sessions WHERE expires_at <= now()").execute(&pool).await {
+            Ok(result) if result.rows_affected() > 0 => {
+                tracing::info!("Purged {} expired sessions", result.rows_affected());
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Failed to purge expired sessions: {e}"),
+        }
+        tokio::time::sleep(PURGE_INTERVAL).await;
+    }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry_slides_until_the_absolute_limit() {
+        let policy = SessionPolicy { ttl_hours: 24, max_days: 7 };
+        let created = Utc::now();
+
+        assert_eq!(policy.expires_at(created, created), created + Duration::hours(24));
+        let later = created + Duration::days(3);
+        assert_eq!(policy.expires_at(created, later), later + Duration::hours(24));
+        let last_day = created + Duration::days(6) + Duration::hours(12);
+        assert_eq!(policy.expires_at(created, last_day), created + Duration::days(7));
+    }
+
+    #[tokio::test]
+    async fn client_info_ignores_forwarded_for_from_untrusted_peers() {
+        let request = axum::http::Request::builder()
+            .header("x-forwarded-for", "203.0.113.7")
+            .header("user-agent", "Firefox")
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        parts.extensions.insert(ConnectInfo(SocketAddr::from(([198, 51, 100, 9], 4000))));
+
+        let client = ClientInfo::from_request_parts(&mut parts, &TrustedProxies::default()).await.unwrap();
+        assert_eq!(client.ip.as_deref(), Some("198.51.100.9"));
+        assert_eq!(client.user_agent.as_deref(), Some("Firefox"));
+    }
+
+    #[tokio::test]
+    async fn client_info_takes_the_rightmost_untrusted_hop_behind_a_trusted_proxy() {
+        let trusted = TrustedProxies(vec!["10.0.0.0/8".parse().unwrap()]);
+        let request = axum::http::Request::builder()
+            // Spoofed by the client, then its real address, then an inner proxy.
+            .header("x-forwarded-for", "1.2.3.4, 203.0.113.7")
+            .header("x-forwarded-for", "10.0.0.3")
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        parts.extensions.insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 2], 4000))));
+
+        let client = ClientInfo::from_request_parts(&mut parts, &trusted).await.unwrap();
+        assert_eq!(client.ip.as_deref(), Some("203.0.113.7"));
+
+        parts.headers.remove("x-forwarded-for");
+        let client = ClientInfo::from_request_parts(&mut parts, &trusted).await.unwrap();
+        assert_eq!(client.ip.as_deref(), Some("10.0.0.2"));
+
+        parts.headers.insert("x-forwarded-for", "garbage, 10.0.0.3".parse().unwrap());
+        let client = ClientInfo::from_request_parts(&mut parts, &trusted).await.unwrap();
+        assert_eq!(client.ip.as_deref(), Some("10.0.0.3"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+use crate::error::AppError;
+
+/// A count restarts after this long without further attempts.
+const WINDOW_MINUTES: i64 = 60;
+
+/// When the attempts counted against one username or address start to slow down, and when
+/// they lock it out.
+struct Limits {
+    /// From this many, each attempt waits twice as long as the last, starting at one second...
+    backoff_after: i32,
+    /// ...until this many lock it out for `lockout_minutes`, and every attempt after that again.
+    lockout_after: i32,
+    lockout_minutes: i64,
+}
+
+/// A few typos are free; ten guesses at one account lock it for a quarter of an hour.
+const USERNAME_LIMITS: Limits = Limits { backoff_after: 3, lockout_after: 10, lockout_minutes: 15 };
+/// Laxer, since one address may be a whole office behind NAT; this catches password spraying
+/// across many usernames.
+const ADDRESS_LIMITS: Limits = Limits { backoff_after: 10, lockout_after: 50, lockout_minutes: 15 };
+
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    Username,
+    Address,
+}
+
+impl Scope {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Username => "username",
+            Self::Address  => "ip",
+        }
+    }
+
+    fn limits(self) -> &'static Limits {
+        match self {
+            Self::Username => &USERNAME_LIMITS,
+            Self::Address  => &ADDRESS_LIMITS,
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Policy                                             */
+/* ============================================================================================== */
+
+/// How long to refuse further attempts after the `attempts`-th one in a row.
+fn lock_for(limits: &Limits, attempts: i32) -> Option<Duration> {
+    let lockout = Duration::minutes(limits.lockout_minutes);
+    if attempts >= limits.lockout_after {
+        Some(lockout)
+    } else if attempts >= limits.backoff_after {
+        let doublings = (attempts - limits.backoff_after).min(30) as u32;
+        Some(Duration::seconds(1i64 << doublings).min(lockout))
+    } else {
+        None
+    }
+}
+
+/// Whole seconds until `until`, at least one, for the Retry-After header.
+fn retry_after(until: DateTime<Utc>, now: DateTime<Utc>) -> u64 {
+    (until - now).num_seconds().max(1) as u64
+}
+
+/* ============================================================================================== */
+/*                                            Database                                            */
+/* ============================================================================================== */
+
+/// Counts a sign-in attempt against the username and, if known, the client address, or refuses
+/// it with 429 while either is locked.
+///
+/// Attempts are counted before the password is checked, so that a burst of parallel requests
+/// cannot slip through before the first failure is recorded; `refund_address` and `reset` take
+/// them back once the credentials turn out to be right. The username is counted whether or not
+/// such an account exists.
+pub async fn charge(pool: &PgPool, username: &str, ip: Option<&str>) -> Result<(), AppError> {
+    sqlx::query!(
+        "
+        DELETE FROM portal.failed_login_attempts
+        WHERE last_failed_at < now() - make_interval(mins => $1)
+          AND (locked_until IS NULL OR locked_until < now())
+        ",
+        WINDOW_MINUTES as i32
+    )
+    .execute(pool)
+    .await?;
+
+    if let Some(ip) = ip {
+        charge_one(pool, Scope::Address, ip).await?;
+    }
+    charge_one(pool, Scope::Username, username).await
+}
+
+async fn charge_one(pool: &PgPool, scope: Scope, key: &str) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        "
+        INSERT INTO portal.failed_login_attempts (scope, key) VALUES ($1, $2)
+        ON CONFLICT (scope, key) DO NOTHING
+        ",
+        scope.as_str(),
+        key
+    )
+    .execute(&mut *tx)
+    .await?;
+    // The row lock serialises concurrent attempts on the same key.
+    let row = sqlx::query!(
+        "
+        SELECT failures, last_failed_at, locked_until
+        FROM portal.failed_login_attempts
+        WHERE scope = $1 AND key = $2
+        FOR UPDATE
+        ",
+        scope.as_str(),
+        key
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let now = Utc::now();
+    if let Some(until) = row.locked_until.filter(|until| *until > now) {
+        return Err(AppError::TooManyRequests(retry_after(until, now)));
+    }
+    let stale = now - row.last_failed_at > Duration::minutes(WINDOW_MINUTES);
+    let attempts = if stale { 1 } else { row.failures + 1 };
+    let locked_until = lock_for(scope.limits(), attempts).map(|d| now + d);
+
+    sqlx::query!(
+        "
+        UPDATE portal.failed_login_attempts
+        SET failures = $3, last_failed_at = $4, locked_until = $5
+        WHERE scope = $1 AND key = $2
+        ",
+        scope.as_str(),
+        key,
+        attempts,
+        now,
+        locked_until
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/// Takes back the attempt `charge` counted against the address, and the backoff it may have
+/// started, once the credentials were right, so that an office signing in all day is never
+/// slowed down.
+pub async fn refund_address(pool: &PgPool, ip: Option<&str>) -> Result<(), AppError> {
+    let Some(ip) = ip else { return Ok(()) };
+    sqlx::query!(
+        "
+        UPDATE portal.failed_login_attempts
+        SET failures = GREATEST(failures - 1, 0), locked_until = NULL
+        WHERE scope = $1 AND key = $2
+        ",
+        Scope::Address.as_str(),
+        ip
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/// Forgets the attempts against a username: after a sign-in, or when an admin unlocks it.
+pub async fn reset(pool: &PgPool, username: &str) -> Result<(), AppError> {
+    sqlx::query!(
+        "DELETE FROM portal.failed_login_attempts WHERE scope = $1 AND key = $2",
+        Scope::Username.as_str(),
+        username
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_then_locks_out() {
+        let delays: Vec<_> = (1..=11).map(|n| lock_for(&USERNAME_LIMITS, n).map(|d| d.num_seconds())).collect();
+        assert_eq!(
+            delays,
+            [None, None, Some(1), Some(2), Some(4), Some(8), Some(16), Some(32), Some(64), Some(900), Some(900)]
+        );
+        // Addresses never back off past the lockout, however long the run.
+        assert_eq!(lock_for(&ADDRESS_LIMITS, 45).map(|d| d.num_minutes()), Some(15));
+    }
+
+    #[test]
+    fn retry_after_rounds_to_at_least_a_second() {
+        let now = Utc::now();
+        assert_eq!(retry_after(now + Duration::milliseconds(200), now), 1);
+        assert_eq!(retry_after(now + Duration::seconds(90), now), 90);
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_right_password_lifts_the_address_backoff(pool: PgPool) {
+        let ip = Some("203.0.113.7");
+        for n in 0..ADDRESS_LIMITS.backoff_after {
+            charge(&pool, &format!("user{n}"), ip).await.unwrap();
+        }
+        // The last wrong guess started a backoff for the address...
+        assert!(matches!(charge(&pool, "someone", ip).await, Err(AppError::TooManyRequests(_))));
+
+        // ...which the next right password lifts.
+        refund_address(&pool, ip).await.unwrap();
+        charge(&pool, "someone", ip).await.unwrap();
+    }
+}
@@ -0,0 +1,445 @@
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use super::session;
+
+/// Shown by authenticator apps next to the account name.
+const ISSUER: &str = "Mosaic";
+/// RFC 6238 defaults, which every authenticator app supports.
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+/// Codes of the previous and next time step are accepted too, for clock drift.
+const DRIFT_STEPS: i64 = 1;
+
+const RECOVERY_CODES: usize = 10;
+/// A password-verified login waits this long for its second factor, for this many tries. A
+/// signed-in user gets as many tries at a code within as long, see `attempt_code`.
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+const CHALLENGE_ATTEMPTS: i32 = 5;
+
+/// Returned by `begin_enrollment`: the secret as typed into an app, and as a QR code.
+pub struct Enrollment {
+    pub secret: String,
+    pub uri:    String,
+    pub qr_svg: String,
+}
+
+/* ============================================================================================== */
+/*                                            Algorithm                                           */
+/* ============================================================================================== */
+
+/// Unpadded RFC 4648 base32, the encoding authenticator apps expect secrets in.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    for chunk in bytes.chunks(5) {
+        let mut buf = [0u8; 5];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let bits = buf.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            out.push(ALPHABET[((bits >> (35 - i * 5)) & 31) as usize] as char);
+        }
+    }
+    out
+}
+
+/// The code for a time step (RFC 4226 dynamic truncation of HMAC-SHA1).
+fn code_at(secret: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = usize::from(digest[19] & 0x0f);
+    let value = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]]);
+    (value & 0x7fff_ffff) % 10u32.pow(DIGITS)
+}
+
+/// The time step, within the drift window around `unix_time`, whose code is `code`.
+fn matching_step(secret: &[u8], code: &str, unix_time: i64) -> Option<i64> {
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let now = unix_time / STEP_SECONDS;
+    (now - DRIFT_STEPS..=now + DRIFT_STEPS).find(|step| code_at(secret, *step) == code)
+}
+
+/// Strips the spaces and dashes people type into codes.
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/* ============================================================================================== */
+/// The `otpauth://` URI that authenticator apps import, from a QR code or by hand.
+fn provisioning_uri(username: &str, secret: &[u8]) -> String {
+    let mut uri = reqwest::Url::parse("otpauth://totp/").expect("static URL is valid");
+    uri.path_segments_mut()
+        .expect("otpauth URLs have a path")
+        .pop_if_empty()
+        .push(&format!("{ISSUER}:{username}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", &base32(secret))
+        .append_pair("issuer", ISSUER)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP_SECONDS.to_string());
+    uri.into()
+}
+
+fn qr_svg(uri: &str) -> Result<String, AppError> {
+    let code = qrcode::QrCode::new(uri.as_bytes())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("QR encoding failed: {e}")))?;
+    Ok(code.render::<qrcode::render::svg::Color>().min_dimensions(200, 200).build())
+}
+
+/// Fresh recovery codes, 64 random bits each, as `xxxx-xxxx-xxxx-xxxx`.
+fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODES)
+        .map(|_| {
+            let mut bytes = [0u8; 8];
+            rand::fill(&mut bytes);
+            let hex = hex::encode(bytes);
+            format!("{}-{}-{}-{}", &hex[0..4], &hex[4..8], &hex[8..12], &hex[12..16])
+        })
+        .collect()
+}
+
+/* ============================================================================================== */
+/*                                           Enrollment                                           */
+/* ============================================================================================== */
+
+/// Whether the user has two-factor authentication on, and how many recovery codes are unused.
+pub async fn status(pool: &PgPool, user_id: Uuid) -> Result<(bool, i64), AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT u.totp_secret IS NOT NULL AS "enabled!",
+               (SELECT COUNT(*) FROM portal.recovery_codes r
+                WHERE r.user_id = u.id AND r.used_at IS NULL) AS "recovery_codes_left!"
+        FROM portal.users u
+        WHERE u.id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("user not found".into()))?;
+
+    Ok((row.enabled, row.recovery_codes_left))
+}
+
+/* ============================================================================================== */
+/// Generates a new secret, pending until `confirm_enrollment`. Fails if 2FA is already on.
+pub async fn begin_enrollment(pool: &PgPool, user_id: Uuid) -> Result<Enrollment, AppError> {
+    let mut secret = [0u8; 20];
+    rand::fill(&mut secret);
+
+    let username = sqlx::query_scalar!(
+        r#"
+        UPDATE portal.users SET totp_pending_secret = $2
+        WHERE id = $1 AND totp_secret IS NULL
+        RETURNING username
+        "#,
+        user_id,
+        hex::encode(secret)
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::Validation("two-factor authentication is already enabled".into()))?;
+
+    let uri = provisioning_uri(&username, &secret);
+    Ok(Enrollment { secret: base32(&secret), qr_svg: qr_svg(&uri)?, uri })
+}
+
+/* ============================================================================================== */
+/// Turns 2FA on once `code` shows the authenticator holds the pending secret, and returns the
+/// first recovery codes.
+pub async fn confirm_enrollment(pool: &PgPool, user_id: Uuid, code: &str) -> Result<Vec<String>, AppError> {
+    let pending = sqlx::query_scalar!(
+        "SELECT totp_pending_secret FROM portal.users WHERE id = $1 AND totp_secret IS NULL",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .flatten()
+    .ok_or_else(|| AppError::Validation("no two-factor setup in progress".into()))?;
+    let secret = hex::decode(&pending).map_err(|e| AppError::Internal(e.into()))?;
+
+    let step = matching_step(&secret, &normalize(code), Utc::now().timestamp())
+        .ok_or_else(|| AppError::Validation("the code does not match; check the device's clock".into()))?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"
+        UPDATE portal.users
+        SET totp_secret = totp_pending_secret, totp_pending_secret = NULL, totp_last_step = $2,
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        user_id,
+        step
+    )
+    .execute(&mut *tx)
+    .await?;
+    let codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+
+    Ok(codes)
+}
+
+/* ============================================================================================== */
+/// Replaces the user's recovery codes with fresh ones and returns them.
+pub async fn regenerate_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<Vec<String>, AppError> {
+    let mut tx = pool.begin().await?;
+    let codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+    Ok(codes)
+}
+
+async fn replace_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>, AppError> {
+    let codes = generate_recovery_codes();
+    let hashes: Vec<String> = codes.iter().map(|c| session::hash_token(&normalize(c))).collect();
+
+    sqlx::query!("DELETE FROM portal.recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO portal.recovery_codes (user_id, code_hash) SELECT $1, UNNEST($2::text[])",
+        user_id,
+        &hashes
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(codes)
+}
+
+/* ============================================================================================== */
+/// Turns 2FA off and forgets the secret and recovery codes. Used by the user and by admins.
+pub async fn disable(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+    let result = sqlx::query!(
+        r#"
+        UPDATE portal.users
+        SET totp_secret = NULL, totp_pending_secret = NULL, totp_last_step = NULL, updated_at = now()
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("user not found".into()));
+    }
+    sqlx::query!("DELETE FROM portal.recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/*                                          Verification                                          */
+/* ============================================================================================== */
+
+/// Checks a second factor: a current code from the authenticator, or an unused recovery code,
+/// which is then used up. A code is never accepted twice.
+pub async fn verify(pool: &PgPool, user_id: Uuid, code: &str) -> Result<bool, AppError> {
+    let code = normalize(code);
+
+    if code.len() == DIGITS as usize {
+        let Some(secret) = sqlx::query_scalar!(
+            "SELECT totp_secret FROM portal.users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten() else {
+            return Ok(false);
+        };
+        let secret = hex::decode(&secret).map_err(|e| AppError::Internal(e.into()))?;
+        let Some(step) = matching_step(&secret, &code, Utc::now().timestamp()) else {
+            return Ok(false);
+        };
+        // Only steps after the last accepted one, so a code seen over a shoulder is spent.
+        let accepted = sqlx::query!(
+            r#"
+            UPDATE portal.users SET totp_last_step = $2
+            WHERE id = $1 AND (totp_last_step IS NULL OR totp_last_step < $2)
+            "#,
+            user_id,
+            step
+        )
+        .execute(pool)
+        .await?;
+        return Ok(accepted.rows_affected() == 1);
+    }
+
+    let used = sqlx::query!(
+        r#"
+        UPDATE portal.recovery_codes SET used_at = now()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+        user_id,
+        session::hash_token(&code)
+    )
+    .execute(pool)
+    .await?;
+    Ok(used.rows_affected() == 1)
+}
+
+/* ============================================================================================== */
+/// Records a login that passed the password check and returns the raw challenge token that the
+/// client exchanges, with a code, for a session: `attempt_challenge` counts each try and
+/// `delete_challenge` spends the token once the code is right.
+pub async fn create_challenge(pool: &PgPool, user_id: Uuid) -> Result<String, AppError> {
+    let token = session::generate_session_token();
+    let expires_at = Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES);
+
+    sqlx::query!("DELETE FROM portal.login_challenges WHERE expires_at < now()")
+        .execute(pool)
+        .await?;
+    sqlx::query!(
+        "INSERT INTO portal.login_challenges (token_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+        session::hash_token(&token),
+        user_id,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(token)
+}
+
+/* ============================================================================================== */
+/// Counts an attempt at a challenge and returns its user, or Unauthorized if the challenge is
+/// unknown, expired or out of attempts.
+pub async fn attempt_challenge(pool: &PgPool, token: &str) -> Result<Uuid, AppError> {
+    sqlx::query_scalar!(
+        r#"
+        UPDATE portal.login_challenges SET attempts = attempts + 1
+        WHERE token_hash = $1 AND expires_at > now() AND attempts < $2
+        RETURNING user_id
+        "#,
+        session::hash_token(token),
+        CHALLENGE_ATTEMPTS
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AppError::Unauthorized)
+}
+
+/// Deletes a challenge once it has been answered.
+pub async fn delete_challenge(pool: &PgPool, token: &str) -> Result<(), AppError> {
+    sqlx::query!(
+        "DELETE FROM portal.login_challenges WHERE token_hash = $1",
+        session::hash_token(token)
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/// Counts a signed-in user's attempt at a code, to enable or disable 2FA or replace the recovery
+/// codes, or refuses it with 429 once the user has had as many as a login challenge allows. The
+/// count starts over after as long as a challenge lives, or once `forget_attempts` is called.
+pub async fn attempt_code(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO portal.totp_attempts (user_id, attempts, expires_at)
+        VALUES ($1, 1, now() + make_interval(mins => $2))
+        ON CONFLICT (user_id) DO UPDATE SET
+            attempts   = CASE WHEN totp_attempts.expires_at > now()
+                              THEN totp_attempts.attempts + 1 ELSE 1 END,
+            expires_at = CASE WHEN totp_attempts.expires_at > now()
+                              THEN totp_attempts.expires_at ELSE EXCLUDED.expires_at END
+        RETURNING attempts, expires_at
+        "#,
+        user_id,
+        CHALLENGE_TTL_MINUTES as i32
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if row.attempts > CHALLENGE_ATTEMPTS {
+        let wait = (row.expires_at - Utc::now()).num_seconds().max(1) as u64;
+        return Err(AppError::TooManyRequests(wait));
+    }
+    Ok(())
+}
+
+/// Forgets a user's attempts at a code once one was right.
+pub async fn forget_attempts(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    sqlx::query!("DELETE FROM portal.totp_attempts WHERE user_id = $1", user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-1 secret of the RFC 6238 test vectors.
+    const SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn codes_match_the_rfc_vectors() {
+        // RFC 6238 appendix B lists 8 digits; the last 6 are the 6-digit code.
+        for (time, code) in [(59, 287_082), (1_111_111_109, 81_804), (1_234_567_890, 5_924), (2_000_000_000, 279_037)] {
+            assert_eq!(code_at(SECRET, time / STEP_SECONDS), code, "T = {time}");
+        }
+    }
+
+    #[test]
+    fn accepts_codes_within_the_drift_window() {
+        let now = 1_111_111_109;
+        let step = now / STEP_SECONDS;
+        let code = |step| format!("{:06}", code_at(SECRET, step));
+
+        assert_eq!(matching_step(SECRET, &code(step), now), Some(step));
+        assert_eq!(matching_step(SECRET, &code(step - 1), now), Some(step - 1));
+        assert_eq!(matching_step(SECRET, &code(step + 1), now), Some(step + 1));
+        assert_eq!(matching_step(SECRET, &code(step - 2), now), None);
+        assert_eq!(matching_step(SECRET, "12345", now), None);
+        assert_eq!(matching_step(SECRET, "abcdef", now), None);
+    }
+
+    #[test]
+    fn encodes_secrets_in_base32() {
+        assert_eq!(base32(b""), "");
+        assert_eq!(base32(b"f"), "MY");
+        assert_eq!(base32(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32(SECRET), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    }
+
+    #[test]
+    fn provisioning_uri_names_the_account() {
+        let uri = provisioning_uri("jane doe", SECRET);
+        assert!(uri.starts_with("otpauth://totp/Mosaic:jane%20doe?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Mosaic"));
+        assert!(qr_svg(&uri).unwrap().starts_with("<?xml"));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_normalize() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODES);
+        assert_eq!(codes.iter().collect::<std::collections::HashSet<_>>().len(), RECOVERY_CODES);
+        assert_eq!(normalize(" ABCD-ef01 2345-6789 "), "abcdef0123456789");
+        assert_eq!(normalize(&codes[0]).len(), 16);
+    }
+}
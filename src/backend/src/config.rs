@@ -1,4 +1,7 @@
 use std::env;
+use std::net::IpAddr;
+
+use ipnet::IpNet;
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
@@ -9,8 +12,33 @@ pub struct AppConfig {
     pub nocodb_internal_url:            String,
     pub nocodb_api_token:               String,
     pub session_secret:                 String,
+    /// A session expires this long after it was last used...
     pub session_ttl_hours:              u64,
+    /// ...and this long after sign-in, however active.
+    pub session_max_days:               u64,
+    pub trash_retention_days:           u64,
     pub bind_address:                   String,
+    /// Reverse proxies (addresses or CIDR ranges) whose `X-Forwarded-For` is believed.
+    pub trusted_proxies:                Vec<IpNet>,
+    /// Single sign-on; None unless `OIDC_ISSUER_URL` is set.
+    pub oidc:                           Option<OidcConfig>,
+}
+
+/// An OpenID Connect provider to sign in with, besides local passwords.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer_url:     String,
+    pub client_id:      String,
+    /// None for public clients, which rely on PKCE alone.
+    pub client_secret:  Option<String>,
+    /// This portal's `/api/auth/oidc/callback`, as registered with the provider.
+    pub redirect_url:   String,
+    pub scopes:         String,
+    /// ID token claim holding the user's groups or roles.
+    pub role_claim:     String,
+    /// Claim values granting the admin and editor roles; anyone else is a viewer.
+    pub admin_values:   Vec<String>,
+    pub editor_values:  Vec<String>,
 }
 
 impl AppConfig {
@@ -28,8 +56,53 @@ impl AppConfig {
                                                 .unwrap_or_else(|_| "168".to_string())
                                                 .parse()
                                                 .expect("SESSION_TTL_HOURS must be a valid u64"),
+            session_max_days:               env::var("SESSION_MAX_DAYS")
+                                                .unwrap_or_else(|_| "30".to_string())
+                                                .parse()
+                                                .expect("SESSION_MAX_DAYS must be a valid u64"),
+            trash_retention_days:           env::var("TRASH_RETENTION_DAYS")
+                                                .unwrap_or_else(|_| "30".to_string())
+                                                .parse()
+                                                .expect("TRASH_RETENTION_DAYS must be a valid u64"),
             bind_address:                   env::var("BIND_ADDRESS")
                                                 .unwrap_or_else(|_| "0.0.0.0:8080".to_string()),
+            trusted_proxies:                env::var("TRUSTED_PROXIES")
+                                                .unwrap_or_default()
+                                                .split(',')
+                                                .map(str::trim)
+                                                .filter(|s| !s.is_empty())
+                                                .map(|s| s.parse().or_else(|_| s.parse::<IpAddr>().map(IpNet::from)))
+                                                .collect::<Result<_, _>>()
+                                                .expect("TRUSTED_PROXIES must list addresses or CIDR ranges"),
+            oidc:                           OidcConfig::from_env()?,
         })
     }
+}
+
+impl OidcConfig {
+    fn from_env() -> Result<Option<Self>, env::VarError> {
+        let Some(issuer_url) = env::var("OIDC_ISSUER_URL").ok().filter(|s| !s.is_empty()) else {
+            return Ok(None);
+        };
+        let list = |name: &str| -> Vec<String> {
+            env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+        Ok(Some(Self {
+            issuer_url,
+            client_id:      env::var("OIDC_CLIENT_ID")?,
+            client_secret:  env::var("OIDC_CLIENT_SECRET").ok().filter(|s| !s.is_empty()),
+            redirect_url:   env::var("OIDC_REDIRECT_URL")?,
+            scopes:         env::var("OIDC_SCOPES")
+                                .unwrap_or_else(|_| "openid profile email groups".to_string()),
+            role_claim:     env::var("OIDC_ROLE_CLAIM")
+                                .unwrap_or_else(|_| "groups".to_string()),
+            admin_values:   list("OIDC_ADMIN_GROUPS"),
+            editor_values:  list("OIDC_EDITOR_GROUPS"),
+        }))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,23 @@
+-- Provisioning jobs: one row per template creation, checkpointed after each pipeline step
+-- so that a restart can resume (or compensate) instead of orphaning external resources.
+CREATE TABLE IF NOT EXISTS portal.provisioning_jobs (
+    id                    UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id               UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    name                  VARCHAR(255) NOT NULL,
+    description           TEXT,
+    fields                JSONB NOT NULL,
+    state                 VARCHAR(32) NOT NULL DEFAULT 'unstarted',
+    base_id               VARCHAR(255),
+    table_id              VARCHAR(255),
+    table_name            VARCHAR(255),
+    form_view_id          VARCHAR(255),
+    form_share_uuid       VARCHAR(255),
+    grafana_dashboard_uid VARCHAR(255),
+    grafana_dashboard_url TEXT,
+    template_id           UUID REFERENCES portal.dataset_templates(id) ON DELETE SET NULL,
+    error                 TEXT,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at            TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_provisioning_jobs_state ON portal.provisioning_jobs(state);
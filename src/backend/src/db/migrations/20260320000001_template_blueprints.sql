@@ -0,0 +1,13 @@
+-- Saved template blueprints: reusable field definitions that can be instantiated as new
+-- datasets. Saving a blueprint under an existing name adds a new version rather than
+-- overwriting the old one.
+CREATE TABLE IF NOT EXISTS portal.template_blueprints (
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name        VARCHAR(255) NOT NULL,
+    version     INT NOT NULL,
+    description TEXT,
+    fields      JSONB NOT NULL,
+    created_by  UUID REFERENCES portal.users(id) ON DELETE SET NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (name, version)
+);
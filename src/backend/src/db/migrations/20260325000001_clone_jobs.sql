@@ -0,0 +1,4 @@
+-- Clone jobs: the template whose rows are copied into the new table before it is registered.
+-- Deliberately not a foreign key, so deleting the source mid-clone fails the job instead of
+-- silently producing an empty copy.
+ALTER TABLE portal.provisioning_jobs ADD COLUMN IF NOT EXISTS copy_rows_from UUID;
@@ -0,0 +1,13 @@
+-- Soft delete: rows with `deleted_at` set are in the trash. They are hidden from the API until
+-- restored, and purged for good (with any NocoDB table and Grafana dashboard) once the trash
+-- retention has passed.
+ALTER TABLE portal.dataset_templates ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE portal.dashboards        ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE portal.panels            ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_dataset_templates_deleted_at ON portal.dataset_templates(deleted_at)
+    WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_dashboards_deleted_at ON portal.dashboards(deleted_at)
+    WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_panels_deleted_at ON portal.panels(deleted_at)
+    WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,19 @@
+-- Template ownership and sharing. Every user could see every template before, so existing
+-- templates start out public; new ones are private to their owner until shared.
+ALTER TABLE portal.dataset_templates
+    ADD COLUMN IF NOT EXISTS owner_id   UUID REFERENCES portal.users(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS visibility VARCHAR(16) NOT NULL DEFAULT 'public';
+ALTER TABLE portal.dataset_templates ALTER COLUMN visibility SET DEFAULT 'private';
+UPDATE portal.dataset_templates SET owner_id = created_by WHERE owner_id IS NULL;
+
+-- Per-user grants on top of the visibility: read < enter (records) < manage (schema, sharing).
+CREATE TABLE IF NOT EXISTS portal.template_acl (
+    template_id UUID NOT NULL REFERENCES portal.dataset_templates(id) ON DELETE CASCADE,
+    user_id     UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    permission  VARCHAR(16) NOT NULL CHECK (permission IN ('read', 'enter', 'manage')),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (template_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_template_acl_user_id ON portal.template_acl(user_id);
@@ -0,0 +1,12 @@
+-- Per-user grants on a dashboard, on top of the all-or-nothing `is_shared` flag:
+-- view < edit (panels and their layout). Only the owner and admins change the dashboard itself.
+CREATE TABLE IF NOT EXISTS portal.dashboard_shares (
+    dashboard_id UUID NOT NULL REFERENCES portal.dashboards(id) ON DELETE CASCADE,
+    user_id      UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    permission   VARCHAR(16) NOT NULL CHECK (permission IN ('view', 'edit')),
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (dashboard_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dashboard_shares_user_id ON portal.dashboard_shares(user_id);
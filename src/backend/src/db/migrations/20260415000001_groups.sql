@@ -0,0 +1,37 @@
+-- User groups, managed by admins.
+CREATE TABLE IF NOT EXISTS portal.groups (
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name        VARCHAR(64) NOT NULL UNIQUE,
+    description TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS portal.group_members (
+    group_id   UUID NOT NULL REFERENCES portal.groups(id) ON DELETE CASCADE,
+    user_id    UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (group_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_group_members_user_id ON portal.group_members(user_id);
+
+-- Template grants and dashboard shares may now name a group instead of a user. Exactly one of
+-- the two is set; user and group ids are random UUIDs, so one unique index covers both.
+ALTER TABLE portal.template_acl DROP CONSTRAINT IF EXISTS template_acl_pkey;
+ALTER TABLE portal.template_acl
+    ALTER COLUMN user_id DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES portal.groups(id) ON DELETE CASCADE,
+    ADD CONSTRAINT template_acl_grantee_check CHECK (num_nonnulls(user_id, group_id) = 1);
+CREATE UNIQUE INDEX IF NOT EXISTS template_acl_grantee_key
+    ON portal.template_acl (template_id, (COALESCE(user_id, group_id)));
+CREATE INDEX IF NOT EXISTS idx_template_acl_group_id ON portal.template_acl(group_id);
+
+ALTER TABLE portal.dashboard_shares DROP CONSTRAINT IF EXISTS dashboard_shares_pkey;
+ALTER TABLE portal.dashboard_shares
+    ALTER COLUMN user_id DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS group_id UUID REFERENCES portal.groups(id) ON DELETE CASCADE,
+    ADD CONSTRAINT dashboard_shares_grantee_check CHECK (num_nonnulls(user_id, group_id) = 1);
+CREATE UNIQUE INDEX IF NOT EXISTS dashboard_shares_grantee_key
+    ON portal.dashboard_shares (dashboard_id, (COALESCE(user_id, group_id)));
+CREATE INDEX IF NOT EXISTS idx_dashboard_shares_group_id ON portal.dashboard_shares(group_id);
@@ -0,0 +1,13 @@
+-- Read-only links to a dashboard for people without an account. The token is derived from the
+-- id and the session secret, so only the row is stored; revoking or expiring it kills the link.
+CREATE TABLE IF NOT EXISTS portal.public_links (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    dashboard_id UUID NOT NULL REFERENCES portal.dashboards(id) ON DELETE CASCADE,
+    label        VARCHAR(128) NOT NULL DEFAULT '',
+    created_by   UUID REFERENCES portal.users(id) ON DELETE SET NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at   TIMESTAMPTZ NOT NULL,
+    revoked_at   TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_public_links_dashboard_id ON portal.public_links(dashboard_id);
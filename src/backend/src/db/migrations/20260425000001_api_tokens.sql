@@ -0,0 +1,17 @@
+-- Personal API tokens for scripts, sent as `Authorization: Bearer <token>`. Like sessions, only
+-- the SHA-256 of the token is stored; each token is limited to its scopes and the user's role.
+CREATE TABLE IF NOT EXISTS portal.api_tokens (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id      UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    name         VARCHAR(64) NOT NULL,
+    token_hash   TEXT NOT NULL UNIQUE,
+    scopes       TEXT[] NOT NULL CHECK (
+                     cardinality(scopes) > 0
+                     AND scopes <@ ARRAY['dashboards:read', 'records:write', 'admin']
+                 ),
+    expires_at   TIMESTAMPTZ NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id ON portal.api_tokens(user_id);
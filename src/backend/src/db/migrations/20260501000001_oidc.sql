@@ -0,0 +1,15 @@
+-- Single sign-on: users provisioned from an OpenID Connect provider have no local password,
+-- and are found again by their issuer and subject.
+ALTER TABLE portal.users ALTER COLUMN password_hash DROP NOT NULL;
+ALTER TABLE portal.users ADD COLUMN oidc_issuer  TEXT;
+ALTER TABLE portal.users ADD COLUMN oidc_subject TEXT;
+ALTER TABLE portal.users ADD CONSTRAINT users_oidc_identity_key UNIQUE (oidc_issuer, oidc_subject);
+
+-- Sign-ins in flight, keyed by the hash of their state parameter: the PKCE verifier and the
+-- nonce the ID token must carry. Each is used once and is only valid for a few minutes.
+CREATE TABLE IF NOT EXISTS portal.oidc_logins (
+    state_hash    TEXT PRIMARY KEY,
+    nonce         TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
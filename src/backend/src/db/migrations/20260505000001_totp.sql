@@ -0,0 +1,25 @@
+-- Two-factor authentication with time-based one-time passwords (RFC 6238). The secret is kept
+-- pending until the first code confirms the authenticator has it. `totp_last_step` is the time
+-- step of the last accepted code, so that a code cannot be used twice.
+ALTER TABLE portal.users ADD COLUMN totp_secret         TEXT;
+ALTER TABLE portal.users ADD COLUMN totp_pending_secret TEXT;
+ALTER TABLE portal.users ADD COLUMN totp_last_step      BIGINT;
+
+-- Single-use codes for when the authenticator is lost; only their SHA-256 is stored.
+CREATE TABLE IF NOT EXISTS portal.recovery_codes (
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id    UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    code_hash  TEXT NOT NULL,
+    used_at    TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, code_hash)
+);
+
+-- Logins that passed the password and wait for the second factor, keyed by the SHA-256 of the
+-- challenge token handed to the browser.
+CREATE TABLE IF NOT EXISTS portal.login_challenges (
+    token_hash TEXT PRIMARY KEY,
+    user_id    UUID NOT NULL REFERENCES portal.users(id) ON DELETE CASCADE,
+    attempts   INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Where and when each session was last used, so users can recognise and revoke their sessions.
+-- `last_seen_at` also drives the sliding expiry.
+ALTER TABLE portal.sessions ADD COLUMN user_agent   TEXT;
+ALTER TABLE portal.sessions ADD COLUMN ip           TEXT;
+ALTER TABLE portal.sessions ADD COLUMN last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON portal.sessions(expires_at);
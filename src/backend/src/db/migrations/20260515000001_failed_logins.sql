@@ -0,0 +1,14 @@
+-- Sign-in attempts that have not (yet) succeeded, counted per username and per client address,
+-- for exponential backoff and temporary lockout. Usernames are counted whether or not the
+-- account exists, so that the throttle reveals nothing about which ones do.
+CREATE TABLE IF NOT EXISTS portal.failed_login_attempts (
+    scope          TEXT NOT NULL,           -- 'username' | 'ip'
+    key            TEXT NOT NULL,
+    failures       INT NOT NULL DEFAULT 0,
+    last_failed_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    locked_until   TIMESTAMPTZ,
+    PRIMARY KEY (scope, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_failed_login_attempts_last_failed_at
+    ON portal.failed_login_attempts(last_failed_at);
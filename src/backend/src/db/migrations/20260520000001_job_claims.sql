@@ -0,0 +1,4 @@
+-- A worker claims a job by setting `claimed_until` with `FOR UPDATE SKIP LOCKED`, and each
+-- checkpoint renews it. Until it passes, no other worker (or replica) picks the job up; a
+-- job whose worker died becomes claimable again once it does.
+ALTER TABLE portal.provisioning_jobs ADD COLUMN IF NOT EXISTS claimed_until TIMESTAMPTZ;
@@ -0,0 +1,4 @@
+-- Only live dashboards hold their slug, so trashing one frees it for a new dashboard. The index
+-- keeps the old constraint's name, which is how a taken slug is recognised.
+ALTER TABLE portal.dashboards DROP CONSTRAINT IF EXISTS dashboards_slug_key;
+CREATE UNIQUE INDEX IF NOT EXISTS dashboards_slug_key ON portal.dashboards(slug) WHERE deleted_at IS NULL;
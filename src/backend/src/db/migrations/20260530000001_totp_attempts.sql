@@ -0,0 +1,7 @@
+-- Codes a signed-in user has tried in order to turn 2FA on or off or to replace their recovery
+-- codes, limited like the attempts at a login challenge.
+CREATE TABLE IF NOT EXISTS portal.totp_attempts (
+    user_id    UUID PRIMARY KEY REFERENCES portal.users(id) ON DELETE CASCADE,
+    attempts   INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL
+);
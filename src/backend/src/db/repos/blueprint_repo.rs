@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredBlueprint {
+    pub id:          Uuid,
+    pub name:        String,
+    pub version:     i32,
+    pub description: Option<String>,
+    pub fields:      JsonValue,            // serialised Vec<FieldDefinition>
+    pub created_by:  Option<Uuid>,
+    pub created_at:  DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CreateBlueprint {
+    pub name:        String,
+    pub description: Option<String>,
+    pub fields:      JsonValue,
+    pub created_by:  Uuid,
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait BlueprintRepo: Send + Sync {
+    /// Every version of every blueprint, by name then newest version first.
+    async fn list_all(&self) -> Result<Vec<StoredBlueprint>, AppError>;
+    async fn get_by_id(&self, id: Uuid) -> Result<StoredBlueprint, AppError>;
+    /// Saves `blueprint` as the next version of its name, starting from 1.
+    async fn create(&self, blueprint: CreateBlueprint) -> Result<StoredBlueprint, AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgBlueprintRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_blueprint {
+    ($r:expr) => {
+        StoredBlueprint {
+            id:          $r.id,
+            name:        $r.name,
+            version:     $r.version,
+            description: $r.description,
+            fields:      $r.fields,
+            created_by:  $r.created_by,
+            created_at:  $r.created_at,
+        }
+    };
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl BlueprintRepo for PgBlueprintRepo {
+    async fn list_all(&self) -> Result<Vec<StoredBlueprint>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, version, description, fields as "fields!: JsonValue",
+                   created_by, created_at
+            FROM portal.template_blueprints
+            ORDER BY name ASC, version DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_blueprint!(r)).collect())
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<StoredBlueprint, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, name, version, description, fields as "fields!: JsonValue",
+                   created_by, created_at
+            FROM portal.template_blueprints
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_blueprint!(r))
+        .ok_or_else(|| AppError::NotFound(format!("blueprint '{id}' not found")))
+    }
+
+    async fn create(&self, blueprint: CreateBlueprint) -> Result<StoredBlueprint, AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO portal.template_blueprints (name, version, description, fields, created_by)
+            VALUES (
+                $1::VARCHAR,
+                COALESCE((SELECT MAX(version) FROM portal.template_blueprints WHERE name = $1::VARCHAR), 0) + 1,
+                $2, $3::jsonb, $4
+            )
+            RETURNING id, name, version, description, fields as "fields!: JsonValue",
+                      created_by, created_at
+            "#,
+            blueprint.name,
+            blueprint.description,
+            blueprint.fields,
+            blueprint.created_by,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|r| map_blueprint!(r))
+        .map_err(AppError::Database)
+    }
+}
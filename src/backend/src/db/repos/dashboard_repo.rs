@@ -20,6 +20,8 @@ pub struct Dashboard {
     pub is_shared: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the dashboard is in the trash; its panels are hidden along with it.
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -46,12 +48,24 @@ pub struct UpdateDashboard {
 
 #[async_trait::async_trait]
 pub trait DashboardRepo: Send + Sync {
-    async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<Dashboard>, AppError>;
+    /// Dashboards the user owns, that are shared with everyone or that are shared with them,
+    /// directly or through one of `group_ids`.
+    async fn list_for_user(&self, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<Dashboard>, AppError>;
     async fn get_by_slug(&self, slug: &str) -> Result<Dashboard, AppError>;
     async fn get_by_id(&self, id: Uuid) -> Result<Dashboard, AppError>;
     async fn create(&self, owner_id: Uuid, input: CreateDashboard) -> Result<Dashboard, AppError>;
     async fn update(&self, id: Uuid, input: UpdateDashboard) -> Result<Dashboard, AppError>;
+    /// Permanently removes the dashboard and its panels, whether or not it is in the trash.
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    /// Dashboards in the trash, most recently deleted first.
+    async fn list_trashed(&self) -> Result<Vec<Dashboard>, AppError>;
+    async fn get_trashed(&self, id: Uuid) -> Result<Dashboard, AppError>;
+    /// Move to the trash, which frees the slug for other dashboards.
+    async fn trash(&self, id: Uuid) -> Result<(), AppError>;
+    /// Take out of the trash under the old slug, or under `{slug}-2`, `{slug}-3`… if a live
+    /// dashboard has taken it since.
+    async fn restore(&self, id: Uuid) -> Result<Dashboard, AppError>;
 }
 
 /* ============================================================================================== */
@@ -86,6 +100,7 @@ macro_rules! map_dashboard {
             is_shared: $r.is_shared,
             created_at: $r.created_at,
             updated_at: $r.updated_at,
+            deleted_at: $r.deleted_at,
         }
     };
 }
@@ -93,15 +108,23 @@ macro_rules! map_dashboard {
 /* ============================================================================================== */
 #[async_trait::async_trait]
 impl DashboardRepo for PgDashboardRepo {
-    async fn list_for_user(&self, user_id: Uuid) -> Result<Vec<Dashboard>, AppError> {
+    async fn list_for_user(&self, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<Dashboard>, AppError> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at
+            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
             FROM portal.dashboards
-            WHERE owner_id = $1 OR is_shared = true
+            WHERE (
+                owner_id = $1
+                OR is_shared = true
+                OR id IN (
+                    SELECT dashboard_id FROM portal.dashboard_shares
+                    WHERE user_id = $1 OR group_id = ANY($2)
+                )
+            ) AND deleted_at IS NULL
             ORDER BY sort_order ASC, title ASC
             "#,
-            user_id
+            user_id,
+            group_ids
         )
         .fetch_all(&self.pool)
         .await?;
@@ -112,8 +135,8 @@ impl DashboardRepo for PgDashboardRepo {
     async fn get_by_slug(&self, slug: &str) -> Result<Dashboard, AppError> {
         sqlx::query!(
             r#"
-            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at
-            FROM portal.dashboards WHERE slug = $1
+            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
+            FROM portal.dashboards WHERE slug = $1 AND deleted_at IS NULL
             "#,
             slug
         )
@@ -126,8 +149,8 @@ impl DashboardRepo for PgDashboardRepo {
     async fn get_by_id(&self, id: Uuid) -> Result<Dashboard, AppError> {
         sqlx::query!(
             r#"
-            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at
-            FROM portal.dashboards WHERE id = $1
+            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
+            FROM portal.dashboards WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -148,7 +171,7 @@ impl DashboardRepo for PgDashboardRepo {
             r#"
             INSERT INTO portal.dashboards (owner_id, title, slug, icon ,sort_order, is_shared)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at
+            RETURNING id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
             "#,
             owner_id,
             input.title,
@@ -180,8 +203,8 @@ impl DashboardRepo for PgDashboardRepo {
                 sort_order = COALESCE($5, sort_order),
                 is_shared  = COALESCE($6, is_shared),
                 updated_at = now() 
-            WHERE id = $1
-            RETURNING id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
             "#,
             id,
             input.title,
@@ -212,4 +235,86 @@ impl DashboardRepo for PgDashboardRepo {
         }
         Ok(())
     }
+
+    async fn list_trashed(&self) -> Result<Vec<Dashboard>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
+            FROM portal.dashboards
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| map_dashboard!(r)).collect())
+    }
+
+    async fn get_trashed(&self, id: Uuid) -> Result<Dashboard, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
+            FROM portal.dashboards WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_dashboard!(r))
+        .ok_or_else(|| AppError::NotFound(format!("dashboard '{id}' is not in the trash")))
+    }
+
+    async fn trash(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE portal.dashboards
+            SET deleted_at = now()
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("dashboard '{id}' not found")));
+        }
+        Ok(())
+    }
+
+    async fn restore(&self, id: Uuid) -> Result<Dashboard, AppError> {
+        let trashed = self.get_trashed(id).await?;
+        let mut suffix = 1;
+        loop {
+            let slug = match suffix {
+                1 => trashed.slug.clone(),
+                n => format!("{}-{n}", trashed.slug),
+            };
+            let restored = sqlx::query!(
+                r#"
+                UPDATE portal.dashboards
+                SET deleted_at = NULL, slug = $2
+                WHERE id = $1 AND deleted_at IS NOT NULL
+                RETURNING id, owner_id, title, slug, icon, sort_order, is_shared, created_at, updated_at, deleted_at
+                "#,
+                id,
+                slug,
+            )
+            .fetch_optional(&self.pool)
+            .await;
+
+            match restored {
+                Ok(row) => {
+                    return row
+                        .map(|r| map_dashboard!(r))
+                        .ok_or_else(|| AppError::NotFound(format!("dashboard '{id}' is not in the trash")));
+                }
+                Err(sqlx::Error::Database(ref db_err)) if db_err.constraint() == Some("dashboards_slug_key") => {
+                    suffix += 1;
+                }
+                Err(e) => return Err(AppError::Database(e)),
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use super::group_repo::Grantee;
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+/// One user's or group's share of one dashboard. Exactly one of `user_id` and `group_id` is set.
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardShare {
+    pub dashboard_id: Uuid,
+    pub user_id:      Option<Uuid>,
+    pub username:     Option<String>,
+    pub group_id:     Option<Uuid>,
+    pub group_name:   Option<String>,
+    pub permission:   String,               // "view" | "edit"
+    pub created_at:   DateTime<Utc>,
+    pub updated_at:   DateTime<Utc>,
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait DashboardShareRepo: Send + Sync {
+    async fn list_for_dashboard(&self, dashboard_id: Uuid) -> Result<Vec<DashboardShare>, AppError>;
+    /// The shares of one dashboard that apply to the user, directly or through `group_ids`.
+    async fn get_for_user(&self, dashboard_id: Uuid, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<DashboardShare>, AppError>;
+    /// Replaces every share of the dashboard with `grants`.
+    async fn replace(&self, dashboard_id: Uuid, grants: &[(Grantee, String)]) -> Result<Vec<DashboardShare>, AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgDashboardShareRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_share {
+    ($r:expr) => {
+        DashboardShare {
+            dashboard_id: $r.dashboard_id,
+            user_id:      $r.user_id,
+            username:     $r.username,
+            group_id:     $r.group_id,
+            group_name:   $r.group_name,
+            permission:   $r.permission,
+            created_at:   $r.created_at,
+            updated_at:   $r.updated_at,
+        }
+    };
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl DashboardShareRepo for PgDashboardShareRepo {
+    async fn list_for_dashboard(&self, dashboard_id: Uuid) -> Result<Vec<DashboardShare>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.dashboard_id, s.user_id, u.username AS "username?", s.group_id,
+                   g.name AS "group_name?", s.permission, s.created_at, s.updated_at
+            FROM portal.dashboard_shares s
+            LEFT JOIN portal.users u ON u.id = s.user_id
+            LEFT JOIN portal.groups g ON g.id = s.group_id
+            WHERE s.dashboard_id = $1
+            ORDER BY g.name ASC NULLS FIRST, u.username ASC
+            "#,
+            dashboard_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_share!(r)).collect())
+    }
+
+    async fn get_for_user(&self, dashboard_id: Uuid, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<DashboardShare>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.dashboard_id, s.user_id, u.username AS "username?", s.group_id,
+                   g.name AS "group_name?", s.permission, s.created_at, s.updated_at
+            FROM portal.dashboard_shares s
+            LEFT JOIN portal.users u ON u.id = s.user_id
+            LEFT JOIN portal.groups g ON g.id = s.group_id
+            WHERE s.dashboard_id = $1 AND (s.user_id = $2 OR s.group_id = ANY($3))
+            "#,
+            dashboard_id,
+            user_id,
+            group_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_share!(r)).collect())
+    }
+
+    async fn replace(&self, dashboard_id: Uuid, grants: &[(Grantee, String)]) -> Result<Vec<DashboardShare>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Grants that survive keep their `created_at`.
+        let grantee_ids: Vec<Uuid> = grants.iter().map(|(grantee, _)| grantee.id()).collect();
+        sqlx::query!(
+            r#"
+            DELETE FROM portal.dashboard_shares
+            WHERE dashboard_id = $1 AND COALESCE(user_id, group_id) <> ALL($2)
+            "#,
+            dashboard_id,
+            &grantee_ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for (grantee, permission) in grants {
+            sqlx::query!(
+                r#"
+                INSERT INTO portal.dashboard_shares (dashboard_id, user_id, group_id, permission)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (dashboard_id, (COALESCE(user_id, group_id)))
+                DO UPDATE SET permission = EXCLUDED.permission, updated_at = now()
+                WHERE portal.dashboard_shares.permission <> EXCLUDED.permission
+                "#,
+                dashboard_id,
+                grantee.user_id(),
+                grantee.group_id(),
+                permission,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                if let sqlx::Error::Database(ref db_err) = e {
+                    match db_err.constraint() {
+                        Some("dashboard_shares_user_id_fkey") => {
+                            return AppError::NotFound(format!("user '{}' not found", grantee.id()));
+                        }
+                        Some("dashboard_shares_group_id_fkey") => {
+                            return AppError::NotFound(format!("group '{}' not found", grantee.id()));
+                        }
+                        _ => {}
+                    }
+                }
+                AppError::Database(e)
+            })?;
+        }
+
+        tx.commit().await?;
+        self.list_for_dashboard(dashboard_id).await
+    }
+}
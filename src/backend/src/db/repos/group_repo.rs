@@ -0,0 +1,259 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Group {
+    pub id:          Uuid,
+    pub name:        String,
+    pub description: Option<String>,
+    pub created_at:  DateTime<Utc>,
+    pub updated_at:  DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupMember {
+    pub user_id:  Uuid,
+    pub username: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateGroup {
+    pub name:        String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateGroup {
+    pub name:        Option<String>,
+    pub description: Option<String>,
+}
+
+/// Who a template grant or dashboard share is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Grantee {
+    User(Uuid),
+    Group(Uuid),
+}
+
+impl Grantee {
+    pub fn user_id(self) -> Option<Uuid> {
+        match self {
+            Self::User(id)  => Some(id),
+            Self::Group(_)  => None,
+        }
+    }
+
+    pub fn group_id(self) -> Option<Uuid> {
+        match self {
+            Self::User(_)   => None,
+            Self::Group(id) => Some(id),
+        }
+    }
+
+    /// The user or group id; the two never collide, so this identifies the grantee.
+    pub fn id(self) -> Uuid {
+        match self {
+            Self::User(id) | Self::Group(id) => id,
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait GroupRepo: Send + Sync {
+    async fn list(&self) -> Result<Vec<Group>, AppError>;
+    async fn get_by_id(&self, id: Uuid) -> Result<Group, AppError>;
+    async fn create(&self, input: CreateGroup) -> Result<Group, AppError>;
+    async fn update(&self, id: Uuid, input: UpdateGroup) -> Result<Group, AppError>;
+    /// Deletes the group along with its memberships and every grant made to it.
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    async fn list_members(&self, group_id: Uuid) -> Result<Vec<GroupMember>, AppError>;
+    /// Adding an existing member is a no-op.
+    async fn add_member(&self, group_id: Uuid, user_id: Uuid) -> Result<(), AppError>;
+    async fn remove_member(&self, group_id: Uuid, user_id: Uuid) -> Result<(), AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgGroupRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_group {
+    ($r:expr) => {
+        Group {
+            id:          $r.id,
+            name:        $r.name,
+            description: $r.description,
+            created_at:  $r.created_at,
+            updated_at:  $r.updated_at,
+        }
+    };
+}
+
+/// Turns a violated `groups_name_key` into a validation error.
+fn map_name_conflict(e: sqlx::Error, name: &str) -> AppError {
+    if let sqlx::Error::Database(ref db_err) = e
+        && db_err.constraint() == Some("groups_name_key")
+    {
+        return AppError::Validation(format!("group '{name}' already exists"));
+    }
+    AppError::Database(e)
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl GroupRepo for PgGroupRepo {
+    async fn list(&self) -> Result<Vec<Group>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, description, created_at, updated_at
+            FROM portal.groups
+            ORDER BY name ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_group!(r)).collect())
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<Group, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, name, description, created_at, updated_at
+            FROM portal.groups WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_group!(r))
+        .ok_or_else(|| AppError::NotFound(format!("group '{id}' not found")))
+    }
+
+    async fn create(&self, input: CreateGroup) -> Result<Group, AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO portal.groups (name, description)
+            VALUES ($1, $2)
+            RETURNING id, name, description, created_at, updated_at
+            "#,
+            input.name,
+            input.description,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|r| map_group!(r))
+        .map_err(|e| map_name_conflict(e, &input.name))
+    }
+
+    async fn update(&self, id: Uuid, input: UpdateGroup) -> Result<Group, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.groups
+            SET name        = COALESCE($2, name),
+                description = COALESCE($3, description),
+                updated_at  = now()
+            WHERE id = $1
+            RETURNING id, name, description, created_at, updated_at
+            "#,
+            id,
+            input.name,
+            input.description,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| map_name_conflict(e, input.name.as_deref().unwrap_or_default()))?
+        .map(|r| map_group!(r))
+        .ok_or_else(|| AppError::NotFound(format!("group '{id}' not found")))
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!("DELETE FROM portal.groups WHERE id = $1", id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("group '{id}' not found")));
+        }
+        Ok(())
+    }
+
+    async fn list_members(&self, group_id: Uuid) -> Result<Vec<GroupMember>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT m.user_id, u.username, m.created_at AS added_at
+            FROM portal.group_members m
+            JOIN portal.users u ON u.id = m.user_id
+            WHERE m.group_id = $1
+            ORDER BY u.username ASC
+            "#,
+            group_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter()
+            .map(|r| GroupMember { user_id: r.user_id, username: r.username, added_at: r.added_at })
+            .collect())
+    }
+
+    async fn add_member(&self, group_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO portal.group_members (group_id, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            group_id,
+            user_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            if let sqlx::Error::Database(ref db_err) = e {
+                match db_err.constraint() {
+                    Some("group_members_group_id_fkey") => {
+                        return AppError::NotFound(format!("group '{group_id}' not found"));
+                    }
+                    Some("group_members_user_id_fkey") => {
+                        return AppError::NotFound(format!("user '{user_id}' not found"));
+                    }
+                    _ => {}
+                }
+            }
+            AppError::Database(e)
+        })?;
+        Ok(())
+    }
+
+    async fn remove_member(&self, group_id: Uuid, user_id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            "DELETE FROM portal.group_members WHERE group_id = $1 AND user_id = $2",
+            group_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("user '{user_id}' is not in group '{group_id}'")));
+        }
+        Ok(())
+    }
+}
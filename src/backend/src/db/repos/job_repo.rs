@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/// How long a claimed job stays hidden from other workers; every checkpoint renews it.
+pub const CLAIM_LEASE: Duration = Duration::from_secs(120);
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvisioningJob {
+    pub id:                     Uuid,
+    pub user_id:                Uuid,
+    pub name:                   String,
+    pub description:            Option<String>,
+    pub fields:                 JsonValue,
+    pub state:                  String,
+    pub base_id:                Option<String>,
+    pub table_id:               Option<String>,
+    pub table_name:             Option<String>,
+    pub form_view_id:           Option<String>,
+    pub form_share_uuid:        Option<String>,
+    pub grafana_dashboard_uid:  Option<String>,
+    pub grafana_dashboard_url:  Option<String>,
+    pub template_id:            Option<Uuid>,
+    /// Set on clone jobs that copy the rows of this template into the new table.
+    pub copy_rows_from:         Option<Uuid>,
+    pub error:                  Option<String>,
+    pub created_at:             DateTime<Utc>,
+    pub updated_at:             DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct CreateJob {
+    pub user_id:        Uuid,
+    pub name:           String,
+    pub description:    Option<String>,
+    pub fields:         JsonValue,            // serialised Vec<FieldDefinition>
+    pub copy_rows_from: Option<Uuid>,
+}
+
+/// Progress recorded after a pipeline step. `None` leaves the stored value untouched,
+/// so each step only needs to fill in the IDs it produced.
+#[derive(Debug, Default)]
+pub struct JobCheckpoint {
+    pub state:                  String,
+    pub base_id:                Option<String>,
+    pub table_id:               Option<String>,
+    pub table_name:             Option<String>,
+    pub form_view_id:           Option<String>,
+    pub form_share_uuid:        Option<String>,
+    pub grafana_dashboard_uid:  Option<String>,
+    pub grafana_dashboard_url:  Option<String>,
+    pub template_id:            Option<Uuid>,
+    pub error:                  Option<String>,
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait JobRepo: Send + Sync {
+    async fn create(&self, job: CreateJob) -> Result<ProvisioningJob, AppError>;
+    async fn get_by_id(&self, id: Uuid) -> Result<ProvisioningJob, AppError>;
+    /// Jobs not yet in a terminal state, oldest first.
+    async fn list_unfinished(&self) -> Result<Vec<ProvisioningJob>, AppError>;
+    /// Claim the oldest unfinished job nobody holds, for [`CLAIM_LEASE`]. Concurrent callers
+    /// never get the same job.
+    async fn claim_next(&self) -> Result<Option<ProvisioningJob>, AppError>;
+    /// Extend the claim on a job whose current step is still running.
+    async fn renew_claim(&self, id: Uuid) -> Result<(), AppError>;
+    /// Record progress and renew the claim; a terminal state releases it.
+    async fn checkpoint(&self, id: Uuid, checkpoint: JobCheckpoint) -> Result<ProvisioningJob, AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgJobRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_job {
+    ($r:expr) => {
+        ProvisioningJob {
+            id:                     $r.id,
+            user_id:                $r.user_id,
+            name:                   $r.name,
+            description:            $r.description,
+            fields:                 $r.fields,
+            state:                  $r.state,
+            base_id:                $r.base_id,
+            table_id:               $r.table_id,
+            table_name:             $r.table_name,
+            form_view_id:           $r.form_view_id,
+            form_share_uuid:        $r.form_share_uuid,
+            grafana_dashboard_uid:  $r.grafana_dashboard_uid,
+            grafana_dashboard_url:  $r.grafana_dashboard_url,
+            template_id:            $r.template_id,
+            copy_rows_from:         $r.copy_rows_from,
+            error:                  $r.error,
+            created_at:             $r.created_at,
+            updated_at:             $r.updated_at,
+        }
+    };
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl JobRepo for PgJobRepo {
+    async fn create(&self, job: CreateJob) -> Result<ProvisioningJob, AppError> {
+        sqlx::query!(
+            r#"
+            INSERT INTO portal.provisioning_jobs (user_id, name, description, fields, copy_rows_from)
+            VALUES ($1, $2, $3, $4::jsonb, $5)
+            RETURNING id, user_id, name, description, fields as "fields!: JsonValue", state,
+                      base_id, table_id, table_name, form_view_id, form_share_uuid,
+                      grafana_dashboard_uid, grafana_dashboard_url, template_id, copy_rows_from, error,
+                      created_at, updated_at
+            "#,
+            job.user_id,
+            job.name,
+            job.description,
+            job.fields,
+            job.copy_rows_from,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|r| map_job!(r))
+        .map_err(AppError::Database)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<ProvisioningJob, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, user_id, name, description, fields as "fields!: JsonValue", state,
+                   base_id, table_id, table_name, form_view_id, form_share_uuid,
+                   grafana_dashboard_uid, grafana_dashboard_url, template_id, copy_rows_from, error,
+                   created_at, updated_at
+            FROM portal.provisioning_jobs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_job!(r))
+        .ok_or_else(|| AppError::NotFound(format!("provisioning job '{id}' not found")))
+    }
+
+    async fn list_unfinished(&self) -> Result<Vec<ProvisioningJob>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, user_id, name, description, fields as "fields!: JsonValue", state,
+                   base_id, table_id, table_name, form_view_id, form_share_uuid,
+                   grafana_dashboard_uid, grafana_dashboard_url, template_id, copy_rows_from, error,
+                   created_at, updated_at
+            FROM portal.provisioning_jobs
+            WHERE state NOT IN ('completed', 'failed')
+            ORDER BY created_at ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_job!(r)).collect())
+    }
+
+    async fn claim_next(&self) -> Result<Option<ProvisioningJob>, AppError> {
+        let row = sqlx::query!(
+            r#"
+            UPDATE portal.provisioning_jobs
+            SET claimed_until = now() + make_interval(secs => $1)
+            WHERE id = (
+                SELECT id FROM portal.provisioning_jobs
+                WHERE state NOT IN ('completed', 'failed')
+                  AND (claimed_until IS NULL OR claimed_until < now())
+                ORDER BY created_at ASC
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, user_id, name, description, fields as "fields!: JsonValue", state,
+                      base_id, table_id, table_name, form_view_id, form_share_uuid,
+                      grafana_dashboard_uid, grafana_dashboard_url, template_id, copy_rows_from, error,
+                      created_at, updated_at
+            "#,
+            CLAIM_LEASE.as_secs_f64(),
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(|r| map_job!(r)))
+    }
+
+    async fn renew_claim(&self, id: Uuid) -> Result<(), AppError> {
+        sqlx::query!(
+            "UPDATE portal.provisioning_jobs SET claimed_until = now() + make_interval(secs => $2) WHERE id = $1",
+            id,
+            CLAIM_LEASE.as_secs_f64(),
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn checkpoint(&self, id: Uuid, checkpoint: JobCheckpoint) -> Result<ProvisioningJob, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.provisioning_jobs
+            SET state                 = $2,
+                base_id               = COALESCE($3, base_id),
+                table_id              = COALESCE($4, table_id),
+                table_name            = COALESCE($5, table_name),
+                form_view_id          = COALESCE($6, form_view_id),
+                form_share_uuid       = COALESCE($7, form_share_uuid),
+                grafana_dashboard_uid = COALESCE($8, grafana_dashboard_uid),
+                grafana_dashboard_url = COALESCE($9, grafana_dashboard_url),
+                template_id           = COALESCE($10, template_id),
+                error                 = COALESCE($11, error),
+                claimed_until         = CASE WHEN $2::varchar IN ('completed', 'failed') THEN NULL
+                                             ELSE now() + make_interval(secs => $12) END,
+                updated_at            = now()
+            WHERE id = $1
+            RETURNING id, user_id, name, description, fields as "fields!: JsonValue", state,
+                      base_id, table_id, table_name, form_view_id, form_share_uuid,
+                      grafana_dashboard_uid, grafana_dashboard_url, template_id, copy_rows_from, error,
+                      created_at, updated_at
+            "#,
+            id,
+            checkpoint.state,
+            checkpoint.base_id,
+            checkpoint.table_id,
+            checkpoint.table_name,
+            checkpoint.form_view_id,
+            checkpoint.form_share_uuid,
+            checkpoint.grafana_dashboard_uid,
+            checkpoint.grafana_dashboard_url,
+            checkpoint.template_id,
+            checkpoint.error,
+            CLAIM_LEASE.as_secs_f64(),
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_job!(r))
+        .ok_or_else(|| AppError::NotFound(format!("provisioning job '{id}' not found")))
+    }
+}
@@ -1,9 +1,21 @@
+pub mod blueprint_repo;
 pub mod dashboard_repo;
+pub mod dashboard_share_repo;
+pub mod group_repo;
+pub mod job_repo;
 pub mod panel_repo;
+pub mod public_link_repo;
+pub mod template_acl_repo;
 pub mod template_repo;
 pub mod user_repo;
 
+pub use blueprint_repo::{BlueprintRepo, PgBlueprintRepo};
 pub use dashboard_repo::{CreateDashboard, Dashboard, DashboardRepo, PgDashboardRepo, UpdateDashboard};
+pub use dashboard_share_repo::{DashboardShareRepo, PgDashboardShareRepo};
+pub use group_repo::{Grantee, GroupRepo, PgGroupRepo};
+pub use job_repo::{JobRepo, PgJobRepo};
 pub use panel_repo::{BatchPositionUpdate, CreatePanel, GridPosition, Panel, PanelRepo, PgPanelRepo, UpdatePanel};
+pub use public_link_repo::{PgPublicLinkRepo, PublicLink, PublicLinkRepo};
+pub use template_acl_repo::{PgTemplateAclRepo, TemplateAclRepo};
 pub use user_repo::{PgUserRepo, User, UserRepo};
 pub use template_repo::{FieldDefinition, PgTemplateRepo, Template, TemplateRepo};
@@ -24,6 +24,8 @@ pub struct Panel {
     pub grid_h: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set while the panel itself is in the trash (not when only its dashboard is).
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,12 +72,24 @@ pub struct BatchPositionUpdate {
 #[async_trait::async_trait]
 pub trait PanelRepo: Send + Sync {
     async fn list_for_dashboard(&self, dashboard_id: Uuid) -> Result<Vec<Panel>, AppError>;
+    /// The live panels of several dashboards at once.
+    async fn list_for_dashboards(&self, dashboard_ids: &[Uuid]) -> Result<Vec<Panel>, AppError>;
+    /// Every panel that embeds an external URL, across all dashboards, trashed ones included.
+    async fn list_with_source(&self) -> Result<Vec<Panel>, AppError>;
     async fn get_by_id(&self, id: Uuid) -> Result<Panel, AppError>;
     async fn create(&self, dashboard_id: Uuid, input: CreatePanel) -> Result<Panel, AppError>;
     async fn update(&self, id: Uuid, input: UpdatePanel) -> Result<Panel, AppError>;
     async fn update_position(&self, id: Uuid, pos: GridPosition) -> Result<Panel, AppError>;
     async fn batch_update_positions(&self, updates: Vec<BatchPositionUpdate>) -> Result<(), AppError>;
+    /// Permanently removes the panel, whether or not it is in the trash.
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    /// Panels in the trash, most recently deleted first. Panels of a trashed dashboard are
+    /// not listed unless they were deleted individually before it.
+    async fn list_trashed(&self) -> Result<Vec<Panel>, AppError>;
+    async fn get_trashed(&self, id: Uuid) -> Result<Panel, AppError>;
+    async fn trash(&self, id: Uuid) -> Result<(), AppError>;
+    async fn restore(&self, id: Uuid) -> Result<Panel, AppError>;
 }
 
 /* ============================================================================================== */
@@ -101,6 +115,7 @@ macro_rules! map_panel {
             grid_h: $r.grid_h,
             created_at: $r.created_at,
             updated_at: $r.updated_at,
+            deleted_at: $r.deleted_at,
         }
     };
 }
@@ -113,9 +128,9 @@ impl PanelRepo for PgPanelRepo {
             r#"
             SELECT id, dashboard_id, title, panel_type, source_url,
                    config as "config!: JsonValue",
-                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
             FROM portal.panels
-            WHERE dashboard_id = $1
+            WHERE dashboard_id = $1 AND deleted_at IS NULL
             ORDER BY grid_y ASC, grid_x ASC
             "#,
             dashboard_id
@@ -126,14 +141,48 @@ impl PanelRepo for PgPanelRepo {
         Ok(rows.into_iter().map(|r| map_panel!(r)).collect())
     }
 
+    async fn list_for_dashboards(&self, dashboard_ids: &[Uuid]) -> Result<Vec<Panel>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, title, panel_type, source_url,
+                   config as "config!: JsonValue",
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
+            FROM portal.panels
+            WHERE dashboard_id = ANY($1) AND deleted_at IS NULL
+            "#,
+            dashboard_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| map_panel!(r)).collect())
+    }
+
+    async fn list_with_source(&self) -> Result<Vec<Panel>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, title, panel_type, source_url,
+                   config as "config!: JsonValue",
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
+            FROM portal.panels
+            WHERE source_url IS NOT NULL
+            ORDER BY dashboard_id, grid_y ASC, grid_x ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| map_panel!(r)).collect())
+    }
+
     async fn get_by_id(&self, id: Uuid) -> Result<Panel, AppError> {
         sqlx::query!(
             r#"
             SELECT id, dashboard_id, title, panel_type, source_url,
                    config as "config!: JsonValue",
-                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
             FROM portal.panels
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -153,7 +202,7 @@ impl PanelRepo for PgPanelRepo {
             VALUES ($1, $2, $3, $4, $5::jsonb, $6, $7, $8, $9)
             RETURNING id, dashboard_id, title, panel_type, source_url,
                       config as "config!: JsonValue",
-                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at
+                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
             "#,
             dashboard_id,
             input.title,
@@ -180,10 +229,10 @@ impl PanelRepo for PgPanelRepo {
                 source_url = COALESCE($4, source_url),
                 config     = COALESCE($5::jsonb, config),
                 updated_at = now()
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             RETURNING id, dashboard_id, title, panel_type, source_url,
                       config as "config!: JsonValue",
-                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at
+                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
             "#,
             id,
             input.title,
@@ -206,10 +255,10 @@ impl PanelRepo for PgPanelRepo {
                 grid_w     = $4,
                 grid_h     = $5,
                 updated_at = now()
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             RETURNING id, dashboard_id, title, panel_type, source_url,
                       config as "config!: JsonValue",
-                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at
+                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
             "#,
             id,
             pos.grid_x,
@@ -229,7 +278,7 @@ impl PanelRepo for PgPanelRepo {
             sqlx::query!(
                 "UPDATE portal.panels
                 SET grid_x = $2, grid_y = $3, grid_w = $4, grid_h = $5, updated_at = now()
-                WHERE id = $1",
+                WHERE id = $1 AND deleted_at IS NULL",
                 u.id, u.grid_x, u.grid_y, u.grid_w, u.grid_h
             )
             .execute(&mut *tx)
@@ -249,4 +298,70 @@ impl PanelRepo for PgPanelRepo {
         }
         Ok(())
     }
+
+    async fn list_trashed(&self) -> Result<Vec<Panel>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, title, panel_type, source_url,
+                   config as "config!: JsonValue",
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
+            FROM portal.panels
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| map_panel!(r)).collect())
+    }
+
+    async fn get_trashed(&self, id: Uuid) -> Result<Panel, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, title, panel_type, source_url,
+                   config as "config!: JsonValue",
+                   grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
+            FROM portal.panels
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_panel!(r))
+        .ok_or_else(|| AppError::NotFound(format!("panel '{id}' is not in the trash")))
+    }
+
+    async fn trash(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query!(
+            "UPDATE portal.panels SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("panel '{id}' not found")));
+        }
+        Ok(())
+    }
+
+    async fn restore(&self, id: Uuid) -> Result<Panel, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.panels
+            SET deleted_at = NULL
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING id, dashboard_id, title, panel_type, source_url,
+                      config as "config!: JsonValue",
+                      grid_x, grid_y, grid_w, grid_h, created_at, updated_at, deleted_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_panel!(r))
+        .ok_or_else(|| AppError::NotFound(format!("panel '{id}' is not in the trash")))
+    }
 }
\ No newline at end of file
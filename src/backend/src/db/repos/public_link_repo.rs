@@ -0,0 +1,144 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+/// A read-only link to one dashboard for people without an account.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicLink {
+    pub id:           Uuid,
+    pub dashboard_id: Uuid,
+    pub label:        String,
+    pub created_by:   Option<Uuid>,
+    pub created_at:   DateTime<Utc>,
+    pub expires_at:   DateTime<Utc>,
+    pub revoked_at:   Option<DateTime<Utc>>,
+}
+
+impl PublicLink {
+    /// Neither revoked nor expired.
+    pub fn is_active(&self) -> bool {
+        self.revoked_at.is_none() && self.expires_at > Utc::now()
+    }
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait PublicLinkRepo: Send + Sync {
+    /// Every link of the dashboard, revoked and expired ones included, newest first.
+    async fn list_for_dashboard(&self, dashboard_id: Uuid) -> Result<Vec<PublicLink>, AppError>;
+    async fn get_by_id(&self, id: Uuid) -> Result<PublicLink, AppError>;
+    async fn create(
+        &self,
+        dashboard_id: Uuid,
+        created_by: Uuid,
+        label: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PublicLink, AppError>;
+    /// Revoking an already revoked link keeps the original `revoked_at`.
+    async fn revoke(&self, dashboard_id: Uuid, id: Uuid) -> Result<PublicLink, AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgPublicLinkRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_link {
+    ($r:expr) => {
+        PublicLink {
+            id:           $r.id,
+            dashboard_id: $r.dashboard_id,
+            label:        $r.label,
+            created_by:   $r.created_by,
+            created_at:   $r.created_at,
+            expires_at:   $r.expires_at,
+            revoked_at:   $r.revoked_at,
+        }
+    };
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl PublicLinkRepo for PgPublicLinkRepo {
+    async fn list_for_dashboard(&self, dashboard_id: Uuid) -> Result<Vec<PublicLink>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, label, created_by, created_at, expires_at, revoked_at
+            FROM portal.public_links
+            WHERE dashboard_id = $1
+            ORDER BY created_at DESC
+            "#,
+            dashboard_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_link!(r)).collect())
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> Result<PublicLink, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, dashboard_id, label, created_by, created_at, expires_at, revoked_at
+            FROM portal.public_links WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_link!(r))
+        .ok_or_else(|| AppError::NotFound(format!("public link '{id}' not found")))
+    }
+
+    async fn create(
+        &self,
+        dashboard_id: Uuid,
+        created_by: Uuid,
+        label: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<PublicLink, AppError> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO portal.public_links (dashboard_id, label, created_by, expires_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, dashboard_id, label, created_by, created_at, expires_at, revoked_at
+            "#,
+            dashboard_id,
+            label,
+            created_by,
+            expires_at,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(map_link!(row))
+    }
+
+    async fn revoke(&self, dashboard_id: Uuid, id: Uuid) -> Result<PublicLink, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.public_links
+            SET revoked_at = COALESCE(revoked_at, now())
+            WHERE id = $1 AND dashboard_id = $2
+            RETURNING id, dashboard_id, label, created_by, created_at, expires_at, revoked_at
+            "#,
+            id,
+            dashboard_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_link!(r))
+        .ok_or_else(|| AppError::NotFound(format!("public link '{id}' not found")))
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::AppError;
+use super::group_repo::Grantee;
+
+/* ============================================================================================== */
+/*                                          Domain types                                          */
+/* ============================================================================================== */
+
+/// One user's or group's grant on one template. Exactly one of `user_id` and `group_id` is set.
+#[derive(Debug, Clone, Serialize)]
+pub struct AclEntry {
+    pub template_id: Uuid,
+    pub user_id:     Option<Uuid>,
+    pub username:    Option<String>,
+    pub group_id:    Option<Uuid>,
+    pub group_name:  Option<String>,
+    pub permission:  String,               // "read" | "enter" | "manage"
+    pub created_at:  DateTime<Utc>,
+    pub updated_at:  DateTime<Utc>,
+}
+
+/* ============================================================================================== */
+/*                                        Repository trait                                        */
+/* ============================================================================================== */
+
+#[async_trait::async_trait]
+pub trait TemplateAclRepo: Send + Sync {
+    async fn list_for_template(&self, template_id: Uuid) -> Result<Vec<AclEntry>, AppError>;
+    /// Every grant to the user or to one of `group_ids`, across all templates.
+    async fn list_for_user(&self, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<AclEntry>, AppError>;
+    /// The grants on one template that apply to the user, directly or through `group_ids`.
+    async fn get_for_user(&self, template_id: Uuid, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<AclEntry>, AppError>;
+    /// Grants `permission`, replacing any earlier grant to the same grantee.
+    async fn upsert(&self, template_id: Uuid, grantee: Grantee, permission: &str) -> Result<AclEntry, AppError>;
+    async fn delete(&self, template_id: Uuid, grantee: Grantee) -> Result<(), AppError>;
+}
+
+/* ============================================================================================== */
+/*                                     Postgres implementation                                    */
+/* ============================================================================================== */
+
+pub struct PgTemplateAclRepo {
+    pub pool: PgPool,
+}
+
+macro_rules! map_acl_entry {
+    ($r:expr) => {
+        AclEntry {
+            template_id: $r.template_id,
+            user_id:     $r.user_id,
+            username:    $r.username,
+            group_id:    $r.group_id,
+            group_name:  $r.group_name,
+            permission:  $r.permission,
+            created_at:  $r.created_at,
+            updated_at:  $r.updated_at,
+        }
+    };
+}
+
+/* ============================================================================================== */
+#[async_trait::async_trait]
+impl TemplateAclRepo for PgTemplateAclRepo {
+    async fn list_for_template(&self, template_id: Uuid) -> Result<Vec<AclEntry>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT a.template_id, a.user_id, u.username AS "username?", a.group_id,
+                   g.name AS "group_name?", a.permission, a.created_at, a.updated_at
+            FROM portal.template_acl a
+            LEFT JOIN portal.users u ON u.id = a.user_id
+            LEFT JOIN portal.groups g ON g.id = a.group_id
+            WHERE a.template_id = $1
+            ORDER BY g.name ASC NULLS FIRST, u.username ASC
+            "#,
+            template_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_acl_entry!(r)).collect())
+    }
+
+    async fn list_for_user(&self, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<AclEntry>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT a.template_id, a.user_id, u.username AS "username?", a.group_id,
+                   g.name AS "group_name?", a.permission, a.created_at, a.updated_at
+            FROM portal.template_acl a
+            LEFT JOIN portal.users u ON u.id = a.user_id
+            LEFT JOIN portal.groups g ON g.id = a.group_id
+            WHERE a.user_id = $1 OR a.group_id = ANY($2)
+            "#,
+            user_id,
+            group_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_acl_entry!(r)).collect())
+    }
+
+    async fn get_for_user(&self, template_id: Uuid, user_id: Uuid, group_ids: &[Uuid]) -> Result<Vec<AclEntry>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT a.template_id, a.user_id, u.username AS "username?", a.group_id,
+                   g.name AS "group_name?", a.permission, a.created_at, a.updated_at
+            FROM portal.template_acl a
+            LEFT JOIN portal.users u ON u.id = a.user_id
+            LEFT JOIN portal.groups g ON g.id = a.group_id
+            WHERE a.template_id = $1 AND (a.user_id = $2 OR a.group_id = ANY($3))
+            "#,
+            template_id,
+            user_id,
+            group_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_acl_entry!(r)).collect())
+    }
+
+    async fn upsert(&self, template_id: Uuid, grantee: Grantee, permission: &str) -> Result<AclEntry, AppError> {
+        sqlx::query!(
+            r#"
+            WITH entry AS (
+                INSERT INTO portal.template_acl (template_id, user_id, group_id, permission)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (template_id, (COALESCE(user_id, group_id)))
+                DO UPDATE SET permission = EXCLUDED.permission, updated_at = now()
+                RETURNING template_id, user_id, group_id, permission, created_at, updated_at
+            )
+            SELECT e.template_id, e.user_id, u.username AS "username?", e.group_id,
+                   g.name AS "group_name?", e.permission, e.created_at, e.updated_at
+            FROM entry e
+            LEFT JOIN portal.users u ON u.id = e.user_id
+            LEFT JOIN portal.groups g ON g.id = e.group_id
+            "#,
+            template_id,
+            grantee.user_id(),
+            grantee.group_id(),
+            permission,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|r| map_acl_entry!(r))
+        .map_err(|e| {
+            if let sqlx::Error::Database(ref db_err) = e {
+                match db_err.constraint() {
+                    Some("template_acl_user_id_fkey") => {
+                        return AppError::NotFound(format!("user '{}' not found", grantee.id()));
+                    }
+                    Some("template_acl_group_id_fkey") => {
+                        return AppError::NotFound(format!("group '{}' not found", grantee.id()));
+                    }
+                    _ => {}
+                }
+            }
+            AppError::Database(e)
+        })
+    }
+
+    async fn delete(&self, template_id: Uuid, grantee: Grantee) -> Result<(), AppError> {
+        let res = sqlx::query!(
+            r#"
+            DELETE FROM portal.template_acl
+            WHERE template_id = $1
+              AND user_id IS NOT DISTINCT FROM $2
+              AND group_id IS NOT DISTINCT FROM $3
+            "#,
+            template_id,
+            grantee.user_id(),
+            grantee.group_id()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("'{}' has no grant on template '{template_id}'", grantee.id())));
+        }
+        Ok(())
+    }
+}
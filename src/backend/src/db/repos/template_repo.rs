@@ -10,11 +10,220 @@ use crate::error::AppError;
 /*                                          Domain types                                          */
 /* ============================================================================================== */
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FieldDefinition {
-    pub name:       String,            // lowercase alphanumeric + underscore
-    pub field_type: String,            // "number" | "text" | "date" | "select"
-    pub unit:       Option<String>,    // e.g. "kg", "%", "bpm"
+    pub name:        String,            // lowercase alphanumeric + underscore
+    pub field_type:  FieldType,
+    pub unit:        Option<String>,    // e.g. "kg", "%", "bpm"; the currency code for currency fields
+    /// Allowed choices of a select or multi-select field. Empty means any value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options:     Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required:    bool,
+    /// Inclusive bounds: the value of numeric fields, the length of text fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min:         Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max:         Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default:     Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Marks the date/datetime field that dates each row; see [`time_axis`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub time_axis:   bool,
+    /// IANA zone (e.g. "Europe/Amsterdam") that the time axis' dates were recorded in.
+    /// Only meaningful on the time axis; datetimes already carry their offset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone:    Option<String>,
+    /// How the field is drawn on the generated Grafana dashboard. Only numeric and boolean
+    /// fields are charted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart:       Option<ChartSettings>,
+    /// Formula of a computed field over the template's stored numeric fields, e.g.
+    /// `weight / (height / 100) ^ 2`; see `orchestrator::expression`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression:  Option<String>,
+}
+
+impl FieldDefinition {
+    /// Top of a rating field's scale: its `max` if set, else [`MAX_RATING`].
+    pub fn rating_max(&self) -> i64 {
+        self.max.map_or(MAX_RATING, |max| max as i64)
+    }
+}
+
+/// The field that dates a template's rows: the one marked `time_axis`, else its only
+/// date/datetime field. `None` means rows are dated by NocoDB's creation timestamp.
+pub fn time_axis(fields: &[FieldDefinition]) -> Option<&FieldDefinition> {
+    if let Some(marked) = fields.iter().find(|f| f.time_axis) {
+        return Some(marked);
+    }
+    let mut temporal = fields.iter().filter(|f| f.field_type.is_temporal());
+    match (temporal.next(), temporal.next()) {
+        (Some(only), None) => Some(only),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartSettings {
+    /// `None` picks the field type's usual chart: a time series, a state timeline for booleans
+    /// or a bar gauge for ratings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart_type:  Option<ChartType>,
+    #[serde(default)]
+    pub aggregation: Aggregation,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub thresholds:  Vec<Threshold>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color:       Option<String>,    // Grafana colour name or hex, e.g. "orange", "#ff7f0e"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_min:       Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_max:       Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartType {
+    Timeseries,
+    Bar,
+    Stat,
+    Gauge,
+    Histogram,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    #[default]
+    Raw,
+    DailyAvg,
+    WeeklySum,
+}
+
+/// Colour change at `value`; below the lowest threshold the chart uses its base colour.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Threshold {
+    pub value: f64,
+    pub color: String,
+}
+
+/// The kind of value a field holds. Serialised as its snake_case name; unknown names are
+/// rejected rather than degraded to text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Number,         // decimal
+    Integer,
+    Currency,
+    Duration,       // seconds
+    Rating,         // whole number from 0 to `max`, else `MAX_RATING`
+    Boolean,
+    #[default]
+    Text,
+    LongText,
+    Email,
+    Url,
+    Date,
+    Datetime,
+    Select,
+    MultiSelect,
+    Computed,       // derived from `expression`, never stored
+}
+
+/// Upper bound of a rating field without a `max`, matching NocoDB's default of five stars.
+pub const MAX_RATING: i64 = 5;
+/// The most stars NocoDB will draw for a rating field.
+pub const RATING_SCALE_LIMIT: i64 = 10;
+
+impl FieldType {
+    pub const ALL: [FieldType; 15] = [
+        FieldType::Number, FieldType::Integer, FieldType::Currency, FieldType::Duration,
+        FieldType::Rating, FieldType::Boolean, FieldType::Text, FieldType::LongText,
+        FieldType::Email, FieldType::Url, FieldType::Date, FieldType::Datetime,
+        FieldType::Select, FieldType::MultiSelect, FieldType::Computed,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FieldType::Number      => "number",
+            FieldType::Integer     => "integer",
+            FieldType::Currency    => "currency",
+            FieldType::Duration    => "duration",
+            FieldType::Rating      => "rating",
+            FieldType::Boolean     => "boolean",
+            FieldType::Text        => "text",
+            FieldType::LongText    => "long_text",
+            FieldType::Email       => "email",
+            FieldType::Url         => "url",
+            FieldType::Date        => "date",
+            FieldType::Datetime    => "datetime",
+            FieldType::Select      => "select",
+            FieldType::MultiSelect => "multi_select",
+            FieldType::Computed    => "computed",
+        }
+    }
+
+    /// Types whose values are plotted as numbers.
+    pub fn is_numeric(self) -> bool {
+        matches!(self, FieldType::Number | FieldType::Integer | FieldType::Currency
+            | FieldType::Duration | FieldType::Rating)
+    }
+
+    /// Types that get a panel on the generated Grafana dashboard.
+    pub fn is_charted(self) -> bool {
+        self.is_numeric() || matches!(self, FieldType::Boolean | FieldType::Computed)
+    }
+
+    /// Types backed by a NocoDB column; computed fields are evaluated on read instead.
+    pub fn is_stored(self) -> bool {
+        self != FieldType::Computed
+    }
+
+    pub fn is_temporal(self) -> bool {
+        matches!(self, FieldType::Date | FieldType::Datetime)
+    }
+
+    /// Types whose values are free text, so `min`/`max` bound their length.
+    pub fn is_textual(self) -> bool {
+        matches!(self, FieldType::Text | FieldType::LongText | FieldType::Email | FieldType::Url)
+    }
+
+    pub fn has_options(self) -> bool {
+        matches!(self, FieldType::Select | FieldType::MultiSelect)
+    }
+}
+
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for FieldType {
+    type Error = AppError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        if s == "checkbox" {
+            return Ok(FieldType::Boolean);
+        }
+        FieldType::ALL.into_iter().find(|t| t.as_str() == s).ok_or_else(|| {
+            let known: Vec<&str> = FieldType::ALL.iter().map(|t| t.as_str()).collect();
+            AppError::Validation(format!("unknown field type '{s}' (expected one of: {})", known.join(", ")))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        FieldType::try_from(s.as_str()).map_err(|e| match e {
+            AppError::Validation(message) => serde::de::Error::custom(message),
+            other => serde::de::Error::custom(other),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -27,14 +236,21 @@ pub struct Template {
     pub grafana_dashboard_uid:  Option<String>,
     pub fields:                 JsonValue,
     pub created_by:             Option<Uuid>,
+    /// Holds the `manage` permission; starts out as the creator.
+    pub owner_id:               Option<Uuid>,
+    /// `private` (owner and ACL entries only) or `public` (every user may read).
+    pub visibility:             String,
     pub created_at:             DateTime<Utc>,
     pub updated_at:             DateTime<Utc>,
+    /// Set while the template is in the trash.
+    pub deleted_at:             Option<DateTime<Utc>>,
 }
 
 /// Used by the orchestrator after external resources are created — all IDs are
 /// known before the DB row is inserted.
 #[derive(Debug)]
 pub struct CreateTemplateRecord {
+    pub id:                    Option<Uuid>,        // generated when `None`
     pub name:                  String,
     pub description:           Option<String>,
     pub fields:                JsonValue,            // serialised Vec<FieldDefinition>
@@ -45,16 +261,40 @@ pub struct CreateTemplateRecord {
 
 }
 
+/// Used by the orchestrator once a schema change has been applied to the external resources.
+#[derive(Debug)]
+pub struct UpdateTemplateRecord {
+    pub name:        Option<String>,
+    pub description: Option<String>,
+    pub fields:      JsonValue,            // serialised Vec<FieldDefinition>
+}
+
 /* ============================================================================================== */
 /*                                        Repository trait                                        */
 /* ============================================================================================== */
 
 #[async_trait::async_trait]
 pub trait TemplateRepo: Send + Sync {
+    /// Templates not in the trash, newest first.
     async fn list_all(&self) -> Result<Vec<Template>, AppError>;
     async fn get_by_id(&self, id: Uuid) -> Result<Template, AppError>;
     async fn create(&self, record: CreateTemplateRecord) -> Result<Template, AppError>;
+    async fn update(&self, id: Uuid, record: UpdateTemplateRecord) -> Result<Template, AppError>;
+    /// Changes the visibility and/or owner; `None` keeps the current value.
+    async fn update_sharing(
+        &self,
+        id: Uuid,
+        visibility: Option<&str>,
+        owner_id: Option<Uuid>,
+    ) -> Result<Template, AppError>;
+    /// Permanently removes the row, whether or not it is in the trash.
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+
+    /// Templates in the trash, most recently deleted first.
+    async fn list_trashed(&self) -> Result<Vec<Template>, AppError>;
+    async fn get_trashed(&self, id: Uuid) -> Result<Template, AppError>;
+    async fn trash(&self, id: Uuid) -> Result<(), AppError>;
+    async fn restore(&self, id: Uuid) -> Result<Template, AppError>;
 }
 
 /* ============================================================================================== */
@@ -76,8 +316,11 @@ macro_rules! map_template {
             grafana_dashboard_uid:  $r.grafana_dashboard_uid,
             fields:                 $r.fields,
             created_by:             $r.created_by,
+            owner_id:               $r.owner_id,
+            visibility:             $r.visibility,
             created_at:             $r.created_at,
             updated_at:             $r.updated_at,
+            deleted_at:             $r.deleted_at,
         }
     };
 }
@@ -90,8 +333,9 @@ impl TemplateRepo for PgTemplateRepo {
             r#"
             SELECT id, name, description, nocodb_table_id, nocodb_form_id, 
                    grafana_dashboard_uid, fields as "fields!: JsonValue",
-                   created_by, created_at, updated_at
+                   created_by, owner_id, visibility, created_at, updated_at, deleted_at
             FROM portal.dataset_templates
+            WHERE deleted_at IS NULL
             ORDER BY created_at DESC
             "#
         )
@@ -105,9 +349,9 @@ impl TemplateRepo for PgTemplateRepo {
             r#"
             SELECT id, name, description, nocodb_table_id, nocodb_form_id, 
                    grafana_dashboard_uid, fields as "fields!: JsonValue",
-                   created_by, created_at, updated_at
+                   created_by, owner_id, visibility, created_at, updated_at, deleted_at
             FROM portal.dataset_templates
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -121,12 +365,12 @@ impl TemplateRepo for PgTemplateRepo {
         sqlx::query!(
             r#"
             INSERT INTO portal.dataset_templates
-                (name, description, fields, created_by,
+                (id, name, description, fields, created_by, owner_id,
                  nocodb_table_id, nocodb_form_id, grafana_dashboard_uid)
-            VALUES ($1, $2, $3::jsonb, $4, $5, $6, $7)
+            VALUES (COALESCE($8, gen_random_uuid()), $1, $2, $3::jsonb, $4, $4, $5, $6, $7)
             RETURNING id, name, description, nocodb_table_id, nocodb_form_id,
                       grafana_dashboard_uid, fields as "fields!: JsonValue",
-                      created_by, created_at, updated_at
+                      created_by, owner_id, visibility, created_at, updated_at, deleted_at
             "#,
             record.name,
             record.description,
@@ -135,6 +379,7 @@ impl TemplateRepo for PgTemplateRepo {
             record.nocodb_table_id,
             record.nocodb_form_id,
             record.grafana_dashboard_uid,
+            record.id,
         )
         .fetch_one(&self.pool)
         .await
@@ -142,6 +387,57 @@ impl TemplateRepo for PgTemplateRepo {
         .map_err(AppError::Database)
     }
 
+    async fn update(&self, id: Uuid, record: UpdateTemplateRecord) -> Result<Template, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.dataset_templates
+            SET name        = COALESCE($2, name),
+                description = COALESCE($3, description),
+                fields      = $4::jsonb,
+                updated_at  = now()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, name, description, nocodb_table_id, nocodb_form_id,
+                      grafana_dashboard_uid, fields as "fields!: JsonValue",
+                      created_by, owner_id, visibility, created_at, updated_at, deleted_at
+            "#,
+            id,
+            record.name,
+            record.description,
+            record.fields,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_template!(r))
+        .ok_or_else(|| AppError::NotFound(format!("template '{id}' not found")))
+    }
+
+    async fn update_sharing(
+        &self,
+        id: Uuid,
+        visibility: Option<&str>,
+        owner_id: Option<Uuid>,
+    ) -> Result<Template, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.dataset_templates
+            SET visibility = COALESCE($2, visibility),
+                owner_id   = COALESCE($3, owner_id),
+                updated_at = now()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id, name, description, nocodb_table_id, nocodb_form_id,
+                      grafana_dashboard_uid, fields as "fields!: JsonValue",
+                      created_by, owner_id, visibility, created_at, updated_at, deleted_at
+            "#,
+            id,
+            visibility,
+            owner_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_template!(r))
+        .ok_or_else(|| AppError::NotFound(format!("template '{id}' not found")))
+    }
+
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         let res = sqlx::query!(
             "DELETE FROM portal.dataset_templates WHERE id = $1", id
@@ -154,4 +450,69 @@ impl TemplateRepo for PgTemplateRepo {
         }
         Ok(())
     }
+
+    async fn list_trashed(&self) -> Result<Vec<Template>, AppError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, name, description, nocodb_table_id, nocodb_form_id,
+                   grafana_dashboard_uid, fields as "fields!: JsonValue",
+                   created_by, owner_id, visibility, created_at, updated_at, deleted_at
+            FROM portal.dataset_templates
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| map_template!(r)).collect())
+    }
+
+    async fn get_trashed(&self, id: Uuid) -> Result<Template, AppError> {
+        sqlx::query!(
+            r#"
+            SELECT id, name, description, nocodb_table_id, nocodb_form_id,
+                   grafana_dashboard_uid, fields as "fields!: JsonValue",
+                   created_by, owner_id, visibility, created_at, updated_at, deleted_at
+            FROM portal.dataset_templates
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_template!(r))
+        .ok_or_else(|| AppError::NotFound(format!("template '{id}' is not in the trash")))
+    }
+
+    async fn trash(&self, id: Uuid) -> Result<(), AppError> {
+        let res = sqlx::query!(
+            "UPDATE portal.dataset_templates SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("template '{id}' not found")))
+        }
+        Ok(())
+    }
+
+    async fn restore(&self, id: Uuid) -> Result<Template, AppError> {
+        sqlx::query!(
+            r#"
+            UPDATE portal.dataset_templates
+            SET deleted_at = NULL
+            WHERE id = $1 AND deleted_at IS NOT NULL
+            RETURNING id, name, description, nocodb_table_id, nocodb_form_id,
+                      grafana_dashboard_uid, fields as "fields!: JsonValue",
+                      created_by, owner_id, visibility, created_at, updated_at, deleted_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|r| map_template!(r))
+        .ok_or_else(|| AppError::NotFound(format!("template '{id}' is not in the trash")))
+    }
 }
\ No newline at end of file
@@ -15,6 +15,9 @@ pub struct User {
     pub username: String,
     pub email: Option<String>,
     pub role: String,
+    pub totp_enabled: bool,
+    /// Set while failed sign-ins lock the username out.
+    pub locked_until: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -44,6 +47,8 @@ macro_rules! map_user {
             username: $r.username,
             email: $r.email,
             role: $r.role,
+            totp_enabled: $r.totp_enabled,
+            locked_until: $r.locked_until,
             created_at: $r.created_at,
             updated_at: $r.updated_at,
         }
@@ -55,9 +60,14 @@ macro_rules! map_user {
 impl UserRepo for PgUserRepo {
     async fn list(&self) -> Result<Vec<User>, AppError> {
         let rows = sqlx::query!(
-            "SELECT id, username, email, role, created_at, updated_at
-            FROM portal.users 
-            ORDER BY created_at ASC"
+            r#"
+            SELECT u.id, u.username, u.email, u.role, u.totp_secret IS NOT NULL AS "totp_enabled!",
+                   f.locked_until AS "locked_until?", u.created_at, u.updated_at
+            FROM portal.users u
+            LEFT JOIN portal.failed_login_attempts f
+                   ON f.scope = 'username' AND f.key = u.username AND f.locked_until > now()
+            ORDER BY u.created_at ASC
+            "#
         )
         .fetch_all(&self.pool)
         .await?;
@@ -75,7 +85,10 @@ impl UserRepo for PgUserRepo {
             SET role       = $2,
                 updated_at = now()
             WHERE id = $1
-            RETURNING id, username, email, role, created_at, updated_at
+            RETURNING id, username, email, role, totp_secret IS NOT NULL AS "totp_enabled!",
+                      (SELECT f.locked_until FROM portal.failed_login_attempts f
+                       WHERE f.scope = 'username' AND f.key = username AND f.locked_until > now()) AS "locked_until?",
+                      created_at, updated_at
             "#,
             id,
             role
@@ -1,3 +1,4 @@
+use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 
@@ -11,6 +12,9 @@ pub enum AppError {
     Forbidden,
     #[error("validation: {0}")]
     Validation(String),
+    /// Seconds until the caller may try again, sent as Retry-After.
+    #[error("too many attempts; try again in {0} seconds")]
+    TooManyRequests(u64),
     #[error("internal: {0}")]
     Internal(#[from] anyhow::Error),
     #[error("database: {0}")]
@@ -24,9 +28,21 @@ impl IntoResponse for AppError {
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
             Self::Forbidden => StatusCode::FORBIDDEN,
             Self::Validation(_) => StatusCode::BAD_REQUEST,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
             Self::Internal(_) | Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let body = serde_json::json!({"error": self.to_string() });
-        (status, axum::Json(body)).into_response()
+        let mut response = (status, axum::Json(body)).into_response();
+        if let Self::TooManyRequests(seconds) = self {
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, seconds.into());
+        }
+        response
     }
-}
\ No newline at end of file
+}
+/// Malformed JSON bodies, including unknown enum values such as field types, are the
+/// caller's fault. Handlers opt in by taking `Result<Json<T>, JsonRejection>`.
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::Validation(rejection.body_text())
+    }
+}
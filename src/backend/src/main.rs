@@ -10,6 +10,7 @@ mod spa;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use auth::session::{SessionPolicy, TrustedProxies};
 use axum::extract::FromRef;
 use config::AppConfig;
 use db::pool::create_pool;
@@ -21,30 +22,55 @@ use db::repos::{
 use sqlx::PgPool;
 use tracing_subscriber::EnvFilter;
 
-use crate::db::repos::{PgTemplateRepo, TemplateRepo};
+use crate::db::repos::{
+    BlueprintRepo, DashboardShareRepo, GroupRepo, JobRepo, PgBlueprintRepo, PgDashboardShareRepo,
+    PgGroupRepo, PgJobRepo, PgPublicLinkRepo, PgTemplateAclRepo, PgTemplateRepo, PublicLinkRepo,
+    TemplateAclRepo, TemplateRepo,
+};
 use crate::orchestrator::{GrafanaClient, NocodbClient, Orchestrator};
 
 /// Shared application state injected into all handlers via Axum's `State` extractors.
 #[derive(Clone)]
 pub struct AppState {
-    pub pool:           PgPool,
-    pub config:         AppConfig,
-    pub http_client:    reqwest::Client,
-    pub dashboards:     Arc<dyn DashboardRepo>,
-    pub panels:         Arc<dyn PanelRepo>,
-    pub users:          Arc<dyn UserRepo>,
-    pub templates:      Arc<dyn TemplateRepo>,
-    pub orchestrator:   Arc<Orchestrator>,
+    pub pool:             PgPool,
+    pub config:           AppConfig,
+    pub http_client:      reqwest::Client,
+    pub dashboards:       Arc<dyn DashboardRepo>,
+    pub dashboard_shares: Arc<dyn DashboardShareRepo>,
+    pub groups:           Arc<dyn GroupRepo>,
+    pub panels:           Arc<dyn PanelRepo>,
+    pub public_links:     Arc<dyn PublicLinkRepo>,
+    pub users:            Arc<dyn UserRepo>,
+    pub templates:        Arc<dyn TemplateRepo>,
+    pub template_acl:     Arc<dyn TemplateAclRepo>,
+    pub jobs:             Arc<dyn JobRepo>,
+    pub blueprints:       Arc<dyn BlueprintRepo>,
+    pub orchestrator:     Arc<Orchestrator>,
 }
 
-/// Allows extractors (e.g. `AuthenticatedUser`) to pull the pool directly from state
-/// without needing to import `AppState`.
+/// Allows extractors (e.g. `AuthenticatedUser`) to pull the pool and session policy directly
+/// from state without needing to import `AppState`.
 impl FromRef<AppState> for PgPool {
     fn from_ref(state: &AppState) -> Self {
         state.pool.clone()
     }
 }
 
+impl FromRef<AppState> for SessionPolicy {
+    fn from_ref(state: &AppState) -> Self {
+        SessionPolicy {
+            ttl_hours: state.config.session_ttl_hours,
+            max_days:  state.config.session_max_days,
+        }
+    }
+}
+
+impl FromRef<AppState> for TrustedProxies {
+    fn from_ref(state: &AppState) -> Self {
+        TrustedProxies(state.config.trusted_proxies.clone())
+    }
+}
+
 /* ============================================================================================== */
 #[tokio::main]
 async fn main() {
@@ -122,17 +148,40 @@ async fn main() {
         config.grafana_service_account_token.clone(),
         config.grafana_datasource_uid.clone(),
     );
-    let orchestrator = Arc::new(Orchestrator { nocodb, grafana, pool: pool.clone() });
+    let orchestrator = Arc::new(Orchestrator {
+        nocodb,
+        grafana,
+        pool:       pool.clone(),
+        job_signal: tokio::sync::Notify::new(),
+    });
+
+    // Provisioning worker — also resumes jobs interrupted by a previous shutdown
+    let worker = orchestrator.clone();
+    tokio::spawn(async move { worker.run_provisioning_worker().await });
+
+    // Trash purger — permanently deletes whatever outlived the retention period
+    let purger = orchestrator.clone();
+    let retention_days = config.trash_retention_days;
+    tokio::spawn(async move { purger.run_trash_purger(retention_days).await });
+
+    // Session purger — deletes expired sessions
+    tokio::spawn(auth::session::run_session_purger(pool.clone()));
 
     let bind_address = config.bind_address.clone();
     let state = AppState {
-        pool:         pool.clone(),
+        pool:             pool.clone(),
         config,
         http_client,
-        dashboards:   Arc::new(PgDashboardRepo { pool: pool.clone() }),
-        panels:       Arc::new(PgPanelRepo     { pool: pool.clone() }),
-        users:        Arc::new(PgUserRepo       { pool: pool.clone() }),
-        templates:    Arc::new(PgTemplateRepo   { pool: pool.clone() }),
+        dashboards:       Arc::new(PgDashboardRepo      { pool: pool.clone() }),
+        dashboard_shares: Arc::new(PgDashboardShareRepo { pool: pool.clone() }),
+        groups:           Arc::new(PgGroupRepo          { pool: pool.clone() }),
+        panels:           Arc::new(PgPanelRepo          { pool: pool.clone() }),
+        public_links:     Arc::new(PgPublicLinkRepo     { pool: pool.clone() }),
+        users:            Arc::new(PgUserRepo           { pool: pool.clone() }),
+        templates:        Arc::new(PgTemplateRepo       { pool: pool.clone() }),
+        template_acl:     Arc::new(PgTemplateAclRepo    { pool: pool.clone() }),
+        jobs:             Arc::new(PgJobRepo            { pool: pool.clone() }),
+        blueprints:       Arc::new(PgBlueprintRepo      { pool: pool.clone() }),
         orchestrator,
     };
 
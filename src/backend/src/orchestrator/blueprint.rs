@@ -0,0 +1,181 @@
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::repos::blueprint_repo::StoredBlueprint;
+use crate::db::repos::template_repo::{FieldDefinition, Template};
+use crate::error::AppError;
+use super::records::template_fields;
+
+/// Version of the blueprint file layout. Files declaring a newer format are rejected rather
+/// than half-understood.
+pub const BLUEPRINT_FORMAT: u32 = 1;
+
+/* ============================================================================================== */
+/*                                             Format                                             */
+/* ============================================================================================== */
+
+/// A portable template definition: everything needed to provision a dataset, and nothing
+/// tied to an existing one (no NocoDB or Grafana IDs).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blueprint {
+    #[serde(default = "current_format")]
+    pub format:      u32,
+    pub name:        String,
+    /// Set on saved blueprints; ignored on import, where the next version is assigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version:     Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub fields:      Vec<FieldDefinition>,
+}
+
+fn current_format() -> u32 {
+    BLUEPRINT_FORMAT
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlueprintEncoding {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl BlueprintEncoding {
+    /// YAML for `*yaml` content types, JSON otherwise.
+    pub fn from_content_type(content_type: Option<&str>) -> Self {
+        match content_type {
+            Some(ct) if ct.contains("yaml") => BlueprintEncoding::Yaml,
+            _ => BlueprintEncoding::Json,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            BlueprintEncoding::Json => "application/json",
+            BlueprintEncoding::Yaml => "application/yaml",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            BlueprintEncoding::Json => "json",
+            BlueprintEncoding::Yaml => "yaml",
+        }
+    }
+}
+
+impl Blueprint {
+    pub fn from_template(template: &Template) -> Result<Self, AppError> {
+        Ok(Blueprint {
+            format:      BLUEPRINT_FORMAT,
+            name:        template.name.clone(),
+            version:     None,
+            description: template.description.clone(),
+            fields:      template_fields(template)?,
+        })
+    }
+
+    pub fn from_stored(stored: &StoredBlueprint) -> Result<Self, AppError> {
+        let fields = serde_json::from_value(stored.fields.clone())
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("stored blueprint fields are invalid: {e}")))?;
+        Ok(Blueprint {
+            format:      BLUEPRINT_FORMAT,
+            name:        stored.name.clone(),
+            version:     Some(stored.version),
+            description: stored.description.clone(),
+            fields,
+        })
+    }
+
+    /// Read an uploaded blueprint. Only the layout is checked here; the fields still need
+    /// the same validation as a template created through the API.
+    pub fn decode(body: &[u8], encoding: BlueprintEncoding) -> Result<Self, AppError> {
+        let blueprint: Blueprint = match encoding {
+            BlueprintEncoding::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            BlueprintEncoding::Yaml => serde_yaml::from_slice(body).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| AppError::Validation(format!("invalid blueprint: {e}")))?;
+
+        if blueprint.format > BLUEPRINT_FORMAT {
+            return Err(AppError::Validation(format!(
+                "blueprint format {} is newer than the supported format {BLUEPRINT_FORMAT}",
+                blueprint.format,
+            )));
+        }
+        if blueprint.name.trim().is_empty() {
+            return Err(AppError::Validation("blueprint name is required".into()));
+        }
+        Ok(blueprint)
+    }
+
+    pub fn encode(&self, encoding: BlueprintEncoding) -> Result<Vec<u8>, AppError> {
+        match encoding {
+            BlueprintEncoding::Json => serde_json::to_vec_pretty(self).map_err(|e| AppError::Internal(e.into())),
+            BlueprintEncoding::Yaml => serde_yaml::to_string(self)
+                .map(String::into_bytes)
+                .map_err(|e| AppError::Internal(e.into())),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                            Catalogue                                           */
+/* ============================================================================================== */
+
+/// Blueprints shipped with the portal, keyed by a stable name used in URLs.
+static BUILTIN: LazyLock<Vec<(&'static str, Blueprint)>> = LazyLock::new(|| {
+    [
+        ("weight", include_str!("../../blueprints/weight.yaml")),
+        ("sleep", include_str!("../../blueprints/sleep.yaml")),
+        ("expenses", include_str!("../../blueprints/expenses.yaml")),
+        ("workouts", include_str!("../../blueprints/workouts.yaml")),
+    ]
+    .into_iter()
+    .map(|(key, yaml)| {
+        let blueprint = Blueprint::decode(yaml.as_bytes(), BlueprintEncoding::Yaml)
+            .unwrap_or_else(|e| panic!("built-in blueprint '{key}' is invalid: {e}"));
+        (key, blueprint)
+    })
+    .collect()
+});
+
+pub fn builtin_blueprints() -> &'static [(&'static str, Blueprint)] {
+    &BUILTIN
+}
+
+pub fn builtin_blueprint(key: &str) -> Option<&'static Blueprint> {
+    BUILTIN.iter().find(|(k, _)| *k == key).map(|(_, b)| b)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::templates::validate_fields;
+
+    #[test]
+    fn builtin_catalogue_is_valid() {
+        assert_eq!(builtin_blueprints().len(), 4);
+        for (key, blueprint) in builtin_blueprints() {
+            validate_fields(&blueprint.fields).unwrap_or_else(|e| panic!("{key}: {e}"));
+        }
+    }
+
+    #[test]
+    fn round_trips_through_json_and_yaml() {
+        let weight = builtin_blueprint("weight").unwrap();
+        for encoding in [BlueprintEncoding::Json, BlueprintEncoding::Yaml] {
+            let decoded = Blueprint::decode(&weight.encode(encoding).unwrap(), encoding).unwrap();
+            assert_eq!(&decoded, weight);
+        }
+
+        let future = br#"{"format": 2, "name": "x", "fields": []}"#;
+        assert!(Blueprint::decode(future, BlueprintEncoding::Json).is_err());
+        assert!(Blueprint::decode(b"name: [", BlueprintEncoding::Yaml).is_err());
+    }
+}
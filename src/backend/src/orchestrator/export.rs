@@ -0,0 +1,395 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use arrow_array::{
+    builder::{
+        BooleanBuilder, Date32Builder, Float64Builder, Int64Builder, StringBuilder,
+        TimestampMillisecondBuilder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use bytes::Bytes;
+use chrono::{DateTime, Duration, NaiveDate};
+use futures::Stream;
+use parquet::arrow::ArrowWriter;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::db::repos::template_repo::{FieldDefinition, FieldType, Template};
+use crate::error::AppError;
+use super::expression::{Computation, Lookback};
+use super::nocodb_client::RecordQuery;
+use super::records::{date_filter, table_id, template_fields, time_column, MAX_PAGE_SIZE};
+use super::Orchestrator;
+
+/* ============================================================================================== */
+/*                                              Input                                             */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv     => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl   => "application/x-ndjson",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv     => "csv",
+            ExportFormat::Jsonl   => "jsonl",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,    // inclusive, on the dashboards' time; see `date_filter`
+    pub to:   Option<NaiveDate>,    // inclusive
+}
+
+/* ============================================================================================== */
+/*                                            Exporter                                            */
+/* ============================================================================================== */
+
+/// Paging state carried between chunks of an export stream.
+struct ExportCursor {
+    orchestrator: Arc<Orchestrator>,
+    table_id:     String,
+    filter:       Option<String>,
+    sort:         String,
+    offset:       u32,
+    computation:  Option<Computation>,
+    /// Rows dated before this day only fill the windows of computed fields.
+    skip_before:  Option<NaiveDate>,
+    encoder:      Encoder,
+}
+
+impl Orchestrator {
+    /// Stream every row of a dataset, oldest first, encoded as `format`.
+    ///
+    /// Rows are fetched from NocoDB one page at a time and each page is encoded and yielded
+    /// before the next is requested, so memory use is bounded by the page size rather than the
+    /// table. Columns follow the order of the template's fields, computed ones included. Setup
+    /// errors are returned up front; a NocoDB failure mid-stream ends the stream with an error.
+    pub fn export_records(
+        self: Arc<Self>,
+        template: &Template,
+        format: ExportFormat,
+        filter: ExportFilter,
+    ) -> Result<impl Stream<Item = Result<Bytes, AppError>> + Send + 'static, AppError> {
+        let table_id = table_id(template)?.to_string();
+        let fields = template_fields(template)?;
+        let time = time_column(&fields);
+        let computation = Computation::new(&fields, time)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("stored template expression is invalid: {e}")))?;
+
+        // Windows reaching back before `from` are filled from the rows before it.
+        let from = match computation.as_ref().filter(|c| c.has_windows()).map(Computation::lookback) {
+            Some(Lookback::Days(days)) => filter.from.map(|from| from - Duration::days(days.into())),
+            Some(Lookback::All) => None,
+            None => filter.from,
+        };
+
+        let cursor = ExportCursor {
+            orchestrator: self,
+            table_id,
+            filter:       date_filter(&fields, from, filter.to),
+            // `Id` breaks ties so offset paging neither skips nor repeats rows.
+            sort:         format!("{time},Id"),
+            offset:       0,
+            computation,
+            skip_before:  filter.from.filter(|_| from != filter.from),
+            encoder:      Encoder::new(format, fields)?,
+        };
+
+        Ok(futures::stream::try_unfold(Some(cursor), |cursor| async move {
+            let Some(mut cursor) = cursor else { return Ok(None) };
+
+            let page = cursor.orchestrator.nocodb.list_records(&cursor.table_id, &RecordQuery {
+                limit:  MAX_PAGE_SIZE,
+                offset: cursor.offset,
+                filter: cursor.filter.clone(),
+                sort:   Some(cursor.sort.clone()),
+            }).await?;
+            cursor.offset += page.list.len() as u32;
+            let last = page.page_info.is_last_page || page.list.is_empty();
+
+            let mut rows = page.list;
+            if let Some(computation) = &mut cursor.computation {
+                rows = rows.into_iter().flat_map(|row| computation.push(row)).collect();
+                if last {
+                    rows.extend(computation.finish());
+                }
+                if let Some(until) = cursor.skip_before {
+                    rows.retain(|row| computation.row_time(row).is_none_or(|t| t.date() >= until));
+                }
+            }
+
+            let mut chunk = cursor.encoder.write(&rows)?;
+            if last {
+                chunk.extend(cursor.encoder.finish()?);
+                return Ok(Some((Bytes::from(chunk), None)));
+            }
+            Ok(Some((Bytes::from(chunk), Some(cursor))))
+        }))
+    }
+}
+
+/* ============================================================================================== */
+/*                                            Encoding                                            */
+/* ============================================================================================== */
+
+/// Incremental encoder: each call returns only the bytes produced since the previous one.
+pub struct Encoder {
+    fields: Vec<FieldDefinition>,
+    kind:   EncoderKind,
+}
+
+enum EncoderKind {
+    Csv(Vec<u8>),
+    Jsonl(Vec<u8>),
+    Parquet(Box<ArrowWriter<Vec<u8>>>, SchemaRef),
+}
+
+impl Encoder {
+    pub fn new(format: ExportFormat, fields: Vec<FieldDefinition>) -> Result<Self, AppError> {
+        let kind = match format {
+            ExportFormat::Csv => {
+                // The header goes out with the first page.
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                writer.write_record(fields.iter().map(|f| f.name.as_str())).map_err(internal)?;
+                EncoderKind::Csv(writer.into_inner().map_err(|e| internal(e.into_error()))?)
+            }
+            ExportFormat::Jsonl => EncoderKind::Jsonl(Vec::new()),
+            ExportFormat::Parquet => {
+                let schema = Arc::new(parquet_schema(&fields));
+                let writer = ArrowWriter::try_new(Vec::new(), schema.clone(), None).map_err(internal)?;
+                EncoderKind::Parquet(Box::new(writer), schema)
+            }
+        };
+        Ok(Self { fields, kind })
+    }
+
+    /// Encode one page of NocoDB rows. Keys that are not template fields are dropped.
+    pub fn write(&mut self, rows: &[Map<String, Value>]) -> Result<Vec<u8>, AppError> {
+        match &mut self.kind {
+            EncoderKind::Csv(buf) => {
+                let mut writer = csv::Writer::from_writer(std::mem::take(buf));
+                for row in rows {
+                    writer.write_record(self.fields.iter().map(|f| csv_cell(row.get(&f.name))))
+                        .map_err(internal)?;
+                }
+                writer.into_inner().map_err(|e| internal(e.into_error()))
+            }
+            EncoderKind::Jsonl(buf) => {
+                for row in rows {
+                    write_json_line(buf, &self.fields, row)?;
+                }
+                Ok(std::mem::take(buf))
+            }
+            EncoderKind::Parquet(writer, schema) => {
+                if !rows.is_empty() {
+                    let batch = record_batch(schema, &self.fields, rows)?;
+                    writer.write(&batch).map_err(internal)?;
+                    // One row group per page, so the encoded bytes can leave right away.
+                    writer.flush().map_err(internal)?;
+                }
+                Ok(std::mem::take(writer.inner_mut()))
+            }
+        }
+    }
+
+    /// Remaining bytes: the Parquet footer, or the CSV header if no page was written.
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        match self.kind {
+            EncoderKind::Csv(buf) | EncoderKind::Jsonl(buf) => Ok(buf),
+            EncoderKind::Parquet(writer, _) => writer.into_inner().map_err(internal),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+fn internal(e: impl Into<anyhow::Error>) -> AppError {
+    AppError::Internal(anyhow::anyhow!("export encoding failed: {}", e.into()))
+}
+
+fn csv_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s))   => s.clone(),
+        Some(other)              => other.to_string(),
+    }
+}
+
+/// Write `row` as one JSON object whose keys follow the template's field order.
+fn write_json_line(buf: &mut Vec<u8>, fields: &[FieldDefinition], row: &Map<String, Value>) -> Result<(), AppError> {
+    buf.push(b'{');
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            buf.push(b',');
+        }
+        serde_json::to_writer(&mut *buf, &field.name).map_err(internal)?;
+        buf.push(b':');
+        serde_json::to_writer(&mut *buf, row.get(&field.name).unwrap_or(&Value::Null)).map_err(internal)?;
+    }
+    buf.write_all(b"}\n").map_err(internal)
+}
+
+/// Parquet column type per field type. Everything not listed is a string; all columns are nullable.
+fn parquet_type(field_type: FieldType) -> DataType {
+    match field_type {
+        FieldType::Number | FieldType::Currency | FieldType::Duration | FieldType::Computed => DataType::Float64,
+        FieldType::Integer | FieldType::Rating => DataType::Int64,
+        FieldType::Boolean  => DataType::Boolean,
+        FieldType::Date     => DataType::Date32,
+        FieldType::Datetime => DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        _                   => DataType::Utf8,
+    }
+}
+
+fn parquet_schema(fields: &[FieldDefinition]) -> Schema {
+    Schema::new(fields.iter()
+        .map(|f| Field::new(&f.name, parquet_type(f.field_type), true))
+        .collect::<Vec<_>>())
+}
+
+fn record_batch(
+    schema: &SchemaRef,
+    fields: &[FieldDefinition],
+    rows: &[Map<String, Value>],
+) -> Result<RecordBatch, AppError> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+
+    let columns = fields.iter().map(|field| {
+        let values = rows.iter().map(|row| row.get(&field.name).filter(|v| !v.is_null()));
+        let array: ArrayRef = match parquet_type(field.field_type) {
+            DataType::Float64 => {
+                let mut builder = Float64Builder::with_capacity(rows.len());
+                // NocoDB returns decimals as strings on some backends.
+                builder.extend(values.map(|v| match v? {
+                    Value::Number(n) => n.as_f64(),
+                    Value::String(s) => s.trim().parse().ok(),
+                    _ => None,
+                }));
+                Arc::new(builder.finish())
+            }
+            DataType::Int64 => {
+                let mut builder = Int64Builder::with_capacity(rows.len());
+                builder.extend(values.map(|v| match v? {
+                    Value::Number(n) => n.as_i64(),
+                    Value::String(s) => s.trim().parse().ok(),
+                    _ => None,
+                }));
+                Arc::new(builder.finish())
+            }
+            DataType::Boolean => {
+                let mut builder = BooleanBuilder::with_capacity(rows.len());
+                builder.extend(values.map(|v| v?.as_bool()));
+                Arc::new(builder.finish())
+            }
+            DataType::Date32 => {
+                let mut builder = Date32Builder::with_capacity(rows.len());
+                builder.extend(values.map(|v| {
+                    let date = v?.as_str()?.get(..10)?;
+                    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+                    Some((date - epoch).num_days() as i32)
+                }));
+                Arc::new(builder.finish())
+            }
+            DataType::Timestamp(..) => {
+                let mut builder = TimestampMillisecondBuilder::with_capacity(rows.len()).with_timezone("UTC");
+                builder.extend(values.map(|v| {
+                    let timestamp = DateTime::parse_from_rfc3339(v?.as_str()?).ok()?;
+                    Some(timestamp.timestamp_millis())
+                }));
+                Arc::new(builder.finish())
+            }
+            _ => {
+                let mut builder = StringBuilder::new();
+                builder.extend(values.map(|v| v.map(|v| csv_cell(Some(v)))));
+                Arc::new(builder.finish())
+            }
+        };
+        array
+    }).collect();
+
+    RecordBatch::try_new(schema.clone(), columns).map_err(internal)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{cast::AsArray, types::{Date32Type, Float64Type}, Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use serde_json::json;
+
+    fn fields() -> Vec<FieldDefinition> {
+        vec![
+            FieldDefinition { name: "weight".into(), field_type: FieldType::Number, unit: Some("kg".into()), ..Default::default() },
+            FieldDefinition { name: "measured_at".into(), field_type: FieldType::Date, ..Default::default() },
+            FieldDefinition { name: "notes".into(), field_type: FieldType::Text, ..Default::default() },
+        ]
+    }
+
+    fn rows() -> Vec<Map<String, Value>> {
+        vec![
+            json!({ "Id": 1, "notes": "fasted, \"early\"", "measured_at": "2024-03-01", "weight": 72.5 }),
+            json!({ "Id": 2, "measured_at": "2024-03-02", "weight": null }),
+        ].into_iter().map(|v| v.as_object().cloned().unwrap()).collect()
+    }
+
+    fn encode(format: ExportFormat) -> Vec<u8> {
+        let mut encoder = Encoder::new(format, fields()).unwrap();
+        let mut out = encoder.write(&rows()[..1]).unwrap();
+        out.extend(encoder.write(&rows()[1..]).unwrap());
+        out.extend(encoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn text_formats_follow_field_order() {
+        let csv = String::from_utf8(encode(ExportFormat::Csv)).unwrap();
+        assert_eq!(csv, "weight,measured_at,notes\n72.5,2024-03-01,\"fasted, \"\"early\"\"\"\n,2024-03-02,\n");
+
+        let jsonl = String::from_utf8(encode(ExportFormat::Jsonl)).unwrap();
+        assert_eq!(jsonl, concat!(
+            "{\"weight\":72.5,\"measured_at\":\"2024-03-01\",\"notes\":\"fasted, \\\"early\\\"\"}\n",
+            "{\"weight\":null,\"measured_at\":\"2024-03-02\",\"notes\":null}\n",
+        ));
+    }
+
+    #[test]
+    fn parquet_round_trips_typed_columns() {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(encode(ExportFormat::Parquet))).unwrap();
+        assert_eq!(builder.metadata().num_row_groups(), 2);
+        let batch = builder.build().unwrap().next().unwrap().unwrap();
+
+        let schema = batch.schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, ["weight", "measured_at", "notes"]);
+        assert_eq!(batch.column(0).as_primitive::<Float64Type>().value(0), 72.5);
+        assert_eq!(batch.column(1).as_primitive::<Date32Type>().value(0), 19783);
+        assert_eq!(batch.column(2).as_string::<i32>().value(0), "fasted, \"early\"");
+        assert!(batch.column(0).is_null(1));
+    }
+}
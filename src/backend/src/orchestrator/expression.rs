@@ -0,0 +1,694 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime};
+use serde_json::{Map, Number, Value};
+
+use crate::db::repos::template_repo::{FieldDefinition, FieldType};
+
+/// Longest accepted expression, in bytes.
+pub const MAX_EXPRESSION_LEN: usize = 500;
+/// Longest rolling window, in days.
+pub const MAX_WINDOW_DAYS: u32 = 366;
+
+/* ============================================================================================== */
+/*                                               AST                                              */
+/* ============================================================================================== */
+
+/// A parsed computed-field expression.
+///
+/// The grammar is deliberately small: numbers, references to the template's stored numeric
+/// fields, `+ - * / ^`, parentheses and the functions below. Anything else is a parse error,
+/// so an expression can only ever render to arithmetic over quoted, known column names.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Field(String),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(Scalar, Box<Expr>),
+    /// A window over the rows ordered by the template's time axis. Windows cannot nest.
+    Window(Window, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    Abs,
+    Round,
+    Sqrt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// Mean over the rows dated within the last `n` days, up to and including this row's time.
+    RollingAvg(u32),
+    RollingSum(u32),
+    /// Running total of every row up to and including this row's time.
+    CumSum,
+}
+
+impl Expr {
+    /// Windows in the expression, in evaluation (pre-)order.
+    fn windows(&self) -> Vec<(Window, &Expr)> {
+        let mut out = Vec::new();
+        self.collect_windows(&mut out);
+        out
+    }
+
+    fn collect_windows<'a>(&'a self, out: &mut Vec<(Window, &'a Expr)>) {
+        match self {
+            Expr::Number(_) | Expr::Field(_) => {}
+            Expr::Neg(inner) | Expr::Call(_, inner) => inner.collect_windows(out),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_windows(out);
+                rhs.collect_windows(out);
+            }
+            Expr::Window(window, arg) => out.push((*window, arg)),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Parsing                                            */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Parse `source`, resolving names against the stored numeric fields of `fields`.
+pub fn parse(source: &str, fields: &[FieldDefinition]) -> Result<Expr, String> {
+    if source.trim().is_empty() {
+        return Err("expression is empty".into());
+    }
+    if source.len() > MAX_EXPRESSION_LEN {
+        return Err(format!("expression is longer than {MAX_EXPRESSION_LEN} characters"));
+    }
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0, fields, in_window: false };
+    let expr = parser.expr()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {}", describe(token))),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => { chars.next(); }
+            '0'..='9' | '.' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let literal = &source[start..end];
+                let n: f64 = literal.parse().map_err(|_| format!("'{literal}' is not a number"))?;
+                tokens.push(Token::Number(n));
+            }
+            'a'..='z' | 'A'..='Z' | '_' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                tokens.push(Token::Ident(source[start..end].to_string()));
+            }
+            '+' | '-' | '*' | '/' | '^' => { tokens.push(Token::Op(c)); chars.next(); }
+            '(' => { tokens.push(Token::Open); chars.next(); }
+            ')' => { tokens.push(Token::Close); chars.next(); }
+            ',' => { tokens.push(Token::Comma); chars.next(); }
+            other => return Err(format!("unexpected character '{other}'")),
+        }
+    }
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number {n}"),
+        Token::Ident(name) => format!("'{name}'"),
+        Token::Op(op) => format!("'{op}'"),
+        Token::Open => "'('".into(),
+        Token::Close => "')'".into(),
+        Token::Comma => "','".into(),
+    }
+}
+
+struct Parser<'a> {
+    tokens:    Vec<Token>,
+    pos:       usize,
+    fields:    &'a [FieldDefinition],
+    in_window: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("expected {}, found {}", describe(&expected), describe(&token))),
+            None => Err(format!("expected {} at the end", describe(&expected))),
+        }
+    }
+
+    // expr := term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = if *op == '+' { BinOp::Add } else { BinOp::Sub };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+        Ok(lhs)
+    }
+
+    // term := factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.factor()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = if *op == '*' { BinOp::Mul } else { BinOp::Div };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.factor()?));
+        }
+        Ok(lhs)
+    }
+
+    // factor := '-' factor | primary ('^' factor)?
+    fn factor(&mut self) -> Result<Expr, String> {
+        if let Some(Token::Op('-')) = self.peek() {
+            self.next();
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        let base = self.primary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.next();
+            return Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(self.factor()?)));
+        }
+        Ok(base)
+    }
+
+    // primary := number | field | function '(' args ')' | '(' expr ')'
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Open) => {
+                let inner = self.expr()?;
+                self.expect(Token::Close)?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if self.peek() == Some(&Token::Open) => {
+                self.next();
+                self.call(&name)
+            }
+            Some(Token::Ident(name)) => self.field(name),
+            Some(token) => Err(format!("unexpected {}", describe(&token))),
+            None => Err("expression ends too early".into()),
+        }
+    }
+
+    fn field(&self, name: String) -> Result<Expr, String> {
+        match self.fields.iter().find(|f| f.name == name) {
+            Some(f) if f.field_type.is_numeric() => Ok(Expr::Field(name)),
+            Some(f) => Err(format!("'{name}' is a {} field; only stored numeric fields can be used", f.field_type)),
+            None => Err(format!("unknown field '{name}'")),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, String> {
+        let scalar = match name {
+            "abs" => Some(Scalar::Abs),
+            "round" => Some(Scalar::Round),
+            "sqrt" => Some(Scalar::Sqrt),
+            _ => None,
+        };
+        if let Some(scalar) = scalar {
+            let arg = self.expr()?;
+            self.expect(Token::Close)?;
+            return Ok(Expr::Call(scalar, Box::new(arg)));
+        }
+
+        if !matches!(name, "rolling_avg" | "rolling_sum" | "cumsum") {
+            return Err(format!(
+                "unknown function '{name}' (expected abs, round, sqrt, rolling_avg, rolling_sum or cumsum)"
+            ));
+        }
+        if self.in_window {
+            return Err(format!("'{name}' cannot be used inside another window function"));
+        }
+        self.in_window = true;
+        let arg = self.expr()?;
+        self.in_window = false;
+
+        let window = match name {
+            "cumsum" => Window::CumSum,
+            _ => {
+                self.expect(Token::Comma)?;
+                let days = match self.next() {
+                    Some(Token::Number(n)) if n.fract() == 0.0 && (1.0..=MAX_WINDOW_DAYS as f64).contains(&n) => n as u32,
+                    _ => return Err(format!("{name} takes a whole number of days from 1 to {MAX_WINDOW_DAYS}")),
+                };
+                if name == "rolling_avg" { Window::RollingAvg(days) } else { Window::RollingSum(days) }
+            }
+        };
+        self.expect(Token::Close)?;
+        Ok(Expr::Window(window, Box::new(arg)))
+    }
+}
+
+/* ============================================================================================== */
+/*                                               SQL                                              */
+/* ============================================================================================== */
+
+/// Render as a subquery over the dataset `table`, yielding each row's `time` and its value as
+/// `column`. `time` also orders the window functions; it must be the same expression the panel
+/// uses as its time column.
+///
+/// Division by zero, square roots of negatives and undefined powers yield NULL, as they do
+/// in [`Computation`]. Every operand is rendered once, however deeply nested: the guards on
+/// powers and square roots bind their operands in a scalar subquery, and window functions,
+/// which cannot sit in one, are computed first as columns `"W0"`, `"W1"`... of an inner query
+/// (upper case, so no field can clash with them).
+pub fn to_sql(expr: &Expr, time: &str, table: &str, column: &str) -> String {
+    let mut windows = Vec::new();
+    let value = render(expr, time, &mut windows);
+    let source = if windows.is_empty() {
+        table.to_string()
+    } else {
+        let columns: String = windows.iter().enumerate().map(|(i, w)| format!(", {w} AS \"W{i}\"")).collect();
+        format!("(SELECT *{columns} FROM {table}) AS windowed")
+    };
+    format!("(SELECT {time} AS time, {value} AS \"{column}\" FROM {source}) AS computed")
+}
+
+/// Render one node, pushing its window functions onto `windows` and referring to them by column.
+fn render(expr: &Expr, time: &str, windows: &mut Vec<String>) -> String {
+    match expr {
+        Expr::Number(n) => format!("{n}"),
+        Expr::Field(name) => format!("\"{name}\""),
+        Expr::Neg(inner) => format!("(-{})", render(inner, time, windows)),
+        Expr::Binary(op, lhs, rhs) => {
+            let (l, r) = (render(lhs, time, windows), render(rhs, time, windows));
+            match op {
+                BinOp::Add => format!("({l} + {r})"),
+                BinOp::Sub => format!("({l} - {r})"),
+                BinOp::Mul => format!("({l} * {r})"),
+                BinOp::Div => format!("({l} / NULLIF({r}, 0))"),
+                BinOp::Pow => format!(
+                    "(SELECT CASE WHEN l > 0 OR (l = 0 AND r > 0) OR (l < 0 AND r = trunc(r)) \
+                     THEN power(l, r) END FROM (SELECT {l} AS l, {r} AS r) AS operands)"
+                ),
+            }
+        }
+        Expr::Call(scalar, arg) => {
+            let a = render(arg, time, windows);
+            match scalar {
+                Scalar::Abs => format!("abs({a})"),
+                Scalar::Round => format!("round({a})"),
+                Scalar::Sqrt => format!("(SELECT CASE WHEN a >= 0 THEN sqrt(a) END FROM (SELECT {a} AS a) AS operand)"),
+            }
+        }
+        Expr::Window(window, arg) => {
+            // Windows cannot nest, so the argument adds none of its own.
+            let a = render(arg, time, windows);
+            // The frame ends at the current row's peers, matching `Computation`, which
+            // evaluates rows that share a time together.
+            let rolling = |days: u32| format!(
+                "OVER (ORDER BY {time} RANGE BETWEEN INTERVAL '{} days 23:59:59.999999' PRECEDING AND CURRENT ROW)",
+                days - 1,
+            );
+            windows.push(match window {
+                Window::RollingAvg(days) => format!("AVG({a}) {}", rolling(*days)),
+                Window::RollingSum(days) => format!("SUM({a}) {}", rolling(*days)),
+                Window::CumSum => format!("SUM({a}) OVER (ORDER BY {time})"),
+            });
+            format!("\"W{}\"", windows.len() - 1)
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                           Evaluation                                           */
+/* ============================================================================================== */
+
+/// How far back a computation needs to see: the longest rolling window, or every row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lookback {
+    Days(u32),
+    All,
+}
+
+/// Evaluates a template's computed fields over rows fed in ascending time order.
+///
+/// Rows that share a time are buffered and completed together, so [`Computation::push`] may
+/// return rows from earlier calls and [`Computation::finish`] returns whatever is still held.
+/// Expressions without windows complete every row immediately.
+pub struct Computation {
+    time:    String,
+    columns: Vec<Column>,
+    pending: Vec<Map<String, Value>>,
+    at:      Option<NaiveDateTime>,
+}
+
+struct Column {
+    name:    String,
+    expr:    Expr,
+    windows: Vec<WindowState>,
+}
+
+impl Computation {
+    /// `None` when the template has no computed fields. Fails only if a stored expression no
+    /// longer parses, which template validation prevents.
+    pub fn new(fields: &[FieldDefinition], time: &str) -> Result<Option<Self>, String> {
+        let mut columns = Vec::new();
+        for field in fields.iter().filter(|f| f.field_type == FieldType::Computed) {
+            let expr = parse(field.expression.as_deref().unwrap_or_default(), fields)
+                .map_err(|e| format!("field '{}': {e}", field.name))?;
+            let windows = expr.windows().into_iter().map(|(w, _)| WindowState::new(w)).collect();
+            columns.push(Column { name: field.name.clone(), expr, windows });
+        }
+        Ok((!columns.is_empty()).then(|| Computation {
+            time: time.to_string(),
+            columns,
+            pending: Vec::new(),
+            at: None,
+        }))
+    }
+
+    pub fn has_windows(&self) -> bool {
+        self.columns.iter().any(|c| !c.windows.is_empty())
+    }
+
+    /// Rows before the first one of interest that must also be fed for its windows to be full.
+    pub fn lookback(&self) -> Lookback {
+        let mut days = 0;
+        for state in self.columns.iter().flat_map(|c| &c.windows) {
+            match state.window {
+                Window::CumSum => return Lookback::All,
+                Window::RollingAvg(n) | Window::RollingSum(n) => days = days.max(n),
+            }
+        }
+        Lookback::Days(days)
+    }
+
+    /// Time of a row: its time column, falling back to NocoDB's `CreatedAt` as the dashboard
+    /// queries do.
+    pub fn row_time(&self, row: &Map<String, Value>) -> Option<NaiveDateTime> {
+        row_time(row, &self.time)
+    }
+
+    /// Feed the next row; returns the rows whose computed values are now known.
+    pub fn push(&mut self, row: Map<String, Value>) -> Vec<Map<String, Value>> {
+        let time = self.row_time(&row);
+        let mut done = Vec::new();
+        if time.is_none() || time != self.at {
+            done = self.finish();
+        }
+        match time {
+            Some(_) if self.has_windows() => {
+                self.at = time;
+                self.pending.push(row);
+            }
+            _ => {
+                self.pending.push(row);
+                let ready = self.complete(None);
+                done.extend(ready);
+            }
+        }
+        done
+    }
+
+    /// Complete the rows still held back.
+    pub fn finish(&mut self) -> Vec<Map<String, Value>> {
+        match self.at.take() {
+            Some(time) => self.complete(Some(time)),
+            None => self.complete(None),
+        }
+    }
+
+    /// Fill in the pending rows, which all share `time`. Rows without a time get no window
+    /// values.
+    fn complete(&mut self, time: Option<NaiveDateTime>) -> Vec<Map<String, Value>> {
+        let mut rows = std::mem::take(&mut self.pending);
+        for column in &mut self.columns {
+            let aggregates: Vec<Option<f64>> = match time {
+                Some(time) => {
+                    let args: Vec<&Expr> = column.expr.windows().into_iter().map(|(_, arg)| arg).collect();
+                    column.windows.iter_mut().zip(args).map(|(state, arg)| {
+                        for row in &rows {
+                            state.push(time, eval(arg, row, &[], &mut 0));
+                        }
+                        state.aggregate(time)
+                    }).collect()
+                }
+                None => vec![None; column.windows.len()],
+            };
+            for row in &mut rows {
+                column.fill(row, &aggregates);
+            }
+        }
+        rows
+    }
+
+    /// Fill in a row on its own, outside of any window: windowed terms come out null.
+    pub fn fill_alone(&self, row: &mut Map<String, Value>) {
+        for column in &self.columns {
+            column.fill(row, &vec![None; column.windows.len()]);
+        }
+    }
+}
+
+impl Column {
+    fn fill(&self, row: &mut Map<String, Value>, aggregates: &[Option<f64>]) {
+        let value = eval(&self.expr, row, aggregates, &mut 0)
+            .and_then(Number::from_f64)
+            .map_or(Value::Null, Value::Number);
+        row.insert(self.name.clone(), value);
+    }
+}
+
+/// `aggregates` holds the value of each window, in pre-order; `next` indexes into it.
+fn eval(expr: &Expr, row: &Map<String, Value>, aggregates: &[Option<f64>], next: &mut usize) -> Option<f64> {
+    let value = match expr {
+        Expr::Number(n) => Some(*n),
+        Expr::Field(name) => match row.get(name)? {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        },
+        Expr::Neg(inner) => eval(inner, row, aggregates, next).map(|v| -v),
+        Expr::Binary(op, lhs, rhs) => {
+            let l = eval(lhs, row, aggregates, next);
+            let r = eval(rhs, row, aggregates, next);
+            let (l, r) = (l?, r?);
+            match op {
+                BinOp::Add => Some(l + r),
+                BinOp::Sub => Some(l - r),
+                BinOp::Mul => Some(l * r),
+                BinOp::Div => (r != 0.0).then(|| l / r),
+                BinOp::Pow => Some(l.powf(r)),
+            }
+        }
+        Expr::Call(scalar, arg) => {
+            let a = eval(arg, row, aggregates, next)?;
+            match scalar {
+                Scalar::Abs => Some(a.abs()),
+                Scalar::Round => Some(a.round()),
+                Scalar::Sqrt => (a >= 0.0).then(|| a.sqrt()),
+            }
+        }
+        Expr::Window(..) => {
+            let value = aggregates.get(*next).copied().flatten();
+            *next += 1;
+            value
+        }
+    };
+    value.filter(|v| v.is_finite())
+}
+
+struct WindowState {
+    window:  Window,
+    entries: VecDeque<(NaiveDateTime, f64)>,
+    sum:     f64,
+    count:   usize,
+}
+
+impl WindowState {
+    fn new(window: Window) -> Self {
+        WindowState { window, entries: VecDeque::new(), sum: 0.0, count: 0 }
+    }
+
+    fn push(&mut self, time: NaiveDateTime, value: Option<f64>) {
+        let Some(value) = value else { return };
+        self.sum += value;
+        self.count += 1;
+        if self.window != Window::CumSum {
+            self.entries.push_back((time, value));
+        }
+    }
+
+    fn aggregate(&mut self, time: NaiveDateTime) -> Option<f64> {
+        if let Window::RollingAvg(days) | Window::RollingSum(days) = self.window {
+            let start = time - Duration::days(days.into());
+            while let Some(&(at, value)) = self.entries.front() {
+                if at > start {
+                    break;
+                }
+                self.entries.pop_front();
+                self.sum -= value;
+                self.count -= 1;
+            }
+        }
+        if self.count == 0 {
+            return None;
+        }
+        match self.window {
+            Window::RollingAvg(_) => Some(self.sum / self.count as f64),
+            Window::RollingSum(_) | Window::CumSum => Some(self.sum),
+        }
+    }
+}
+
+/// Dates count from midnight; timestamps are taken in UTC.
+fn row_time(row: &Map<String, Value>, time: &str) -> Option<NaiveDateTime> {
+    let parse = |value: Option<&Value>| -> Option<NaiveDateTime> {
+        let s = value?.as_str()?;
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return date.and_hms_opt(0, 0, 0);
+        }
+        DateTime::parse_from_rfc3339(s)
+            .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%:z"))
+            .map(|t| t.naive_utc())
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+            .ok()
+    };
+    parse(row.get(time)).or_else(|| parse(row.get("CreatedAt")))
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields() -> Vec<FieldDefinition> {
+        vec![
+            FieldDefinition { name: "measured_at".into(), field_type: FieldType::Date, ..Default::default() },
+            FieldDefinition { name: "weight".into(), field_type: FieldType::Number, ..Default::default() },
+            FieldDefinition { name: "height".into(), field_type: FieldType::Integer, ..Default::default() },
+            FieldDefinition { name: "notes".into(), field_type: FieldType::Text, ..Default::default() },
+        ]
+    }
+
+    fn computed(name: &str, expression: &str) -> FieldDefinition {
+        FieldDefinition {
+            name: name.into(),
+            field_type: FieldType::Computed,
+            expression: Some(expression.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parses_precedence_and_renders_quoted_sql() {
+        let expr = parse("weight / (height / 100) ^ 2", &fields()).unwrap();
+        assert_eq!(
+            to_sql(&expr, "created_at", "t", "bmi").replace(char::is_whitespace, ""),
+            "(SELECTcreated_atAStime,(\"weight\"/NULLIF((SELECTCASEWHENl>0OR(l=0ANDr>0)OR(l<0ANDr=trunc(r))\
+             THENpower(l,r)ENDFROM(SELECT(\"height\"/NULLIF(100,0))ASl,2ASr)ASoperands),0))AS\"bmi\"FROMt)AScomputed"
+        );
+
+        let expr = parse("-weight ^ 2 + cumsum(weight)", &fields()).unwrap();
+        assert!(matches!(expr, Expr::Binary(BinOp::Add, ref neg, _) if matches!(**neg, Expr::Neg(_))));
+        let sql = to_sql(&expr, "t", "t", "x");
+        assert!(sql.contains(" + \"W0\") AS \"x\" FROM (SELECT *, SUM(\"weight\") OVER (ORDER BY t) AS \"W0\" FROM t) AS windowed"));
+    }
+
+    #[test]
+    fn renders_each_operand_once_however_deep() {
+        let fields = fields();
+        let powers = parse(&format!("weight{}", " ^ 2".repeat(100)), &fields).unwrap();
+        let roots = parse(&format!("{}weight{}", "sqrt(".repeat(60), ")".repeat(60)), &fields).unwrap();
+        let mixed = parse(&format!("rolling_avg({}weight{}, 7)", "sqrt(2 ^ ".repeat(30), ")".repeat(30)), &fields).unwrap();
+        for expr in [powers, roots, mixed] {
+            let sql = to_sql(&expr, "t", "t", "x");
+            assert_eq!(sql.matches("\"weight\"").count(), 1);
+            assert!(sql.len() < 15_000, "{} bytes", sql.len());
+        }
+    }
+
+    #[test]
+    fn rejects_anything_outside_the_grammar() {
+        let fields = fields();
+        for bad in [
+            "", "weight; DROP TABLE x", "\"weight\"", "weight'", "notes * 2", "unknown + 1",
+            "pg_sleep(10)", "rolling_avg(weight)", "rolling_avg(weight, 0)", "rolling_avg(weight, 1.5)",
+            "cumsum(cumsum(weight))", "weight +", "(weight", "weight weight", "1..2",
+        ] {
+            assert!(parse(bad, &fields).is_err(), "{bad:?} should not parse");
+        }
+        assert!(parse(&"1+".repeat(300), &fields).is_err());
+    }
+
+    #[test]
+    fn evaluates_rolling_windows_over_peers() {
+        let mut fields = fields();
+        fields.push(computed("bmi", "round(weight / (height / 100) ^ 2)"));
+        fields.push(computed("avg", "rolling_avg(weight, 2)"));
+        fields.push(computed("total", "cumsum(weight)"));
+        let mut computation = Computation::new(&fields, "measured_at").unwrap().unwrap();
+        assert_eq!(computation.lookback(), Lookback::All);
+
+        let rows = [
+            json!({ "measured_at": "2024-03-01", "weight": 80, "height": 200 }),
+            json!({ "measured_at": "2024-03-02", "weight": 60, "height": 0 }),
+            json!({ "measured_at": "2024-03-02", "weight": "90" }),
+            json!({ "measured_at": "2024-03-03", "weight": null }),
+        ];
+        let mut out = Vec::new();
+        for row in rows {
+            out.extend(computation.push(row.as_object().cloned().unwrap()));
+        }
+        assert_eq!(out.len(), 3, "the last day is held back until finish");
+        out.extend(computation.finish());
+
+        let column = |name: &str| out.iter().map(|r| r[name].as_f64()).collect::<Vec<_>>();
+        assert_eq!(column("bmi"), [Some(20.0), None, None, None]);
+        assert_eq!(column("avg"), [Some(80.0), Some(230.0 / 3.0), Some(230.0 / 3.0), Some(75.0)]);
+        assert_eq!(column("total"), [Some(80.0), Some(230.0), Some(230.0), Some(230.0)]);
+    }
+}
@@ -2,8 +2,11 @@ use reqwest::Client;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::db::repos::template_repo::FieldDefinition;
+use crate::db::repos::template_repo::{
+    time_axis, Aggregation, ChartType, FieldDefinition, FieldType,
+};
 use crate::error::AppError;
+use super::expression;
 
 pub struct GrafanaClient {
     client:         Client,
@@ -18,6 +21,19 @@ pub struct GrafanaClient {
         pub url: String,  // e.g. "/d/{uid}/{slug}"
     }
 
+/// Entry of `GET /api/search`.
+#[derive(Deserialize)]
+pub struct DashboardSummary {
+    pub uid:   String,
+    pub title: String,
+    pub url:   String,  // e.g. "/d/{uid}/{slug}"
+    #[serde(default)]
+    pub tags:  Vec<String>,
+}
+
+/// Tag stamped on every dashboard the orchestrator generates.
+pub const GENERATED_TAG: &str = "mosaic-generated";
+
 /* ============================================================================================== */
 impl GrafanaClient {
     pub fn new(
@@ -45,13 +61,40 @@ impl GrafanaClient {
         base_id: &str,
         table_name: &str, // actual Postgres table name in NocoDB DB
         fields: &[FieldDefinition],
+    ) -> Result<CreatedDashboard, AppError> {
+        self.save_dashboard(None, title, base_id, table_name, fields).await
+    }
+
+    /// Regenerate the panels of an existing dashboard in place (same UID, `overwrite: true`).
+    pub async fn update_dashboard(
+        &self,
+        uid: &str,
+        title: &str,
+        base_id: &str,
+        table_name: &str,
+        fields: &[FieldDefinition],
+    ) -> Result<CreatedDashboard, AppError> {
+        self.save_dashboard(Some(uid), title, base_id, table_name, fields).await
+    }
+
+    async fn save_dashboard(
+        &self,
+        uid: Option<&str>,
+        title: &str,
+        base_id: &str,
+        table_name: &str,
+        fields: &[FieldDefinition],
     ) -> Result<CreatedDashboard, AppError> {
         let panels = self.build_panels(base_id, table_name, fields);
+        let (overwrite, message) = match uid {
+            Some(_) => (true,  "Updated by Mosaic orchestrator"),
+            None    => (false, "Created by Mosaic orchestrator"),
+        };
         let body = json!({
             "dashboard": {
-                "uid":           serde_json::Value::Null,
+                "uid":           uid,
                 "title":         title,
-                "tags":          ["mosaic-generated"],
+                "tags":          [GENERATED_TAG],
                 "timezone":      "browser",
                 "schemaVersion": 38,
                 "version":       0,
@@ -59,8 +102,8 @@ impl GrafanaClient {
                 "time":          { "from": "now-7d", "to": "now" },
                 "panels":        panels,
             },
-            "overwrite": false,
-            "message":   "Created by Mosaic orchestrator",
+            "overwrite": overwrite,
+            "message":   message,
         });
 
         self.client
@@ -71,12 +114,27 @@ impl GrafanaClient {
             .map_err(|e| AppError::Internal(e.into()))?
             .error_for_status()
             .map_err(|e| AppError::Internal(
-                anyhow::anyhow!("Grafana create_dashboard failed: {e}")
+                anyhow::anyhow!("Grafana save_dashboard failed: {e}")
             ))?
             .json::<CreatedDashboard>().await
             .map_err(|e| AppError::Internal(e.into()))
     }
 
+    /// List all dashboards visible to the service account (generated or not).
+    pub async fn list_dashboards(&self) -> Result<Vec<DashboardSummary>, AppError> {
+        self.client
+            .get(self.url("/api/search?type=dash-db&limit=5000"))
+            .header("Authorization", self.auth())
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("Grafana list_dashboards failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))
+    }
+
     pub async fn delete_dashboard(&self, uid: &str) -> Result<(), AppError> {
         self.client
             .delete(self.url(&format!("/api/dashboards/uid/{uid}")))
@@ -88,6 +146,22 @@ impl GrafanaClient {
         Ok(())
     }
 
+    /// The raw SQL of every panel target on a dashboard.
+    pub async fn dashboard_queries(&self, uid: &str) -> Result<Vec<String>, AppError> {
+        let body: Value = self.client
+            .get(self.url(&format!("/api/dashboards/uid/{uid}")))
+            .header("Authorization", self.auth())
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("Grafana get_dashboard failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+        Ok(target_sql(&body["dashboard"]))
+    }
+
     /* ====================================== Panel builder ===================================== */
 
     fn build_panels(
@@ -98,60 +172,347 @@ impl GrafanaClient {
     ) -> Value {
         let qualified_table = format!(r#"{base_id}."{table_name}""#);
 
-        let has_measured_at = fields.iter().any(|f| f.name == "measured_at");
-        let time_expr = if has_measured_at {
-            "COALESCE(measured_at, created_at)"
-        } else {
-            "created_at"
-        };
+        let time_expr = time_expr(time_axis(fields));
+        let time_expr = time_expr.as_str();
 
-        let numeric: Vec<&FieldDefinition> = fields
+        let charted: Vec<(&FieldDefinition, Visual)> = fields
             .iter()
-            .filter(|f| f.field_type == "number")
+            .filter_map(|f| Visual::for_field(f).map(|v| (f, v)))
             .collect();
+        let positions = pack_grid(&charted.iter().map(|(_, v)| v.width()).collect::<Vec<_>>());
 
-        let panels: Vec<Value> = numeric.iter().enumerate().map(|(i, field)| {
+        let panels: Vec<Value> = charted.iter().zip(positions).enumerate()
+            .map(|(i, ((field, visual), (x, y)))| {
             let title = match &field.unit {
                 Some(u) => format!("{} ({})", field.name, u),
                 None => field.name.clone(),
             };
 
-            let sql = format!(
-                "SELECT\n  {time} AS time,\n  \"{col}\"\n\
-                 FROM {tbl}\n\
-                 WHERE $__timeFilter({time})\n\
-                 ORDER BY time",
-                time = time_expr,
-                col  = field.name,
-                tbl  = qualified_table,
-            );
-
             json!({
                 "id":    i + 1,
-                "type":  "timeseries",
+                "type":  visual.panel_type(),
                 "title": title,
                 "datasource": { "type": "postgres", "uid": self.datasource_uid },
                 "targets": [{
-                    "rawSql":      sql,
+                    "rawSql":      panel_sql(field, fields, time_expr, &qualified_table),
                     "rawQuery":    true,
                     "format":      "time_series",
                     "refId":       "A",
                     "datasource":  { "type": "postgres", "uid": self.datasource_uid },
                 }],
-                "fieldConfig": {
-                    "defaults": { "custom": { "lineWidth": 2 } },
-                    "overrides": []
-                },
-                "options":  {},
+                "fieldConfig": field_config(field, *visual),
+                "options":  panel_options(*visual),
                 "gridPos":  {
-                    "x": 0,
-                    "y": (i as u32) * 8,
-                    "w": 24,
-                    "h": 8,
+                    "x": x,
+                    "y": y,
+                    "w": visual.width(),
+                    "h": PANEL_HEIGHT,
                 },
             })
         }).collect();
 
         json!(panels)
     }
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Raw SQL of the targets of a dashboard model's panels, including panels nested in rows.
+fn target_sql(dashboard: &Value) -> Vec<String> {
+    fn collect(panels: &Value, out: &mut Vec<String>) {
+        for panel in panels.as_array().into_iter().flatten() {
+            out.extend(panel["targets"].as_array().into_iter().flatten()
+                .filter_map(|t| t["rawSql"].as_str())
+                .map(str::to_owned));
+            collect(&panel["panels"], out);
+        }
+    }
+    let mut out = Vec::new();
+    collect(&dashboard["panels"], &mut out);
+    out
+}
+
+/// Dashboard grid width, in Grafana's units.
+const GRID_WIDTH: u32 = 24;
+const PANEL_HEIGHT: u32 = 8;
+
+/// The Grafana visualization a field is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Visual {
+    Timeseries,
+    Bars,
+    Stat,
+    Gauge,
+    Histogram,
+    StateTimeline,
+    BarGauge,
+}
+
+impl Visual {
+    /// The field's chosen chart, else the default for its type. `None` when it is not charted.
+    fn for_field(field: &FieldDefinition) -> Option<Visual> {
+        if !field.field_type.is_charted() {
+            return None;
+        }
+        let chosen = field.chart.as_ref().and_then(|c| c.chart_type);
+        Some(match (chosen, field.field_type) {
+            (Some(ChartType::Timeseries), _) => Visual::Timeseries,
+            (Some(ChartType::Bar), _)        => Visual::Bars,
+            (Some(ChartType::Stat), _)       => Visual::Stat,
+            (Some(ChartType::Gauge), _)      => Visual::Gauge,
+            (Some(ChartType::Histogram), _)  => Visual::Histogram,
+            (None, FieldType::Boolean)       => Visual::StateTimeline,
+            (None, FieldType::Rating)        => Visual::BarGauge,
+            (None, _)                        => Visual::Timeseries,
+        })
+    }
+
+    fn panel_type(self) -> &'static str {
+        match self {
+            // Bars over time are a time series drawn with bars; Grafana's bar chart panel
+            // expects categories on the x axis.
+            Visual::Timeseries | Visual::Bars => "timeseries",
+            Visual::Stat          => "stat",
+            Visual::Gauge         => "gauge",
+            Visual::Histogram     => "histogram",
+            Visual::StateTimeline => "state-timeline",
+            Visual::BarGauge      => "bargauge",
+        }
+    }
+
+    /// Charts over time take half the grid; single-value panels a quarter.
+    fn width(self) -> u32 {
+        match self {
+            Visual::Stat | Visual::Gauge | Visual::BarGauge => GRID_WIDTH / 4,
+            _ => GRID_WIDTH / 2,
+        }
+    }
+}
+
+/// Place panels of the given widths left to right, wrapping onto a new row when the next
+/// one does not fit. Returns the `(x, y)` of each panel.
+fn pack_grid(widths: &[u32]) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (0, 0);
+    widths.iter().map(|&w| {
+        if x + w > GRID_WIDTH {
+            x = 0;
+            y += PANEL_HEIGHT;
+        }
+        let position = (x, y);
+        x += w;
+        position
+    }).collect()
+}
+
+/// SQL for a row's timestamp: the time axis, falling back to the creation time for rows that
+/// leave it empty. Dates are read as midnight in the axis' timezone, if it has one.
+fn time_expr(axis: Option<&FieldDefinition>) -> String {
+    let Some(axis) = axis else {
+        return "created_at".into();
+    };
+    let column = match (axis.field_type, axis.timezone.as_deref()) {
+        (FieldType::Date, Some(tz)) => format!("(\"{}\"::timestamp AT TIME ZONE '{tz}')", axis.name),
+        _                           => format!("\"{}\"", axis.name),
+    };
+    format!("COALESCE({column}, created_at)")
+}
+
+/// Query for one field's panel, bucketed per day or week when the field is aggregated.
+///
+/// Computed fields are evaluated in a subquery over the whole table, so rolling windows and
+/// running totals at the start of the dashboard's range still see the rows before it.
+fn panel_sql(field: &FieldDefinition, fields: &[FieldDefinition], time_expr: &str, table: &str) -> String {
+    let col = &field.name;
+    // State timelines and averages need a number rather than a boolean.
+    let value = match field.field_type {
+        FieldType::Boolean  => format!("CAST(\"{col}\" AS integer)"),
+        FieldType::Computed => {
+            let source = match expression::parse(field.expression.as_deref().unwrap_or_default(), fields) {
+                Ok(expr) => expression::to_sql(&expr, time_expr, table, col),
+                Err(_)   => format!("(SELECT {time_expr} AS time, NULL AS \"{col}\" FROM {table}) AS computed"),
+            };
+            return grouped_sql(field, &format!("\"{col}\""), "time", &source);
+        }
+        _                   => format!("\"{col}\""),
+    };
+    grouped_sql(field, &value, time_expr, table)
+}
+
+fn grouped_sql(field: &FieldDefinition, value: &str, time_expr: &str, table: &str) -> String {
+    let col = &field.name;
+
+    let aggregation = field.chart.as_ref().map(|c| c.aggregation).unwrap_or_default();
+    let (interval, function) = match aggregation {
+        Aggregation::Raw => {
+            return format!(
+                "SELECT\n  {time_expr} AS time,\n  {value} AS \"{col}\"\n\
+                 FROM {table}\n\
+                 WHERE $__timeFilter({time_expr})\n\
+                 ORDER BY time"
+            );
+        }
+        Aggregation::DailyAvg  => ("1d", "AVG"),
+        Aggregation::WeeklySum => ("1w", "SUM"),
+    };
+    format!(
+        "SELECT\n  $__timeGroupAlias({time_expr}, '{interval}'),\n  {function}({value}) AS \"{col}\"\n\
+         FROM {table}\n\
+         WHERE $__timeFilter({time_expr})\n\
+         GROUP BY 1\n\
+         ORDER BY 1"
+    )
+}
+
+fn field_config(field: &FieldDefinition, visual: Visual) -> Value {
+    let chart = field.chart.clone().unwrap_or_default();
+    let mut defaults = json!({});
+
+    match field.field_type {
+        FieldType::Boolean => {
+            defaults["mappings"] = json!([{
+                "type": "value",
+                "options": {
+                    "0": { "text": "No",  "color": "red" },
+                    "1": { "text": "Yes", "color": "green" }
+                }
+            }]);
+        }
+        FieldType::Rating => {
+            defaults["min"] = json!(0);
+            defaults["max"] = json!(field.rating_max());
+            defaults["decimals"] = json!(1);
+        }
+        FieldType::Duration => defaults["unit"] = json!("s"),
+        FieldType::Currency => {
+            defaults["unit"] = json!(format!("currency{}", field.unit.as_deref().unwrap_or("EUR")));
+        }
+        _ => {}
+    }
+
+    match visual {
+        Visual::Timeseries => defaults["custom"] = json!({ "lineWidth": 2 }),
+        Visual::Bars       => defaults["custom"] = json!({ "drawStyle": "bars", "fillOpacity": 80 }),
+        _ => {}
+    }
+    if let Some(min) = chart.y_min {
+        defaults["min"] = json!(min);
+    }
+    if let Some(max) = chart.y_max {
+        defaults["max"] = json!(max);
+    }
+    if let Some(color) = &chart.color {
+        defaults["color"] = json!({ "mode": "fixed", "fixedColor": color });
+    }
+    if !chart.thresholds.is_empty() {
+        let base = chart.color.as_deref().unwrap_or("green");
+        let mut steps = vec![json!({ "color": base, "value": null })];
+        steps.extend(chart.thresholds.iter().map(|t| json!({ "color": t.color, "value": t.value })));
+        defaults["thresholds"] = json!({ "mode": "absolute", "steps": steps });
+        if matches!(visual, Visual::Timeseries | Visual::Bars) {
+            defaults["custom"]["thresholdsStyle"] = json!({ "mode": "line" });
+        }
+    }
+
+    json!({ "defaults": defaults, "overrides": [] })
+}
+
+fn panel_options(visual: Visual) -> Value {
+    match visual {
+        // Average over the selected range rather than the last entry alone.
+        Visual::BarGauge => json!({
+            "orientation": "horizontal",
+            "reduceOptions": { "calcs": ["mean"], "fields": "", "values": false }
+        }),
+        Visual::Stat | Visual::Gauge => json!({
+            "reduceOptions": { "calcs": ["lastNotNull"], "fields": "", "values": false }
+        }),
+        _ => json!({}),
+    }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repos::template_repo::ChartSettings;
+
+    #[test]
+    fn collects_target_sql_from_rows() {
+        let dashboard = json!({
+            "panels": [
+                { "targets": [{ "rawSql": "SELECT 1" }, { "refId": "B" }] },
+                { "type": "row", "panels": [{ "targets": [{ "rawSql": "SELECT 2" }] }] },
+            ],
+        });
+        assert_eq!(target_sql(&dashboard), vec!["SELECT 1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn packs_panels_into_rows() {
+        let widths = [12, 6, 6, 12, 6, 12];
+        assert_eq!(pack_grid(&widths), vec![(0, 0), (12, 0), (18, 0), (0, 8), (12, 8), (0, 16)]);
+    }
+
+    #[test]
+    fn chart_settings_choose_panel_and_query() {
+        let mut field = FieldDefinition { name: "steps".into(), field_type: FieldType::Integer, ..Default::default() };
+        assert_eq!(Visual::for_field(&field), Some(Visual::Timeseries));
+        assert!(panel_sql(&field, &[], "created_at", "t").contains("ORDER BY time"));
+
+        field.chart = Some(ChartSettings {
+            chart_type:  Some(ChartType::Bar),
+            aggregation: Aggregation::WeeklySum,
+            y_min:       Some(0.0),
+            ..Default::default()
+        });
+        assert_eq!(Visual::for_field(&field), Some(Visual::Bars));
+        let sql = panel_sql(&field, &[], "created_at", "t");
+        assert!(sql.contains("$__timeGroupAlias(created_at, '1w')") && sql.contains("SUM(\"steps\")"));
+        assert_eq!(field_config(&field, Visual::Bars)["defaults"]["min"], json!(0.0));
+
+        let notes = FieldDefinition { name: "notes".into(), ..Default::default() };
+        assert_eq!(Visual::for_field(&notes), None);
+    }
+
+    #[test]
+    fn computed_fields_query_a_windowed_subquery() {
+        let weight = FieldDefinition { name: "weight".into(), field_type: FieldType::Number, ..Default::default() };
+        let trend = FieldDefinition {
+            name:       "trend".into(),
+            field_type: FieldType::Computed,
+            expression: Some("rolling_avg(weight, 7)".into()),
+            ..Default::default()
+        };
+        let fields = [weight, trend.clone()];
+        assert_eq!(Visual::for_field(&trend), Some(Visual::Timeseries));
+
+        let sql = panel_sql(&trend, &fields, "created_at", "t");
+        assert!(sql.contains(
+            "FROM (SELECT created_at AS time, \"W0\" AS \"trend\" FROM (SELECT *, AVG(\"weight\") OVER \
+             (ORDER BY created_at RANGE BETWEEN INTERVAL '6 days 23:59:59.999999' PRECEDING AND CURRENT ROW) \
+             AS \"W0\" FROM t) AS windowed) AS computed"
+        ));
+        assert!(sql.contains("WHERE $__timeFilter(time)"));
+    }
+
+    #[test]
+    fn time_axis_drives_the_time_expression() {
+        let date = |name: &str| FieldDefinition { name: name.into(), field_type: FieldType::Date, ..Default::default() };
+        assert_eq!(time_expr(time_axis(&[])), "created_at");
+        assert_eq!(time_expr(time_axis(&[date("day")])), "COALESCE(\"day\", created_at)");
+
+        let mut backfilled = date("taken_on");
+        backfilled.time_axis = true;
+        backfilled.timezone = Some("Europe/Amsterdam".into());
+        let fields = [date("day"), backfilled];
+        assert_eq!(
+            time_expr(time_axis(&fields)),
+            "COALESCE((\"taken_on\"::timestamp AT TIME ZONE 'Europe/Amsterdam'), created_at)",
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::db::repos::template_repo::{FieldDefinition, FieldType, Template};
+use crate::error::AppError;
+use super::records::{apply_defaults, table_id, template_fields, validate_value};
+use super::Orchestrator;
+
+/// Rows per NocoDB insert request.
+const BATCH_SIZE: usize = 100;
+/// Parsed rows echoed back so the user can eyeball the mapping.
+const PREVIEW_ROWS: usize = 10;
+/// Row errors listed in the report; `error_count` still counts all of them.
+const MAX_REPORTED_ERRORS: usize = 100;
+
+/* ============================================================================================== */
+/*                                              Input                                             */
+/* ============================================================================================== */
+
+#[derive(Debug, Default)]
+pub struct ImportOptions {
+    /// CSV header → template field name. When absent, headers are matched to field names
+    /// case-insensitively, with spaces treated as underscores.
+    pub mapping: Option<HashMap<String, String>>,
+    pub dry_run: bool,
+}
+
+/* ============================================================================================== */
+/*                                             Report                                             */
+/* ============================================================================================== */
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub dry_run:          bool,
+    pub columns:          Vec<String>,               // CSV headers, in file order
+    pub mapping:          BTreeMap<String, String>,  // CSV header → field name, as applied
+    pub unmapped_columns: Vec<String>,
+    pub total_rows:       usize,
+    pub valid_rows:       usize,
+    pub error_count:      usize,
+    pub errors:           Vec<RowError>,
+    pub preview:          Vec<Map<String, Value>>,
+    pub inserted:         usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowError {
+    pub line:    usize,           // 1-based line in the file, header included
+    pub column:  Option<String>,
+    pub message: String,
+}
+
+/* ============================================================================================== */
+/*                                            Importer                                            */
+/* ============================================================================================== */
+
+impl Orchestrator {
+    /// Validate a CSV file against the template and, unless this is a dry run or any row is
+    /// invalid, insert it in batches. Nothing is inserted when a single row fails validation.
+    pub async fn import_csv(
+        &self,
+        template: &Template,
+        csv: &[u8],
+        options: ImportOptions,
+    ) -> Result<ImportReport, AppError> {
+        let table_id = table_id(template)?;
+        let fields = template_fields(template)?;
+        let (mut report, rows) = plan_import(&fields, csv, options.mapping.as_ref())?;
+        report.dry_run = options.dry_run;
+
+        if options.dry_run || report.error_count > 0 {
+            return Ok(report);
+        }
+
+        for batch in rows.chunks(BATCH_SIZE) {
+            if let Err(e) = self.nocodb.create_records(table_id, batch).await {
+                return Err(AppError::Internal(anyhow::anyhow!(
+                    "import stopped after {} of {} rows: {e}", report.inserted, rows.len()
+                )));
+            }
+            report.inserted += batch.len();
+        }
+        Ok(report)
+    }
+}
+
+/* ============================================================================================== */
+/*                                            Planning                                            */
+/* ============================================================================================== */
+
+/// Parse and validate the whole file without touching NocoDB.
+/// Returns the report and the rows ready for insertion (empty if any row failed).
+pub fn plan_import(
+    fields: &[FieldDefinition],
+    csv: &[u8],
+    mapping: Option<&HashMap<String, String>>,
+) -> Result<(ImportReport, Vec<Map<String, Value>>), AppError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(csv);
+
+    let columns: Vec<String> = reader
+        .headers()
+        .map_err(|e| AppError::Validation(format!("could not read CSV header: {e}")))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    if columns.is_empty() {
+        return Err(AppError::Validation("CSV file has no header row".into()));
+    }
+
+    let resolved = resolve_mapping(fields, &columns, mapping)?;
+    let unmapped_required: Vec<&str> = fields.iter()
+        .filter(|f| f.required && f.default.is_none() && !resolved.values().any(|n| n == &f.name))
+        .map(|f| f.name.as_str())
+        .collect();
+    if !unmapped_required.is_empty() {
+        return Err(AppError::Validation(format!(
+            "required field(s) not mapped from any column: {}", unmapped_required.join(", ")
+        )));
+    }
+    // Column index → field, for the columns that are imported.
+    let targets: Vec<(usize, &FieldDefinition)> = columns.iter().enumerate()
+        .filter_map(|(i, c)| {
+            let name = resolved.get(c)?;
+            fields.iter().find(|f| &f.name == name).map(|f| (i, f))
+        })
+        .collect();
+
+    let mut report = ImportReport {
+        dry_run:          false,
+        unmapped_columns: columns.iter().filter(|c| !resolved.contains_key(*c)).cloned().collect(),
+        columns,
+        mapping:          resolved,
+        total_rows:       0,
+        valid_rows:       0,
+        error_count:      0,
+        errors:           Vec::new(),
+        preview:          Vec::new(),
+        inserted:         0,
+    };
+    let mut rows = Vec::new();
+
+    for (index, result) in reader.records().enumerate() {
+        let line = index + 2;
+        report.total_rows += 1;
+
+        let record = match result {
+            Ok(r) => r,
+            Err(e) => {
+                push_error(&mut report, line, None, format!("unreadable row: {e}"));
+                continue;
+            }
+        };
+
+        let mut row = Map::new();
+        let mut row_ok = true;
+        for &(i, field) in &targets {
+            let cell = record.get(i).unwrap_or("");
+            match parse_cell(field, cell) {
+                Ok(value) => { row.insert(field.name.clone(), value); }
+                Err(message) => {
+                    row_ok = false;
+                    let column = report.columns[i].clone();
+                    push_error(&mut report, line, Some(column), message);
+                }
+            }
+        }
+
+        apply_defaults(fields, &mut row);
+
+        if row_ok {
+            report.valid_rows += 1;
+            if report.preview.len() < PREVIEW_ROWS {
+                report.preview.push(row.clone());
+            }
+            rows.push(row);
+        }
+    }
+
+    if report.error_count > 0 {
+        rows.clear();
+    }
+    Ok((report, rows))
+}
+
+/* ============================================================================================== */
+/// Check an explicit mapping, or derive one from the header names.
+fn resolve_mapping(
+    fields: &[FieldDefinition],
+    columns: &[String],
+    mapping: Option<&HashMap<String, String>>,
+) -> Result<BTreeMap<String, String>, AppError> {
+    let resolved: BTreeMap<String, String> = match mapping {
+        Some(explicit) => {
+            for (column, field) in explicit {
+                if !columns.contains(column) {
+                    return Err(AppError::Validation(format!("mapped column '{column}' is not in the CSV header")));
+                }
+                match fields.iter().find(|f| &f.name == field) {
+                    None => {
+                        return Err(AppError::Validation(format!("mapped field '{field}' is not part of the template")));
+                    }
+                    Some(f) if !f.field_type.is_stored() => {
+                        return Err(AppError::Validation(format!("mapped field '{field}' is computed and cannot be imported")));
+                    }
+                    Some(_) => {}
+                }
+            }
+            explicit.iter().map(|(c, f)| (c.clone(), f.clone())).collect()
+        }
+        None => columns.iter()
+            .filter_map(|c| {
+                let normalised = c.trim().to_lowercase().replace(' ', "_");
+                fields.iter()
+                    .find(|f| f.name == normalised && f.field_type.is_stored())
+                    .map(|f| (c.clone(), f.name.clone()))
+            })
+            .collect(),
+    };
+
+    let mut seen = HashSet::new();
+    for field in resolved.values() {
+        if !seen.insert(field) {
+            return Err(AppError::Validation(format!("field '{field}' is mapped from more than one column")));
+        }
+    }
+    if resolved.is_empty() {
+        return Err(AppError::Validation("no CSV column maps to a template field".into()));
+    }
+    Ok(resolved)
+}
+
+/* ============================================================================================== */
+/// Convert a CSV cell to the JSON value NocoDB expects for the field.
+///
+/// Empty cells take the field's default, or become `null`. Numbers may carry the field's unit as a suffix ("72.5 kg").
+/// Dates are accepted as `YYYY-MM-DD`, `YYYY/MM/DD` or an ISO timestamp, and stored as a date.
+/// Timestamps without an offset are taken as UTC. Booleans accept true/false, yes/no and 1/0;
+/// multi-select options are separated by `;` or `,`.
+fn parse_cell(field: &FieldDefinition, cell: &str) -> Result<Value, String> {
+    if cell.is_empty() {
+        return validate_value(field, field.default.clone().unwrap_or(Value::Null));
+    }
+
+    let value = match field.field_type {
+        FieldType::Number | FieldType::Currency | FieldType::Duration => {
+            Value::Number(Number::from_f64(parse_number(field, cell)?)
+                .ok_or_else(|| format!("'{cell}' is not a finite number"))?)
+        }
+        FieldType::Integer | FieldType::Rating => {
+            let parsed = parse_number(field, cell)?;
+            if parsed.fract() != 0.0 {
+                return Err(format!("'{cell}' is not a whole number"));
+            }
+            Value::Number(Number::from(parsed as i64))
+        }
+        FieldType::Boolean => match cell.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1"  => Value::Bool(true),
+            "false" | "no" | "n" | "0" => Value::Bool(false),
+            _ => return Err(format!("'{cell}' is not a boolean (expected true/false)")),
+        },
+        FieldType::Date => Value::String(parse_date(cell)?.to_string()),
+        FieldType::Datetime => Value::String(parse_datetime(cell)?.to_rfc3339()),
+        FieldType::MultiSelect => Value::Array(
+            cell.split([';', ',']).map(|o| Value::String(o.trim().to_string())).collect()
+        ),
+        _ => Value::String(cell.to_string()),
+    };
+    validate_value(field, value)
+}
+
+fn parse_number(field: &FieldDefinition, cell: &str) -> Result<f64, String> {
+    let number = match field.unit.as_deref() {
+        Some(unit) => cell.strip_suffix(unit).unwrap_or(cell).trim_end(),
+        None => cell,
+    };
+    number.parse().map_err(|_| format!("'{cell}' is not a number"))
+}
+
+fn parse_date(cell: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(cell, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(cell, "%Y/%m/%d"))
+        .or_else(|_| DateTime::parse_from_rfc3339(cell).map(|d| d.date_naive()))
+        .or_else(|_| NaiveDateTime::parse_from_str(cell, "%Y-%m-%d %H:%M:%S").map(|d| d.date()))
+        .map_err(|_| format!("'{cell}' is not a date (expected YYYY-MM-DD)"))
+}
+
+fn parse_datetime(cell: &str) -> Result<DateTime<FixedOffset>, String> {
+    DateTime::parse_from_rfc3339(cell)
+        .or_else(|_| NaiveDateTime::parse_from_str(cell, "%Y-%m-%d %H:%M:%S").map(|d| d.and_utc().fixed_offset()))
+        .or_else(|_| NaiveDateTime::parse_from_str(cell, "%Y-%m-%dT%H:%M:%S").map(|d| d.and_utc().fixed_offset()))
+        .map_err(|_| format!("'{cell}' is not a timestamp (expected ISO 8601)"))
+}
+
+fn push_error(report: &mut ImportReport, line: usize, column: Option<String>, message: String) {
+    report.error_count += 1;
+    if report.errors.len() < MAX_REPORTED_ERRORS {
+        report.errors.push(RowError { line, column, message });
+    }
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> Vec<FieldDefinition> {
+        vec![
+            FieldDefinition { name: "measured_at".into(), field_type: FieldType::Date, ..Default::default() },
+            FieldDefinition { name: "weight".into(), field_type: FieldType::Number, unit: Some("kg".into()), ..Default::default() },
+            FieldDefinition { name: "mood".into(), field_type: FieldType::Select, ..Default::default() },
+        ]
+    }
+
+    #[test]
+    fn auto_maps_headers_and_parses_cells() {
+        let csv = b"Measured At,Weight,Comment\n2024-01-02,72.5 kg,fine\n2024/01/03,,\n";
+        let (report, rows) = plan_import(&fields(), csv, None).unwrap();
+
+        assert_eq!(report.unmapped_columns, vec!["Comment".to_string()]);
+        assert_eq!(report.total_rows, 2);
+        assert_eq!(report.error_count, 0);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["weight"], serde_json::json!(72.5));
+        assert_eq!(rows[1]["measured_at"], serde_json::json!("2024-01-03"));
+        assert!(rows[1]["weight"].is_null());
+    }
+
+    #[test]
+    fn reports_row_errors_and_inserts_nothing() {
+        let csv = b"date,kg\n2024-01-02,72\nyesterday,heavy\n";
+        let mapping = HashMap::from([
+            ("date".to_string(), "measured_at".to_string()),
+            ("kg".to_string(), "weight".to_string()),
+        ]);
+        let (report, rows) = plan_import(&fields(), csv, Some(&mapping)).unwrap();
+
+        assert_eq!(report.valid_rows, 1);
+        assert_eq!(report.error_count, 2);
+        assert_eq!(report.errors[0].line, 3);
+        assert!(rows.is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_mappings() {
+        let csv = b"a,b\n1,2\n";
+        let to_same = HashMap::from([("a".to_string(), "weight".to_string()), ("b".to_string(), "weight".to_string())]);
+        let unknown = HashMap::from([("a".to_string(), "height".to_string())]);
+        assert!(plan_import(&fields(), csv, Some(&to_same)).is_err());
+        assert!(plan_import(&fields(), csv, Some(&unknown)).is_err());
+        assert!(plan_import(&fields(), csv, None).is_err());
+
+        let mut with_computed = fields();
+        with_computed.push(FieldDefinition {
+            name: "trend".into(), field_type: FieldType::Computed, expression: Some("weight".into()),
+            ..Default::default()
+        });
+        let to_computed = HashMap::from([("a".to_string(), "trend".to_string())]);
+        assert!(plan_import(&with_computed, csv, Some(&to_computed)).is_err());
+    }
+}
\ No newline at end of file
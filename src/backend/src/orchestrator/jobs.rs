@@ -0,0 +1,737 @@
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use crate::db::repos::{
+    job_repo::{CreateJob, JobCheckpoint, JobRepo, PgJobRepo, ProvisioningJob},
+    template_repo::{FieldDefinition, PgTemplateRepo, Template, TemplateRepo},
+};
+use crate::error::AppError;
+use super::nocodb_client::{provisioned_table_name, provisioning_job, RecordQuery};
+use super::records::{table_id, template_fields, MAX_PAGE_SIZE};
+use super::provisioner::{CreateTemplate, FormReady, GrafanaReady, Pipeline, TableReady};
+use super::Orchestrator;
+
+/// How often the worker re-scans for unfinished jobs when nobody wakes it up.
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Rows per NocoDB insert when copying a dataset; a batch is the unit a resumed copy redoes.
+const COPY_BATCH_SIZE: usize = 100;
+
+/* ============================================================================================== */
+/*                                           Job states                                           */
+/* ============================================================================================== */
+
+/// Persisted mirror of the pipeline typestates, plus the post-pipeline and terminal states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobState {
+    Unstarted,
+    TableReady,
+    /// Clone jobs only: the source rows are in the new table, which is not yet registered.
+    RowsCopied,
+    FormReady,
+    GrafanaReady,
+    Registered,
+    Completed,
+    Failed,
+}
+
+impl JobState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unstarted    => "unstarted",
+            Self::TableReady   => "table_ready",
+            Self::RowsCopied   => "rows_copied",
+            Self::FormReady    => "form_ready",
+            Self::GrafanaReady => "grafana_ready",
+            Self::Registered   => "registered",
+            Self::Completed    => "completed",
+            Self::Failed       => "failed",
+        }
+    }
+}
+
+impl TryFrom<&str> for JobState {
+    type Error = AppError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "unstarted"     => Ok(Self::Unstarted),
+            "table_ready"   => Ok(Self::TableReady),
+            "rows_copied"   => Ok(Self::RowsCopied),
+            "form_ready"    => Ok(Self::FormReady),
+            "grafana_ready" => Ok(Self::GrafanaReady),
+            "registered"    => Ok(Self::Registered),
+            "completed"     => Ok(Self::Completed),
+            "failed"        => Ok(Self::Failed),
+            other => Err(AppError::Internal(anyhow::anyhow!("unknown job state '{other}'"))),
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Worker                                             */
+/* ============================================================================================== */
+
+impl Orchestrator {
+    /// Persist a provisioning request and wake the worker. The returned job is in the
+    /// `unstarted` state; poll it via `GET /api/templates/jobs/{id}`.
+    pub async fn enqueue_provisioning(
+        &self,
+        input: CreateTemplate,
+        user_id: Uuid,
+    ) -> Result<ProvisioningJob, AppError> {
+        self.enqueue_job(input, user_id, None).await
+    }
+
+    /// Like [`Self::enqueue_provisioning`] with the fields of `source`, optionally copying its
+    /// rows into the new table before the dataset is registered. The source is left untouched.
+    pub async fn enqueue_clone(
+        &self,
+        source: &Template,
+        name: String,
+        copy_rows: bool,
+        user_id: Uuid,
+    ) -> Result<ProvisioningJob, AppError> {
+        let input = CreateTemplate {
+            name,
+            description: source.description.clone(),
+            fields:      template_fields(source)?,
+        };
+        if copy_rows {
+            table_id(source)?;
+        }
+        self.enqueue_job(input, user_id, copy_rows.then_some(source.id)).await
+    }
+
+    async fn enqueue_job(
+        &self,
+        input: CreateTemplate,
+        user_id: Uuid,
+        copy_rows_from: Option<Uuid>,
+    ) -> Result<ProvisioningJob, AppError> {
+        let job = self.jobs().create(CreateJob {
+            user_id,
+            name:        input.name,
+            description: input.description,
+            fields:      serde_json::to_value(&input.fields).map_err(|e| AppError::Internal(e.into()))?,
+            copy_rows_from,
+        }).await?;
+        self.job_signal.notify_one();
+        Ok(job)
+    }
+
+    /* ============================================================================================== */
+    /// Background loop: claims unfinished jobs one at a time and drives each to `completed`
+    /// or `failed`, until none is left to claim.
+    ///
+    /// Runs once immediately, then again whenever a job is enqueued or `POLL_INTERVAL`
+    /// elapses. Claims keep replicas off each other's jobs; a job interrupted by a restart
+    /// is resumed from its last checkpoint once its claim runs out.
+    pub async fn run_provisioning_worker(&self) {
+        loop {
+            while let Some(job) = self.claim_next_job().await {
+                let id = job.id;
+                if let Err(e) = self.drive_job(job).await {
+                    // Checkpoint writes failed — leave the job where it is and retry later.
+                    tracing::error!("Provisioning job '{id}' stalled: {e}");
+                }
+            }
+
+            let _ = tokio::time::timeout(POLL_INTERVAL, self.job_signal.notified()).await;
+        }
+    }
+
+    /* ======================================== Internal ======================================== */
+
+    fn jobs(&self) -> PgJobRepo {
+        PgJobRepo { pool: self.pool.clone() }
+    }
+
+    async fn claim_next_job(&self) -> Option<ProvisioningJob> {
+        self.jobs().claim_next().await
+            .inspect_err(|e| tracing::error!("Failed to claim a provisioning job: {e}"))
+            .ok()
+            .flatten()
+    }
+
+    /// Advance a job one step at a time, checkpointing after each. A failing step
+    /// compensates the external resources created so far and marks the job failed.
+    async fn drive_job(&self, mut job: ProvisioningJob) -> Result<(), AppError> {
+        loop {
+            let state = JobState::try_from(job.state.as_str())?;
+            if matches!(state, JobState::Completed | JobState::Failed) {
+                return Ok(());
+            }
+
+            match self.run_step(&job, state).await {
+                Ok(checkpoint) => {
+                    job = self.jobs().checkpoint(job.id, checkpoint).await?;
+                }
+                Err(e) => {
+                    tracing::warn!("Provisioning job '{}' failed in state '{}': {e}", job.id, job.state);
+                    self.compensate(&job).await;
+                    self.jobs().checkpoint(job.id, JobCheckpoint {
+                        state: JobState::Failed.as_str().to_string(),
+                        error: Some(e.to_string()),
+                        ..Default::default()
+                    }).await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /* ============================================================================================== */
+    /// Rebuild the typed pipeline from the job's checkpoint and run the next step.
+    async fn run_step(&self, job: &ProvisioningJob, state: JobState) -> Result<JobCheckpoint, AppError> {
+        let input = job_input(job)?;
+
+        match state {
+            JobState::Unstarted => {
+                // The table is named after the job, so one created just before a crash is
+                // found here and adopted instead of created twice.
+                let table = match self.provisioned_table(job).await? {
+                    Some(table) => table,
+                    None => Pipeline::new(input, job.user_id)
+                        .create_table(&self.nocodb, &provisioned_table_name(job.id)).await
+                        .map_err(|(e, _)| e)?
+                        .state,
+                };
+                Ok(JobCheckpoint {
+                    state:      JobState::TableReady.as_str().to_string(),
+                    base_id:    Some(table.base_id),
+                    table_id:   Some(table.table_id),
+                    table_name: Some(table.table_name),
+                    ..Default::default()
+                })
+            }
+            JobState::TableReady if job.copy_rows_from.is_some() => {
+                self.copy_rows(job, &input.fields).await?;
+                Ok(JobCheckpoint {
+                    state: JobState::RowsCopied.as_str().to_string(),
+                    ..Default::default()
+                })
+            }
+            JobState::TableReady | JobState::RowsCopied => {
+                let pipeline = Pipeline {
+                    input,
+                    user_id: job.user_id,
+                    state: TableReady {
+                        base_id:    checkpointed(&job.base_id, "base_id")?,
+                        table_id:   checkpointed(&job.table_id, "table_id")?,
+                        table_name: checkpointed(&job.table_name, "table_name")?,
+                    },
+                };
+                let next = pipeline.create_form(&self.nocodb).await.map_err(|(e, _)| e)?;
+                Ok(JobCheckpoint {
+                    state:           JobState::FormReady.as_str().to_string(),
+                    form_view_id:    Some(next.state.form_view_id),
+                    form_share_uuid: Some(next.state.form_share_uuid),
+                    ..Default::default()
+                })
+            }
+            JobState::FormReady => {
+                let pipeline = Pipeline {
+                    input,
+                    user_id: job.user_id,
+                    state: FormReady {
+                        base_id:         checkpointed(&job.base_id, "base_id")?,
+                        table_id:        checkpointed(&job.table_id, "table_id")?,
+                        table_name:      checkpointed(&job.table_name, "table_name")?,
+                        form_view_id:    checkpointed(&job.form_view_id, "form_view_id")?,
+                        form_share_uuid: checkpointed(&job.form_share_uuid, "form_share_uuid")?,
+                    },
+                };
+                let next = pipeline.create_grafana_dashboard(&self.grafana).await.map_err(|(e, _)| e)?;
+                Ok(JobCheckpoint {
+                    state:                 JobState::GrafanaReady.as_str().to_string(),
+                    grafana_dashboard_uid: Some(next.state.grafana_dashboard_uid),
+                    grafana_dashboard_url: Some(next.state.grafana_dashboard_url),
+                    ..Default::default()
+                })
+            }
+            JobState::GrafanaReady => {
+                // The template reuses the job ID, so a crash between the insert and the
+                // checkpoint is detected here instead of registering the dataset twice.
+                let templates = PgTemplateRepo { pool: self.pool.clone() };
+                let template = match templates.get_by_id(job.id).await {
+                    Ok(existing) => existing,
+                    Err(AppError::NotFound(_)) => {
+                        let pipeline = Pipeline {
+                            input,
+                            user_id: job.user_id,
+                            state: GrafanaReady {
+                                table_id:              checkpointed(&job.table_id, "table_id")?,
+                                table_name:            checkpointed(&job.table_name, "table_name")?,
+                                form_view_id:          checkpointed(&job.form_view_id, "form_view_id")?,
+                                form_share_uuid:       checkpointed(&job.form_share_uuid, "form_share_uuid")?,
+                                grafana_dashboard_uid: checkpointed(&job.grafana_dashboard_uid, "grafana_dashboard_uid")?,
+                                grafana_dashboard_url: checkpointed(&job.grafana_dashboard_url, "grafana_dashboard_url")?,
+                            },
+                        };
+                        pipeline.register(&self.pool, job.id).await?
+                    }
+                    Err(e) => return Err(e),
+                };
+                Ok(JobCheckpoint {
+                    state:       JobState::Registered.as_str().to_string(),
+                    template_id: Some(template.id),
+                    ..Default::default()
+                })
+            }
+            JobState::Registered => {
+                // Best-effort; the dataset is usable without a portal dashboard.
+                let templates = PgTemplateRepo { pool: self.pool.clone() };
+                let template_id = job.template_id.unwrap_or(job.id);
+                match templates.get_by_id(template_id).await {
+                    Ok(template) => {
+                        if let Err(e) = self.auto_create_portal_dashboard(
+                            &template,
+                            job.user_id,
+                            job.grafana_dashboard_uid.as_deref().unwrap_or_default(),
+                            job.grafana_dashboard_url.as_deref().unwrap_or_default(),
+                            job.form_share_uuid.as_deref().unwrap_or_default(),
+                        ).await {
+                            tracing::warn!("Auto-portal-dashboard creation failed (non-fatal): {e}");
+                        }
+                    }
+                    Err(e) => tracing::warn!("Registered template '{template_id}' is gone: {e}"),
+                }
+                Ok(JobCheckpoint {
+                    state: JobState::Completed.as_str().to_string(),
+                    ..Default::default()
+                })
+            }
+            JobState::Completed | JobState::Failed => {
+                Err(AppError::Internal(anyhow::anyhow!("job '{}' is already finished", job.id)))
+            }
+        }
+    }
+
+    /// The NocoDB table an earlier run of `job` created, if any.
+    async fn provisioned_table(&self, job: &ProvisioningJob) -> Result<Option<TableReady>, AppError> {
+        let base_id = self.nocodb.get_first_base_id().await?;
+        let table = self.nocodb.list_tables(&base_id).await?
+            .into_iter()
+            .find(|t| provisioning_job(&t.table_name) == Some(job.id));
+        Ok(table.map(|t| TableReady { base_id, table_id: t.id, table_name: t.table_name }))
+    }
+
+    /* ============================================================================================== */
+    /// Copy the stored columns of every row of the job's source template into its new table,
+    /// in `Id` order. A source deleted since the job was enqueued fails the job.
+    ///
+    /// Nobody else writes to `target` yet, so its row count is the number already copied and
+    /// a resumed copy carries on from there. `CreatedAt` is not preserved; datasets without a
+    /// time axis restart their timeline at the copy.
+    async fn copy_rows(&self, job: &ProvisioningJob, fields: &[FieldDefinition]) -> Result<(), AppError> {
+        let Some(source_id) = job.copy_rows_from else { return Ok(()) };
+        let source = PgTemplateRepo { pool: self.pool.clone() }.get_by_id(source_id).await?;
+        let source_table = table_id(&source)?;
+        let target = checkpointed(&job.table_id, "table_id")?;
+
+        let copied = self.nocodb.list_records(&target, &RecordQuery { limit: 1, ..Default::default() }).await?;
+        let mut offset = copied.page_info.total_rows as u32;
+
+        loop {
+            let page = self.nocodb.list_records(source_table, &RecordQuery {
+                limit:  MAX_PAGE_SIZE,
+                offset,
+                filter: None,
+                sort:   Some("Id".into()),
+            }).await?;
+            let rows: Vec<_> = page.list.iter()
+                .map(|row| fields.iter()
+                    .filter(|f| f.field_type.is_stored())
+                    .filter_map(|f| Some((f.name.clone(), row.get(&f.name)?.clone())))
+                    .collect())
+                .collect();
+            for batch in rows.chunks(COPY_BATCH_SIZE) {
+                self.nocodb.create_records(&target, batch).await?;
+            }
+            offset += rows.len() as u32;
+            if page.page_info.is_last_page || rows.is_empty() {
+                return Ok(());
+            }
+            self.jobs().renew_claim(job.id).await?;
+        }
+    }
+
+    /* ============================================================================================== */
+    /// Remove whatever external resources the job created before it failed, including a
+    /// table whose creation failed half-way and was never checkpointed.
+    async fn compensate(&self, job: &ProvisioningJob) {
+        if let Some(ref uid) = job.grafana_dashboard_uid
+            && let Err(e) = self.grafana.delete_dashboard(uid).await
+        {
+            tracing::warn!("Failed to delete Grafana dashboard '{uid}': {e}");
+        }
+        let table_id = match job.table_id {
+            Some(ref id) => Some(id.clone()),
+            None => self.provisioned_table(job).await.ok().flatten().map(|t| t.table_id),
+        };
+        if let Some(ref table_id) = table_id
+            && let Err(e) = self.nocodb.delete_table(table_id).await
+        {
+            tracing::warn!("Failed to delete NocoDB table '{table_id}': {e}");
+        }
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+fn job_input(job: &ProvisioningJob) -> Result<CreateTemplate, AppError> {
+    let fields: Vec<FieldDefinition> = serde_json::from_value(job.fields.clone())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("stored job fields are invalid: {e}")))?;
+    Ok(CreateTemplate {
+        name:        job.name.clone(),
+        description: job.description.clone(),
+        fields,
+    })
+}
+
+fn checkpointed(value: &Option<String>, column: &str) -> Result<String, AppError> {
+    value.clone().ok_or_else(|| {
+        AppError::Internal(anyhow::anyhow!("job checkpoint is missing '{column}'"))
+    })
+}
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        extract::{Path, Query, State},
+        http::StatusCode,
+        routing::{delete, get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Map, Value};
+    use sqlx::PgPool;
+    use tokio::sync::Notify;
+
+    use crate::db::repos::template_repo::FieldType;
+    use crate::orchestrator::{GrafanaClient, NocodbClient};
+
+    /// What the mock NocoDB and Grafana hold.
+    #[derive(Default)]
+    struct Remote {
+        /// NocoDB table ID → physical name and rows.
+        tables:          HashMap<String, (String, Vec<Map<String, Value>>)>,
+        dashboards:      Vec<String>,
+        next_id:         usize,
+        fail_dashboards: bool,
+    }
+
+    type Shared = Arc<Mutex<Remote>>;
+
+    /// NocoDB and Grafana on one random local port, just deep enough for the pipeline.
+    async fn mock_remote(remote: Shared) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let app = Router::new()
+            .route("/api/v2/meta/bases", get(|| async { Json(json!({ "list": [{ "id": "b1" }] })) }))
+            .route("/api/v2/meta/bases/{base}/tables", get(|State(r): State<Shared>| async move {
+                let r = r.lock().unwrap();
+                let list: Vec<_> = r.tables.iter()
+                    .map(|(id, (name, _))| json!({ "id": id, "title": name, "table_name": name }))
+                    .collect();
+                Json(json!({ "list": list }))
+            }).post(|State(r): State<Shared>, Json(body): Json<Value>| async move {
+                let mut r = r.lock().unwrap();
+                r.next_id += 1;
+                let id = format!("md_{}", r.next_id);
+                let name = format!("nc_b1__{}", body["table_name"].as_str().unwrap());
+                r.tables.insert(id.clone(), (name.clone(), vec![]));
+                Json(json!({ "id": id, "table_name": name }))
+            }))
+            .route("/api/v2/meta/tables/{id}", get(|State(r): State<Shared>, Path(id): Path<String>| async move {
+                let name = r.lock().unwrap().tables[&id].0.clone();
+                Json(json!({ "id": id, "base_id": "b1", "table_name": name, "columns": [] }))
+            }).delete(|State(r): State<Shared>, Path(id): Path<String>| async move {
+                r.lock().unwrap().tables.remove(&id);
+            }))
+            .route("/api/v2/meta/tables/{id}/views", get(|| async { Json(json!({ "list": [] })) }))
+            .route("/api/v2/meta/tables/{id}/forms", post(|Path(id): Path<String>| async move {
+                Json(json!({ "id": format!("vw_{id}"), "title": "form" }))
+            }))
+            .route("/api/v2/meta/forms/{view}", get(|| async { Json(json!({ "columns": [] })) }))
+            .route("/api/v2/meta/views/{view}/share", post(|Path(view): Path<String>| async move {
+                Json(json!({ "uuid": format!("share_{view}") }))
+            }))
+            .route("/api/v2/tables/{id}/records", get(|
+                State(r): State<Shared>,
+                Path(id): Path<String>,
+                Query(q): Query<HashMap<String, String>>,
+            | async move {
+                let r = r.lock().unwrap();
+                let rows = &r.tables[&id].1;
+                let from = q["offset"].parse::<usize>().unwrap().min(rows.len());
+                let to = (from + q["limit"].parse::<usize>().unwrap()).min(rows.len());
+                Json(json!({
+                    "list":     rows[from..to],
+                    "pageInfo": { "totalRows": rows.len(), "isLastPage": to == rows.len() },
+                }))
+            }).post(|State(r): State<Shared>, Path(id): Path<String>, Json(new): Json<Vec<Map<String, Value>>>| async move {
+                let mut r = r.lock().unwrap();
+                let rows = &mut r.tables.get_mut(&id).unwrap().1;
+                let ids: Vec<_> = new.iter().enumerate().map(|(i, _)| json!({ "Id": rows.len() + i + 1 })).collect();
+                rows.extend(new);
+                Json(ids)
+            }))
+            .route("/api/dashboards/db", post(|State(r): State<Shared>| async move {
+                let mut r = r.lock().unwrap();
+                if r.fail_dashboards {
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                r.next_id += 1;
+                let uid = format!("dash{}", r.next_id);
+                r.dashboards.push(uid.clone());
+                Ok(Json(json!({ "uid": uid, "url": format!("/d/{uid}/slug") })))
+            }))
+            .route("/api/dashboards/uid/{uid}", delete(|State(r): State<Shared>, Path(uid): Path<String>| async move {
+                r.lock().unwrap().dashboards.retain(|d| *d != uid);
+            }))
+            .with_state(remote);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        base_url
+    }
+
+    async fn orchestrator(pool: PgPool) -> (Orchestrator, Shared, Uuid) {
+        let remote = Shared::default();
+        let base_url = mock_remote(remote.clone()).await;
+        let http = reqwest::Client::new();
+        let orchestrator = Orchestrator {
+            nocodb:     NocodbClient::new(http.clone(), base_url.clone(), "token".into()),
+            grafana:    GrafanaClient::new(http, base_url, "token".into(), "ds".into()),
+            pool:       pool.clone(),
+            job_signal: Notify::new(),
+        };
+        let user_id = sqlx::query_scalar(
+            "INSERT INTO portal.users (username, password_hash, role) VALUES ('jane', '', 'editor') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        (orchestrator, remote, user_id)
+    }
+
+    fn input(name: &str) -> CreateTemplate {
+        CreateTemplate {
+            name:        name.into(),
+            description: None,
+            fields:      vec![FieldDefinition { name: "weight".into(), field_type: FieldType::Number, ..Default::default() }],
+        }
+    }
+
+    /// Run and checkpoint steps until the job reaches `target`, as a worker would.
+    async fn advance_to(o: &Orchestrator, mut job: ProvisioningJob, target: JobState) -> ProvisioningJob {
+        loop {
+            let state = JobState::try_from(job.state.as_str()).unwrap();
+            if state == target {
+                return job;
+            }
+            let checkpoint = o.run_step(&job, state).await.unwrap();
+            job = o.jobs().checkpoint(job.id, checkpoint).await.unwrap();
+        }
+    }
+
+    /// The worker died: its claims run out and the next worker picks the job up.
+    async fn resume(o: &Orchestrator) -> ProvisioningJob {
+        sqlx::query("UPDATE portal.provisioning_jobs SET claimed_until = now() - interval '1 second'")
+            .execute(&o.pool)
+            .await
+            .unwrap();
+        let job = o.claim_next_job().await.expect("the job is claimable again");
+        o.drive_job(job.clone()).await.unwrap();
+        o.jobs().get_by_id(job.id).await.unwrap()
+    }
+
+    fn tables_of(remote: &Shared, job_id: Uuid) -> usize {
+        remote.lock().unwrap().tables.values()
+            .filter(|(name, _)| provisioning_job(name) == Some(job_id))
+            .count()
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn resumes_from_every_checkpoint(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+        let checkpoints = [
+            JobState::Unstarted,
+            JobState::TableReady,
+            JobState::FormReady,
+            JobState::GrafanaReady,
+            JobState::Registered,
+        ];
+
+        for (n, checkpoint) in checkpoints.into_iter().enumerate() {
+            let job = o.enqueue_provisioning(input(&format!("dataset {n}")), user_id).await.unwrap();
+            advance_to(&o, job, checkpoint).await;
+
+            let job = resume(&o).await;
+            assert_eq!(job.state, "completed", "resumed from {checkpoint:?}");
+            assert_eq!(job.template_id, Some(job.id));
+            assert_eq!(tables_of(&remote, job.id), 1, "resumed from {checkpoint:?}");
+            assert_eq!(remote.lock().unwrap().dashboards.len(), n + 1, "resumed from {checkpoint:?}");
+        }
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn work_done_before_a_lost_checkpoint_is_not_redone(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+
+        // The table was created, but the worker died before recording it.
+        let job = o.enqueue_provisioning(input("table"), user_id).await.unwrap();
+        Pipeline::new(input("table"), user_id)
+            .create_table(&o.nocodb, &provisioned_table_name(job.id)).await
+            .map_err(|(e, _)| e)
+            .unwrap();
+        let job = resume(&o).await;
+        assert_eq!(job.state, "completed");
+        assert_eq!(tables_of(&remote, job.id), 1);
+
+        // The dataset was registered, but the worker died before recording it.
+        let job = o.enqueue_provisioning(input("template"), user_id).await.unwrap();
+        let job = advance_to(&o, job, JobState::GrafanaReady).await;
+        o.run_step(&job, JobState::GrafanaReady).await.unwrap();
+        let job = resume(&o).await;
+        assert_eq!(job.state, "completed");
+        assert_eq!(job.template_id, Some(job.id));
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_failing_step_removes_what_the_job_created(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+        remote.lock().unwrap().fail_dashboards = true;
+
+        o.enqueue_provisioning(input("doomed"), user_id).await.unwrap();
+        let job = o.claim_next_job().await.unwrap();
+        o.drive_job(job.clone()).await.unwrap();
+
+        let job = o.jobs().get_by_id(job.id).await.unwrap();
+        assert_eq!(job.state, "failed");
+        assert!(job.error.is_some_and(|e| e.contains("save_dashboard")));
+        assert!(remote.lock().unwrap().tables.is_empty());
+        assert!(PgTemplateRepo { pool: o.pool.clone() }.get_by_id(job.id).await.is_err());
+        // A finished job is never claimed again.
+        assert!(o.claim_next_job().await.is_none());
+
+        // A table created by a step that then failed is found by its name.
+        let job = o.enqueue_provisioning(input("half-made"), user_id).await.unwrap();
+        Pipeline::new(input("half-made"), user_id)
+            .create_table(&o.nocodb, &provisioned_table_name(job.id)).await
+            .map_err(|(e, _)| e)
+            .unwrap();
+        o.compensate(&job).await;
+        assert!(remote.lock().unwrap().tables.is_empty());
+    }
+
+    /// A provisioned dataset whose table holds `rows` rows, over more than one page.
+    async fn source(o: &Orchestrator, remote: &Shared, user_id: Uuid, rows: usize) -> Template {
+        o.enqueue_provisioning(input("source"), user_id).await.unwrap();
+        let job = resume(o).await;
+        let template = PgTemplateRepo { pool: o.pool.clone() }.get_by_id(job.template_id.unwrap()).await.unwrap();
+        let mut remote = remote.lock().unwrap();
+        let table = &mut remote.tables.get_mut(template.nocodb_table_id.as_ref().unwrap()).unwrap().1;
+        table.extend((1..=rows).map(|n| {
+            json!({ "Id": n, "weight": n, "CreatedAt": "2026-05-01 08:00:00+00:00" }).as_object().unwrap().clone()
+        }));
+        template
+    }
+
+    fn rows_of(remote: &Shared, table_id: &str) -> Vec<Map<String, Value>> {
+        remote.lock().unwrap().tables[table_id].1.clone()
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_clone_copies_the_stored_columns_of_every_row(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+        let source = source(&o, &remote, user_id, 2500).await;
+
+        let job = o.enqueue_clone(&source, "copy".into(), true, user_id).await.unwrap();
+        assert_eq!(job.copy_rows_from, Some(source.id));
+        let job = resume(&o).await;
+        assert_eq!(job.state, "completed");
+
+        let clone = PgTemplateRepo { pool: o.pool.clone() }.get_by_id(job.id).await.unwrap();
+        assert_eq!(clone.name, "copy");
+        assert_eq!(clone.fields, source.fields);
+        let copied = rows_of(&remote, clone.nocodb_table_id.as_ref().unwrap());
+        assert_eq!(copied.len(), 2500);
+        assert!(copied.iter().enumerate().all(|(n, row)| *row == *json!({ "weight": n + 1 }).as_object().unwrap()));
+        assert_eq!(rows_of(&remote, source.nocodb_table_id.as_ref().unwrap()).len(), 2500);
+
+        // Without `copy_rows` only the schema is cloned.
+        let job = o.enqueue_clone(&source, "empty".into(), false, user_id).await.unwrap();
+        assert_eq!(job.copy_rows_from, None);
+        let job = resume(&o).await;
+        assert_eq!(job.state, "completed");
+        assert!(rows_of(&remote, job.table_id.as_ref().unwrap()).is_empty());
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_resumed_copy_carries_on_from_the_rows_already_copied(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+        let source = source(&o, &remote, user_id, 1500).await;
+
+        // The worker died after copying the first batches.
+        let job = o.enqueue_clone(&source, "copy".into(), true, user_id).await.unwrap();
+        let job = advance_to(&o, job, JobState::TableReady).await;
+        let target = job.table_id.clone().unwrap();
+        let done: Vec<_> = (1..=1200).map(|n| json!({ "weight": n }).as_object().unwrap().clone()).collect();
+        remote.lock().unwrap().tables.get_mut(&target).unwrap().1.extend(done);
+
+        let job = resume(&o).await;
+        assert_eq!(job.state, "completed");
+        let copied = rows_of(&remote, &target);
+        assert_eq!(copied.len(), 1500);
+        assert!(copied.iter().enumerate().all(|(n, row)| row["weight"] == json!(n + 1)));
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn a_clone_whose_source_is_gone_fails(pool: PgPool) {
+        let (o, remote, user_id) = orchestrator(pool).await;
+        let source = source(&o, &remote, user_id, 10).await;
+
+        o.enqueue_clone(&source, "copy".into(), true, user_id).await.unwrap();
+        sqlx::query("DELETE FROM portal.dataset_templates WHERE id = $1")
+            .bind(source.id)
+            .execute(&o.pool)
+            .await
+            .unwrap();
+
+        let job = resume(&o).await;
+        assert_eq!(job.state, "failed");
+        assert_eq!(tables_of(&remote, job.id), 0);
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn concurrent_workers_never_claim_the_same_job(pool: PgPool) {
+        let (o, _, user_id) = orchestrator(pool).await;
+        for n in 0..2 {
+            o.enqueue_provisioning(input(&format!("dataset {n}")), user_id).await.unwrap();
+        }
+
+        let jobs = o.jobs();
+        let (a, b, c) = tokio::join!(jobs.claim_next(), jobs.claim_next(), jobs.claim_next());
+        let mut claimed: Vec<Uuid> = [a, b, c].into_iter().filter_map(|j| j.unwrap()).map(|j| j.id).collect();
+        claimed.sort();
+        claimed.dedup();
+        assert_eq!(claimed.len(), 2);
+    }
+}
@@ -1,19 +1,30 @@
+pub mod blueprint;
+pub mod export;
+pub mod expression;
 pub mod grafana_client;
+pub mod import;
+pub mod jobs;
 pub mod nocodb_client;
 pub mod provisioner;
+pub mod reconcile;
+pub mod records;
+pub mod schema_diff;
+pub mod trash;
 
 use std::{io::pipe, sync::Arc};
 use sqlx::PgPool;
+use tokio::sync::Notify;
 
 
 use crate::db::repos::{
     dashboard_repo::{self, CreateDashboard, DashboardRepo, PgDashboardRepo},
     panel_repo::{CreatePanel, PanelRepo, PgPanelRepo},
-    template_repo::{PgTemplateRepo, Template, TemplateRepo},
+    template_repo::{FieldDefinition, PgTemplateRepo, Template, TemplateRepo, UpdateTemplateRecord},
 };
 use crate::error::AppError;
-use provisioner::{CreateTemplate, Pipeline, Unstarted};
 pub use provisioner::CreateTemplate as CreateTemplateInput;
+pub use schema_diff::{FieldUpdate, UpdateTemplate as UpdateTemplateInput};
+use schema_diff::FieldChange;
 pub use nocodb_client::{NocodbClient};
 pub use grafana_client::GrafanaClient;
 use uuid::Uuid;
@@ -25,60 +36,12 @@ use uuid::Uuid;
 pub struct Orchestrator {
     pub nocodb:     NocodbClient,
     pub grafana:    GrafanaClient,
-    pub pool:       PgPool
+    pub pool:       PgPool,
+    /// Wakes the provisioning worker when a job is enqueued.
+    pub job_signal: Notify,
 }
 
 impl Orchestrator {
-    /// Run the full provisioning pipeline:
-    /// NocoDB table → form → Grafana dashboard → DB record → portal dashboard.
-    pub async fn provision_dataset(
-        &self,
-        input: CreateTemplateInput,
-        user_id: Uuid,
-    ) -> Result<Template, AppError> {
-        let pipeline = Pipeline::new(input, user_id);
-
-        // Step 1 - NocoDB table + columns
-         let pipeline = pipeline.create_table(&self.nocodb).await
-            .map_err(|(e, _)| e)?;
-
-        // Step 2 - NocoDB form view (cleanup on failure)
-        let pipeline = match pipeline.create_form(&self.nocodb).await {
-            Ok(p) => p,
-            Err((e, prev)) => {
-                tracing::warn!("Form creation failed; cleaning up table '{}': {e}", prev.state.table_id);
-                let _ = self.nocodb.delete_table(&prev.state.table_id).await;
-                return Err(e);
-            }
-        };
-
-        // Step 3 - Grafana dashboard (cleanup on fialure)
-        let pipeline = match pipeline.create_grafana_dashboard(&self.grafana).await {
-            Ok(p) => p,
-            Err((e, prev)) => {
-                tracing::warn!("Grafana dashboard creation failed; cleaning up NocoDB table: {e}");
-                let _ = self.nocodb.delete_table(&prev.state.table_id).await;
-                return Err(e);
-            }
-        };
-
-        // Step 4 - Persist to DB
-        let grafana_uid = pipeline.state.grafana_dashboard_uid.clone();
-        let grafana_url_path = pipeline.state.grafana_dashboard_url.clone();
-        let form_uuid = pipeline.state.form_share_uuid.clone();
-
-        let template = pipeline.register(&self.pool).await?;
-
-        // Step 5 - Auto-create portal dashboard (best-effort; don't fail provisioning)
-        if let Err(e) = self.auto_create_portal_dashboard(
-            &template, user_id, &grafana_uid, &grafana_url_path, &form_uuid,
-        ).await {
-            tracing::warn!("Auto-portal-dashboard creation failed (non-fatal): {e}");
-        }
-
-        Ok(template)
-    }
-
     /// Best-effort cleanup when a template is deleted.
     pub async fn deprovision_dataset(&self, template: &Template) {
         if let Some(ref table_id) = template.nocodb_table_id {
@@ -93,6 +56,70 @@ impl Orchestrator {
         }
     }
 
+    /// Evolve a provisioned dataset's schema in place:
+    /// NocoDB columns (drop → rename → alter → add) → form inputs → DB record → Grafana panels.
+    ///
+    /// Column changes the table already shows are skipped, and the DB record is written before
+    /// the Grafana dashboard is regenerated, so if any step fails, repeating the same update
+    /// picks up where it stopped.
+    pub async fn update_dataset(
+        &self,
+        template: &Template,
+        input: UpdateTemplateInput,
+    ) -> Result<Template, AppError> {
+        let current: Vec<FieldDefinition> = serde_json::from_value(template.fields.clone())
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("stored template fields are invalid: {e}")))?;
+        let changes = schema_diff::diff_fields(&current, &input.fields)?;
+        let fields: Vec<FieldDefinition> = input.fields.into_iter().map(|u| u.field).collect();
+
+        let table_id = template.nocodb_table_id.as_deref().ok_or_else(|| {
+            AppError::Validation("template has no provisioned NocoDB table".into())
+        })?;
+        let table = self.nocodb.get_table(table_id).await?;
+        let columns: Vec<&str> = table.columns.iter().map(|c| c.title.as_str()).collect();
+
+        let mut added = Vec::new();
+        for change in schema_diff::outstanding_changes(changes, &columns) {
+            match change {
+                FieldChange::Drop(name) => {
+                    let column = find_column(&table, &name)?;
+                    self.nocodb.delete_column(&column.id).await?;
+                }
+                FieldChange::Rename { from, to } => {
+                    let column = find_column(&table, &from)?;
+                    self.nocodb.rename_column(&column.id, &to).await?;
+                }
+                FieldChange::Alter { column, field } => {
+                    let column = find_column(&table, &column)?;
+                    self.nocodb.update_column(&column.id, &field).await?;
+                }
+                FieldChange::Add(field) => added.push(field),
+            }
+        }
+        if !added.is_empty() {
+            self.nocodb.add_columns(table_id, &added).await?;
+        }
+        // Required flags and descriptions live on the form inputs, which includes new columns.
+        for view_id in self.nocodb.list_form_views(table_id).await? {
+            self.nocodb.configure_form(&view_id, table_id, &fields).await?;
+        }
+
+        let repo = PgTemplateRepo { pool: self.pool.clone() };
+        let updated = repo.update(template.id, UpdateTemplateRecord {
+            name:        input.name,
+            description: input.description,
+            fields:      serde_json::to_value(&fields).map_err(|e| AppError::Internal(e.into()))?,
+        }).await?;
+
+        if let Some(ref uid) = updated.grafana_dashboard_uid {
+            self.grafana.update_dashboard(
+                uid, &updated.name, &table.base_id, &table.table_name, &fields,
+            ).await?;
+        }
+
+        Ok(updated)
+    }
+
     /* ======================================== Internal ======================================== */
     
     async fn auto_create_portal_dashboard(
@@ -179,4 +206,20 @@ impl Orchestrator {
 
         Ok(())
     }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+fn find_column<'a>(
+    table: &'a nocodb_client::TableMeta,
+    name: &str,
+) -> Result<&'a nocodb_client::ColumnMeta, AppError> {
+    table.columns
+        .iter()
+        .find(|c| c.title == name)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!(
+            "NocoDB table '{}' has no column '{name}'", table.id
+        )))
 }
\ No newline at end of file
@@ -1,10 +1,32 @@
+use chrono::{DateTime, Utc};
 use reqwest::Client;
-use serde::Deserialize;
-use serde_json::json;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
 
-use crate::db::repos::template_repo::FieldDefinition;
+use crate::db::repos::template_repo::{FieldDefinition, FieldType};
 use crate::error::AppError;
 
+/// Start of the physical name of every table Mosaic provisions, followed by the ID of the job
+/// that did, so that its tables can be told apart from ones made by hand in NocoDB. NocoDB may
+/// put its own base prefix in front.
+pub const TABLE_PREFIX: &str = "mosaic_";
+
+/// Physical name for the table of the dataset that job `job_id` provisions.
+pub fn provisioned_table_name(job_id: Uuid) -> String {
+    format!("{TABLE_PREFIX}{}", job_id.simple())
+}
+
+/// The job that provisioned a table, read back from its physical name; None for tables Mosaic
+/// did not create.
+pub fn provisioning_job(table_name: &str) -> Option<Uuid> {
+    let (_, id) = table_name.rsplit_once(TABLE_PREFIX)?;
+    if id.len() != 32 {
+        return None;
+    }
+    Uuid::try_parse(id).ok()
+}
+
 /* ============================================================================================== */
 /*                                          NocoDB Client                                         */
 /* ============================================================================================== */
@@ -21,12 +43,68 @@ pub struct CreatedTable {
     pub table_name: String,   // actual Postgres table name (nc_p_xxx_name)
 }
 
+/// Table metadata as returned by `GET /api/v2/meta/tables/{table_id}`.
+#[derive(Deserialize)]
+pub struct TableMeta {
+    pub id:         String,
+    pub base_id:    String,
+    pub table_name: String,
+    #[serde(default)]
+    pub columns:    Vec<ColumnMeta>,
+}
+
+/// Entry of `GET /api/v2/meta/bases/{base_id}/tables`.
+#[derive(Deserialize)]
+pub struct TableSummary {
+    pub id:         String,
+    pub title:      String,
+    pub table_name: String,
+    /// None if NocoDB leaves it out or in a format we do not read.
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+pub struct ColumnMeta {
+    pub id:          String,
+    pub title:       String,
+    pub column_name: Option<String>,
+    pub uidt:        String,
+}
+
+/// Query for `GET /api/v2/tables/{table_id}/records`.
+#[derive(Debug, Default)]
+pub struct RecordQuery {
+    pub limit:  u32,
+    pub offset: u32,
+    pub filter: Option<String>,   // NocoDB `where` expression, e.g. "(date,gte,exactDate,2024-01-01)"
+    pub sort:   Option<String>,   // comma-separated titles, "-" prefix for descending
+}
+
+#[derive(Deserialize)]
+pub struct RecordPage {
+    pub list:      Vec<Map<String, Value>>,
+    #[serde(rename = "pageInfo")]
+    pub page_info: PageInfo,
+}
+
+#[derive(Deserialize)]
+pub struct PageInfo {
+    #[serde(rename = "totalRows", default)]
+    pub total_rows:   u64,
+    #[serde(rename = "isLastPage", default)]
+    pub is_last_page: bool,
+}
+
 #[derive(Deserialize)]
 struct CreatedFormView { 
     id:     String,
     title:  String,
 }
 
+/// `type` of a form view in NocoDB's view list.
+const FORM_VIEW_TYPE: i64 = 1;
+
 /* ============================================================================================== */
 impl NocodbClient {
     pub fn new(
@@ -75,31 +153,23 @@ impl NocodbClient {
 
     /* ========================================================================================== */
     /// Create a table with all columns in a single request, as required by NocoDB v2 API.
+    /// `table_name` is the physical name, see [`provisioned_table_name`].
     pub async fn create_table(
         &self,
         base_id: &str,
         title: &str,
+        table_name: &str,
         fields: &[FieldDefinition],
     ) -> Result<CreatedTable, AppError> {
-        let columns: Vec<serde_json::Value> = fields.iter().map(|field| {
-            match field.field_type.as_str() {
-                "number" => json!({
-                    "title": field.name,
-                    "uidt":  "Decimal",
-                    "meta":  { "precision": 8 }
-                }),
-                "date"   => json!({ "title": field.name, "uidt": "Date" }),
-                "select" => json!({ "title": field.name, "uidt": "SingleSelect" }),
-                _        => json!({ "title": field.name, "uidt": "SingleLineText" }),
-            }
-        }).collect();
+        let columns: Vec<serde_json::Value> = fields.iter().filter_map(column_definition).collect();
 
         let resp = self.client
             .post(self.url(&format!("/api/v2/meta/bases/{base_id}/tables")))
             .header(self.auth().0, self.auth().1)
             .json(&json!({
-                "title":   title,
-                "columns": columns,
+                "title":      title,
+                "table_name": table_name,
+                "columns":    columns,
             }))
             .send().await
             .map_err(|e| AppError::Internal(e.into()))?
@@ -165,6 +235,42 @@ impl NocodbClient {
         )))
     }
 
+    /* ========================================================================================== */
+    /// List every table in a base.
+    pub async fn list_tables(&self, base_id: &str) -> Result<Vec<TableSummary>, AppError> {
+        #[derive(Deserialize)]
+        struct Response { list: Vec<TableSummary> }
+
+        let resp: Response = self.client
+            .get(self.url(&format!("/api/v2/meta/bases/{base_id}/tables")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB list_tables '{base_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+        Ok(resp.list)
+    }
+
+    /* ========================================================================================== */
+    /// Fetch a table's metadata, including its physical name and column list.
+    pub async fn get_table(&self, table_id: &str) -> Result<TableMeta, AppError> {
+        self.client
+            .get(self.url(&format!("/api/v2/meta/tables/{table_id}")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB get_table '{table_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))
+    }
+
     /* ========================================================================================== */
     /// Delete a table by its NocoDB table ID.
     pub async fn delete_table(&self, table_id: &str) -> Result<(), AppError> {
@@ -180,23 +286,13 @@ impl NocodbClient {
 
     /* ========================================= Columns ======================================== */
 
-    /// This method may be useful to keep around for updating tables later.
+    /// Append columns to an existing table (used when a template's schema evolves).
     pub async fn add_columns(
         &self,
         table_id: &str,
         fields: &[FieldDefinition],
     ) -> Result<(), AppError> {
-        for field in fields {
-            let column = match field.field_type.as_str() {
-                "number" => json!({
-                    "title": field.name,
-                    "uidt":  "Decimal",
-                    "meta":  { "precision": 8 }
-                }),
-                "date"   => json!({ "title": field.name, "uidt": "Date" }),
-                "select" => json!({ "title": field.name, "uidt": "SingleSelect" }),
-                _        => json!({ "title": field.name, "uidt": "SingleLineText" }),
-            };
+        for column in fields.iter().filter_map(column_definition) {
             self.client
                 .post(self.url(&format!("/api/v2/meta/tables/{table_id}/fields")))
                 .header(self.auth().0, self.auth().1)
@@ -205,20 +301,270 @@ impl NocodbClient {
                 .map_err(|e| AppError::Internal(e.into()))?
                 .error_for_status()
                 .map_err(|e| AppError::Internal(
-                    anyhow::anyhow!("NocoDB create_column '{}' failed: {e}", field.name)
+                    anyhow::anyhow!("NocoDB create_column '{}' failed: {e}", column["title"])
                 ))?;
         }
         Ok(())
     }
 
+    /* ========================================================================================== */
+    /// Rename a column, keeping the physical column name in step with the title so
+    /// that the generated Grafana SQL can keep addressing columns by field name.
+    pub async fn rename_column(&self, column_id: &str, new_name: &str) -> Result<(), AppError> {
+        self.client
+            .patch(self.url(&format!("/api/v2/meta/columns/{column_id}")))
+            .header(self.auth().0, self.auth().1)
+            .json(&json!({
+                "title":       new_name,
+                "column_name": new_name,
+            }))
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB rename_column '{column_id}' → '{new_name}' failed: {e}")
+            ))?;
+        Ok(())
+    }
+
+    /* ========================================================================================== */
+    /// Rewrite a column's definition (options, default, description) in place. The column
+    /// keeps its type and name.
+    pub async fn update_column(&self, column_id: &str, field: &FieldDefinition) -> Result<(), AppError> {
+        let Some(mut column) = column_definition(field) else {
+            return Ok(());
+        };
+        column["column_name"] = json!(field.name);
+        self.client
+            .patch(self.url(&format!("/api/v2/meta/columns/{column_id}")))
+            .header(self.auth().0, self.auth().1)
+            .json(&column)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB update_column '{}' failed: {e}", field.name)
+            ))?;
+        Ok(())
+    }
+
+    /* ========================================================================================== */
+    /// Drop a column (and its data) from a table.
+    pub async fn delete_column(&self, column_id: &str) -> Result<(), AppError> {
+        self.client
+            .delete(self.url(&format!("/api/v2/meta/columns/{column_id}")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB delete_column '{column_id}' failed: {e}")
+            ))?;
+        Ok(())
+    }
+
+    /* ========================================= Records ======================================== */
+
+    /// Fetch one page of rows from a table's data API.
+    pub async fn list_records(&self, table_id: &str, query: &RecordQuery) -> Result<RecordPage, AppError> {
+        let mut url = reqwest::Url::parse(&self.url(&format!("/api/v2/tables/{table_id}/records")))
+            .map_err(|e| AppError::Internal(e.into()))?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.append_pair("limit", &query.limit.to_string());
+            pairs.append_pair("offset", &query.offset.to_string());
+            if let Some(ref filter) = query.filter {
+                pairs.append_pair("where", filter);
+            }
+            if let Some(ref sort) = query.sort {
+                pairs.append_pair("sort", sort);
+            }
+        }
+
+        self.client
+            .get(url)
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB list_records '{table_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))
+    }
+
+    /* ========================================================================================== */
+    /// Fetch a single row by its primary key.
+    pub async fn get_record(&self, table_id: &str, row_id: i64) -> Result<Map<String, Value>, AppError> {
+        let resp = self.client
+            .get(self.url(&format!("/api/v2/tables/{table_id}/records/{row_id}")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound(format!("record '{row_id}' not found")));
+        }
+        let row: Value = resp
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB get_record '{table_id}/{row_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+
+        // Some NocoDB versions answer a missing row with 200 and an empty object.
+        match row {
+            Value::Object(map) if !map.is_empty() => Ok(map),
+            _ => Err(AppError::NotFound(format!("record '{row_id}' not found"))),
+        }
+    }
+
+    /* ========================================================================================== */
+    /// Insert rows in one request and return their new primary keys, in order.
+    pub async fn create_records(
+        &self,
+        table_id: &str,
+        rows: &[Map<String, Value>],
+    ) -> Result<Vec<i64>, AppError> {
+        #[derive(Deserialize)]
+        struct Created {
+            #[serde(rename = "Id")]
+            id: i64,
+        }
+
+        let created: Vec<Created> = self.client
+            .post(self.url(&format!("/api/v2/tables/{table_id}/records")))
+            .header(self.auth().0, self.auth().1)
+            .json(rows)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB create_records '{table_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+        Ok(created.into_iter().map(|c| c.id).collect())
+    }
+
+    /* ========================================================================================== */
+    /// Overwrite the given columns of a row; columns not in `values` are left untouched.
+    pub async fn update_record(
+        &self,
+        table_id: &str,
+        row_id: i64,
+        mut values: Map<String, Value>,
+    ) -> Result<(), AppError> {
+        values.insert("Id".into(), json!(row_id));
+        self.client
+            .patch(self.url(&format!("/api/v2/tables/{table_id}/records")))
+            .header(self.auth().0, self.auth().1)
+            .json(&json!([values]))
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB update_record '{table_id}/{row_id}' failed: {e}")
+            ))?;
+        Ok(())
+    }
+
+    /* ========================================================================================== */
+    pub async fn delete_record(&self, table_id: &str, row_id: i64) -> Result<(), AppError> {
+        self.client
+            .delete(self.url(&format!("/api/v2/tables/{table_id}/records")))
+            .header(self.auth().0, self.auth().1)
+            .json(&json!([{ "Id": row_id }]))
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB delete_record '{table_id}/{row_id}' failed: {e}")
+            ))?;
+        Ok(())
+    }
+
     /* ======================================= Form views ======================================= */
 
+    /// IDs of every form view on a table.
+    pub async fn list_form_views(&self, table_id: &str) -> Result<Vec<String>, AppError> {
+        #[derive(Deserialize)]
+        struct View { id: String, #[serde(rename = "type")] view_type: i64 }
+        #[derive(Deserialize)]
+        struct Response { list: Vec<View> }
+
+        let resp: Response = self.client
+            .get(self.url(&format!("/api/v2/meta/tables/{table_id}/views")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB list_views '{table_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+        Ok(resp.list.into_iter().filter(|v| v.view_type == FORM_VIEW_TYPE).map(|v| v.id).collect())
+    }
+
+    /* ========================================================================================== */
+    /// Mark the form inputs of `fields` as required and give them their descriptions.
+    pub async fn configure_form(
+        &self,
+        view_id: &str,
+        table_id: &str,
+        fields: &[FieldDefinition],
+    ) -> Result<(), AppError> {
+        #[derive(Deserialize)]
+        struct FormColumn { id: String, fk_column_id: String }
+        #[derive(Deserialize)]
+        struct Form { #[serde(default)] columns: Vec<FormColumn> }
+
+        let form: Form = self.client
+            .get(self.url(&format!("/api/v2/meta/forms/{view_id}")))
+            .header(self.auth().0, self.auth().1)
+            .send().await
+            .map_err(|e| AppError::Internal(e.into()))?
+            .error_for_status()
+            .map_err(|e| AppError::Internal(
+                anyhow::anyhow!("NocoDB get_form '{view_id}' failed: {e}")
+            ))?
+            .json().await
+            .map_err(|e| AppError::Internal(e.into()))?;
+        let table = self.get_table(table_id).await?;
+
+        for field in fields {
+            let Some(column) = table.columns.iter().find(|c| c.title == field.name) else { continue };
+            let Some(form_column) = form.columns.iter().find(|f| f.fk_column_id == column.id) else { continue };
+            self.client
+                .patch(self.url(&format!("/api/v2/meta/form-columns/{}", form_column.id)))
+                .header(self.auth().0, self.auth().1)
+                .json(&json!({
+                    "required":    field.required,
+                    "description": field.description,
+                    "show":        true,
+                }))
+                .send().await
+                .map_err(|e| AppError::Internal(e.into()))?
+                .error_for_status()
+                .map_err(|e| AppError::Internal(
+                    anyhow::anyhow!("NocoDB update_form_column '{}' failed: {e}", field.name)
+                ))?;
+        }
+        Ok(())
+    }
+
+    /* ========================================================================================== */
+
     /// Create a form view and return a publicly shareable form URL segment.
     /// Returns `(view_id, share_uuid)` — embed via `/proxy/nocodb/nc/form/{uuid}`.
     pub async fn create_shared_form(
         &self,
         table_id: &str,
         title: &str,
+        fields: &[FieldDefinition],
     ) -> Result<(String, String), AppError> {
         // 1. Create form view (type = "form")
         //    POST /api/v2/meta/tables/{tableId}/forms
@@ -240,6 +586,7 @@ impl NocodbClient {
             .map_err(|e| AppError::Internal(e.into()))?;
 
         tracing::info!("Created NocoDB form view '{}' (id: {})", title, view.id);
+        self.configure_form(&view.id, table_id, fields).await?;
 
         // 2. Enable sharing → get public UUID
         //    POST /api/v2/meta/views/{viewId}/share
@@ -263,4 +610,65 @@ impl NocodbClient {
 
         Ok((view.id, share.uuid))
     }
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Map a template field onto a NocoDB column definition, including its select options,
+/// default and description. `required` is left to the form view: a NOT NULL column could
+/// not be added to a table that already has rows. Computed fields have no column.
+fn column_definition(field: &FieldDefinition) -> Option<serde_json::Value> {
+    let title = &field.name;
+    let mut column = match field.field_type {
+        FieldType::Number      => json!({ "title": title, "uidt": "Decimal", "meta": { "precision": 8 } }),
+        FieldType::Integer     => json!({ "title": title, "uidt": "Number" }),
+        FieldType::Currency    => json!({
+            "title": title,
+            "uidt":  "Currency",
+            "meta":  { "currency_code": field.unit.as_deref().unwrap_or("EUR") }
+        }),
+        FieldType::Duration    => json!({ "title": title, "uidt": "Duration", "meta": { "duration": 0 } }),
+        FieldType::Rating      => json!({
+            "title": title,
+            "uidt":  "Rating",
+            "meta":  { "max": field.rating_max() }
+        }),
+        FieldType::Boolean     => json!({ "title": title, "uidt": "Checkbox" }),
+        FieldType::Text        => json!({ "title": title, "uidt": "SingleLineText" }),
+        FieldType::LongText    => json!({ "title": title, "uidt": "LongText" }),
+        FieldType::Email       => json!({ "title": title, "uidt": "Email" }),
+        FieldType::Url         => json!({ "title": title, "uidt": "URL" }),
+        FieldType::Date        => json!({ "title": title, "uidt": "Date" }),
+        FieldType::Datetime    => json!({ "title": title, "uidt": "DateTime" }),
+        FieldType::Select      => json!({ "title": title, "uidt": "SingleSelect" }),
+        FieldType::MultiSelect => json!({ "title": title, "uidt": "MultiSelect" }),
+        FieldType::Computed    => return None,
+    };
+
+    if field.field_type.has_options() && !field.options.is_empty() {
+        let options: Vec<Value> = field.options.iter().map(|o| json!({ "title": o })).collect();
+        column["colOptions"] = json!({ "options": options });
+    }
+    if let Some(default) = &field.default {
+        column["cdf"] = match default {
+            Value::String(s) => json!(s),
+            other => json!(other.to_string()),
+        };
+    }
+    if let Some(description) = &field.description {
+        column["description"] = json!(description);
+    }
+    Some(column)
+}
+/// NocoDB writes meta timestamps as `2024-03-01 08:30:00+00:00`; anything unreadable is None.
+fn lenient_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let raw = Option::<Value>::deserialize(deserializer)?;
+    Ok(raw.as_ref().and_then(Value::as_str).and_then(|s| {
+        DateTime::parse_from_rfc3339(s)
+            .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%:z"))
+            .map(|t| t.with_timezone(&Utc))
+            .ok()
+    }))
+}
@@ -68,18 +68,19 @@ impl Pipeline<Unstarted> {
         Self { input, user_id, state: Unstarted }
     }
 
-    /// Step 1: Create NocoDB table + columns.
+    /// Step 1: Create NocoDB table + columns, under the physical name `table_name`.
     /// On error the caller gets back ownership of the pipeline for cleanup.
     pub async fn create_table(
         self,
         nocodb: &NocodbClient,
+        table_name: &str,
     ) -> Result<Pipeline<TableReady>, (AppError, Pipeline<Unstarted>)> {
         let base_id = match nocodb.get_first_base_id().await {
             Ok(id) => id,
             Err(e) => return Err((e, self)),
         };
 
-        let created = match nocodb.create_table(&base_id, &self.input.name, &self.input.fields).await {
+        let created = match nocodb.create_table(&base_id, &self.input.name, table_name, &self.input.fields).await {
             Ok(t) => t,
             Err(e) => return Err((e, self)),
         };
@@ -104,7 +105,7 @@ impl Pipeline<TableReady> {
         nocodb: &NocodbClient,
     ) -> Result<Pipeline<FormReady>, (AppError, Pipeline<TableReady>)> {
         let form_title = format!("{} - Entry Form", self.input.name);
-        match nocodb.create_shared_form(&self.state.table_id, &form_title).await {
+        match nocodb.create_shared_form(&self.state.table_id, &form_title, &self.input.fields).await {
             Ok((view_id, uuid)) => Ok(Pipeline {
                 state: FormReady { 
                     base_id:            self.state.base_id,
@@ -153,13 +154,14 @@ impl Pipeline<FormReady> {
 
 /* ============================================================================================== */
 impl Pipeline<GrafanaReady> {
-    /// Step 4: Persist template record to the portal DB.
-    pub async fn register(self, pool: &PgPool) -> Result<Template, AppError> {
+    /// Step 4: Persist template record to the portal DB under a caller-chosen ID.
+    pub async fn register(self, pool: &PgPool, template_id: Uuid) -> Result<Template, AppError> {
         let repo = PgTemplateRepo { pool: pool.clone() };
         let fields_json = serde_json::to_value(&self.input.fields)
             .map_err(|e| AppError::Internal(e.into()))?;
 
         repo.create(CreateTemplateRecord { 
+            id:                     Some(template_id),
             name:                   self.input.name, 
             description:            self.input.description, 
             fields:                 fields_json, 
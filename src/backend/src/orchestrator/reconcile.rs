@@ -0,0 +1,437 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::db::repos::{
+    job_repo::{JobRepo, PgJobRepo, ProvisioningJob},
+    panel_repo::{Panel, PanelRepo, PgPanelRepo},
+    template_repo::{FieldDefinition, PgTemplateRepo, Template, TemplateRepo},
+};
+use crate::error::AppError;
+use super::grafana_client::{DashboardSummary, GENERATED_TAG};
+use super::nocodb_client::{provisioning_job, TableSummary};
+use super::Orchestrator;
+
+/* ============================================================================================== */
+/*                                             Report                                             */
+/* ============================================================================================== */
+
+/// Drift between the portal DB and the external services.
+#[derive(Debug, Default, Serialize)]
+pub struct ReconcileReport {
+    /// NocoDB tables Mosaic provisioned that no template or in-flight job refers to. Tables
+    /// made by hand, or by Mosaic before it named its tables, are never listed.
+    pub orphan_tables:              Vec<OrphanTable>,
+    /// `mosaic-generated` Grafana dashboards that no template or in-flight job refers to.
+    pub orphan_dashboards:          Vec<OrphanDashboard>,
+    /// Templates whose NocoDB table is gone. Not repairable — the data is lost.
+    pub missing_tables:             Vec<TemplateRef>,
+    /// Templates whose Grafana dashboard is gone; regenerated from the template fields.
+    pub missing_dashboards:         Vec<TemplateRef>,
+    /// Templates no portal panel embeds, usually because provisioning step 5 failed.
+    pub missing_portal_dashboards:  Vec<TemplateRef>,
+    /// Portal panels embedding a Grafana dashboard that no longer exists.
+    pub orphan_panels:              Vec<OrphanPanel>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OrphanTable {
+    pub id:         String,
+    pub title:      String,
+    pub table_name: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OrphanDashboard {
+    pub uid:   String,
+    pub title: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TemplateRef {
+    pub template_id: Uuid,
+    pub name:        String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct OrphanPanel {
+    pub panel_id:     Uuid,
+    pub dashboard_id: Uuid,
+    pub source_url:   String,
+}
+
+/// Result of a repair pass: the report that was acted on, plus any action that failed.
+#[derive(Debug, Serialize)]
+pub struct ReconcileOutcome {
+    pub report:   ReconcileReport,
+    pub failures: Vec<String>,
+}
+
+/// Everything the reconciler compares, fetched up front.
+pub struct Inventory {
+    pub templates:  Vec<Template>,           // trashed ones included
+    pub jobs:       Vec<ProvisioningJob>,    // unfinished only
+    pub tables:     Vec<TableSummary>,
+    pub dashboards: Vec<DashboardSummary>,
+    pub panels:     Vec<Panel>,              // panels with a source_url only
+}
+
+/* ============================================================================================== */
+/*                                           Reconciler                                           */
+/* ============================================================================================== */
+
+impl Orchestrator {
+    /// Compare the portal DB with NocoDB and Grafana without changing anything.
+    pub async fn reconcile_report(&self) -> Result<ReconcileReport, AppError> {
+        Ok(build_report(&self.inventory().await?))
+    }
+
+    /* ============================================================================================== */
+    /// Remove orphaned external resources and panels, regenerate missing Grafana dashboards
+    /// and create missing portal dashboards. Individual failures are collected rather than
+    /// aborting the pass, so one bad resource does not block the rest.
+    pub async fn reconcile_repair(&self) -> Result<ReconcileOutcome, AppError> {
+        let inventory = self.inventory().await?;
+        let report = build_report(&inventory);
+        let mut failures = Vec::new();
+
+        for table in &report.orphan_tables {
+            if let Err(e) = self.nocodb.delete_table(&table.id).await {
+                failures.push(format!("delete NocoDB table '{}': {e}", table.id));
+            }
+        }
+        for dashboard in &report.orphan_dashboards {
+            if let Err(e) = self.grafana.delete_dashboard(&dashboard.uid).await {
+                failures.push(format!("delete Grafana dashboard '{}': {e}", dashboard.uid));
+            }
+        }
+
+        let panels = PgPanelRepo { pool: self.pool.clone() };
+        for panel in &report.orphan_panels {
+            if let Err(e) = panels.delete(panel.panel_id).await {
+                failures.push(format!("delete panel '{}': {e}", panel.panel_id));
+            }
+        }
+
+        for missing in &report.missing_dashboards {
+            if let Some(template) = find_template(&inventory, missing.template_id)
+                && let Err(e) = self.regenerate_dashboard(template).await
+            {
+                failures.push(format!("regenerate dashboard for '{}': {e}", missing.name));
+            }
+        }
+
+        for missing in &report.missing_portal_dashboards {
+            let Some(template) = find_template(&inventory, missing.template_id) else { continue };
+            if let Err(e) = self.recreate_portal_dashboard(template, &inventory.dashboards).await {
+                failures.push(format!("create portal dashboard for '{}': {e}", missing.name));
+            }
+        }
+
+        Ok(ReconcileOutcome { report, failures })
+    }
+
+    /* ======================================== Internal ======================================== */
+
+    async fn inventory(&self) -> Result<Inventory, AppError> {
+        let base_id = self.nocodb.get_first_base_id().await?;
+        // Trashed templates keep their NocoDB table and Grafana dashboard until purged.
+        let repo = PgTemplateRepo { pool: self.pool.clone() };
+        let mut templates = repo.list_all().await?;
+        templates.extend(repo.list_trashed().await?);
+
+        Ok(Inventory {
+            templates,
+            jobs:       PgJobRepo { pool: self.pool.clone() }.list_unfinished().await?,
+            tables:     self.nocodb.list_tables(&base_id).await?,
+            dashboards: self.grafana.list_dashboards().await?,
+            panels:     PgPanelRepo { pool: self.pool.clone() }.list_with_source().await?,
+        })
+    }
+
+    async fn regenerate_dashboard(&self, template: &Template) -> Result<(), AppError> {
+        let (Some(table_id), Some(uid)) = (&template.nocodb_table_id, &template.grafana_dashboard_uid) else {
+            return Ok(());
+        };
+        let fields: Vec<FieldDefinition> = serde_json::from_value(template.fields.clone())
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("stored template fields are invalid: {e}")))?;
+        let table = self.nocodb.get_table(table_id).await?;
+        // Saving under the old UID keeps existing portal panel URLs valid.
+        self.grafana.update_dashboard(uid, &template.name, &table.base_id, &table.table_name, &fields).await?;
+        Ok(())
+    }
+
+    async fn recreate_portal_dashboard(
+        &self,
+        template: &Template,
+        dashboards: &[DashboardSummary],
+    ) -> Result<(), AppError> {
+        let (Some(owner_id), Some(uid)) = (template.created_by, &template.grafana_dashboard_uid) else {
+            return Err(AppError::Validation("template has no owner or Grafana dashboard".into()));
+        };
+        // A dashboard regenerated in this pass is not in the listing yet; its slug only
+        // affects the embed URL cosmetically, so fall back to a bare path.
+        let url = dashboards
+            .iter()
+            .find(|d| &d.uid == uid)
+            .map(|d| d.url.clone())
+            .unwrap_or_else(|| format!("/d/{uid}/dashboard"));
+        let form_uuid = template.nocodb_form_id.clone().unwrap_or_default();
+        self.auto_create_portal_dashboard(template, owner_id, uid, &url, &form_uuid).await
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Pure comparison of the fetched inventory; kept free of I/O so it can be unit tested.
+pub fn build_report(inv: &Inventory) -> ReconcileReport {
+    let mut report = ReconcileReport::default();
+
+    let existing_tables: HashSet<&str> = inv.tables.iter().map(|t| t.id.as_str()).collect();
+    let existing_dashboards: HashSet<&str> = inv.dashboards.iter().map(|d| d.uid.as_str()).collect();
+
+    // Resources owned by a template or by a job that is still provisioning.
+    let known_tables: HashSet<&str> = inv.templates.iter()
+        .filter_map(|t| t.nocodb_table_id.as_deref())
+        .chain(inv.jobs.iter().filter_map(|j| j.table_id.as_deref()))
+        .collect();
+    let known_dashboards: HashSet<&str> = inv.templates.iter()
+        .filter_map(|t| t.grafana_dashboard_uid.as_deref())
+        .chain(inv.jobs.iter().filter_map(|j| j.grafana_dashboard_uid.as_deref()))
+        .collect();
+
+    // A job may have created its table without checkpointing it yet, so tables no older than
+    // the oldest unfinished job are left alone, as are tables of unknown age while any is.
+    let running_jobs: HashSet<Uuid> = inv.jobs.iter().map(|j| j.id).collect();
+    let oldest_job = inv.jobs.iter().map(|j| j.created_at).min();
+    report.orphan_tables = inv.tables.iter()
+        .filter(|t| provisioning_job(&t.table_name).is_some_and(|job| !running_jobs.contains(&job)))
+        .filter(|t| oldest_job.is_none_or(|oldest| t.created_at.is_some_and(|created| created < oldest)))
+        .filter(|t| !known_tables.contains(t.id.as_str()))
+        .map(|t| OrphanTable { id: t.id.clone(), title: t.title.clone(), table_name: t.table_name.clone() })
+        .collect();
+
+    report.orphan_dashboards = inv.dashboards.iter()
+        .filter(|d| d.tags.iter().any(|t| t == GENERATED_TAG))
+        .filter(|d| !known_dashboards.contains(d.uid.as_str()))
+        .map(|d| OrphanDashboard { uid: d.uid.clone(), title: d.title.clone() })
+        .collect();
+
+    let embedded: HashSet<&str> = inv.panels.iter()
+        .filter_map(|p| p.source_url.as_deref().and_then(grafana_uid))
+        .collect();
+
+    for template in &inv.templates {
+        let template_ref = || TemplateRef { template_id: template.id, name: template.name.clone() };
+
+        if let Some(ref table_id) = template.nocodb_table_id
+            && !existing_tables.contains(table_id.as_str())
+        {
+            report.missing_tables.push(template_ref());
+            continue;
+        }
+        let Some(ref uid) = template.grafana_dashboard_uid else { continue };
+        if !existing_dashboards.contains(uid.as_str()) {
+            report.missing_dashboards.push(template_ref());
+        }
+        if !embedded.contains(uid.as_str()) {
+            report.missing_portal_dashboards.push(template_ref());
+        }
+    }
+
+    // A panel pointing at a template's dashboard is kept even if that dashboard is
+    // missing — the repair pass regenerates it under the same UID.
+    report.orphan_panels = inv.panels.iter()
+        .filter_map(|p| {
+            let url = p.source_url.as_deref()?;
+            let uid = grafana_uid(url)?;
+            (!existing_dashboards.contains(uid) && !known_dashboards.contains(uid)).then(|| OrphanPanel {
+                panel_id:     p.id,
+                dashboard_id: p.dashboard_id,
+                source_url:   url.to_string(),
+            })
+        })
+        .collect();
+
+    report
+}
+
+/* ============================================================================================== */
+/// Extract the dashboard UID from a proxied Grafana URL such as
+/// `/proxy/grafana/d/{uid}/{slug}?kiosk` or `/proxy/grafana/d-solo/{uid}/...`.
+pub(crate) fn grafana_uid(source_url: &str) -> Option<&str> {
+    let rest = source_url
+        .strip_prefix("/proxy/grafana/d/")
+        .or_else(|| source_url.strip_prefix("/proxy/grafana/d-solo/"))?;
+    let uid = rest.split(['/', '?']).next()?;
+    (!uid.is_empty()).then_some(uid)
+}
+
+fn find_template(inv: &Inventory, id: Uuid) -> Option<&Template> {
+    inv.templates.iter().find(|t| t.id == id)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, Utc};
+    use crate::orchestrator::nocodb_client::provisioned_table_name;
+
+    fn template(table_id: &str, uid: &str) -> Template {
+        Template {
+            id:                    Uuid::new_v4(),
+            name:                  format!("template {table_id}"),
+            description:           None,
+            nocodb_table_id:       Some(table_id.into()),
+            nocodb_form_id:        None,
+            grafana_dashboard_uid: Some(uid.into()),
+            fields:                serde_json::json!([]),
+            created_by:            None,
+            owner_id:              None,
+            visibility:            "private".into(),
+            created_at:            Utc::now(),
+            updated_at:            Utc::now(),
+            deleted_at:            None,
+        }
+    }
+
+    /// A table Mosaic provisioned a day ago, under NocoDB's base prefix.
+    fn table(id: &str) -> TableSummary {
+        TableSummary {
+            id:         id.into(),
+            title:      id.into(),
+            table_name: format!("nc_x1y2__{}", provisioned_table_name(Uuid::new_v4())),
+            created_at: Some(Utc::now() - Duration::days(1)),
+        }
+    }
+
+    fn job(created_at: DateTime<Utc>) -> ProvisioningJob {
+        ProvisioningJob {
+            id:                     Uuid::new_v4(),
+            user_id:                Uuid::new_v4(),
+            name:                   "job".into(),
+            description:            None,
+            fields:                 serde_json::json!([]),
+            state:                  "unstarted".into(),
+            base_id:                None,
+            table_id:               None,
+            table_name:             None,
+            form_view_id:           None,
+            form_share_uuid:        None,
+            grafana_dashboard_uid:  None,
+            grafana_dashboard_url:  None,
+            template_id:            None,
+            copy_rows_from:         None,
+            error:                  None,
+            created_at,
+            updated_at:             created_at,
+        }
+    }
+
+    fn dashboard(uid: &str, generated: bool) -> DashboardSummary {
+        DashboardSummary {
+            uid:   uid.into(),
+            title: uid.into(),
+            url:   format!("/d/{uid}/slug"),
+            tags:  if generated { vec![GENERATED_TAG.into()] } else { vec![] },
+        }
+    }
+
+    fn panel(source_url: &str) -> Panel {
+        Panel {
+            id:           Uuid::new_v4(),
+            dashboard_id: Uuid::new_v4(),
+            title:        None,
+            panel_type:   "grafana_dashboard".into(),
+            source_url:   Some(source_url.into()),
+            config:       serde_json::json!({}),
+            grid_x:       0,
+            grid_y:       0,
+            grid_w:       12,
+            grid_h:       8,
+            created_at:   Utc::now(),
+            updated_at:   Utc::now(),
+            deleted_at:   None,
+        }
+    }
+
+    #[test]
+    fn extracts_dashboard_uid_from_proxy_urls() {
+        assert_eq!(grafana_uid("/proxy/grafana/d/abc/weight?kiosk"), Some("abc"));
+        assert_eq!(grafana_uid("/proxy/grafana/d/abc?kiosk"), Some("abc"));
+        assert_eq!(grafana_uid("/proxy/grafana/d-solo/abc/x?panelId=1"), Some("abc"));
+        assert_eq!(grafana_uid("/proxy/nocodb/nc/form/abc"), None);
+    }
+
+    #[test]
+    fn classifies_drift() {
+        let healthy = template("t1", "d1");
+        let lost_dashboard = template("t2", "d2");
+        let lost_table = template("t3", "d3");
+
+        let report = build_report(&Inventory {
+            templates:  vec![healthy, lost_dashboard, lost_table],
+            jobs:       vec![],
+            tables:     vec![table("t1"), table("t2"), table("t9")],
+            dashboards: vec![dashboard("d1", true), dashboard("d3", true), dashboard("d9", true), dashboard("mine", false)],
+            panels:     vec![
+                panel("/proxy/grafana/d/d1/slug?kiosk"),
+                panel("/proxy/grafana/d/d2/slug?kiosk"),
+                panel("/proxy/grafana/d/gone/slug?kiosk"),
+                panel("/proxy/grafana/d/mine/slug"),
+            ],
+        });
+
+        assert_eq!(report.orphan_tables.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), ["t9"]);
+        assert_eq!(report.orphan_dashboards.iter().map(|d| d.uid.as_str()).collect::<Vec<_>>(), ["d9"]);
+        assert_eq!(report.missing_tables.len(), 1);
+        assert_eq!(report.missing_dashboards.len(), 1);
+        assert!(report.missing_portal_dashboards.is_empty());
+        assert_eq!(report.orphan_panels.len(), 1);
+        assert_eq!(report.orphan_panels[0].source_url, "/proxy/grafana/d/gone/slug?kiosk");
+    }
+
+    #[test]
+    fn only_old_tables_mosaic_provisioned_are_orphans() {
+        let running = job(Utc::now() - Duration::hours(1));
+        let hand_made = TableSummary { table_name: "nc_x1y2__my_mosaic_notes".into(), ..table("hand") };
+        let unchecked = TableSummary { table_name: provisioned_table_name(running.id), ..table("unchecked") };
+        let young = TableSummary { created_at: Some(Utc::now()), ..table("young") };
+        let undated = TableSummary { created_at: None, ..table("undated") };
+
+        let report = build_report(&Inventory {
+            templates:  vec![],
+            jobs:       vec![running],
+            tables:     vec![table("old"), hand_made, unchecked, young, undated],
+            dashboards: vec![],
+            panels:     vec![],
+        });
+        assert_eq!(report.orphan_tables.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), ["old"]);
+
+        // With nothing in flight, age no longer matters; hand-made tables still survive.
+        let report = build_report(&Inventory {
+            templates:  vec![],
+            jobs:       vec![],
+            tables:     vec![table("hand"), TableSummary { table_name: "hand".into(), ..table("hand") }],
+            dashboards: vec![],
+            panels:     vec![],
+        });
+        assert_eq!(report.orphan_tables.len(), 1);
+    }
+
+    #[test]
+    fn reads_the_provisioning_job_from_table_names() {
+        let id = Uuid::new_v4();
+        assert_eq!(provisioning_job(&provisioned_table_name(id)), Some(id));
+        assert_eq!(provisioning_job(&format!("nc_x1y2__{}", provisioned_table_name(id))), Some(id));
+        assert_eq!(provisioning_job("nc_x1y2__weight"), None);
+        assert_eq!(provisioning_job("mosaic_notes"), None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,609 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+use crate::db::repos::template_repo::{time_axis, FieldDefinition, FieldType, Template};
+use crate::error::AppError;
+use super::expression::{Computation, Lookback};
+use super::nocodb_client::RecordQuery;
+use super::Orchestrator;
+
+/// Upper bound on `limit` for a single page of records.
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/* ============================================================================================== */
+/*                                              Input                                             */
+/* ============================================================================================== */
+
+#[derive(Debug)]
+pub struct RecordFilter {
+    pub limit:  u32,
+    pub offset: u32,
+    pub from:   Option<NaiveDate>,    // inclusive, on the dashboards' time; see `date_filter`
+    pub to:     Option<NaiveDate>,    // inclusive
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecordList {
+    pub records: Vec<Map<String, Value>>,
+    pub total:   u64,
+    pub limit:   u32,
+    pub offset:  u32,
+}
+
+/* ============================================================================================== */
+/*                                             Records                                            */
+/* ============================================================================================== */
+
+impl Orchestrator {
+    /// One page of a dataset's rows, newest first, optionally limited to a date range.
+    pub async fn list_records(&self, template: &Template, filter: RecordFilter) -> Result<RecordList, AppError> {
+        let table_id = table_id(template)?;
+        let fields = template_fields(template)?;
+        let time = time_column(&fields);
+
+        let limit = filter.limit.clamp(1, MAX_PAGE_SIZE);
+        let mut page = self.nocodb.list_records(table_id, &RecordQuery {
+            limit,
+            offset: filter.offset,
+            filter: date_filter(&fields, filter.from, filter.to),
+            sort:   Some(format!("-{time}")),
+        }).await?;
+        self.fill_computed(table_id, &fields, &mut page.list).await?;
+
+        Ok(RecordList {
+            records: page.list,
+            total:   page.page_info.total_rows,
+            limit,
+            offset:  filter.offset,
+        })
+    }
+
+    /* ============================================================================================== */
+    pub async fn create_record(
+        &self,
+        template: &Template,
+        values: Map<String, Value>,
+    ) -> Result<Map<String, Value>, AppError> {
+        let table_id = table_id(template)?;
+        let fields = template_fields(template)?;
+        let values = validate_new_record(&fields, values)?;
+        let ids = self.nocodb.create_records(table_id, &[values]).await?;
+        let id = ids.first().copied().ok_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("NocoDB did not return the new record ID"))
+        })?;
+        self.get_computed_record(table_id, &fields, id).await
+    }
+
+    /* ============================================================================================== */
+    /// Partial update: only the supplied fields change.
+    pub async fn update_record(
+        &self,
+        template: &Template,
+        row_id: i64,
+        values: Map<String, Value>,
+    ) -> Result<Map<String, Value>, AppError> {
+        let table_id = table_id(template)?;
+        let fields = template_fields(template)?;
+        let values = validate_values(&fields, values)?;
+        self.nocodb.get_record(table_id, row_id).await?;
+        self.nocodb.update_record(table_id, row_id, values).await?;
+        self.get_computed_record(table_id, &fields, row_id).await
+    }
+
+    /* ============================================================================================== */
+    pub async fn delete_record(&self, template: &Template, row_id: i64) -> Result<(), AppError> {
+        let table_id = table_id(template)?;
+        self.nocodb.get_record(table_id, row_id).await?;
+        self.nocodb.delete_record(table_id, row_id).await
+    }
+
+    /* ============================================================================================== */
+    async fn get_computed_record(
+        &self,
+        table_id: &str,
+        fields: &[FieldDefinition],
+        row_id: i64,
+    ) -> Result<Map<String, Value>, AppError> {
+        let mut rows = vec![self.nocodb.get_record(table_id, row_id).await?];
+        self.fill_computed(table_id, fields, &mut rows).await?;
+        Ok(rows.remove(0))
+    }
+
+    /// Add the values of the template's computed fields to `rows`.
+    ///
+    /// Windowed expressions need the rows before these ones too, so those are streamed in
+    /// time order from the start of the longest window (or of the dataset, for a running
+    /// total) up to the last day in `rows`.
+    pub(crate) async fn fill_computed(
+        &self,
+        table_id: &str,
+        fields: &[FieldDefinition],
+        rows: &mut [Map<String, Value>],
+    ) -> Result<(), AppError> {
+        let time = time_column(fields);
+        let Some(mut computation) = Computation::new(fields, time).map_err(computation_error)? else {
+            return Ok(());
+        };
+        let times: Vec<_> = rows.iter().filter_map(|r| computation.row_time(r)).collect();
+        let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+            rows.iter_mut().for_each(|row| computation.fill_alone(row));
+            return Ok(());
+        };
+        if !computation.has_windows() {
+            rows.iter_mut().for_each(|row| computation.fill_alone(row));
+            return Ok(());
+        }
+
+        let from = match computation.lookback() {
+            Lookback::Days(days) => Some(first.date() - Duration::days(days.into())),
+            Lookback::All => None,
+        };
+        let filter = date_filter(fields, from, Some(last.date()));
+        let wanted: HashMap<Value, usize> = rows.iter().enumerate()
+            .filter_map(|(i, r)| Some((r.get("Id")?.clone(), i)))
+            .collect();
+
+        let mut computed = HashMap::new();
+        let mut offset = 0;
+        loop {
+            let page = self.nocodb.list_records(table_id, &RecordQuery {
+                limit:  MAX_PAGE_SIZE,
+                offset,
+                filter: filter.clone(),
+                sort:   Some(format!("{time},Id")),
+            }).await?;
+            let fetched = page.list.len() as u32;
+            for row in page.list.into_iter().flat_map(|r| computation.push(r)) {
+                if let Some(&i) = row.get("Id").and_then(|id| wanted.get(id)) {
+                    computed.insert(i, row);
+                }
+            }
+            offset += fetched;
+            if page.page_info.is_last_page || fetched == 0 {
+                break;
+            }
+        }
+        for row in computation.finish() {
+            if let Some(&i) = row.get("Id").and_then(|id| wanted.get(id)) {
+                computed.insert(i, row);
+            }
+        }
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            match computed.remove(&i) {
+                Some(done) => *row = done,
+                // Undated rows fall outside every window.
+                None => computation.fill_alone(row),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn computation_error(e: String) -> AppError {
+    AppError::Internal(anyhow::anyhow!("stored template expression is invalid: {e}"))
+}
+
+/* ============================================================================================== */
+/*                                           Validation                                           */
+/* ============================================================================================== */
+
+/// Check a record against the template's fields and normalise it for NocoDB.
+///
+/// Unknown keys are rejected and missing keys are left out, so the same check serves both
+/// inserts and partial updates; see [`validate_new_record`] for the extra insert checks.
+/// `null` clears a value unless the field is required. Numbers may be sent bare or as
+/// `{"value": 72.5, "unit": "kg"}`, in which case the unit must match the field's unit.
+pub fn validate_values(
+    fields: &[FieldDefinition],
+    values: Map<String, Value>,
+) -> Result<Map<String, Value>, AppError> {
+    let mut errors = Vec::new();
+    let mut normalised = Map::new();
+
+    for (key, value) in values {
+        let Some(field) = fields.iter().find(|f| f.name == key) else {
+            errors.push(format!("unknown field '{key}'"));
+            continue;
+        };
+        if !field.field_type.is_stored() {
+            errors.push(format!("{key}: is computed and cannot be set"));
+            continue;
+        }
+        match validate_value(field, value) {
+            Ok(v) => { normalised.insert(key, v); }
+            Err(e) => errors.push(format!("{key}: {e}")),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(normalised)
+    } else {
+        Err(AppError::Validation(errors.join("; ")))
+    }
+}
+
+/* ============================================================================================== */
+/// Check a record that is about to be inserted: fields left out take their default, and
+/// required fields must then be present.
+pub fn validate_new_record(
+    fields: &[FieldDefinition],
+    values: Map<String, Value>,
+) -> Result<Map<String, Value>, AppError> {
+    let mut record = validate_values(fields, values)?;
+    apply_defaults(fields, &mut record);
+    let missing = missing_required(fields, &record);
+    if !missing.is_empty() {
+        return Err(AppError::Validation(
+            missing.iter().map(|name| format!("{name}: is required")).collect::<Vec<_>>().join("; ")
+        ));
+    }
+    Ok(record)
+}
+
+/// Fill in the default of every field absent from `record`.
+pub fn apply_defaults(fields: &[FieldDefinition], record: &mut Map<String, Value>) {
+    for field in fields {
+        if let Some(default) = &field.default
+            && !record.contains_key(&field.name)
+        {
+            record.insert(field.name.clone(), default.clone());
+        }
+    }
+}
+
+/// Names of required fields that are absent from `record`.
+pub fn missing_required<'a>(fields: &'a [FieldDefinition], record: &Map<String, Value>) -> Vec<&'a str> {
+    fields.iter()
+        .filter(|f| f.required && !record.contains_key(&f.name))
+        .map(|f| f.name.as_str())
+        .collect()
+}
+
+/* ============================================================================================== */
+/// Validate a single value against its field definition and its constraints.
+///
+/// Multi-select values may be sent as an array of options or as a comma-separated string;
+/// NocoDB stores them as the latter.
+pub fn validate_value(field: &FieldDefinition, value: Value) -> Result<Value, String> {
+    if value.is_null() {
+        if field.required {
+            return Err("is required".into());
+        }
+        return Ok(Value::Null);
+    }
+
+    let value = normalise_value(field, value)?;
+    check_constraints(field, &value)?;
+    Ok(value)
+}
+
+/// Type check and normalisation, before constraints are applied.
+fn normalise_value(field: &FieldDefinition, value: Value) -> Result<Value, String> {
+    match field.field_type {
+        FieldType::Number | FieldType::Currency | FieldType::Duration => {
+            Ok(Value::Number(number_value(field, value)?))
+        }
+        FieldType::Integer | FieldType::Rating => {
+            let number = number_value(field, value)?;
+            let n = number.as_i64().ok_or("expected a whole number")?;
+            if field.field_type == FieldType::Rating && !(0..=field.rating_max()).contains(&n) {
+                return Err(format!("rating must be between 0 and {}", field.rating_max()));
+            }
+            Ok(Value::Number(number))
+        }
+        FieldType::Boolean => match value {
+            Value::Bool(_) => Ok(value),
+            _ => Err("expected true or false".into()),
+        },
+        FieldType::Date => {
+            let s = value.as_str().ok_or("expected a date string (YYYY-MM-DD)")?;
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .map_err(|_| format!("'{s}' is not a valid date (YYYY-MM-DD)"))?;
+            Ok(value)
+        }
+        FieldType::Datetime => {
+            let s = value.as_str().ok_or("expected an ISO 8601 timestamp")?;
+            DateTime::parse_from_rfc3339(s)
+                .map_err(|_| format!("'{s}' is not a valid timestamp (e.g. 2024-03-01T08:30:00Z)"))?;
+            Ok(value)
+        }
+        FieldType::Email => {
+            let s = value.as_str().ok_or("expected a string")?;
+            match s.split_once('@') {
+                Some((local, domain)) if !local.is_empty() && domain.contains('.') => Ok(value),
+                _ => Err(format!("'{s}' is not an email address")),
+            }
+        }
+        FieldType::Url => {
+            let s = value.as_str().ok_or("expected a string")?;
+            if !(s.starts_with("http://") || s.starts_with("https://")) {
+                return Err(format!("'{s}' is not an http(s) URL"));
+            }
+            Ok(value)
+        }
+        FieldType::MultiSelect => {
+            let options: Vec<String> = match value {
+                Value::String(s) => s.split(',').map(|o| o.trim().to_string()).collect(),
+                Value::Array(items) => items.into_iter()
+                    .map(|i| match i {
+                        Value::String(s) => Ok(s.trim().to_string()),
+                        _ => Err("expected an array of strings"),
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => return Err("expected an array of strings".into()),
+            };
+            if options.iter().any(|o| o.contains(',')) {
+                return Err("options may not contain commas".into());
+            }
+            Ok(Value::String(options.into_iter().filter(|o| !o.is_empty()).collect::<Vec<_>>().join(",")))
+        }
+        FieldType::Text | FieldType::LongText | FieldType::Select => match value {
+            Value::String(_) => Ok(value),
+            _ => Err("expected a string".into()),
+        },
+        FieldType::Computed => Err("is computed and cannot be set".into()),
+    }
+}
+
+/// Options, and the `min`/`max` bounds on numbers or text length.
+fn check_constraints(field: &FieldDefinition, value: &Value) -> Result<(), String> {
+    if field.field_type.has_options() && !field.options.is_empty() {
+        let chosen: Vec<&str> = match field.field_type {
+            FieldType::MultiSelect => {
+                value.as_str().unwrap_or_default().split(',').filter(|o| !o.is_empty()).collect()
+            }
+            _ => value.as_str().into_iter().collect(),
+        };
+        if let Some(unknown) = chosen.iter().find(|c| !field.options.iter().any(|o| o == *c)) {
+            return Err(format!("'{unknown}' is not one of: {}", field.options.join(", ")));
+        }
+    }
+
+    let (measured, what) = if field.field_type.is_numeric() {
+        (value.as_f64(), "")
+    } else if field.field_type.is_textual() {
+        (value.as_str().map(|s| s.chars().count() as f64), " characters")
+    } else {
+        (None, "")
+    };
+    if let Some(measured) = measured {
+        if let Some(min) = field.min
+            && measured < min
+        {
+            return Err(format!("must be at least {min}{what}"));
+        }
+        if let Some(max) = field.max
+            && measured > max
+        {
+            return Err(format!("must be at most {max}{what}"));
+        }
+    }
+    Ok(())
+}
+
+/// A bare number, or `{"value": 72.5, "unit": "kg"}` whose unit must match the field's.
+fn number_value(field: &FieldDefinition, value: Value) -> Result<Number, String> {
+    match value {
+        Value::Number(n) => Ok(n),
+        Value::Object(mut obj) => {
+            let unit = obj.remove("unit");
+            let unit = unit.as_ref().and_then(Value::as_str);
+            if unit != field.unit.as_deref() {
+                return Err(format!(
+                    "unit '{}' does not match the field unit '{}'",
+                    unit.unwrap_or("none"),
+                    field.unit.as_deref().unwrap_or("none"),
+                ));
+            }
+            match obj.remove("value") {
+                Some(Value::Number(n)) => Ok(n),
+                _ => Err("expected a numeric 'value'".into()),
+            }
+        }
+        _ => Err("expected a number".into()),
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+pub(crate) fn table_id(template: &Template) -> Result<&str, AppError> {
+    template.nocodb_table_id.as_deref().ok_or_else(|| {
+        AppError::Validation("template has no provisioned NocoDB table".into())
+    })
+}
+
+pub(crate) fn template_fields(template: &Template) -> Result<Vec<FieldDefinition>, AppError> {
+    serde_json::from_value(template.fields.clone())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("stored template fields are invalid: {e}")))
+}
+
+/// Column that dates a row: the template's time axis, else NocoDB's `CreatedAt`.
+pub(crate) fn time_column(fields: &[FieldDefinition]) -> &str {
+    time_axis(fields).map_or("CreatedAt", |f| f.name.as_str())
+}
+
+/// NocoDB `where` expression for an inclusive date range on the time the dashboards use: the
+/// time axis, or the creation time of rows that leave it empty. With a timezone on the axis,
+/// creation times count for their day in that zone, just as the axis' dates do.
+pub(crate) fn date_filter(fields: &[FieldDefinition], from: Option<NaiveDate>, to: Option<NaiveDate>) -> Option<String> {
+    if from.is_none() && to.is_none() {
+        return None;
+    }
+    let Some(axis) = time_axis(fields) else {
+        return Some(day_range("CreatedAt", from, to));
+    };
+    let created = match axis.timezone.as_deref().and_then(|tz| tz.parse::<Tz>().ok()) {
+        Some(tz) => instant_range("CreatedAt", tz, from, to),
+        None     => day_range("CreatedAt", from, to),
+    };
+    let axis = &axis.name;
+    Some(format!("({})~or(({axis},blank)~and({created}))", day_range(axis, from, to)))
+}
+
+/// `column` on or between whole days, as NocoDB reads them.
+fn day_range(column: &str, from: Option<NaiveDate>, to: Option<NaiveDate>) -> String {
+    let mut conditions = Vec::new();
+    if let Some(from) = from {
+        conditions.push(format!("({column},gte,exactDate,{from})"));
+    }
+    if let Some(to) = to {
+        conditions.push(format!("({column},lte,exactDate,{to})"));
+    }
+    conditions.join("~and")
+}
+
+/// `column` from the start of `from` up to the end of `to`, both days in `tz`.
+fn instant_range(column: &str, tz: Tz, from: Option<NaiveDate>, to: Option<NaiveDate>) -> String {
+    let midnight = |day: NaiveDate| {
+        let local = day.and_time(NaiveTime::MIN);
+        // Where midnight falls in a DST gap, the day starts at the first instant after it.
+        let start = tz.from_local_datetime(&local).earliest().unwrap_or_else(|| tz.from_utc_datetime(&local));
+        start.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%SZ")
+    };
+    let mut conditions = Vec::new();
+    if let Some(from) = from {
+        conditions.push(format!("({column},gte,exactDate,{})", midnight(from)));
+    }
+    if let Some(to) = to {
+        conditions.push(format!("({column},lt,exactDate,{})", midnight(to + Duration::days(1))));
+    }
+    conditions.join("~and")
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields() -> Vec<FieldDefinition> {
+        vec![
+            FieldDefinition { name: "weight".into(), field_type: FieldType::Number, unit: Some("kg".into()), ..Default::default() },
+            FieldDefinition { name: "measured_at".into(), field_type: FieldType::Date, ..Default::default() },
+            FieldDefinition { name: "notes".into(), field_type: FieldType::Text, ..Default::default() },
+        ]
+    }
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn accepts_and_normalises_valid_values() {
+        let out = validate_values(&fields(), record(json!({
+            "weight": { "value": 72.5, "unit": "kg" },
+            "measured_at": "2024-03-01",
+            "notes": null,
+        }))).unwrap();
+        assert_eq!(Value::Object(out), json!({ "weight": 72.5, "measured_at": "2024-03-01", "notes": null }));
+    }
+
+    #[test]
+    fn rejects_wrong_types_units_and_unknown_fields() {
+        assert!(validate_values(&fields(), record(json!({ "weight": "heavy" }))).is_err());
+        assert!(validate_values(&fields(), record(json!({ "weight": { "value": 160, "unit": "lb" } }))).is_err());
+        assert!(validate_values(&fields(), record(json!({ "measured_at": "01/03/2024" }))).is_err());
+        assert!(validate_values(&fields(), record(json!({ "height": 180 }))).is_err());
+    }
+
+    #[test]
+    fn validates_richer_field_types() {
+        let field = |name: &str, field_type| FieldDefinition { name: name.into(), field_type, ..Default::default() };
+        let fields = vec![
+            field("done", FieldType::Boolean),
+            field("stars", FieldType::Rating),
+            field("steps", FieldType::Integer),
+            field("at", FieldType::Datetime),
+            field("tags", FieldType::MultiSelect),
+            field("contact", FieldType::Email),
+        ];
+
+        let out = validate_values(&fields, record(json!({
+            "done": true, "stars": 4, "steps": 9000, "at": "2024-03-01T08:30:00Z",
+            "tags": ["gym", " run"], "contact": "me@example.org",
+        }))).unwrap();
+        assert_eq!(out["tags"], json!("gym,run"));
+
+        assert!(validate_values(&fields, record(json!({ "done": "yes" }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "stars": 6 }))).is_err());
+        let ten_stars = vec![FieldDefinition { max: Some(10.0), ..field("stars", FieldType::Rating) }];
+        assert!(validate_values(&ten_stars, record(json!({ "stars": 8 }))).is_ok());
+        assert!(validate_values(&ten_stars, record(json!({ "stars": 11 }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "steps": 1.5 }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "at": "2024-03-01" }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "contact": "nobody" }))).is_err());
+    }
+
+    #[test]
+    fn unknown_field_types_are_rejected() {
+        let parsed: Result<FieldDefinition, _> = serde_json::from_value(json!({ "name": "x", "field_type": "blob" }));
+        assert!(parsed.unwrap_err().to_string().contains("unknown field type 'blob'"));
+
+        let legacy: FieldDefinition = serde_json::from_value(json!({ "name": "x", "field_type": "checkbox" })).unwrap();
+        assert_eq!(legacy.field_type, FieldType::Boolean);
+    }
+
+    #[test]
+    fn enforces_constraints_defaults_and_required_fields() {
+        let fields = vec![
+            FieldDefinition {
+                name: "mood".into(), field_type: FieldType::Select,
+                options: vec!["good".into(), "bad".into()], default: Some(json!("good")),
+                ..Default::default()
+            },
+            FieldDefinition {
+                name: "weight".into(), field_type: FieldType::Number,
+                required: true, min: Some(20.0), max: Some(300.0),
+                ..Default::default()
+            },
+            FieldDefinition { name: "notes".into(), max: Some(5.0), ..Default::default() },
+        ];
+
+        let out = validate_new_record(&fields, record(json!({ "weight": 70 }))).unwrap();
+        assert_eq!(out["mood"], json!("good"));
+
+        assert!(validate_new_record(&fields, record(json!({ "mood": "bad" }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "weight": null }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "weight": 10 }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "mood": "meh" }))).is_err());
+        assert!(validate_values(&fields, record(json!({ "notes": "too long" }))).is_err());
+    }
+
+    #[test]
+    fn date_filter_falls_back_to_the_creation_day_in_the_axis_timezone() {
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok();
+        assert_eq!(date_filter(&fields(), None, None), None);
+
+        // Without an axis, rows are dated by their creation alone.
+        assert_eq!(
+            date_filter(&fields()[..1], day("2024-03-01"), None).unwrap(),
+            "(CreatedAt,gte,exactDate,2024-03-01)"
+        );
+
+        assert_eq!(
+            date_filter(&fields(), day("2024-03-01"), day("2024-03-31")).unwrap(),
+            "((measured_at,gte,exactDate,2024-03-01)~and(measured_at,lte,exactDate,2024-03-31))~or(\
+             (measured_at,blank)~and((CreatedAt,gte,exactDate,2024-03-01)~and(CreatedAt,lte,exactDate,2024-03-31)))"
+        );
+
+        // Amsterdam is an hour ahead in March and two in July.
+        let mut zoned = fields();
+        zoned[1].timezone = Some("Europe/Amsterdam".into());
+        assert_eq!(
+            date_filter(&zoned, day("2024-03-01"), day("2024-07-01")).unwrap(),
+            "((measured_at,gte,exactDate,2024-03-01)~and(measured_at,lte,exactDate,2024-07-01))~or(\
+             (measured_at,blank)~and((CreatedAt,gte,exactDate,2024-02-29T23:00:00Z)\
+             ~and(CreatedAt,lt,exactDate,2024-07-01T22:00:00Z)))"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::db::repos::template_repo::{FieldDefinition, FieldType};
+use crate::error::AppError;
+
+/* ============================================================================================== */
+/*                                              Input                                             */
+/* ============================================================================================== */
+
+#[derive(Debug)]
+pub struct UpdateTemplate {
+    pub name:           Option<String>,
+    pub description:    Option<String>,
+    pub fields:         Vec<FieldUpdate>,
+}
+
+/// A field in the desired schema. `renamed_from` names the existing field this one replaces;
+/// without it, a field whose name is not in the current schema is treated as new.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldUpdate {
+    #[serde(flatten)]
+    pub field:          FieldDefinition,
+    #[serde(default)]
+    pub renamed_from:   Option<String>,
+}
+
+/* ============================================================================================== */
+/*                                             Changes                                            */
+/* ============================================================================================== */
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldChange {
+    Drop(String),
+    Rename { from: String, to: String },
+    /// New options, default or description for the existing column `column`. `field` carries
+    /// the new name when the field is also renamed.
+    Alter { column: String, field: FieldDefinition },
+    Add(FieldDefinition),
+}
+
+/* ============================================================================================== */
+/// Compute the column operations needed to move from `current` to `desired`.
+///
+/// Changes are returned drops first, then renames, then alterations, then additions, which
+/// is the order they must be applied in NocoDB. Computed fields have no column, so adding,
+/// renaming or dropping one needs no change. Changing the type of an existing field is
+/// rejected, since NocoDB would have to coerce (and possibly discard) the stored values.
+pub fn diff_fields(
+    current: &[FieldDefinition],
+    desired: &[FieldUpdate],
+) -> Result<Vec<FieldChange>, AppError> {
+    let by_name: HashMap<&str, &FieldDefinition> =
+        current.iter().map(|f| (f.name.as_str(), f)).collect();
+
+    let mut seen_names = HashSet::new();
+    let mut consumed = HashSet::new();
+    let mut renames = Vec::new();
+    let mut alters = Vec::new();
+    let mut adds = Vec::new();
+
+    for update in desired {
+        let name = update.field.name.as_str();
+        if !seen_names.insert(name) {
+            return Err(AppError::Validation(format!("duplicate field name '{name}'")));
+        }
+
+        let source = update.renamed_from.as_deref().unwrap_or(name);
+        let Some(existing) = by_name.get(source) else {
+            if let Some(from) = &update.renamed_from {
+                return Err(AppError::Validation(format!("cannot rename unknown field '{from}'")));
+            }
+            if update.field.field_type.is_stored() {
+                adds.push(update.field.clone());
+            }
+            continue;
+        };
+
+        if !consumed.insert(source) {
+            return Err(AppError::Validation(format!("field '{source}' is renamed more than once")));
+        }
+        if existing.field_type != update.field.field_type {
+            return Err(AppError::Validation(format!(
+                "changing the type of field '{source}' from '{}' to '{}' is not supported",
+                existing.field_type, update.field.field_type,
+            )));
+        }
+        if !existing.field_type.is_stored() {
+            continue;
+        }
+        if source != name {
+            renames.push(FieldChange::Rename { from: source.to_string(), to: name.to_string() });
+        }
+        if column_meta_changed(existing, &update.field) {
+            alters.push(FieldChange::Alter { column: source.to_string(), field: update.field.clone() });
+        }
+    }
+
+    // A new or renamed field may not take over the name of a field that is going away in
+    // the same update — NocoDB would see two columns with the same name mid-migration.
+    for change in renames.iter().filter_map(|c| match c {
+        FieldChange::Rename { to, .. } => Some(to.as_str()),
+        _ => None,
+    }).chain(adds.iter().map(|f| f.name.as_str())) {
+        if by_name.contains_key(change) {
+            return Err(AppError::Validation(format!(
+                "field name '{change}' is still in use by an existing field in this update"
+            )));
+        }
+    }
+
+    let mut changes: Vec<FieldChange> = current
+        .iter()
+        .filter(|f| f.field_type.is_stored() && !consumed.contains(f.name.as_str()))
+        .map(|f| FieldChange::Drop(f.name.clone()))
+        .collect();
+    changes.extend(renames);
+    changes.extend(alters);
+    changes.extend(adds.into_iter().map(FieldChange::Add));
+    Ok(changes)
+}
+
+/* ============================================================================================== */
+/// Leave out the changes that the table, whose column titles are `columns`, already shows: an
+/// earlier attempt at the same update that failed part-way made them. Since `diff_fields` never
+/// lets a renamed or added field take a name still in use, a column under the new name can only
+/// come from that attempt.
+pub fn outstanding_changes(changes: Vec<FieldChange>, columns: &[&str]) -> Vec<FieldChange> {
+    let has = |name: &str| columns.contains(&name);
+    changes
+        .into_iter()
+        .filter_map(|change| match change {
+            FieldChange::Drop(ref name) if !has(name) => None,
+            FieldChange::Rename { ref from, ref to } if !has(from) && has(to) => None,
+            // The column was renamed already, so alter it under its new name.
+            FieldChange::Alter { column, field } if !has(&column) && has(&field.name) => {
+                Some(FieldChange::Alter { column: field.name.clone(), field })
+            }
+            FieldChange::Add(ref field) if has(&field.name) => None,
+            change => Some(change),
+        })
+        .collect()
+}
+
+/// Whether the parts of a field that live in NocoDB's column definition differ. `required`
+/// and `min`/`max` are enforced by the portal and the form view, not the column.
+fn column_meta_changed(old: &FieldDefinition, new: &FieldDefinition) -> bool {
+    old.options != new.options
+        || old.default != new.default
+        || old.description != new.description
+        || (new.field_type == FieldType::Rating && old.max != new.max)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, field_type: &str) -> FieldDefinition {
+        FieldDefinition { name: name.into(), field_type: FieldType::try_from(field_type).unwrap(), ..Default::default() }
+    }
+
+    fn keep(name: &str, field_type: &str) -> FieldUpdate {
+        FieldUpdate { field: field(name, field_type), renamed_from: None }
+    }
+
+    fn rename(from: &str, to: &str, field_type: &str) -> FieldUpdate {
+        FieldUpdate { field: field(to, field_type), renamed_from: Some(from.into()) }
+    }
+
+    #[test]
+    fn add_rename_and_drop() {
+        let current = vec![field("weight", "number"), field("notes", "text"), field("mood", "select")];
+        let desired = vec![
+            rename("weight", "weight_kg", "number"),
+            keep("notes", "text"),
+            keep("sleep_quality", "number"),
+        ];
+
+        let changes = diff_fields(&current, &desired).unwrap();
+        assert_eq!(changes, vec![
+            FieldChange::Drop("mood".into()),
+            FieldChange::Rename { from: "weight".into(), to: "weight_kg".into() },
+            FieldChange::Add(field("sleep_quality", "number")),
+        ]);
+    }
+
+    #[test]
+    fn unchanged_schema_is_a_no_op() {
+        let current = vec![field("weight", "number")];
+        assert!(diff_fields(&current, &[keep("weight", "number")]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_type_change_and_name_reuse() {
+        let current = vec![field("weight", "number"), field("notes", "text")];
+        assert!(diff_fields(&current, &[keep("weight", "text")]).is_err());
+        assert!(diff_fields(&current, &[rename("weight", "notes", "number")]).is_err());
+        assert!(diff_fields(&current, &[rename("height", "height_cm", "number")]).is_err());
+        assert!(diff_fields(&current, &[keep("weight", "number"), keep("weight", "number")]).is_err());
+    }
+
+    #[test]
+    fn option_and_default_changes_alter_the_column() {
+        let current = vec![field("mood", "select"), field("notes", "text")];
+        let mut mood = keep("mood", "select");
+        mood.field.options = vec!["good".into(), "bad".into()];
+        let mut notes = keep("notes", "text");
+        notes.field.required = true;
+
+        let changes = diff_fields(&current, &[mood.clone(), notes]).unwrap();
+        assert_eq!(changes, vec![FieldChange::Alter { column: "mood".into(), field: mood.field }]);
+    }
+
+    #[test]
+    fn computed_fields_need_no_columns() {
+        let mut bmi = field("bmi", "computed");
+        bmi.expression = Some("weight * 2".into());
+        let current = vec![field("weight", "number"), field("old", "computed")];
+        let changes = diff_fields(&current, &[
+            keep("weight", "number"),
+            FieldUpdate { field: bmi, renamed_from: None },
+        ]).unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn changes_an_interrupted_update_made_are_not_repeated() {
+        let current = vec![field("weight", "number"), field("mood", "select"), field("notes", "text")];
+        let mut weight = rename("weight", "weight_kg", "number");
+        weight.field.description = Some("in kilograms".into());
+        let changes = diff_fields(&current, &[weight.clone(), keep("notes", "text"), keep("sleep", "number")]).unwrap();
+
+        // Nothing applied yet: everything is left to do.
+        assert_eq!(outstanding_changes(changes.clone(), &["weight", "mood", "notes"]), changes);
+
+        // The drop, the rename and the addition went through before the alteration failed.
+        assert_eq!(outstanding_changes(changes, &["weight_kg", "notes", "sleep"]), vec![
+            FieldChange::Alter { column: "weight_kg".into(), field: weight.field },
+        ]);
+    }
+}
@@ -0,0 +1,189 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::db::repos::{
+    dashboard_repo::{DashboardRepo, PgDashboardRepo},
+    panel_repo::{PanelRepo, PgPanelRepo},
+    template_repo::{PgTemplateRepo, Template, TemplateRepo},
+};
+use crate::error::AppError;
+use super::Orchestrator;
+
+/// How often the purger looks for trash that has outlived the retention period.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/* ============================================================================================== */
+/*                                             Purger                                             */
+/* ============================================================================================== */
+
+impl Orchestrator {
+    /// Background loop: permanently deletes templates, dashboards and panels that have been in
+    /// the trash for longer than `retention_days`. Runs once immediately, then every
+    /// `PURGE_INTERVAL`.
+    pub async fn run_trash_purger(&self, retention_days: u64) {
+        let retention = chrono::Duration::days(retention_days as i64);
+        loop {
+            if let Err(e) = self.purge_trash(Utc::now() - retention).await {
+                tracing::error!("Failed to purge the trash: {e}");
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    }
+
+    /* ============================================================================================== */
+    /// Permanently delete a template along with, best-effort, its NocoDB table and Grafana
+    /// dashboard. This is the only path that deprovisions a dataset.
+    pub async fn purge_template(&self, template: &Template) -> Result<(), AppError> {
+        self.deprovision_dataset(template).await;
+        PgTemplateRepo { pool: self.pool.clone() }.delete(template.id).await
+    }
+
+    /* ======================================== Internal ======================================== */
+
+    /// Delete everything trashed before `cutoff`. Dashboards go before panels, since purging a
+    /// dashboard cascades to its panels. A failing item is logged and retried next pass.
+    async fn purge_trash(&self, cutoff: DateTime<Utc>) -> Result<(), AppError> {
+        let templates = PgTemplateRepo { pool: self.pool.clone() };
+        for template in templates.list_trashed().await? {
+            if expired(template.deleted_at, cutoff) {
+                match self.purge_template(&template).await {
+                    Ok(()) => tracing::info!("Purged template '{}' from the trash", template.id),
+                    Err(e) => tracing::warn!("Failed to purge template '{}': {e}", template.id),
+                }
+            }
+        }
+
+        let dashboards = PgDashboardRepo { pool: self.pool.clone() };
+        for dashboard in dashboards.list_trashed().await? {
+            if expired(dashboard.deleted_at, cutoff)
+                && let Err(e) = dashboards.delete(dashboard.id).await
+            {
+                tracing::warn!("Failed to purge dashboard '{}': {e}", dashboard.id);
+            }
+        }
+
+        let panels = PgPanelRepo { pool: self.pool.clone() };
+        for panel in panels.list_trashed().await? {
+            if expired(panel.deleted_at, cutoff)
+                && let Err(e) = panels.delete(panel.id).await
+            {
+                tracing::warn!("Failed to purge panel '{}': {e}", panel.id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+fn expired(deleted_at: Option<DateTime<Utc>>, cutoff: DateTime<Utc>) -> bool {
+    deleted_at.is_some_and(|at| at < cutoff)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sqlx::PgPool;
+    use tokio::sync::Notify;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::repos::{
+        dashboard_repo::CreateDashboard,
+        panel_repo::CreatePanel,
+        template_repo::CreateTemplateRecord,
+    };
+    use crate::orchestrator::{GrafanaClient, NocodbClient};
+
+    /// Move a trashed row's `deleted_at` back by `days`.
+    async fn trashed_days_ago(pool: &PgPool, table: &str, id: Uuid, days: i32) {
+        sqlx::query(&format!("UPDATE portal.{table} SET deleted_at = now() - make_interval(days => $2) WHERE id = $1"))
+            .bind(id)
+            .bind(days)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn only_items_trashed_before_the_cutoff_expire() {
+        let cutoff = Utc::now();
+        assert!(expired(Some(cutoff - chrono::Duration::seconds(1)), cutoff));
+        assert!(!expired(Some(cutoff), cutoff));
+        assert!(!expired(None, cutoff));
+    }
+
+    #[sqlx::test(migrations = "src/db/migrations")]
+    #[ignore = "needs a Postgres DATABASE_URL"]
+    async fn purges_what_outlived_the_retention_period(pool: PgPool) {
+        let http = reqwest::Client::new();
+        let o = Orchestrator {
+            nocodb:     NocodbClient::new(http.clone(), "http://127.0.0.1:1".into(), String::new()),
+            grafana:    GrafanaClient::new(http, "http://127.0.0.1:1".into(), String::new(), String::new()),
+            pool:       pool.clone(),
+            job_signal: Notify::new(),
+        };
+        let owner: Uuid = sqlx::query_scalar(
+            "INSERT INTO portal.users (username, password_hash) VALUES ('alice', '') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let templates = PgTemplateRepo { pool: pool.clone() };
+        let dashboards = PgDashboardRepo { pool: pool.clone() };
+        let panels = PgPanelRepo { pool: pool.clone() };
+        let template = |name: &str| CreateTemplateRecord {
+            id: None, name: name.into(), description: None, fields: json!([]), created_by: owner,
+            nocodb_table_id: None, nocodb_form_id: None, grafana_dashboard_uid: None,
+        };
+        let dashboard = |title: &str| CreateDashboard {
+            title: title.into(), slug: None, icon: None, sort_order: None, is_shared: None,
+        };
+        let panel = || CreatePanel {
+            title: None, panel_type: "markdown".into(), source_url: None, config: None,
+            grid_x: 0, grid_y: 0, grid_w: None, grid_h: None,
+        };
+
+        let old_template = templates.create(template("old")).await.unwrap();
+        let new_template = templates.create(template("new")).await.unwrap();
+        let old_dashboard = dashboards.create(owner, dashboard("Old")).await.unwrap();
+        let new_dashboard = dashboards.create(owner, dashboard("New")).await.unwrap();
+        let live_dashboard = dashboards.create(owner, dashboard("Live")).await.unwrap();
+        // Trashed after its dashboard: too recent on its own, but purged along with it.
+        let riding_panel = panels.create(old_dashboard.id, panel()).await.unwrap();
+        let old_panel = panels.create(live_dashboard.id, panel()).await.unwrap();
+        let new_panel = panels.create(live_dashboard.id, panel()).await.unwrap();
+
+        for (table, id, days) in [
+            ("dataset_templates", old_template.id,   40),
+            ("dataset_templates", new_template.id,   1),
+            ("dashboards",        old_dashboard.id,  40),
+            ("dashboards",        new_dashboard.id,  1),
+            ("panels",            riding_panel.id,   1),
+            ("panels",            old_panel.id,      40),
+            ("panels",            new_panel.id,      1),
+        ] {
+            trashed_days_ago(&pool, table, id, days).await;
+        }
+
+        o.purge_trash(Utc::now() - chrono::Duration::days(30)).await.unwrap();
+
+        assert!(templates.get_trashed(old_template.id).await.is_err());
+        assert!(dashboards.get_trashed(old_dashboard.id).await.is_err());
+        assert!(panels.get_trashed(riding_panel.id).await.is_err());
+        assert!(panels.get_trashed(old_panel.id).await.is_err());
+
+        templates.get_trashed(new_template.id).await.unwrap();
+        dashboards.get_trashed(new_dashboard.id).await.unwrap();
+        panels.get_trashed(new_panel.id).await.unwrap();
+        dashboards.get_by_id(live_dashboard.id).await.unwrap();
+    }
+}
@@ -1,10 +1,35 @@
 use axum::extract::State;
+use serde_json::Value;
+use uuid::Uuid;
 
-use crate::auth::middleware::AuthenticatedUser;
+use crate::api::dashboards::viewable_panels;
+use crate::api::public_links::resolve_token;
+use crate::api::templates::readable_templates;
+use crate::auth::middleware::{AuthenticatedUser, Role};
+use crate::auth::public_link;
 use crate::error::AppError;
-use crate::proxy::{self, ProxyTarget};
+use crate::orchestrator::reconcile::grafana_uid;
+use crate::proxy::{self, Access, IdFrom, ProxyTarget, Rule, Scope, ADMIN_RULE};
 use crate::AppState;
 
+/// Everything non-admins need to view embedded dashboards, and nothing else: no other
+/// dashboards, no search, no writes. Queries are further checked by `queries_match`.
+static RULES: &[Rule] = &[
+    ADMIN_RULE,
+    Rule { methods: &["GET", "HEAD"], path: "public/**",                least: Role::Viewer, id: IdFrom::None,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "api/frontend/settings",    least: Role::Viewer, id: IdFrom::None,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "api/plugins/*/settings",   least: Role::Viewer, id: IdFrom::None,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "d/{id}/**",                least: Role::Viewer, id: IdFrom::Path,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "d-solo/{id}/**",           least: Role::Viewer, id: IdFrom::Path,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "api/dashboards/uid/{id}",  least: Role::Viewer, id: IdFrom::Path,                       access: Access::Read },
+    Rule { methods: &["GET"],         path: "api/annotations",          least: Role::Viewer, id: IdFrom::Query("dashboardUID"),      access: Access::Read },
+    Rule { methods: &["POST"],        path: QUERY_PATH,                 least: Role::Viewer, id: IdFrom::Header(DASHBOARD_HEADER),   access: Access::Read },
+];
+
+const QUERY_PATH: &str = "api/ds/query";
+/// Sent by Grafana with every panel query.
+const DASHBOARD_HEADER: &str = "x-dashboard-uid";
+
 struct GrafanaProxy {
     base_url: String,
     token: String,
@@ -19,23 +44,44 @@ impl ProxyTarget for GrafanaProxy {
         ""  // Don't strip — Grafana serves from /proxy/grafana/ via SERVE_FROM_SUB_PATH
     }
 
+    fn mount(&self) -> &str {
+        "/proxy/grafana"
+    }
+
+    fn name(&self) -> &'static str {
+        "grafana"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        RULES
+    }
+
     fn inject_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         builder.header(
-            axum::http::header::AUTHORIZATION, 
+            axum::http::header::AUTHORIZATION,
             format!("Bearer {}", self.token),
         )
     }
 }
 
+/// Whoever is behind a proxied request.
+enum Caller {
+    User(AuthenticatedUser),
+    /// Holder of an active public link to the dashboard.
+    PublicLink { link_id: Uuid, dashboard_id: Uuid },
+}
+
 /* ============================================================================================== */
 /// ANY /proxy/grafana/{*path}
 ///
-/// Requires a valid portal session. Strips the `/proxy/grafana` prefix,
-/// forwards the request to the internal Grafana instance, and injects
-/// the configured service account token.
+/// Requires a valid portal session, or else the `portal_public` cookie of an active public
+/// link, which counts as a viewer of that link's dashboard only. Non-admins reach the Grafana
+/// dashboards of the templates and dashboards they can see, read-only, through `RULES`.
+/// Strips the `/proxy/grafana` prefix, forwards the request to the internal Grafana instance,
+/// and injects the configured service account token.
 pub async fn grafana_proxy(
     State(state): State<AppState>,
-    _user: AuthenticatedUser,
+    session: Result<AuthenticatedUser, AppError>,
     req: axum::extract::Request,
 ) ->Result<axum:: response::Response, AppError> {
     let (parts, body) = req.into_parts();
@@ -43,11 +89,38 @@ pub async fn grafana_proxy(
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("request body read error: {e}")))?;
 
+    let caller = match session {
+        Ok(user) => Caller::User(user),
+        Err(e) => {
+            let token = public_link::extract_cookie(&parts.headers).ok_or(e)?;
+            let (link, _) = resolve_token(&state, &token).await.map_err(|e| match e {
+                AppError::NotFound(_) => AppError::Unauthorized,
+                e => e,
+            })?;
+            Caller::PublicLink { link_id: link.id, dashboard_id: link.dashboard_id }
+        }
+    };
+    let (who, role) = match &caller {
+        Caller::User(user)                 => (user.username.clone(), user.role.clone()),
+        Caller::PublicLink { link_id, .. } => (format!("public link {link_id}"), Role::Viewer),
+    };
+
     let target = GrafanaProxy {
         base_url: state.config.grafana_internal_url.clone(),
         token: state.config.grafana_service_account_token.clone(),
     };
 
+    proxy::authorize(&target, &who, &role, &parts, || caller_scope(&state, &caller)).await?;
+
+    // The datasource runs whatever SQL it is sent, so non-admins may only run the panels' own.
+    if role != Role::Admin && parts.uri.path() == format!("/proxy/grafana/{QUERY_PATH}") {
+        let uid = parts.headers.get(DASHBOARD_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+        let allowed = state.orchestrator.grafana.dashboard_queries(uid).await?;
+        if !queries_match(&body_bytes, &allowed, &state.config.grafana_datasource_uid) {
+            return Err(proxy::deny(&target, &who, &parts, "query not on the dashboard"));
+        }
+    }
+
     proxy::forward(
         &target,
         &state.http_client,
@@ -57,4 +130,111 @@ pub async fn grafana_proxy(
         body_bytes,
     )
     .await
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Grafana dashboards the caller may view: those of the templates they can read and those
+/// embedded in the dashboards they can see. A public link only has the latter, for its dashboard.
+async fn caller_scope(state: &AppState, caller: &Caller) -> Result<Scope, AppError> {
+    let mut scope = Scope::new();
+    let panels = match caller {
+        Caller::User(user) => {
+            for (template, _) in readable_templates(state, user).await? {
+                if let Some(uid) = &template.grafana_dashboard_uid {
+                    proxy::grant(&mut scope, uid, Access::Read);
+                }
+            }
+            viewable_panels(state, user).await?
+        }
+        Caller::PublicLink { dashboard_id, .. } => state.panels.list_for_dashboard(*dashboard_id).await?,
+    };
+    for uid in panels.iter().filter_map(|p| p.source_url.as_deref().and_then(grafana_uid)) {
+        proxy::grant(&mut scope, uid, Access::Read);
+    }
+    Ok(scope)
+}
+
+/// True if every query of a `/api/ds/query` body runs one of `allowed` on the portal datasource.
+fn queries_match(body: &[u8], allowed: &[String], datasource_uid: &str) -> bool {
+    let Ok(body) = serde_json::from_slice::<Value>(body) else {
+        return false;
+    };
+    let Some(queries) = body["queries"].as_array().filter(|q| !q.is_empty()) else {
+        return false;
+    };
+    queries.iter().all(|q| {
+        q["datasource"]["uid"].as_str() == Some(datasource_uid)
+            && q["rawSql"].as_str().is_some_and(|sql| allowed.iter().any(|a| a == sql))
+    })
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, Method};
+    use serde_json::json;
+
+    use super::*;
+    use crate::proxy::Verdict;
+
+    fn verdict(role: Role, method: Method, uri: &str, headers: &HeaderMap) -> Verdict {
+        let uri: axum::http::Uri = uri.parse().unwrap();
+        let path = uri.path().strip_prefix("/proxy/grafana").unwrap_or_default();
+        proxy::evaluate(RULES, &role, &method, path, uri.query(), headers)
+    }
+
+    fn in_scope(id: &str) -> Verdict {
+        Verdict::InScope { id: id.into(), access: Access::Read }
+    }
+
+    #[test]
+    fn non_admins_reach_only_dashboards_read_only() {
+        let none = HeaderMap::new();
+        let viewer = |method: Method, uri: &str| verdict(Role::Viewer, method, uri, &none);
+
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/public/build/app.js"), Verdict::Allowed);
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/api/frontend/settings"), Verdict::Allowed);
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/d-solo/abc/steps?panelId=1"), in_scope("abc"));
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/d/abc"), in_scope("abc"));
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/api/dashboards/uid/abc"), in_scope("abc"));
+        assert_eq!(viewer(Method::GET, "/proxy/grafana/api/annotations?from=1&dashboardUID=abc"), in_scope("abc"));
+
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/api/annotations"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/api/search"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/api/admin/users"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::DELETE, "/proxy/grafana/api/dashboards/uid/abc"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::POST, "/proxy/grafana/api/ds/query"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/public/../api/search"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/public/%2E%2e/api/search"), Verdict::Denied(_)));
+        assert!(matches!(viewer(Method::GET, "/proxy/grafana/d-solo/abc/../../api/search"), Verdict::Denied(_)));
+        assert!(matches!(
+            verdict(Role::Editor, Method::POST, "/proxy/grafana/api/dashboards/db", &none),
+            Verdict::Denied(_)
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(DASHBOARD_HEADER, "abc".parse().unwrap());
+        assert_eq!(verdict(Role::Viewer, Method::POST, "/proxy/grafana/api/ds/query", &headers), in_scope("abc"));
+
+        assert_eq!(verdict(Role::Admin, Method::DELETE, "/proxy/grafana/api/dashboards/uid/abc", &none), Verdict::Allowed);
+    }
+
+    #[test]
+    fn only_the_dashboards_own_queries_run() {
+        let allowed = vec!["SELECT 1".to_string()];
+        let body = |queries: Value| serde_json::to_vec(&json!({ "queries": queries })).unwrap();
+        let query = |uid: &str, sql: &str| json!({ "datasource": { "uid": uid }, "rawSql": sql });
+
+        assert!(queries_match(&body(json!([query("pg", "SELECT 1")])), &allowed, "pg"));
+        assert!(!queries_match(&body(json!([query("pg", "SELECT 1"), query("pg", "SELECT 2")])), &allowed, "pg"));
+        assert!(!queries_match(&body(json!([query("other", "SELECT 1")])), &allowed, "pg"));
+        assert!(!queries_match(&body(json!([])), &allowed, "pg"));
+        assert!(!queries_match(b"not json", &allowed, "pg"));
+    }
+}
@@ -1,9 +1,14 @@
 pub mod grafana;
 pub mod nocodb;
 
+use std::collections::HashMap;
+use std::future::Future;
+
+use axum::http::{request::Parts, HeaderMap, Method};
 use axum::Router;
 use bytes::Bytes;
 
+use crate::auth::middleware::Role;
 use crate::error::AppError;
 
 /* ============================================================================================== */
@@ -19,10 +24,190 @@ pub trait ProxyTarget: Send + Sync {
     /// Path prefix to strip from the incoming request URI (e.g. `/proxy/grafana`).
     fn prefix(&self) -> &str;
 
+    /// Where the proxy is mounted; rule paths are relative to it. Defaults to `prefix`.
+    fn mount(&self) -> &str {
+        self.prefix()
+    }
+
+    /// Short name for audit logs (e.g. `grafana`).
+    fn name(&self) -> &'static str;
+
+    /// The requests non-admins may make, in order; the first matching rule decides.
+    fn rules(&self) -> &'static [Rule];
+
     /// Inject upstream service credentials into the outgoing reqwest request.
     fn inject_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder;
 }
 
+/* ============================================================================================== */
+/*                                          Access policy                                         */
+/* ============================================================================================== */
+
+/// How a request uses the upstream resource it names. `Write` includes `Read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Upstream IDs (Grafana dashboard UIDs, NocoDB table and view IDs) a caller may reach.
+pub type Scope = HashMap<String, Access>;
+
+/// Adds `id` to the scope, keeping the stronger access if it is already there.
+pub fn grant(scope: &mut Scope, id: &str, access: Access) {
+    let current = scope.entry(id.to_owned()).or_insert(access);
+    *current = (*current).max(access);
+}
+
+/// Where a rule finds the upstream ID to look up in the caller's scope.
+#[derive(Debug, Clone, Copy)]
+pub enum IdFrom {
+    /// Nothing to look up: static assets and bootstrap calls.
+    None,
+    /// The path segment matched by `{id}`.
+    Path,
+    /// A query string parameter.
+    Query(&'static str),
+    /// A request header.
+    Header(&'static str),
+}
+
+/// One entry of a target's allow-list.
+pub struct Rule {
+    /// Allowed methods; empty allows any.
+    pub methods: &'static [&'static str],
+    /// Path below the mount point. `*` matches one segment, `**` whatever is left (even
+    /// nothing), and `{id}` one segment that `IdFrom::Path` checks against the scope.
+    pub path:    &'static str,
+    pub least:   Role,
+    pub id:      IdFrom,
+    pub access:  Access,
+}
+
+/// Lets admins through to anything; every target's rules start with it.
+pub const ADMIN_RULE: Rule = Rule {
+    methods: &[],
+    path:    "**",
+    least:   Role::Admin,
+    id:      IdFrom::None,
+    access:  Access::Write,
+};
+
+/// What a target's rules make of one request.
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+    Allowed,
+    /// Allowed if the caller's scope grants `access` on `id`.
+    InScope { id: String, access: Access },
+    Denied(&'static str),
+}
+
+/// Matches a request against `rules`. `path` is relative to the target's mount point.
+pub fn evaluate(
+    rules: &[Rule],
+    role: &Role,
+    method: &Method,
+    path: &str,
+    query: Option<&str>,
+    headers: &HeaderMap,
+) -> Verdict {
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    // The upstream URL is normalised, so `public/../api/search` would escape the patterns.
+    if segments.iter().any(|s| matches!(s.to_ascii_lowercase().replace("%2e", ".").as_str(), "." | "..")) {
+        return Verdict::Denied("dot segment in path");
+    }
+
+    let mut outranked = false;
+    for rule in rules {
+        if !rule.methods.is_empty() && !rule.methods.contains(&method.as_str()) {
+            continue;
+        }
+        let Some(path_id) = match_path(rule.path, &segments) else {
+            continue;
+        };
+        if !role.at_least(&rule.least) {
+            outranked = true;
+            continue;
+        }
+        let id = match rule.id {
+            IdFrom::None        => return Verdict::Allowed,
+            IdFrom::Path        => path_id,
+            IdFrom::Query(name) => query.into_iter()
+                .flat_map(|q| q.split('&'))
+                .find_map(|pair| pair.strip_prefix(name)?.strip_prefix('=')),
+            IdFrom::Header(name) => headers.get(name).and_then(|v| v.to_str().ok()),
+        };
+        return match id {
+            Some(id) if !id.is_empty() => Verdict::InScope { id: id.to_owned(), access: rule.access },
+            _ => Verdict::Denied("resource id missing"),
+        };
+    }
+    Verdict::Denied(if outranked { "role too low" } else { "not on the allow-list" })
+}
+
+/// Returns the `{id}` segment (if the pattern has one) when `segments` match `pattern`.
+fn match_path<'a>(pattern: &str, segments: &[&'a str]) -> Option<Option<&'a str>> {
+    let mut id = None;
+    let mut rest = segments;
+    for part in pattern.split('/') {
+        if part == "**" {
+            return Some(id);
+        }
+        let (first, tail) = rest.split_first()?;
+        match part {
+            "*"                         => {}
+            "{id}" if !first.is_empty() => id = Some(*first),
+            literal if literal == *first => {}
+            _                           => return None,
+        }
+        rest = tail;
+    }
+    rest.is_empty().then_some(id)
+}
+
+/// Checks a request against the target's rules, loading the caller's scope only when a rule
+/// names an upstream resource. Denials are logged under the `audit` tracing target as
+/// `Forbidden`; `who` identifies the caller there.
+pub async fn authorize<T, F, Fut>(
+    target: &T,
+    who: &str,
+    role: &Role,
+    parts: &Parts,
+    scope: F,
+) -> Result<(), AppError>
+where
+    T: ProxyTarget,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<Scope, AppError>>,
+{
+    let path = parts.uri.path().strip_prefix(target.mount()).unwrap_or_default();
+    let verdict = evaluate(target.rules(), role, &parts.method, path, parts.uri.query(), &parts.headers);
+    let reason = match verdict {
+        Verdict::Allowed => return Ok(()),
+        Verdict::InScope { id, access } => match scope().await?.get(&id) {
+            Some(granted) if *granted >= access => return Ok(()),
+            Some(_) => "read-only access",
+            None    => "resource out of scope",
+        },
+        Verdict::Denied(reason) => reason,
+    };
+    Err(deny(target, who, parts, reason))
+}
+
+/// Logs a refused proxy request and returns the error to answer it with.
+pub fn deny<T: ProxyTarget>(target: &T, who: &str, parts: &Parts, reason: &str) -> AppError {
+    tracing::warn!(
+        target: "audit",
+        proxy = target.name(),
+        who,
+        method = %parts.method,
+        path = parts.uri.path(),
+        reason,
+        "proxy request denied"
+    );
+    AppError::Forbidden
+}
+
 /* ============================================================================================== */
 /*                                     Shared forwarding logic                                    */
 /* ============================================================================================== */
@@ -118,4 +303,70 @@ fn is_hop_by_hop(name: &str) -> bool {
             | "transfer-encoding"
             | "upgrade"
     )
-}
\ No newline at end of file
+}
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static RULES: &[Rule] = &[
+        ADMIN_RULE,
+        Rule { methods: &["GET"],  path: "static/**",   least: Role::Viewer, id: IdFrom::None, access: Access::Read },
+        Rule { methods: &["GET"],  path: "items/{id}",  least: Role::Viewer, id: IdFrom::Path, access: Access::Read },
+        Rule { methods: &["POST"], path: "items/{id}",  least: Role::Editor, id: IdFrom::Path, access: Access::Write },
+    ];
+
+    struct Upstream;
+
+    impl ProxyTarget for Upstream {
+        fn base_url(&self) -> &str { "http://upstream" }
+        fn prefix(&self) -> &str { "/proxy/test" }
+        fn name(&self) -> &'static str { "test" }
+        fn rules(&self) -> &'static [Rule] { RULES }
+        fn inject_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder { builder }
+    }
+
+    fn parts(method: &str, uri: &str) -> Parts {
+        axum::http::Request::builder().method(method).uri(uri).body(()).unwrap().into_parts().0
+    }
+
+    async fn check(role: Role, method: &str, uri: &str) -> Result<(), AppError> {
+        let mut scope = Scope::new();
+        grant(&mut scope, "mine", Access::Write);
+        grant(&mut scope, "shared", Access::Read);
+        authorize(&Upstream, "someone", &role, &parts(method, uri), || async { Ok(scope) }).await
+    }
+
+    #[test]
+    fn matches_path_patterns() {
+        assert_eq!(match_path("static/**", &["static"]), Some(None));
+        assert_eq!(match_path("static/**", &["static", "a", "b.js"]), Some(None));
+        assert_eq!(match_path("items/{id}", &["items", "x"]), Some(Some("x")));
+        assert_eq!(match_path("items/{id}", &["items", ""]), None);
+        assert_eq!(match_path("items/{id}", &["items", "x", "more"]), None);
+        assert_eq!(match_path("a/*/c", &["a", "b", "c"]), Some(None));
+        assert_eq!(match_path("a/*/c", &["a", "b", "d"]), None);
+    }
+
+    #[tokio::test]
+    async fn requests_are_checked_against_rules_and_scope() {
+        assert!(check(Role::Viewer, "GET", "/proxy/test/static/app.js").await.is_ok());
+        assert!(check(Role::Viewer, "GET", "/proxy/test/items/shared").await.is_ok());
+        assert!(check(Role::Editor, "POST", "/proxy/test/items/mine").await.is_ok());
+        assert!(check(Role::Admin, "DELETE", "/proxy/test/anything").await.is_ok());
+
+        for (role, method, uri) in [
+            (Role::Editor, "POST",   "/proxy/test/items/shared"),   // read-only
+            (Role::Viewer, "POST",   "/proxy/test/items/mine"),     // role too low
+            (Role::Editor, "GET",    "/proxy/test/items/other"),    // out of scope
+            (Role::Editor, "DELETE", "/proxy/test/items/mine"),     // not on the list
+            (Role::Editor, "GET",    "/proxy/test/static/../items/other"),
+        ] {
+            let result = check(role.clone(), method, uri).await;
+            assert!(matches!(result, Err(AppError::Forbidden)), "{role} {method} {uri}");
+        }
+    }
+}
@@ -1,10 +1,28 @@
 use axum::extract::State;
 
-use crate::auth::middleware::AuthenticatedUser;
+use crate::api::dashboards::viewable_panels;
+use crate::api::templates::readable_templates;
+use crate::auth::middleware::{AuthenticatedUser, Role};
+use crate::auth::permissions::TemplatePermission;
 use crate::error::AppError;
-use crate::proxy::{self, ProxyTarget};
+use crate::proxy::{self, Access, IdFrom, ProxyTarget, Rule, Scope, ADMIN_RULE};
 use crate::AppState;
 
+/// The NocoDB app shell, the shared views embedded in panels, and the records of templates the
+/// caller can read. Changes need an editor with `enter` on the template; the NocoDB UI itself,
+/// meta endpoints and other tables stay with admins.
+static RULES: &[Rule] = &[
+    ADMIN_RULE,
+    Rule { methods: &["GET", "HEAD"],              path: "dashboard/**",                         least: Role::Viewer, id: IdFrom::None, access: Access::Read },
+    Rule { methods: &["GET", "HEAD"],              path: "nc/**",                                least: Role::Viewer, id: IdFrom::None, access: Access::Read },
+    Rule { methods: &["GET"],                      path: "api/v1/db/public/shared-view/{id}/**", least: Role::Viewer, id: IdFrom::Path, access: Access::Read },
+    Rule { methods: &["POST"],                     path: "api/v1/db/public/shared-view/{id}/**", least: Role::Editor, id: IdFrom::Path, access: Access::Write },
+    Rule { methods: &["GET"],                      path: "api/v2/public/shared-view/{id}/**",    least: Role::Viewer, id: IdFrom::Path, access: Access::Read },
+    Rule { methods: &["POST"],                     path: "api/v2/public/shared-view/{id}/**",    least: Role::Editor, id: IdFrom::Path, access: Access::Write },
+    Rule { methods: &["GET"],                      path: "api/v2/tables/{id}/records/**",        least: Role::Viewer, id: IdFrom::Path, access: Access::Read },
+    Rule { methods: &["POST", "PATCH", "DELETE"],  path: "api/v2/tables/{id}/records/**",        least: Role::Editor, id: IdFrom::Path, access: Access::Write },
+];
+
 struct NocodbProxy {
     base_url: String,
     token: String,
@@ -19,9 +37,17 @@ impl ProxyTarget for NocodbProxy {
         "/proxy/nocodb"
     }
 
+    fn name(&self) -> &'static str {
+        "nocodb"
+    }
+
+    fn rules(&self) -> &'static [Rule] {
+        RULES
+    }
+
     fn inject_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
         builder.header(
-            "xc-token", 
+            "xc-token",
             &self.token,
         )
     }
@@ -30,12 +56,12 @@ impl ProxyTarget for NocodbProxy {
 /* ============================================================================================== */
 /// ANY /proxy/nocodb/{*path}
 ///
-/// Requires a valid portal session. Strips the `/proxy/nocodb` prefix,
-/// forwards the request to the internal Nocodb instance, and injects
+/// Requires a valid portal session; non-admins are limited by `RULES`. Strips the
+/// `/proxy/nocodb` prefix, forwards the request to the internal Nocodb instance, and injects
 /// the configured API token.
 pub async fn nocodb_proxy(
     State(state): State<AppState>,
-    _user: AuthenticatedUser,
+    user: AuthenticatedUser,
     req: axum::extract::Request,
 ) ->Result<axum:: response::Response, AppError> {
     let (parts, body) = req.into_parts();
@@ -48,6 +74,8 @@ pub async fn nocodb_proxy(
         token: state.config.nocodb_api_token.clone(),
     };
 
+    proxy::authorize(&target, &user.username, &user.role, &parts, || caller_scope(&state, &user)).await?;
+
     proxy::forward(
         &target,
         &state.http_client,
@@ -57,4 +85,78 @@ pub async fn nocodb_proxy(
         body_bytes,
     )
     .await
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Tables and forms of the templates the user can read (writable with `enter`), plus the
+/// shared views embedded in the dashboards they can see, read-only.
+async fn caller_scope(state: &AppState, user: &AuthenticatedUser) -> Result<Scope, AppError> {
+    let mut scope = Scope::new();
+    for (template, permission) in readable_templates(state, user).await? {
+        let access = if permission >= TemplatePermission::Enter { Access::Write } else { Access::Read };
+        for id in [&template.nocodb_table_id, &template.nocodb_form_id].into_iter().flatten() {
+            proxy::grant(&mut scope, id, access);
+        }
+    }
+    for panel in viewable_panels(state, user).await? {
+        if let Some(id) = panel.source_url.as_deref().and_then(shared_view_id) {
+            proxy::grant(&mut scope, id, Access::Read);
+        }
+    }
+    Ok(scope)
+}
+
+/// Extract the shared view UUID from an embedded NocoDB URL such as
+/// `/proxy/nocodb/dashboard/#/nc/form/{uuid}` or `/proxy/nocodb/nc/view/{uuid}`.
+pub(crate) fn shared_view_id(source_url: &str) -> Option<&str> {
+    let (_, rest) = source_url.split_once("/nc/")?;
+    let id = rest.split('/').nth(1)?.split(['?', '#']).next()?;
+    (!id.is_empty()).then_some(id)
+}
+
+/* ============================================================================================== */
+/*                                              Test                                              */
+/* ============================================================================================== */
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, Method};
+
+    use super::*;
+    use crate::proxy::Verdict;
+
+    fn verdict(role: Role, method: Method, path: &str) -> Verdict {
+        proxy::evaluate(RULES, &role, &method, path, None, &HeaderMap::new())
+    }
+
+    #[test]
+    fn extracts_shared_view_ids() {
+        assert_eq!(shared_view_id("/proxy/nocodb/dashboard/#/nc/form/abc-123"), Some("abc-123"));
+        assert_eq!(shared_view_id("/proxy/nocodb/nc/view/abc?embed"), Some("abc"));
+        assert_eq!(shared_view_id("/proxy/nocodb/nc/form/"), None);
+        assert_eq!(shared_view_id("/proxy/grafana/d/abc"), None);
+    }
+
+    #[test]
+    fn non_admins_reach_only_their_tables_and_views() {
+        let write = |id: &str| Verdict::InScope { id: id.into(), access: Access::Write };
+        let read = |id: &str| Verdict::InScope { id: id.into(), access: Access::Read };
+
+        assert_eq!(verdict(Role::Viewer, Method::GET, "/dashboard/index.html"), Verdict::Allowed);
+        assert_eq!(verdict(Role::Viewer, Method::GET, "/api/v1/db/public/shared-view/f1/meta"), read("f1"));
+        assert_eq!(verdict(Role::Editor, Method::POST, "/api/v1/db/public/shared-view/f1/rows"), write("f1"));
+        assert_eq!(verdict(Role::Viewer, Method::GET, "/api/v2/tables/t1/records"), read("t1"));
+        assert_eq!(verdict(Role::Editor, Method::DELETE, "/api/v2/tables/t1/records"), write("t1"));
+
+        assert_eq!(verdict(Role::Viewer, Method::POST, "/api/v1/db/public/shared-view/f1/rows"), Verdict::Denied("role too low"));
+        assert_eq!(verdict(Role::Viewer, Method::PATCH, "/api/v2/tables/t1/records"), Verdict::Denied("role too low"));
+        assert!(matches!(verdict(Role::Editor, Method::DELETE, "/api/v2/meta/tables/t1"), Verdict::Denied(_)));
+        assert!(matches!(verdict(Role::Editor, Method::GET, "/api/v2/meta/bases"), Verdict::Denied(_)));
+        assert!(matches!(verdict(Role::Editor, Method::GET, "/api/v1/auth/user/me"), Verdict::Denied(_)));
+
+        assert_eq!(verdict(Role::Admin, Method::DELETE, "/api/v2/meta/tables/t1"), Verdict::Allowed);
+    }
+}
@@ -1,5 +1,9 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use crate::models::User;
+use crate::models::user::{
+    ApiToken, CreateApiToken, CreatedApiToken, LoginOutcome, RecoveryCodes, SessionInfo, TotpSetup,
+    TotpStatus,
+};
 use super::client::{self, ApiError};
 
 #[derive(Serialize)]
@@ -9,11 +13,24 @@ struct LoginBody<'a> {
 }
 
 /* ============================================================================================== */
-/// POST /api/auth/login - returns the logged-in user on success.
-pub async fn login(username: &str, password: &str) -> Result<User, ApiError> {
+/// POST /api/auth/login - returns the logged-in user on success, or a challenge for
+/// `login_totp` when the account has two-factor authentication.
+pub async fn login(username: &str, password: &str) -> Result<LoginOutcome, ApiError> {
     client::post_json("/api/auth/login", &LoginBody { username, password }).await
 }
 
+/* ============================================================================================== */
+#[derive(Serialize)]
+struct LoginTotpBody<'a> {
+    challenge: &'a str,
+    code: &'a str,
+}
+
+/// POST /api/auth/login/totp - second login step, with an authenticator or recovery code.
+pub async fn login_totp(challenge: &str, code: &str) -> Result<User, ApiError> {
+    client::post_json("/api/auth/login/totp", &LoginTotpBody { challenge, code }).await
+}
+
 /* ============================================================================================== */
 /// POST /api/auth/logout - clears the session cookie.
 pub async fn logout() -> Result<(), ApiError> {
@@ -26,6 +43,17 @@ pub async fn me() -> Result<User, ApiError> {
     client::get("/api/auth/me").await
 }
 
+/* ============================================================================================== */
+#[derive(Deserialize)]
+struct OidcStatus {
+    enabled: bool,
+}
+
+/// GET /api/auth/oidc - whether single sign-on is configured.
+pub async fn oidc_enabled() -> Result<bool, ApiError> {
+    client::get::<OidcStatus>("/api/auth/oidc").await.map(|s| s.enabled)
+}
+
 /* ============================================================================================== */
 #[derive(Serialize)]
 struct ChangePasswordBody<'a> {
@@ -40,4 +68,69 @@ pub async fn change_password(current: &str, new: &str) -> Result<(), ApiError> {
         &ChangePasswordBody { current_password: current, new_password: new },
     )
     .await
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/tokens - the current user's API tokens.
+pub async fn list_api_tokens() -> Result<Vec<ApiToken>, ApiError> {
+    client::get("/api/auth/tokens").await
+}
+
+/* ============================================================================================== */
+/// POST /api/auth/tokens - the response carries the raw token, shown only this once.
+pub async fn create_api_token(input: &CreateApiToken) -> Result<CreatedApiToken, ApiError> {
+    client::post_json("/api/auth/tokens", input).await
+}
+
+/* ============================================================================================== */
+/// DELETE /api/auth/tokens/:id - revokes the token at once.
+pub async fn revoke_api_token(id: &str) -> Result<(), ApiError> {
+    client::delete(&format!("/api/auth/tokens/{id}")).await
+}
+
+/* ============================================================================================== */
+/// GET /api/auth/sessions - where the current user is signed in.
+pub async fn list_sessions() -> Result<Vec<SessionInfo>, ApiError> {
+    client::get("/api/auth/sessions").await
+}
+
+/// DELETE /api/auth/sessions/:id - signs that session out.
+pub async fn revoke_session(id: &str) -> Result<(), ApiError> {
+    client::delete(&format!("/api/auth/sessions/{id}")).await
+}
+
+/// DELETE /api/auth/sessions - signs out every session but this one.
+pub async fn revoke_other_sessions() -> Result<(), ApiError> {
+    client::delete("/api/auth/sessions").await
+}
+
+/* ============================================================================================== */
+#[derive(Serialize)]
+struct TotpCodeBody<'a> {
+    code: &'a str,
+}
+
+/// GET /api/auth/totp - whether two-factor authentication is on.
+pub async fn totp_status() -> Result<TotpStatus, ApiError> {
+    client::get("/api/auth/totp").await
+}
+
+/// POST /api/auth/totp/setup - a new secret and its QR code; off until `totp_enable`.
+pub async fn totp_setup() -> Result<TotpSetup, ApiError> {
+    client::post_json("/api/auth/totp/setup", &serde_json::json!({})).await
+}
+
+/// POST /api/auth/totp/enable - returns the recovery codes, shown only this once.
+pub async fn totp_enable(code: &str) -> Result<RecoveryCodes, ApiError> {
+    client::post_json("/api/auth/totp/enable", &TotpCodeBody { code }).await
+}
+
+/// POST /api/auth/totp/recovery-codes - replaces the recovery codes.
+pub async fn totp_recovery_codes(code: &str) -> Result<RecoveryCodes, ApiError> {
+    client::post_json("/api/auth/totp/recovery-codes", &TotpCodeBody { code }).await
+}
+
+/// POST /api/auth/totp/disable - 204 on success.
+pub async fn totp_disable(code: &str) -> Result<(), ApiError> {
+    client::post_json_empty("/api/auth/totp/disable", &TotpCodeBody { code }).await
+}
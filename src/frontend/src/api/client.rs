@@ -103,4 +103,22 @@ pub async fn put_json_empty<B: Serialize>(path: &str, body: &B) -> Result<(), Ap
 pub async fn delete(path: &str) -> Result<(), ApiError> {
     check(Request::delete(path).send().await?).await?;
     Ok(())
+}
+
+/* ============================================================================================== */
+/// POST a raw text body (e.g. CSV). A 422 response is still decoded into `T`, since
+/// endpoints use it to return a structured validation report.
+pub async fn post_text<T: DeserializeOwned>(
+    path: &str,
+    content_type: &str,
+    body: String,
+) -> Result<T, ApiError> {
+    let resp = Request::post(path)
+        .header("Content-Type", content_type)
+        .body(body)
+        .map_err(|e| ApiError::Network(e.to_string()))?
+        .send()
+        .await?;
+    let resp = if resp.status() == 422 { resp } else { check(resp).await? };
+    resp.json::<T>().await.map_err(|e| ApiError::Deserialize(e.to_string()))
 }
\ No newline at end of file
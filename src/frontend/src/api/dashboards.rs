@@ -1,6 +1,7 @@
 use crate::models::dashboard::{
-    BatchPositionUpdate, CreateDashboard, CreatePanel,
-    Dashboard, DashboardWithPanels, Panel, UpdateDashboard,
+    BatchPositionUpdate, CreateDashboard, CreatePanel, CreatePublicLink, Dashboard,
+    DashboardShare, DashboardWithPanels, Panel, PublicDashboard, PublicLink, ShareGrant,
+    UpdateDashboard,
 };
 use super::client::{self, ApiError};
 
@@ -28,6 +29,39 @@ pub async fn delete_dashboard(id: &str) -> Result<(), ApiError> {
     client::delete(&format!("/api/dashboards/{id}")).await
 }
 
+/* ============================================================================================== */
+pub async fn list_shares(id: &str) -> Result<Vec<DashboardShare>, ApiError> {
+    client::get(&format!("/api/dashboards/{id}/shares")).await
+}
+
+/* ============================================================================================== */
+/// PUT /api/dashboards/:id/shares — replaces every share; users left out lose access.
+pub async fn replace_shares(id: &str, grants: &[ShareGrant]) -> Result<Vec<DashboardShare>, ApiError> {
+    client::put_json(&format!("/api/dashboards/{id}/shares"), &grants).await
+}
+
+/* ============================================================================================== */
+pub async fn list_public_links(id: &str) -> Result<Vec<PublicLink>, ApiError> {
+    client::get(&format!("/api/dashboards/{id}/public-links")).await
+}
+
+/* ============================================================================================== */
+pub async fn create_public_link(id: &str, input: &CreatePublicLink) -> Result<PublicLink, ApiError> {
+    client::post_json(&format!("/api/dashboards/{id}/public-links"), input).await
+}
+
+/* ============================================================================================== */
+/// DELETE /api/dashboards/:id/public-links/:link_id — revokes the link; it stays listed.
+pub async fn revoke_public_link(id: &str, link_id: &str) -> Result<(), ApiError> {
+    client::delete(&format!("/api/dashboards/{id}/public-links/{link_id}")).await
+}
+
+/* ============================================================================================== */
+/// GET /api/public/:token — works without a session.
+pub async fn get_public_dashboard(token: &str) -> Result<PublicDashboard, ApiError> {
+    client::get(&format!("/api/public/{token}")).await
+}
+
 /* ============================================================================================== */
 pub async fn create_panel(dashboard_id: &str, input: &CreatePanel) -> Result<Panel, ApiError> {
     client::post_json(&format!("/api/dashboards/{dashboard_id}/panels"), input).await
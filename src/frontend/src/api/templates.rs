@@ -1,4 +1,6 @@
-use crate::models::template::{CreateTemplateRequest, DatasetTemplate};
+use std::collections::BTreeMap;
+
+use crate::models::template::{CreateTemplateRequest, DatasetTemplate, ImportReport, ProvisioningJob};
 use super::client::{self, ApiError};
 
 pub async fn list_templates() -> Result<Vec<DatasetTemplate>, ApiError> {
@@ -11,11 +13,33 @@ pub async fn get_template(id: &str) -> Result<DatasetTemplate, ApiError> {
 }
 
 /* ============================================================================================== */
-pub async fn create_template(req: &CreateTemplateRequest) -> Result<DatasetTemplate, ApiError> {
+pub async fn create_template(req: &CreateTemplateRequest) -> Result<ProvisioningJob, ApiError> {
     client::post_json("/api/templates", req).await
 }
 
+/* ============================================================================================== */
+pub async fn get_job(id: &str) -> Result<ProvisioningJob, ApiError> {
+    client::get(&format!("/api/templates/jobs/{id}")).await
+}
+
 /* ============================================================================================== */
 pub async fn delete_template(id: &str) -> Result<(), ApiError> {
     client::delete(&format!("/api/templates/{id}")).await
+}
+
+/* ============================================================================================== */
+/// Upload CSV text. `mapping` of `None` lets the server match headers to field names.
+pub async fn import_csv(
+    id: &str,
+    csv: String,
+    mapping: Option<&BTreeMap<String, String>>,
+    dry_run: bool,
+) -> Result<ImportReport, ApiError> {
+    let mut path = format!("/api/templates/{id}/import?dry_run={dry_run}");
+    if let Some(mapping) = mapping {
+        let json = serde_json::to_string(mapping).unwrap_or_default();
+        path.push_str("&mapping=");
+        path.push_str(&String::from(js_sys::encode_uri_component(&json)));
+    }
+    client::post_text(&path, "text/csv", csv).await
 }
\ No newline at end of file
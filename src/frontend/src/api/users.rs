@@ -1,4 +1,5 @@
 use serde::Serialize;
+use crate::models::user::{Group, UserSummary};
 use crate::models::User;
 use super::client::{self, ApiError};
 
@@ -8,6 +9,18 @@ pub async fn list_users() -> Result<Vec<User>, ApiError> {
     client::get("/api/users").await
 }
 
+/* ============================================================================================== */
+/// GET /api/users/directory — ids and usernames of everyone, for share dialogs.
+pub async fn list_directory() -> Result<Vec<UserSummary>, ApiError> {
+    client::get("/api/users/directory").await
+}
+
+/* ============================================================================================== */
+/// GET /api/groups — every group, for share dialogs.
+pub async fn list_groups() -> Result<Vec<Group>, ApiError> {
+    client::get("/api/groups").await
+}
+
 /* ============================================================================================== */
 #[derive(Serialize)]
 struct UpdateRoleBody {
@@ -57,4 +70,16 @@ pub async fn reset_user_password(id: &str, new_password: &str) -> Result<(), Api
         &ResetPasswordBody { new_password },
     )
     .await
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// DELETE /api/users/:id/totp — admin turns off a user's two-factor authentication.
+pub async fn reset_user_totp(id: &str) -> Result<(), ApiError> {
+    client::delete(&format!("/api/users/{id}/totp")).await
+}
+
+/* ============================================================================================== */
+/// DELETE /api/users/:id/lockout — admin lifts a lockout after failed sign-ins.
+pub async fn unlock_user(id: &str) -> Result<(), ApiError> {
+    client::delete(&format!("/api/users/{id}/lockout")).await
+}
@@ -14,6 +14,7 @@ use crate::pages::{
     admin_users::AdminUsersPage,
     dashboard_list::DashboardListPage,
     dashboard_view::DashboardViewPage,
+    public_dashboard::PublicDashboardPage,
     settings::SettingsPage,
 };
 use crate::router::Route;
@@ -39,6 +40,12 @@ fn not_found_page() -> Html {
 #[function_component(AppContent)]
 fn app_content() -> Html {
     let auth = use_context::<AuthContext>().expect("AuthContext missing");
+    let route = use_route::<Route>();
+
+    // Public links work with or without a session, and outside the shell.
+    if let Some(Route::PublicDashboard { token }) = route {
+        return html! { <PublicDashboardPage token={token} /> };
+    }
 
     // While the initial session is in flight, show a loading screen.
     if auth.loading {
@@ -83,6 +90,8 @@ fn app_content() -> Html {
                     } else {
                         html! { <Redirect<Route> to={Route::DashboardList} /> }
                     },
+                Route::PublicDashboard { token } =>
+                    html! { <PublicDashboardPage token={token} /> },
                 Route::NotFound =>
                     html! { <NotFoundPage /> },
             }} />
@@ -0,0 +1,241 @@
+use yew::prelude::*;
+
+use crate::api::auth;
+use crate::context::auth_context::AuthContext;
+use crate::models::user::{ApiToken, CreateApiToken, Role};
+
+/// Scopes offered when creating a token, with the least role that may hold each.
+const SCOPES: &[(&str, &str, Role)] = &[
+    ("dashboards:read", "Read dashboards", Role::Viewer),
+    ("records:write",   "Read and write records", Role::Editor),
+    ("admin",           "Admin (everything but account settings)", Role::Admin),
+];
+
+/// Expiry choices offered when creating a token, in days.
+const EXPIRIES: &[(i64, &str)] = &[
+    (30,  "30 days"),
+    (90,  "90 days"),
+    (365, "1 year"),
+];
+
+/* ============================================================================================== */
+/*                                        API tokens section                                      */
+/* ============================================================================================== */
+
+/// Personal API tokens for scripts, sent as `Authorization: Bearer <token>`. A new token is
+/// shown once, right after it is created.
+#[function_component(ApiTokens)]
+pub fn api_tokens() -> Html {
+    let auth    = use_context::<AuthContext>().expect("AuthContext missing");
+    let role    = auth.user.as_ref().map(|u| u.role.clone()).unwrap_or(Role::Viewer);
+
+    let tokens  = use_state(|| Option::<Vec<ApiToken>>::None);
+    let name    = use_state(String::new);
+    let scopes  = use_state(|| vec!["dashboards:read".to_string()]);
+    let expiry  = use_state(|| 90_i64);
+    let secret  = use_state(|| Option::<String>::None);
+    let busy    = use_state(|| false);
+    let error   = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let tokens = tokens.clone();
+        let error  = error.clone();
+        Callback::from(move |_: ()| {
+            let tokens = tokens.clone();
+            let error  = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::list_api_tokens().await {
+                    Ok(list) => tokens.set(Some(list)),
+                    Err(e)   => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            || ()
+        });
+    }
+
+    let on_create = {
+        let name   = name.clone();
+        let scopes = scopes.clone();
+        let expiry = expiry.clone();
+        let secret = secret.clone();
+        let busy   = busy.clone();
+        let error  = error.clone();
+        let reload = reload.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let input = CreateApiToken {
+                name:            (*name).clone(),
+                scopes:          (*scopes).clone(),
+                expires_in_days: *expiry,
+            };
+            let name   = name.clone();
+            let secret = secret.clone();
+            let busy   = busy.clone();
+            let error  = error.clone();
+            let reload = reload.clone();
+            busy.set(true);
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::create_api_token(&input).await {
+                    Ok(created) => {
+                        name.set(String::new());
+                        secret.set(Some(created.secret));
+                        reload.emit(());
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let field_class = "rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700 \
+                       text-stone-800 dark:text-stone-100 text-sm px-3 py-2 \
+                       focus:outline-none focus:ring-2 focus:ring-amber-400";
+
+    html! {
+        <div class="space-y-4">
+            <p class="text-xs text-stone-400 dark:text-stone-500">
+                {"Tokens let scripts call the API as you, limited to the scopes you pick. \
+                  Send them as an "}
+                <code>{"Authorization: Bearer"}</code>
+                {" header."}
+            </p>
+
+            if let Some(ref value) = *secret {
+                <div class="rounded border border-amber-300 dark:border-amber-700 bg-amber-50 dark:bg-amber-900/20 p-3 space-y-1">
+                    <p class="text-xs font-medium text-amber-800 dark:text-amber-300">
+                        {"Copy this token now — it will not be shown again."}
+                    </p>
+                    <code class="block break-all text-xs text-stone-800 dark:text-stone-100 select-all">{ value }</code>
+                </div>
+            }
+
+            if let Some(ref err) = *error {
+                <p class="text-xs text-red-500">{ err }</p>
+            }
+
+            if let Some(list) = tokens.as_ref().filter(|l| !l.is_empty()) {
+                <div class="rounded-lg border border-stone-200 dark:border-stone-700 divide-y divide-stone-100 dark:divide-stone-700">
+                    { for list.iter().map(|token| render_token(token, &reload, &error)) }
+                </div>
+            }
+
+            <form onsubmit={on_create} class="space-y-3 max-w-sm">
+                <input type="text" required=true placeholder="Token name" value={(*name).clone()}
+                       oninput={Callback::from({
+                           let name = name.clone();
+                           move |e: InputEvent| {
+                               let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+                               name.set(el.value());
+                           }
+                       })}
+                       class={format!("w-full {field_class}")} />
+
+                <div class="space-y-1">
+                    { for SCOPES.iter().filter(|(_, _, least)| role_at_least(&role, least)).map(|(scope, label, _)| {
+                        let checked = scopes.contains(&scope.to_string());
+                        let onchange = {
+                            let scopes = scopes.clone();
+                            let scope  = scope.to_string();
+                            Callback::from(move |_: Event| {
+                                let mut next = (*scopes).clone();
+                                if let Some(i) = next.iter().position(|s| *s == scope) {
+                                    next.remove(i);
+                                } else {
+                                    next.push(scope.clone());
+                                }
+                                scopes.set(next);
+                            })
+                        };
+                        html! {
+                            <label class="flex items-center gap-2 text-sm text-stone-700 dark:text-stone-200">
+                                <input type="checkbox" {checked} {onchange} />
+                                { *label }
+                            </label>
+                        }
+                    })}
+                </div>
+
+                <div class="flex items-center gap-2">
+                    <select onchange={Callback::from({
+                                let expiry = expiry.clone();
+                                move |e: Event| {
+                                    let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                                    if let Ok(days) = el.value().parse() {
+                                        expiry.set(days);
+                                    }
+                                }
+                            })}
+                            class={field_class}>
+                        { for EXPIRIES.iter().map(|(days, label)| html! {
+                            <option value={days.to_string()} selected={*expiry == *days}>{ *label }</option>
+                        })}
+                    </select>
+                    <button type="submit" disabled={*busy || scopes.is_empty()}
+                            class="px-4 py-2 text-sm font-medium rounded bg-amber-500 text-stone-900 \
+                                   hover:bg-amber-400 disabled:opacity-50 transition-colors">
+                        {"Create token"}
+                    </button>
+                </div>
+            </form>
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Admin outranks editor, which outranks viewer.
+fn role_at_least(role: &Role, least: &Role) -> bool {
+    match least {
+        Role::Viewer => true,
+        Role::Editor => *role != Role::Viewer,
+        Role::Admin  => *role == Role::Admin,
+    }
+}
+
+/// One token: its name, scopes, expiry and last use, and a revoke button.
+fn render_token(token: &ApiToken, reload: &Callback<()>, error: &UseStateHandle<Option<String>>) -> Html {
+    let on_revoke = {
+        let id     = token.id.clone();
+        let reload = reload.clone();
+        let error  = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let id     = id.clone();
+            let reload = reload.clone();
+            let error  = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::revoke_api_token(&id).await {
+                    Ok(())  => reload.emit(()),
+                    Err(e)  => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+    let date = |s: &str| s.get(..10).unwrap_or(s).to_string();
+    let last_used = token.last_used_at.as_deref().map_or("never used".to_string(), |s| format!("last used {}", date(s)));
+
+    html! {
+        <div class="flex items-center justify-between gap-3 px-3 py-2 text-sm">
+            <div class="flex-1 min-w-0">
+                <p class="truncate font-medium text-stone-800 dark:text-stone-100">{ &token.name }</p>
+                <span class="text-xs text-stone-400 dark:text-stone-500">
+                    { format!("{} · expires {} · {}", token.scopes.join(", "), date(&token.expires_at), last_used) }
+                </span>
+            </div>
+            <button onclick={on_revoke} title="Revoke"
+                    class="text-xs text-stone-400 hover:text-red-500 dark:hover:text-red-400">
+                {"Revoke"}
+            </button>
+        </div>
+    }
+}
@@ -1,19 +1,42 @@
 use yew::prelude::*;
 use yew_router::prelude::*;
 use crate::api::auth;
+use crate::api::client::ApiError;
 use crate::context::auth_context::AuthAction;
 use crate::hooks::use_auth::use_auth;
+use crate::models::user::LoginOutcome;
 use crate::router::Route;
 
 #[function_component(LoginPage)]
 pub fn login_page() -> Html {
     let auth = use_auth();
     let navigator = use_navigator().expect("navigator not found");
+    let location = use_location().expect("location not found");
 
     let username = use_state(String::new);
     let password = use_state(String::new);
-    let error = use_state(|| Option::<String>::None);
+    // The single sign-on callback sends failures back here with ?error=sso.
+    let error = use_state(|| {
+        location.query_str().contains("error=sso")
+            .then(|| "Single sign-on failed. Try again or sign in with a password.".to_string())
+    });
     let loading = use_state(|| false);
+    let sso_enabled = use_state(|| false);
+    // Set once the password checks out on an account with two-factor authentication.
+    let challenge = use_state(|| Option::<String>::None);
+    let code = use_state(String::new);
+
+    {
+        let sso_enabled = sso_enabled.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(enabled) = auth::oidc_enabled().await {
+                    sso_enabled.set(enabled);
+                }
+            });
+            || ()
+        });
+    }
 
     // If already logged in, redirect away
     if auth.user.is_some() {
@@ -43,6 +66,7 @@ pub fn login_page() -> Html {
         let password = password.clone();
         let error = error.clone();
         let loading = loading.clone();
+        let challenge = challenge.clone();
 
         Callback::from(move |e: SubmitEvent| {
             e.prevent_default();
@@ -52,18 +76,23 @@ pub fn login_page() -> Html {
             let p = (*password).clone();
             let error = error.clone();
             let loading = loading.clone();
+            let challenge = challenge.clone();
 
             loading.set(true);
             error.set(None);
 
             wasm_bindgen_futures::spawn_local(async move {
                 match auth::login(&u, &p).await {
-                    Ok(user) => {
+                    Ok(LoginOutcome::SignedIn(user)) => {
                         auth.dispatch(AuthAction::SetUser(user));
                         navigator.push(&Route::DashboardList);
                     }
+                    Ok(LoginOutcome::TwoFactorRequired { challenge: token }) => {
+                        challenge.set(Some(token));
+                        loading.set(false);
+                    }
                     Err(e) => {
-                        error.set(Some(e.to_string()));
+                        error.set(Some(login_error(&e)));
                         loading.set(false);
                     }
                 }
@@ -71,6 +100,65 @@ pub fn login_page() -> Html {
         })
     };
 
+    let on_code = {
+        let code = code.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            code.set(input.value());
+        })
+    };
+
+    let on_submit_code = {
+        let auth = auth.clone();
+        let navigator = navigator.clone();
+        let challenge = challenge.clone();
+        let code = code.clone();
+        let error = error.clone();
+        let loading = loading.clone();
+
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(token) = (*challenge).clone() else { return };
+            let auth = auth.clone();
+            let navigator = navigator.clone();
+            let c = (*code).clone();
+            let code = code.clone();
+            let error = error.clone();
+            let loading = loading.clone();
+
+            loading.set(true);
+            error.set(None);
+
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::login_totp(&token, &c).await {
+                    Ok(user) => {
+                        auth.dispatch(AuthAction::SetUser(user));
+                        navigator.push(&Route::DashboardList);
+                    }
+                    Err(e @ ApiError::Server { status: 429, .. }) => {
+                        error.set(Some(login_error(&e)));
+                        loading.set(false);
+                    }
+                    Err(_) => {
+                        // The challenge allows a few tries; after that, start over.
+                        error.set(Some("That code did not work. Try again, or sign in again.".to_string()));
+                        code.set(String::new());
+                        loading.set(false);
+                    }
+                }
+            });
+        })
+    };
+
+    let on_back = {
+        let challenge = challenge.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            challenge.set(None);
+            error.set(None);
+        })
+    };
+
     html! {
         <div class="min-h-screen grid grid-cols-1 lg:grid-cols-5">
 
@@ -98,7 +186,13 @@ pub fn login_page() -> Html {
                     </div>
 
                     <h1 class="text-2xl font-bold text-stone-900">{"Sign in"}</h1>
-                    <p class="mt-1 text-sm text-stone-500 mb-8">{"Enter your credentials to continue"}</p>
+                    <p class="mt-1 text-sm text-stone-500 mb-8">
+                        if challenge.is_some() {
+                            {"Enter the code from your authenticator app, or a recovery code"}
+                        } else {
+                            {"Enter your credentials to continue"}
+                        }
+                    </p>
 
                     if let Some(err) = (*error).clone() {
                         <div class="mb-4 rounded-md bg-red-50 border border-red-200 px-4 py-3 text-sm text-red-700">
@@ -106,53 +200,115 @@ pub fn login_page() -> Html {
                         </div>
                     }
 
-                    <form onsubmit={on_submit} class="space-y-4">
-                        <div class="space-y-1">
-                            <label class="block text-xs font-semibold uppercase tracking-wider text-stone-500"
-                                   for="username">
-                                {"Username"}
-                            </label>
-                            <input
-                                id="username" type="text" autocomplete="username" required=true
+                    if challenge.is_some() {
+                        <form onsubmit={on_submit_code} class="space-y-4">
+                            <div class="space-y-1">
+                                <label class="block text-xs font-semibold uppercase tracking-wider text-stone-500"
+                                       for="code">
+                                    {"Code"}
+                                </label>
+                                <input
+                                    id="code" type="text" inputmode="numeric" autocomplete="one-time-code"
+                                    required=true autofocus=true
+                                    disabled={*loading}
+                                    value={(*code).clone()}
+                                    oninput={on_code}
+                                    class="w-full rounded-md border border-stone-300 bg-white px-3 py-2.5 text-sm
+                                           text-stone-900 placeholder-stone-400 tracking-widest
+                                           focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent
+                                           disabled:opacity-50 transition"
+                                />
+                            </div>
+
+                            <button
+                                type="submit"
                                 disabled={*loading}
-                                value={(*username).clone()}
-                                oninput={on_username}
-                                class="w-full rounded-md border border-stone-300 bg-white px-3 py-2.5 text-sm
-                                       text-stone-900 placeholder-stone-400
-                                       focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent
-                                       disabled:opacity-50 transition"
-                            />
-                        </div>
+                                class="mt-2 w-full rounded-md bg-amber-500 px-4 py-2.5 text-sm font-semibold
+                                       text-slate-900 hover:bg-amber-400 active:bg-amber-600
+                                       disabled:opacity-50 transition-colors"
+                            >
+                                if *loading { {"Verifying…"} } else { {"Verify"} }
+                            </button>
+                            <button type="button" onclick={on_back}
+                                    class="w-full text-center text-xs text-stone-500 hover:text-stone-700">
+                                {"Back to sign in"}
+                            </button>
+                        </form>
+                    } else {
+                        <form onsubmit={on_submit} class="space-y-4">
+                            <div class="space-y-1">
+                                <label class="block text-xs font-semibold uppercase tracking-wider text-stone-500"
+                                       for="username">
+                                    {"Username"}
+                                </label>
+                                <input
+                                    id="username" type="text" autocomplete="username" required=true
+                                    disabled={*loading}
+                                    value={(*username).clone()}
+                                    oninput={on_username}
+                                    class="w-full rounded-md border border-stone-300 bg-white px-3 py-2.5 text-sm
+                                           text-stone-900 placeholder-stone-400
+                                           focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent
+                                           disabled:opacity-50 transition"
+                                />
+                            </div>
+
+                            <div class="space-y-1">
+                                <label class="block text-xs font-semibold uppercase tracking-wider text-stone-500"
+                                       for="password">
+                                    {"Password"}
+                                </label>
+                                <input
+                                    id="password" type="password" autocomplete="current-password" required=true
+                                    disabled={*loading}
+                                    value={(*password).clone()}
+                                    oninput={on_password}
+                                    class="w-full rounded-md border border-stone-300 bg-white px-3 py-2.5 text-sm
+                                           text-stone-900 placeholder-stone-400
+                                           focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent
+                                           disabled:opacity-50 transition"
+                                />
+                            </div>
 
-                        <div class="space-y-1">
-                            <label class="block text-xs font-semibold uppercase tracking-wider text-stone-500"
-                                   for="password">
-                                {"Password"}
-                            </label>
-                            <input
-                                id="password" type="password" autocomplete="current-password" required=true
+                            <button
+                                type="submit"
                                 disabled={*loading}
-                                value={(*password).clone()}
-                                oninput={on_password}
-                                class="w-full rounded-md border border-stone-300 bg-white px-3 py-2.5 text-sm
-                                       text-stone-900 placeholder-stone-400
-                                       focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent
-                                       disabled:opacity-50 transition"
-                            />
-                        </div>
+                                class="mt-2 w-full rounded-md bg-amber-500 px-4 py-2.5 text-sm font-semibold
+                                       text-slate-900 hover:bg-amber-400 active:bg-amber-600
+                                       disabled:opacity-50 transition-colors"
+                            >
+                                if *loading { {"Signing in…"} } else { {"Sign in"} }
+                            </button>
+                        </form>
 
-                        <button
-                            type="submit"
-                            disabled={*loading}
-                            class="mt-2 w-full rounded-md bg-amber-500 px-4 py-2.5 text-sm font-semibold
-                                   text-slate-900 hover:bg-amber-400 active:bg-amber-600
-                                   disabled:opacity-50 transition-colors"
-                        >
-                            if *loading { {"Signing in…"} } else { {"Sign in"} }
-                        </button>
-                    </form>
+                        if *sso_enabled {
+                            <div class="my-6 flex items-center gap-3 text-xs text-stone-400">
+                                <div class="flex-1 h-px bg-stone-200" />
+                                {"or"}
+                                <div class="flex-1 h-px bg-stone-200" />
+                            </div>
+                            // A full page load: the provider's sign-in page is not part of the app.
+                            <a href="/api/auth/oidc/login"
+                               class="block w-full rounded-md border border-stone-300 bg-white px-4 py-2.5 text-center
+                                      text-sm font-semibold text-stone-700 hover:bg-stone-100 transition-colors">
+                                {"Sign in with single sign-on"}
+                            </a>
+                        }
+                    }
                 </div>
             </div>
         </div>
     }
-}
\ No newline at end of file
+}
+
+/* ============================================================================================== */
+/// The message for a failed sign-in: throttled attempts are told to wait; anything else stays as
+/// vague as the server's answer.
+fn login_error(error: &ApiError) -> String {
+    match error {
+        ApiError::Server { status: 429, .. } => {
+            "Too many failed attempts. Wait a few minutes before trying again.".to_string()
+        }
+        other => other.to_string(),
+    }
+}
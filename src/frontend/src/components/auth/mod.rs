@@ -1,2 +1,5 @@
+pub mod api_tokens;
 pub mod login_page;
+pub mod sessions;
+pub mod two_factor;
 pub use login_page::LoginPage;
\ No newline at end of file
@@ -0,0 +1,163 @@
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use crate::api::auth;
+use crate::context::auth_context::AuthAction;
+use crate::hooks::use_auth::use_auth;
+use crate::models::user::SessionInfo;
+use crate::router::Route;
+
+/* ============================================================================================== */
+/*                                         Sessions section                                       */
+/* ============================================================================================== */
+
+/// Where the user is signed in, with a sign-out per session and one for all the others.
+/// Signing out this browser's session goes back to the login page.
+#[function_component(Sessions)]
+pub fn sessions() -> Html {
+    let auth      = use_auth();
+    let navigator = use_navigator().expect("navigator not found");
+
+    let sessions  = use_state(|| Option::<Vec<SessionInfo>>::None);
+    let error     = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let sessions = sessions.clone();
+        let error    = error.clone();
+        Callback::from(move |_: ()| {
+            let sessions = sessions.clone();
+            let error    = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::list_sessions().await {
+                    Ok(list) => sessions.set(Some(list)),
+                    Err(e)   => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            || ()
+        });
+    }
+
+    let on_revoke = {
+        let reload = reload.clone();
+        let error  = error.clone();
+        Callback::from(move |(id, current): (String, bool)| {
+            let auth      = auth.clone();
+            let navigator = navigator.clone();
+            let reload    = reload.clone();
+            let error     = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::revoke_session(&id).await {
+                    Ok(()) if current => {
+                        auth.dispatch(AuthAction::ClearUser);
+                        navigator.push(&Route::Login);
+                    }
+                    Ok(())  => reload.emit(()),
+                    Err(e)  => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    let on_revoke_others = {
+        let reload = reload.clone();
+        let error  = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let reload = reload.clone();
+            let error  = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::revoke_other_sessions().await {
+                    Ok(())  => reload.emit(()),
+                    Err(e)  => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    let others = sessions.as_ref().is_some_and(|list| list.iter().any(|s| !s.current));
+
+    html! {
+        <div class="space-y-4">
+            if let Some(ref err) = *error {
+                <p class="text-xs text-red-500">{ err }</p>
+            }
+
+            if let Some(list) = sessions.as_ref().filter(|l| !l.is_empty()) {
+                <div class="rounded-lg border border-stone-200 dark:border-stone-700 divide-y divide-stone-100 dark:divide-stone-700">
+                    { for list.iter().map(|session| render_session(session, &on_revoke)) }
+                </div>
+            }
+
+            if others {
+                <button onclick={on_revoke_others}
+                        class="px-3 py-2 text-sm font-medium rounded text-red-600 hover:bg-red-50
+                               dark:hover:bg-red-900/20 transition-colors">
+                    {"Sign out everywhere else"}
+                </button>
+            }
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// One session: its browser, address, when it was last active, and a sign-out button.
+fn render_session(session: &SessionInfo, on_revoke: &Callback<(String, bool)>) -> Html {
+    let onclick = {
+        let on_revoke = on_revoke.clone();
+        let id        = session.id.clone();
+        let current   = session.current;
+        Callback::from(move |_: MouseEvent| on_revoke.emit((id.clone(), current)))
+    };
+    let minute = |s: &str| s.get(..16).unwrap_or(s).replace('T', " ");
+    let device = session.user_agent.as_deref().map_or("Unknown browser".to_string(), browser_name);
+    let details = format!(
+        "{} · signed in {} · last active {}",
+        session.ip.as_deref().unwrap_or("unknown address"),
+        minute(&session.created_at),
+        minute(&session.last_seen_at),
+    );
+
+    html! {
+        <div class="flex items-center justify-between gap-3 px-3 py-2 text-sm">
+            <div class="flex-1 min-w-0">
+                <p class="truncate font-medium text-stone-800 dark:text-stone-100" title={session.user_agent.clone()}>
+                    { device }
+                    if session.current {
+                        <span class="ml-2 text-xs font-normal text-amber-600 dark:text-amber-400">{"this browser"}</span>
+                    }
+                </p>
+                <span class="text-xs text-stone-400 dark:text-stone-500">{ details }</span>
+            </div>
+            <button {onclick}
+                    class="text-xs text-stone-400 hover:text-red-500 dark:hover:text-red-400">
+                {"Sign out"}
+            </button>
+        </div>
+    }
+}
+
+/// A short name for a user agent string, e.g. "Firefox on Linux"; the full string is the tooltip.
+fn browser_name(user_agent: &str) -> String {
+    let browser = [("Edg/", "Edge"), ("Firefox/", "Firefox"), ("Chrome/", "Chrome"), ("Safari/", "Safari")]
+        .iter()
+        .find(|(needle, _)| user_agent.contains(needle))
+        .map_or("Browser", |(_, name)| name);
+    let system = [("Android", "Android"), ("iPhone", "iOS"), ("iPad", "iOS"), ("Windows", "Windows"),
+                  ("Mac OS", "macOS"), ("Linux", "Linux")]
+        .iter()
+        .find(|(needle, _)| user_agent.contains(needle))
+        .map(|(_, name)| name);
+    match system {
+        Some(system) => format!("{browser} on {system}"),
+        None         => user_agent.chars().take(60).collect(),
+    }
+}
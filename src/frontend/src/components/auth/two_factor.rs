@@ -0,0 +1,197 @@
+use yew::prelude::*;
+
+use crate::api::auth;
+use crate::models::user::{TotpSetup, TotpStatus};
+
+/* ============================================================================================== */
+/*                                   Two-factor authentication                                    */
+/* ============================================================================================== */
+
+/// Turns TOTP two-factor authentication on and off. Setting up shows a QR code, then asks for a
+/// code before anything changes; recovery codes are shown once, when issued.
+#[function_component(TwoFactor)]
+pub fn two_factor() -> Html {
+    let status   = use_state(|| Option::<TotpStatus>::None);
+    let setup    = use_state(|| Option::<TotpSetup>::None);
+    let codes    = use_state(|| Option::<Vec<String>>::None);
+    let code     = use_state(String::new);
+    let busy     = use_state(|| false);
+    let error    = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let status = status.clone();
+        let error  = error.clone();
+        Callback::from(move |_: ()| {
+            let status = status.clone();
+            let error  = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::totp_status().await {
+                    Ok(s)  => status.set(Some(s)),
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            || ()
+        });
+    }
+
+    let on_setup = {
+        let setup = setup.clone();
+        let codes = codes.clone();
+        let error = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let setup = setup.clone();
+            let error = error.clone();
+            codes.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match auth::totp_setup().await {
+                    Ok(s)  => setup.set(Some(s)),
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    // One handler for the three actions that take a code: enable, new recovery codes, disable.
+    let run = {
+        let setup  = setup.clone();
+        let codes  = codes.clone();
+        let code   = code.clone();
+        let busy   = busy.clone();
+        let error  = error.clone();
+        let reload = reload.clone();
+        move |action: &'static str| {
+            let setup  = setup.clone();
+            let codes  = codes.clone();
+            let code   = code.clone();
+            let busy   = busy.clone();
+            let error  = error.clone();
+            let reload = reload.clone();
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                let value  = (*code).clone();
+                let setup  = setup.clone();
+                let codes  = codes.clone();
+                let code   = code.clone();
+                let busy   = busy.clone();
+                let error  = error.clone();
+                let reload = reload.clone();
+                busy.set(true);
+                error.set(None);
+                wasm_bindgen_futures::spawn_local(async move {
+                    let result = match action {
+                        "enable"   => auth::totp_enable(&value).await.map(|r| Some(r.recovery_codes)),
+                        "recovery" => auth::totp_recovery_codes(&value).await.map(|r| Some(r.recovery_codes)),
+                        _          => auth::totp_disable(&value).await.map(|()| None),
+                    };
+                    match result {
+                        Ok(issued) => {
+                            setup.set(None);
+                            codes.set(issued);
+                            reload.emit(());
+                        }
+                        Err(e) => error.set(Some(e.to_string())),
+                    }
+                    code.set(String::new());
+                    busy.set(false);
+                });
+            })
+        }
+    };
+
+    let code_input = html! {
+        <input type="text" inputmode="numeric" autocomplete="one-time-code" placeholder="Code"
+               value={(*code).clone()}
+               oninput={Callback::from({
+                   let code = code.clone();
+                   move |e: InputEvent| {
+                       let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+                       code.set(el.value());
+                   }
+               })}
+               class="w-40 rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                      text-stone-800 dark:text-stone-100 text-sm px-3 py-2 tracking-widest
+                      focus:outline-none focus:ring-2 focus:ring-amber-400" />
+    };
+    let button_class = "px-3 py-2 text-sm font-medium rounded text-stone-700 dark:text-stone-200 \
+                        bg-stone-100 dark:bg-stone-700 hover:bg-stone-200 dark:hover:bg-stone-600 \
+                        disabled:opacity-40 transition-colors";
+
+    html! {
+        <div class="space-y-4">
+            if let Some(ref err) = *error {
+                <p class="text-xs text-red-500">{ err }</p>
+            }
+
+            if let Some(ref list) = *codes {
+                <div class="rounded border border-amber-300 dark:border-amber-700 bg-amber-50 dark:bg-amber-900/20 p-3 space-y-2">
+                    <p class="text-xs font-medium text-amber-800 dark:text-amber-300">
+                        {"Save these recovery codes somewhere safe. Each works once, in place of a code, \
+                          and they will not be shown again."}
+                    </p>
+                    <ul class="grid grid-cols-2 gap-1 font-mono text-xs text-stone-800 dark:text-stone-100 select-all">
+                        { for list.iter().map(|c| html! { <li>{ c }</li> }) }
+                    </ul>
+                </div>
+            }
+
+            { match ((*status).clone(), (*setup).clone()) {
+                (None, _) => html! {},
+
+                (Some(TotpStatus { enabled: true, recovery_codes_left }), _) => html! {
+                    <div class="space-y-3">
+                        <p class="text-sm text-stone-700 dark:text-stone-200">
+                            { format!("On · {recovery_codes_left} recovery codes left") }
+                        </p>
+                        <div class="flex items-center gap-2">
+                            { code_input.clone() }
+                            <button onclick={run("recovery")} disabled={*busy || code.is_empty()} class={button_class}>
+                                {"New recovery codes"}
+                            </button>
+                            <button onclick={run("disable")} disabled={*busy || code.is_empty()}
+                                    class="px-3 py-2 text-sm font-medium rounded text-red-600 hover:bg-red-50
+                                           dark:hover:bg-red-900/20 disabled:opacity-40 transition-colors">
+                                {"Turn off"}
+                            </button>
+                        </div>
+                    </div>
+                },
+
+                (Some(_), Some(pending)) => html! {
+                    <div class="space-y-3">
+                        <p class="text-xs text-stone-500 dark:text-stone-400">
+                            {"Scan this with an authenticator app, or enter the key by hand, then type the code it shows."}
+                        </p>
+                        <div class="w-48 h-48 bg-white p-2 rounded border border-stone-200">
+                            { Html::from_html_unchecked(AttrValue::from(pending.qr_svg.clone())) }
+                        </div>
+                        <code class="block break-all text-xs text-stone-700 dark:text-stone-200 select-all">{ &pending.secret }</code>
+                        <div class="flex items-center gap-2">
+                            { code_input.clone() }
+                            <button onclick={run("enable")} disabled={*busy || code.is_empty()}
+                                    class="px-4 py-2 text-sm font-medium rounded bg-amber-500 text-stone-900 \
+                                           hover:bg-amber-400 disabled:opacity-50 transition-colors">
+                                {"Turn on"}
+                            </button>
+                        </div>
+                    </div>
+                },
+
+                (Some(_), None) => html! {
+                    <div class="flex items-center justify-between">
+                        <p class="text-sm text-stone-500 dark:text-stone-400">
+                            {"Off. Ask for a code from an authenticator app at every sign-in."}
+                        </p>
+                        <button onclick={on_setup} class={button_class}>{"Set up"}</button>
+                    </div>
+                },
+            }}
+        </div>
+    }
+}
@@ -0,0 +1,2 @@
+pub mod public_links;
+pub mod share_dialog;
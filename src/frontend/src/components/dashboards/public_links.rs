@@ -0,0 +1,193 @@
+use yew::prelude::*;
+
+use crate::api::dashboards;
+use crate::models::dashboard::{CreatePublicLink, PublicLink};
+
+#[derive(Properties, PartialEq)]
+pub struct PublicLinksProps {
+    pub dashboard_id: String,
+}
+
+/// Expiry choices offered when creating a link, in hours.
+const EXPIRIES: &[(i64, &str)] = &[
+    (24,       "1 day"),
+    (7 * 24,   "1 week"),
+    (30 * 24,  "30 days"),
+    (365 * 24, "1 year"),
+];
+
+/* ============================================================================================== */
+/*                                       Public links section                                     */
+/* ============================================================================================== */
+
+/// Read-only links for people without an account. Unlike the shares above it, creating and
+/// revoking take effect at once.
+#[function_component(PublicLinks)]
+pub fn public_links(props: &PublicLinksProps) -> Html {
+    let links   = use_state(|| Option::<Vec<PublicLink>>::None);
+    let label   = use_state(String::new);
+    let expiry  = use_state(|| 7 * 24_i64);
+    let busy    = use_state(|| false);
+    let error   = use_state(|| Option::<String>::None);
+
+    let reload = {
+        let id    = props.dashboard_id.clone();
+        let links = links.clone();
+        let error = error.clone();
+        Callback::from(move |_: ()| {
+            let id    = id.clone();
+            let links = links.clone();
+            let error = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match dashboards::list_public_links(&id).await {
+                    Ok(list) => links.set(Some(list)),
+                    Err(e)   => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| {
+            reload.emit(());
+            || ()
+        });
+    }
+
+    let on_create = {
+        let id     = props.dashboard_id.clone();
+        let label  = label.clone();
+        let expiry = expiry.clone();
+        let busy   = busy.clone();
+        let error  = error.clone();
+        let reload = reload.clone();
+        Callback::from(move |_: MouseEvent| {
+            let id     = id.clone();
+            let input  = CreatePublicLink { label: (*label).clone(), expires_in_hours: *expiry };
+            let label  = label.clone();
+            let busy   = busy.clone();
+            let error  = error.clone();
+            let reload = reload.clone();
+            busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match dashboards::create_public_link(&id, &input).await {
+                    Ok(_)  => {
+                        label.set(String::new());
+                        reload.emit(());
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let active: Vec<PublicLink> = (*links).clone().unwrap_or_default()
+        .into_iter()
+        .filter(|l| l.active)
+        .collect();
+
+    html! {
+        <div class="space-y-2 pt-2 border-t border-stone-200 dark:border-stone-700">
+            <h3 class="text-sm font-semibold text-stone-800 dark:text-stone-200">{"Public links"}</h3>
+            <p class="text-xs text-stone-400 dark:text-stone-500">
+                {"Anyone with a link can view this dashboard's charts and notes, without an account."}
+            </p>
+
+            if let Some(ref err) = *error {
+                <p class="text-xs text-red-600 dark:text-red-400">{ err }</p>
+            }
+
+            if !active.is_empty() {
+                <div class="rounded-lg border border-stone-200 dark:border-stone-700 divide-y divide-stone-100 dark:divide-stone-700">
+                    { for active.iter().map(|link| render_link(&props.dashboard_id, link, &reload, &error)) }
+                </div>
+            }
+
+            <div class="flex items-center gap-2">
+                <input type="text" placeholder="Label (optional)" value={(*label).clone()}
+                       oninput={Callback::from({
+                           let label = label.clone();
+                           move |e: InputEvent| {
+                               let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+                               label.set(el.value());
+                           }
+                       })}
+                       class="flex-1 rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                              text-stone-900 dark:text-stone-100 text-sm px-2 py-1.5" />
+                <select onchange={Callback::from({
+                            let expiry = expiry.clone();
+                            move |e: Event| {
+                                let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                                if let Ok(hours) = el.value().parse() {
+                                    expiry.set(hours);
+                                }
+                            }
+                        })}
+                        class="rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                               text-stone-900 dark:text-stone-100 text-sm px-2 py-1.5">
+                    { for EXPIRIES.iter().map(|(hours, name)| html! {
+                        <option value={hours.to_string()} selected={*expiry == *hours}>{ *name }</option>
+                    })}
+                </select>
+                <button onclick={on_create} disabled={*busy}
+                        class="px-3 py-1.5 text-sm font-medium rounded-md text-stone-700 dark:text-stone-200
+                               bg-stone-100 dark:bg-stone-700 hover:bg-stone-200 dark:hover:bg-stone-600
+                               disabled:opacity-40 transition-colors">
+                    {"Create link"}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// One active link: its label, expiry, the link itself and a revoke button.
+fn render_link(
+    dashboard_id: &str,
+    link:         &PublicLink,
+    reload:       &Callback<()>,
+    error:        &UseStateHandle<Option<String>>,
+) -> Html {
+    let on_revoke = {
+        let dashboard_id = dashboard_id.to_string();
+        let link_id      = link.id.clone();
+        let reload       = reload.clone();
+        let error        = error.clone();
+        Callback::from(move |_: MouseEvent| {
+            let dashboard_id = dashboard_id.clone();
+            let link_id      = link_id.clone();
+            let reload       = reload.clone();
+            let error        = error.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match dashboards::revoke_public_link(&dashboard_id, &link_id).await {
+                    Ok(())  => reload.emit(()),
+                    Err(e)  => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+    let name = if link.label.is_empty() { "Untitled link" } else { link.label.as_str() };
+
+    html! {
+        <div class="flex items-center justify-between gap-3 px-3 py-2 text-sm">
+            <div class="flex-1 min-w-0">
+                <a href={link.url.clone()} target="_blank" rel="noopener"
+                   class="block truncate text-amber-600 hover:text-amber-700 font-medium">
+                    { name }
+                </a>
+                <span class="text-xs text-stone-400 dark:text-stone-500">
+                    { format!("expires {}", link.expires_at.get(..10).unwrap_or(&link.expires_at)) }
+                </span>
+            </div>
+            <button onclick={on_revoke} title="Revoke"
+                    class="text-xs text-stone-400 hover:text-red-500 dark:hover:text-red-400">
+                {"Revoke"}
+            </button>
+        </div>
+    }
+}
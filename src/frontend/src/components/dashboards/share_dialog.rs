@@ -0,0 +1,292 @@
+use yew::prelude::*;
+
+use crate::api::{dashboards, users};
+use crate::models::dashboard::{Dashboard, DashboardShare, ShareGrant, UpdateDashboard};
+use crate::models::user::{Group, UserSummary};
+use super::public_links::PublicLinks;
+
+#[derive(Properties, PartialEq)]
+pub struct ShareDialogProps {
+    pub dashboard: Dashboard,
+    pub on_close:  Callback<()>,
+    /// Fired after a successful save, so the list can pick up the new `is_shared`.
+    pub on_saved:  Callback<()>,
+}
+
+/* ============================================================================================== */
+/*                                          Share dialog                                          */
+/* ============================================================================================== */
+
+/// Edits who may see a dashboard: everyone (`is_shared`) and/or specific users and groups with
+/// `view` or `edit` (panels and layout). Nothing is sent until Save, which replaces every share at once.
+#[function_component(ShareDialog)]
+pub fn share_dialog(props: &ShareDialogProps) -> Html {
+    let shares    = use_state(|| Option::<Vec<DashboardShare>>::None);
+    let directory = use_state(Vec::<UserSummary>::new);
+    let groups    = use_state(Vec::<Group>::new);
+    let is_shared = use_state(|| props.dashboard.is_shared);
+    let pick      = use_state(String::new);
+    let busy      = use_state(|| false);
+    let error     = use_state(|| Option::<String>::None);
+
+    {
+        let id        = props.dashboard.id.clone();
+        let shares    = shares.clone();
+        let directory = directory.clone();
+        let groups    = groups.clone();
+        let error     = error.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match dashboards::list_shares(&id).await {
+                    Ok(list) => shares.set(Some(list)),
+                    Err(e)   => error.set(Some(e.to_string())),
+                }
+                if let Ok(list) = users::list_directory().await {
+                    directory.set(list);
+                }
+                if let Ok(list) = users::list_groups().await {
+                    groups.set(list);
+                }
+            });
+            || ()
+        });
+    }
+
+    // Users and groups that can still be added: not the owner and not already on the list.
+    let current = (*shares).clone().unwrap_or_default();
+    let listed = |id: &str| current.iter().any(|s| s.grantee_id() == id);
+    let user_candidates: Vec<UserSummary> = directory
+        .iter()
+        .filter(|u| props.dashboard.owner_id.as_deref() != Some(u.id.as_str()))
+        .filter(|u| !listed(&u.id))
+        .cloned()
+        .collect();
+    let group_candidates: Vec<Group> = groups.iter().filter(|g| !listed(&g.id)).cloned().collect();
+
+    let on_pick = {
+        let pick = pick.clone();
+        Callback::from(move |e: Event| {
+            let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            pick.set(el.value());
+        })
+    };
+
+    // Picker values are `user:<id>` or `group:<id>`.
+    let on_add = {
+        let shares    = shares.clone();
+        let directory = directory.clone();
+        let groups    = groups.clone();
+        let pick      = pick.clone();
+        Callback::from(move |_: MouseEvent| {
+            let share = if let Some(id) = pick.strip_prefix("user:") {
+                directory.iter().find(|u| u.id == id).map(|u| DashboardShare {
+                    user_id:    Some(u.id.clone()),
+                    username:   Some(u.username.clone()),
+                    group_id:   None,
+                    group_name: None,
+                    permission: "view".into(),
+                })
+            } else if let Some(id) = pick.strip_prefix("group:") {
+                groups.iter().find(|g| g.id == id).map(|g| DashboardShare {
+                    user_id:    None,
+                    username:   None,
+                    group_id:   Some(g.id.clone()),
+                    group_name: Some(g.name.clone()),
+                    permission: "view".into(),
+                })
+            } else {
+                None
+            };
+            let Some(share) = share else { return };
+            let mut next = (*shares).clone().unwrap_or_default();
+            next.push(share);
+            shares.set(Some(next));
+            pick.set(String::new());
+        })
+    };
+
+    let on_save = {
+        let dashboard = props.dashboard.clone();
+        let shares    = shares.clone();
+        let is_shared = is_shared.clone();
+        let busy      = busy.clone();
+        let error     = error.clone();
+        let on_saved  = props.on_saved.clone();
+        let on_close  = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| {
+            let grants: Vec<ShareGrant> = (*shares).clone().unwrap_or_default()
+                .into_iter()
+                .map(|s| ShareGrant { user_id: s.user_id, group_id: s.group_id, permission: s.permission })
+                .collect();
+            let dashboard = dashboard.clone();
+            let is_shared = *is_shared;
+            let busy      = busy.clone();
+            let error     = error.clone();
+            let on_saved  = on_saved.clone();
+            let on_close  = on_close.clone();
+            busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                let mut result = dashboards::replace_shares(&dashboard.id, &grants).await.map(|_| ());
+                if result.is_ok() && is_shared != dashboard.is_shared {
+                    result = dashboards::update_dashboard(&dashboard.id, &UpdateDashboard {
+                        title: None, icon: None, is_shared: Some(is_shared),
+                    }).await.map(|_| ());
+                }
+                busy.set(false);
+                match result {
+                    Ok(()) => {
+                        on_saved.emit(());
+                        on_close.emit(());
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            });
+        })
+    };
+
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    html! {
+        <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/40">
+            <div class="bg-white dark:bg-stone-800 rounded-xl shadow-2xl w-full max-w-lg mx-4
+                        max-h-[85vh] overflow-y-auto p-6 space-y-4">
+
+                /* ================================== Header ================================== */
+                <div class="flex items-center justify-between">
+                    <h2 class="text-base font-semibold text-stone-900 dark:text-stone-100">
+                        { format!("Share \"{}\"", props.dashboard.title) }
+                    </h2>
+                    <button onclick={close.clone()}
+                            class="text-stone-400 hover:text-stone-600 dark:hover:text-stone-200 text-sm">
+                        {"✕"}
+                    </button>
+                </div>
+
+                <label class="flex items-center gap-2 text-sm text-stone-700 dark:text-stone-200">
+                    <input type="checkbox" checked={*is_shared}
+                           onchange={Callback::from({
+                               let is_shared = is_shared.clone();
+                               move |_: Event| is_shared.set(!*is_shared)
+                           })} />
+                    {"Everyone can view"}
+                </label>
+
+                if let Some(ref err) = *error {
+                    <div class="rounded-lg bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-700
+                                px-4 py-3 text-sm text-red-700 dark:text-red-200">
+                        { err }
+                    </div>
+                }
+
+                /* ================================== Shares ================================== */
+                if shares.is_none() {
+                    <p class="text-xs text-stone-400 dark:text-stone-500">{"Loading…"}</p>
+                } else if current.is_empty() {
+                    <p class="text-xs text-stone-400 dark:text-stone-500">{"Not shared with anyone yet."}</p>
+                } else {
+                    <div class="rounded-lg border border-stone-200 dark:border-stone-700 divide-y divide-stone-100 dark:divide-stone-700">
+                        { for current.iter().enumerate().map(|(i, share)| render_share(i, share, &shares)) }
+                    </div>
+                }
+
+                <div class="flex items-center gap-2">
+                    <select onchange={on_pick}
+                            class="flex-1 rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                                   text-stone-900 dark:text-stone-100 text-sm px-2 py-1.5">
+                        <option value="" selected={pick.is_empty()}>{"Add a user or group…"}</option>
+                        if !group_candidates.is_empty() {
+                            <optgroup label="Groups">
+                                { for group_candidates.iter().map(|g| {
+                                    let value = format!("group:{}", g.id);
+                                    let selected = *pick == value;
+                                    html! { <option {selected} {value}>{ &g.name }</option> }
+                                })}
+                            </optgroup>
+                        }
+                        <optgroup label="Users">
+                            { for user_candidates.iter().map(|u| {
+                                let value = format!("user:{}", u.id);
+                                let selected = *pick == value;
+                                html! { <option {selected} {value}>{ &u.username }</option> }
+                            })}
+                        </optgroup>
+                    </select>
+                    <button onclick={on_add} disabled={pick.is_empty()}
+                            class="px-3 py-1.5 text-sm font-medium rounded-md text-stone-700 dark:text-stone-200
+                                   bg-stone-100 dark:bg-stone-700 hover:bg-stone-200 dark:hover:bg-stone-600
+                                   disabled:opacity-40 transition-colors">
+                        {"Add"}
+                    </button>
+                </div>
+
+                <PublicLinks dashboard_id={props.dashboard.id.clone()} />
+
+                /* ================================== Actions ================================= */
+                <div class="flex justify-end gap-3 pt-1">
+                    <button onclick={close}
+                            class="px-4 py-2 text-sm text-stone-500 dark:text-stone-400 hover:text-stone-700 dark:hover:text-stone-200">
+                        {"Cancel"}
+                    </button>
+                    <button onclick={on_save} disabled={*busy || shares.is_none()}
+                            class="px-5 py-2 text-sm font-semibold text-slate-900 bg-amber-500 dark:bg-amber-400 rounded-lg
+                                   hover:bg-amber-400 dark:hover:bg-amber-500 disabled:opacity-40 disabled:cursor-not-allowed
+                                   transition-colors">
+                        { if *busy { "Saving…" } else { "Save" } }
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// One share with its permission picker and a remove button; both only change the draft.
+fn render_share(
+    index:  usize,
+    share:  &DashboardShare,
+    shares: &UseStateHandle<Option<Vec<DashboardShare>>>,
+) -> Html {
+    let on_permission = {
+        let shares = shares.clone();
+        Callback::from(move |e: Event| {
+            let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let mut next = (*shares).clone().unwrap_or_default();
+            if let Some(s) = next.get_mut(index) {
+                s.permission = el.value();
+            }
+            shares.set(Some(next));
+        })
+    };
+    let on_remove = {
+        let shares = shares.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut next = (*shares).clone().unwrap_or_default();
+            if index < next.len() {
+                next.remove(index);
+            }
+            shares.set(Some(next));
+        })
+    };
+
+    html! {
+        <div class="flex items-center justify-between gap-3 px-3 py-2 text-sm">
+            <span class="flex-1 text-stone-700 dark:text-stone-200 truncate">{ share.label() }</span>
+            <select onchange={on_permission}
+                    class="rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                           text-stone-900 dark:text-stone-100 text-xs px-2 py-1">
+                <option value="view" selected={share.permission == "view"}>{"Can view"}</option>
+                <option value="edit" selected={share.permission == "edit"}>{"Can edit panels"}</option>
+            </select>
+            <button onclick={on_remove} title="Remove"
+                    class="text-stone-400 hover:text-red-500 dark:hover:text-red-400 text-xs">
+                {"✕"}
+            </button>
+        </div>
+    }
+}
@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod dashboards;
 pub mod layout;
 pub mod grid;
 pub mod panels;
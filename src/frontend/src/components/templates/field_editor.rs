@@ -1,5 +1,8 @@
+use serde_json::Value;
 use yew::prelude::*;
-use crate::models::template::FieldDefinition;
+use crate::models::template::{
+    has_bounds, has_options, is_charted, is_computed, is_temporal, ChartSettings, FieldDefinition, AGGREGATIONS, CHART_TYPES, FIELD_TYPES,
+};
 
 #[derive(Properties, PartialEq)]
 pub struct FieldEditorProps {
@@ -12,6 +15,17 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
     let new_name      = use_state(String::new);
     let new_type      = use_state(|| "number".to_string());
     let new_unit      = use_state(String::new);
+    let new_options   = use_state(String::new);
+    let new_required  = use_state(|| false);
+    let new_min       = use_state(String::new);
+    let new_max       = use_state(String::new);
+    let new_default   = use_state(String::new);
+    let new_desc      = use_state(String::new);
+    let new_chart     = use_state(String::new);     // "" = default for the type
+    let new_agg       = use_state(|| "raw".to_string());
+    let new_axis      = use_state(|| false);
+    let new_tz        = use_state(String::new);
+    let new_expr      = use_state(String::new);
     let name_error    = use_state(|| Option::<String>::None);
 
     let on_add = {
@@ -20,6 +34,17 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
         let new_name   = new_name.clone();
         let new_type   = new_type.clone();
         let new_unit   = new_unit.clone();
+        let new_options  = new_options.clone();
+        let new_required = new_required.clone();
+        let new_min      = new_min.clone();
+        let new_max      = new_max.clone();
+        let new_default  = new_default.clone();
+        let new_desc     = new_desc.clone();
+        let new_chart    = new_chart.clone();
+        let new_agg      = new_agg.clone();
+        let new_axis     = new_axis.clone();
+        let new_tz       = new_tz.clone();
+        let new_expr     = new_expr.clone();
         let name_error = name_error.clone();
 
         Callback::from(move |e: SubmitEvent| {
@@ -39,18 +64,78 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
                 name_error.set(Some("A field with this name already exists".into()));
                 return;
             }
+
+            let field_type = (*new_type).clone();
+            let expression = (*new_expr).trim().to_string();
+            if is_computed(&field_type) && expression.is_empty() {
+                name_error.set(Some("Computed fields need an expression".into()));
+                return;
+            }
+            let (min, max) = match (parse_bound(&new_min), parse_bound(&new_max)) {
+                (Ok(min), Ok(max)) => (min, max),
+                _ => {
+                    name_error.set(Some("Min and max must be numbers".into()));
+                    return;
+                }
+            };
+            let default_text = if is_computed(&field_type) { "" } else { new_default.trim() };
+            let default = match parse_default(&field_type, default_text) {
+                Ok(default) => default,
+                Err(msg) => {
+                    name_error.set(Some(msg));
+                    return;
+                }
+            };
             name_error.set(None);
 
             let unit = (*new_unit).trim().to_string();
+            let description = (*new_desc).trim().to_string();
+            let chart = (is_charted(&field_type) && (!new_chart.is_empty() || *new_agg != "raw"))
+                .then(|| ChartSettings {
+                    chart_type:  (!new_chart.is_empty()).then(|| (*new_chart).clone()),
+                    aggregation: (*new_agg).clone(),
+                    ..Default::default()
+                });
+            let time_axis = is_temporal(&field_type) && *new_axis;
+            let timezone = (*new_tz).trim().to_string();
             let mut updated = fields.clone();
+            // Only one field can be the time axis; the newest choice wins.
+            if time_axis {
+                for f in updated.iter_mut() {
+                    f.time_axis = false;
+                    f.timezone = None;
+                }
+            }
+            let computed = is_computed(&field_type);
             updated.push(FieldDefinition {
                 name,
-                field_type: (*new_type).clone(),
+                options: if has_options(&field_type) { split_options(&new_options) } else { Vec::new() },
+                min: if has_bounds(&field_type) { min } else { None },
+                max: if has_bounds(&field_type) { max } else { None },
+                field_type,
                 unit: if unit.is_empty() { None } else { Some(unit) },
+                required: *new_required && !computed,
+                default,
+                description: if description.is_empty() { None } else { Some(description) },
+                time_axis,
+                timezone: if time_axis && !timezone.is_empty() { Some(timezone) } else { None },
+                chart,
+                expression: computed.then_some(expression),
             });
             on_change.emit(updated);
             new_name.set(String::new());
             new_unit.set(String::new());
+            new_options.set(String::new());
+            new_required.set(false);
+            new_min.set(String::new());
+            new_max.set(String::new());
+            new_default.set(String::new());
+            new_desc.set(String::new());
+            new_chart.set(String::new());
+            new_agg.set("raw".to_string());
+            new_axis.set(false);
+            new_tz.set(String::new());
+            new_expr.set(String::new());
         })
     };
 
@@ -86,6 +171,17 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
                                     if let Some(ref unit) = field.unit {
                                         <span class="ml-1 text-xs text-stone-400 dark:text-stone-500">{ format!("({unit})") }</span>
                                     }
+                                    if field.time_axis {
+                                        <span class="ml-1 text-xs text-amber-600 dark:text-amber-400" title="Time axis">{"⏱"}</span>
+                                    }
+                                    if field.required {
+                                        <span class="ml-1 text-xs text-red-500" title="Required">{"*"}</span>
+                                    }
+                                    if !constraint_summary(field).is_empty() {
+                                        <span class="block font-sans text-xs font-normal text-stone-400 dark:text-stone-500">
+                                            { constraint_summary(field) }
+                                        </span>
+                                    }
                                 </span>
                                 <span class={classes!(
                                     "text-xs", "font-semibold", "px-2", "py-0.5", "rounded-full",
@@ -150,10 +246,9 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
                            bg-white dark:bg-stone-700
                            focus:outline-none focus:ring-2 focus:ring-amber-500 darl:focus:ring-amber-400"
                 >
-                    <option value="number">{"number"}</option>
-                    <option value="text">{"text"}</option>
-                    <option value="date">{"date"}</option>
-                    <option value="select">{"select"}</option>
+                    { for FIELD_TYPES.iter().map(|t| html! {
+                        <option value={*t} selected={*new_type == *t}>{ *t }</option>
+                    })}
                 </select>
 
                 <input
@@ -173,6 +268,110 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
                            focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent"
                 />
 
+                if has_options(&new_type) {
+                    <input
+                        type="text" placeholder="options, comma-separated"
+                        value={(*new_options).clone()}
+                        oninput={text_setter(&new_options)}
+                        class={INPUT_CLASS}
+                    />
+                }
+
+                if has_bounds(&new_type) {
+                    <input
+                        type="number" step="any" placeholder="min"
+                        value={(*new_min).clone()}
+                        oninput={text_setter(&new_min)}
+                        class={classes!(INPUT_CLASS, "w-20")}
+                    />
+                    <input
+                        type="number" step="any" placeholder="max"
+                        value={(*new_max).clone()}
+                        oninput={text_setter(&new_max)}
+                        class={classes!(INPUT_CLASS, "w-20")}
+                    />
+                }
+
+                if is_computed(&new_type) {
+                    <input
+                        type="text" placeholder="e.g. weight / (height / 100) ^ 2"
+                        title="Numeric fields, + - * / ^, abs, round, sqrt, rolling_avg(field, days), rolling_sum(field, days), cumsum(field)"
+                        value={(*new_expr).clone()}
+                        oninput={text_setter(&new_expr)}
+                        class={classes!(INPUT_CLASS, "flex-1", "min-w-60", "font-mono")}
+                    />
+                } else {
+                    <input
+                        type="text" placeholder="default (optional)"
+                        value={(*new_default).clone()}
+                        oninput={text_setter(&new_default)}
+                        class={classes!(INPUT_CLASS, "w-36")}
+                    />
+                }
+
+                <input
+                    type="text" placeholder="description (optional)"
+                    value={(*new_desc).clone()}
+                    oninput={text_setter(&new_desc)}
+                    class={classes!(INPUT_CLASS, "flex-1", "min-w-40")}
+                />
+
+                if is_temporal(&new_type) {
+                    <label class="flex items-center gap-1.5 text-sm text-stone-700 dark:text-stone-300">
+                        <input
+                            type="checkbox"
+                            checked={*new_axis}
+                            onchange={Callback::from({
+                                let new_axis = new_axis.clone();
+                                move |e: Event| {
+                                    let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                    new_axis.set(el.checked());
+                                }
+                            })}
+                        />
+                        {"time axis"}
+                    </label>
+                    if *new_axis && *new_type == "date" {
+                        <input
+                            type="text" placeholder="timezone, e.g. Europe/Amsterdam"
+                            value={(*new_tz).clone()}
+                            oninput={text_setter(&new_tz)}
+                            class={classes!(INPUT_CLASS, "w-52")}
+                        />
+                    }
+                }
+
+                if is_charted(&new_type) {
+                    <select onchange={select_setter(&new_chart)} class={INPUT_CLASS} title="Chart type">
+                        <option value="" selected={new_chart.is_empty()}>{"default chart"}</option>
+                        { for CHART_TYPES.iter().map(|t| html! {
+                            <option value={*t} selected={*new_chart == *t}>{ *t }</option>
+                        })}
+                    </select>
+                    <select onchange={select_setter(&new_agg)} class={INPUT_CLASS} title="Aggregation">
+                        { for AGGREGATIONS.iter().map(|a| html! {
+                            <option value={*a} selected={*new_agg == *a}>{ a.replace('_', " ") }</option>
+                        })}
+                    </select>
+                }
+
+                if !is_computed(&new_type) {
+                    <label class="flex items-center gap-1.5 text-sm text-stone-700 dark:text-stone-300">
+                        <input
+                            type="checkbox"
+                            checked={*new_required}
+                            onchange={Callback::from({
+                                let new_required = new_required.clone();
+                                move |e: Event| {
+                                    let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+                                    new_required.set(el.checked());
+                                }
+                            })}
+                        />
+                        {"required"}
+                    </label>
+                }
+
                 <button
                     type="submit"
                     class="px-3 py-1.5 text-sm font-semibold 
@@ -186,3 +385,90 @@ pub fn field_editor(props: &FieldEditorProps) -> Html {
         </div>
     }
 }
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+const INPUT_CLASS: &str = "rounded border border-stone-300 dark:border-stone-600 \
+                           text-stone-900 dark:text-stone-100 px-2 py-1.5 text-sm \
+                           bg-white dark:bg-stone-700 \
+                           focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent";
+
+fn text_setter(state: &UseStateHandle<String>) -> Callback<InputEvent> {
+    let state = state.clone();
+    Callback::from(move |e: InputEvent| {
+        let el: web_sys::HtmlInputElement = e.target_unchecked_into();
+        state.set(el.value());
+    })
+}
+
+fn select_setter(state: &UseStateHandle<String>) -> Callback<Event> {
+    let state = state.clone();
+    Callback::from(move |e: Event| {
+        let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        state.set(el.value());
+    })
+}
+
+fn split_options(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|o| !o.is_empty()).map(str::to_string).collect()
+}
+
+fn parse_bound(text: &str) -> Result<Option<f64>, ()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse().map(Some).map_err(|_| ())
+}
+
+/// Turn the default typed into the editor into the JSON value the backend expects.
+fn parse_default(field_type: &str, text: &str) -> Result<Option<Value>, String> {
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let value = match field_type {
+        "number" | "integer" | "currency" | "duration" | "rating" => {
+            let n: f64 = text.parse().map_err(|_| format!("Default '{text}' is not a number"))?;
+            serde_json::Number::from_f64(n).map(Value::Number)
+                .ok_or_else(|| format!("Default '{text}' is not a number"))?
+        }
+        "boolean" => match text {
+            "true" | "yes"  => Value::Bool(true),
+            "false" | "no"  => Value::Bool(false),
+            _ => return Err("Default of a boolean field must be true or false".into()),
+        },
+        "multi_select" => Value::Array(split_options(text).into_iter().map(Value::String).collect()),
+        _ => Value::String(text.to_string()),
+    };
+    Ok(Some(value))
+}
+
+/// One-line description of a field's expression, options, bounds and default for the field list.
+fn constraint_summary(field: &FieldDefinition) -> String {
+    let mut parts = Vec::new();
+    if let Some(ref expression) = field.expression {
+        parts.push(format!("= {expression}"));
+    }
+    if !field.options.is_empty() {
+        parts.push(field.options.join(" / "));
+    }
+    match (field.min, field.max) {
+        (Some(min), Some(max)) => parts.push(format!("{min}–{max}")),
+        (Some(min), None)      => parts.push(format!("≥ {min}")),
+        (None, Some(max))      => parts.push(format!("≤ {max}")),
+        (None, None)           => {}
+    }
+    if let Some(ref default) = field.default {
+        parts.push(format!("default {default}"));
+    }
+    if let Some(ref chart) = field.chart {
+        let chart_type = chart.chart_type.as_deref().unwrap_or("chart");
+        parts.push(format!("{chart_type}, {}", chart.aggregation.replace('_', " ")));
+    }
+    if let Some(ref description) = field.description {
+        parts.push(description.clone());
+    }
+    parts.join(" · ")
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen_futures::JsFuture;
+use yew::prelude::*;
+
+use crate::api::templates;
+use crate::models::template::{DatasetTemplate, FieldDefinition, ImportReport};
+
+/// `(csv, mapping, dry_run)` — `None` mapping lets the server match headers to fields.
+type ImportRun = (String, Option<BTreeMap<String, String>>, bool);
+
+#[derive(Properties, PartialEq)]
+pub struct ImportDialogProps {
+    pub template: DatasetTemplate,
+    pub on_close: Callback<()>,
+}
+
+/* ============================================================================================== */
+/*                                          Import dialog                                         */
+/* ============================================================================================== */
+
+/// CSV import: pick a file → dry run with automatic column mapping → adjust the mapping
+/// (each change re-runs the dry run) → import once every row validates.
+#[function_component(ImportDialog)]
+pub fn import_dialog(props: &ImportDialogProps) -> Html {
+    let fields: Vec<FieldDefinition> =
+        serde_json::from_value(props.template.fields.clone()).unwrap_or_default();
+
+    let csv     = use_state(|| Option::<String>::None);
+    let mapping = use_state(|| Option::<BTreeMap<String, String>>::None);
+    let report  = use_state(|| Option::<ImportReport>::None);
+    let busy    = use_state(|| false);
+    let error   = use_state(|| Option::<String>::None);
+
+    let run = {
+        let id      = props.template.id.clone();
+        let mapping = mapping.clone();
+        let report  = report.clone();
+        let busy    = busy.clone();
+        let error   = error.clone();
+        Callback::from(move |(text, map, dry_run): ImportRun| {
+            let id      = id.clone();
+            let mapping = mapping.clone();
+            let report  = report.clone();
+            let busy    = busy.clone();
+            let error   = error.clone();
+            busy.set(true);
+            wasm_bindgen_futures::spawn_local(async move {
+                match templates::import_csv(&id, text, map.as_ref(), dry_run).await {
+                    Ok(r) => {
+                        mapping.set(Some(r.mapping.clone()));
+                        report.set(Some(r));
+                        error.set(None);
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+                busy.set(false);
+            });
+        })
+    };
+
+    let on_file = {
+        let csv = csv.clone();
+        let run = run.clone();
+        Callback::from(move |e: Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else { return };
+            let csv = csv.clone();
+            let run = run.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(text) = JsFuture::from(file.text()).await {
+                    let text = text.as_string().unwrap_or_default();
+                    csv.set(Some(text.clone()));
+                    run.emit((text, None, true));
+                }
+            });
+        })
+    };
+
+    let close = {
+        let on_close = props.on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+
+    html! {
+        <div class="fixed inset-0 z-50 flex items-center justify-center bg-black/40">
+            <div class="bg-white dark:bg-stone-800 rounded-xl shadow-2xl w-full max-w-3xl mx-4
+                        max-h-[85vh] overflow-y-auto p-6 space-y-4">
+
+                /* ================================== Header ================================== */
+                <div class="flex items-center justify-between">
+                    <h2 class="text-base font-semibold text-stone-900 dark:text-stone-100">
+                        { format!("Import into \"{}\"", props.template.name) }
+                    </h2>
+                    <button onclick={close.clone()}
+                            class="text-stone-400 hover:text-stone-600 dark:hover:text-stone-200 text-sm">
+                        {"✕"}
+                    </button>
+                </div>
+
+                <input type="file" accept=".csv,text/csv" onchange={on_file}
+                       class="block text-sm text-stone-600 dark:text-stone-300" />
+
+                if *busy {
+                    <p class="text-xs text-stone-400 dark:text-stone-500">{"Checking…"}</p>
+                }
+
+                if let Some(ref err) = *error {
+                    <div class="rounded-lg bg-red-50 dark:bg-red-900 border border-red-200 dark:border-red-700
+                                px-4 py-3 text-sm text-red-700 dark:text-red-200">
+                        { err }
+                    </div>
+                }
+
+                if let Some(ref r) = *report {
+                    if !r.dry_run {
+                        <div class="rounded-lg bg-green-50 dark:bg-green-900 border border-green-200
+                                    px-4 py-3 text-sm text-green-700 dark:text-green-300">
+                            { format!("✓ Imported {} row{}", r.inserted, if r.inserted == 1 { "" } else { "s" }) }
+                        </div>
+                    } else {
+                        { render_mapping(r, &fields, (*mapping).clone().unwrap_or_default(), &mapping, &csv, &run) }
+
+                        <p class="text-xs text-stone-500 dark:text-stone-400">
+                            { format!("{} of {} rows valid", r.valid_rows, r.total_rows) }
+                            if r.error_count > 0 {
+                                <span class="text-red-600 dark:text-red-400">
+                                    { format!(" · {} error{}", r.error_count, if r.error_count == 1 { "" } else { "s" }) }
+                                </span>
+                            }
+                        </p>
+
+                        if !r.errors.is_empty() {
+                            <ul class="text-xs text-red-600 dark:text-red-400 space-y-0.5 max-h-32 overflow-y-auto">
+                                { for r.errors.iter().map(|e| html! {
+                                    <li>
+                                        { format!("Line {}{}: {}",
+                                            e.line,
+                                            e.column.as_ref().map(|c| format!(" ({c})")).unwrap_or_default(),
+                                            e.message) }
+                                    </li>
+                                })}
+                            </ul>
+                        }
+
+                        { render_preview(r, &fields) }
+                    }
+                }
+
+                /* ================================== Actions ================================= */
+                <div class="flex justify-end gap-3 pt-1">
+                    <button onclick={close}
+                            class="px-4 py-2 text-sm text-stone-500 dark:text-stone-400 hover:text-stone-700 dark:hover:text-stone-200">
+                        {"Close"}
+                    </button>
+                    <button
+                        disabled={!(*report).as_ref().is_some_and(|r| r.dry_run && r.error_count == 0 && r.valid_rows > 0) || *busy}
+                        onclick={Callback::from({
+                            let csv     = csv.clone();
+                            let mapping = mapping.clone();
+                            let run     = run.clone();
+                            move |_: MouseEvent| {
+                                if let Some(text) = (*csv).clone() {
+                                    run.emit((text, (*mapping).clone(), false));
+                                }
+                            }
+                        })}
+                        class="px-5 py-2 text-sm font-semibold text-slate-900 bg-amber-500 dark:bg-amber-400 rounded-lg
+                               hover:bg-amber-400 dark:hover:bg-amber-500 disabled:opacity-40 disabled:cursor-not-allowed
+                               transition-colors"
+                    >
+                        {"Import"}
+                    </button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// One row per CSV column with a field picker; changing a picker re-runs the dry run.
+fn render_mapping(
+    report:  &ImportReport,
+    fields:  &[FieldDefinition],
+    current: BTreeMap<String, String>,
+    mapping: &UseStateHandle<Option<BTreeMap<String, String>>>,
+    csv:     &UseStateHandle<Option<String>>,
+    run:     &Callback<ImportRun>,
+) -> Html {
+    html! {
+        <div class="rounded-lg border border-stone-200 dark:border-stone-700 divide-y divide-stone-100 dark:divide-stone-700">
+            { for report.columns.iter().map(|column| {
+                let selected = current.get(column).cloned().unwrap_or_default();
+                let onchange = {
+                    let column  = column.clone();
+                    let current = current.clone();
+                    let mapping = mapping.clone();
+                    let csv     = csv.clone();
+                    let run     = run.clone();
+                    Callback::from(move |e: Event| {
+                        let el: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                        let mut next = current.clone();
+                        if el.value().is_empty() {
+                            next.remove(&column);
+                        } else {
+                            next.insert(column.clone(), el.value());
+                        }
+                        mapping.set(Some(next.clone()));
+                        if let Some(text) = (*csv).clone() {
+                            run.emit((text, Some(next), true));
+                        }
+                    })
+                };
+                html! {
+                    <div class="flex items-center justify-between px-3 py-2 text-sm">
+                        <span class="font-mono text-xs text-stone-700 dark:text-stone-200">{ column }</span>
+                        <select {onchange}
+                                class="rounded border border-stone-300 dark:border-stone-600 bg-white dark:bg-stone-700
+                                       text-stone-900 dark:text-stone-100 text-xs px-2 py-1">
+                            <option value="" selected={selected.is_empty()}>{"— skip —"}</option>
+                            { for fields.iter().map(|f| html! {
+                                <option value={f.name.clone()} selected={selected == f.name}>{ &f.name }</option>
+                            })}
+                        </select>
+                    </div>
+                }
+            })}
+        </div>
+    }
+}
+
+/* ============================================================================================== */
+/// First parsed rows, in template field order, restricted to the mapped fields.
+fn render_preview(report: &ImportReport, fields: &[FieldDefinition]) -> Html {
+    if report.preview.is_empty() {
+        return html! {};
+    }
+    let columns: Vec<&FieldDefinition> = fields
+        .iter()
+        .filter(|f| report.mapping.values().any(|m| m == &f.name))
+        .collect();
+
+    html! {
+        <div class="overflow-x-auto">
+            <table class="min-w-full text-xs">
+                <thead>
+                    <tr class="text-left text-stone-400 dark:text-stone-500">
+                        { for columns.iter().map(|f| html! { <th class="px-2 py-1 font-semibold">{ &f.name }</th> }) }
+                    </tr>
+                </thead>
+                <tbody class="text-stone-700 dark:text-stone-200">
+                    { for report.preview.iter().map(|row| html! {
+                        <tr class="border-t border-stone-100 dark:border-stone-700">
+                            { for columns.iter().map(|f| {
+                                let cell = match row.get(&f.name) {
+                                    Some(serde_json::Value::String(s)) => s.clone(),
+                                    Some(serde_json::Value::Null) | None => String::new(),
+                                    Some(other) => other.to_string(),
+                                };
+                                html! { <td class="px-2 py-1">{ cell }</td> }
+                            })}
+                        </tr>
+                    })}
+                </tbody>
+            </table>
+        </div>
+    }
+}
\ No newline at end of file
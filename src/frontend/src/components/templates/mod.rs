@@ -1,3 +1,4 @@
 pub mod field_editor;
+pub mod import_dialog;
 pub mod template_wizard;
 pub mod template_list;
\ No newline at end of file
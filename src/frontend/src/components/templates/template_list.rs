@@ -7,6 +7,7 @@ use crate::hooks::use_api::use_api;
 use crate::models::template::DatasetTemplate;
 use crate::models::user::Role;
 use crate::router::Route;
+use super::import_dialog::ImportDialog;
 
 /* ============================================================================================== */
 /*                                            List page                                           */
@@ -16,9 +17,11 @@ use crate::router::Route;
 pub fn template_list() -> Html {
     let auth = use_context::<AuthContext>().expect("AuthContext missing");
     let is_admin = auth.user.as_ref().map(|u| u.role == Role::Admin).unwrap_or(false);
+    let can_write = auth.user.as_ref().map(|u| u.role != Role::Viewer).unwrap_or(false);
     let (state, reload) = use_api(|| templates::list_templates());
 
     let form_modal: UseStateHandle<Option<String>> = use_state(|| None);
+    let import_modal: UseStateHandle<Option<DatasetTemplate>> = use_state(|| None);
 
     html! {
         <div class="max-w-5xl mx-auto">
@@ -85,12 +88,19 @@ pub fn template_list() -> Html {
                                     form_modal.set(Some(uuid));
                                 })
                             };
+                            let on_import = {
+                                let import_modal = import_modal.clone();
+                                let template     = t.clone();
+                                Callback::from(move |()| import_modal.set(Some(template.clone())))
+                            };
                             html! {
                                 <TemplateCard
                                     template={t.clone()}
                                     is_admin={is_admin}
+                                    can_write={can_write}
                                     on_delete={on_delete}
                                     on_open_form={on_open_form}
+                                    on_import={on_import}
                                 />
                             }
                         })}
@@ -108,6 +118,17 @@ pub fn template_list() -> Html {
                     })}
                 />
             }
+
+            /* ================================= Import dialog ================================== */
+            if let Some(ref template) = *import_modal {
+                <ImportDialog
+                    template={template.clone()}
+                    on_close={Callback::from({
+                        let import_modal = import_modal.clone();
+                        move |_: ()| import_modal.set(None)
+                    })}
+                />
+            }
         </div>
     }
 }
@@ -120,8 +141,10 @@ pub fn template_list() -> Html {
 struct TemplateCardProps {
     template:       DatasetTemplate,
     is_admin:       bool,
+    can_write:      bool,
     on_delete:      Callback<()>,
     on_open_form:   Callback<String>,
+    on_import:      Callback<()>,
 }
 
 #[function_component(TemplateCard)]
@@ -198,6 +221,32 @@ fn template_card(props: &TemplateCardProps) -> Html {
                                 {"📊 Grafana Dashboard"}
                             </a>
                         }
+                        if props.can_write && t.nocodb_table_id.is_some() {
+                            <button
+                                onclick={Callback::from({
+                                    let on_import = props.on_import.clone();
+                                    move |_: MouseEvent| on_import.emit(())
+                                })}
+                                class="text-xs text-blue-500 dark:text-blue-400
+                                       hover:underline cursor-pointer"
+                            >
+                                {"⇪ Import CSV"}
+                            </button>
+                        }
+                        if t.nocodb_table_id.is_some() {
+                            <a  href={format!("/api/templates/{}/export?format=csv", t.id)}
+                                download=""
+                                class="text-xs text-blue-500 dark:text-blue-400 hover:underline"
+                            >
+                                {"⇩ Export CSV"}
+                            </a>
+                        }
+                        <a  href={format!("/api/templates/{}/blueprint?format=yaml", t.id)}
+                            download=""
+                            class="text-xs text-blue-500 dark:text-blue-400 hover:underline"
+                        >
+                            {"⇩ Blueprint"}
+                        </a>
                     </div>
                 </div>
 
@@ -2,7 +2,7 @@ use yew::prelude::*;
 use yew_router::prelude::*;
 
 use crate::api::templates;
-use crate::models::template::{CreateTemplateRequest, DatasetTemplate, FieldDefinition};
+use crate::models::template::{is_charted, CreateTemplateRequest, DatasetTemplate, FieldDefinition};
 use crate::router::Route;
 use super::field_editor::FieldEditor;
 
@@ -27,6 +27,8 @@ pub fn template_wizard() -> Html {
             name:           "measured_at".to_string(),
             field_type:     "date".to_string(),
             unit:           None,
+            time_axis:      true,
+            ..Default::default()
         },
     ]);
     let submitting  = use_state(|| false);
@@ -287,7 +289,7 @@ pub fn template_wizard() -> Html {
                         let fields_snap = (*fields).clone();
                         let name_snap   = (*name).clone();
                         let desc_snap   = (*description).clone();
-                        let num_panels  = fields_snap.iter().filter(|f| f.field_type == "number").count();
+                        let num_panels  = fields_snap.iter().filter(|f| is_charted(&f.field_type)).count();
                         let is_sub      = *submitting;
                         let err_msg     = (*error).clone();
 
@@ -404,9 +406,9 @@ pub fn template_wizard() -> Html {
                                                         description: if desc_v.is_empty() { None } else { Some(desc_v) },
                                                         fields:      fields_v,
                                                     };
-                                                    match templates::create_template(&req).await {
+                                                    match provision(&req).await {
                                                         Ok(t)  => created.set(Some(t)),
-                                                        Err(e) => error.set(Some(e.to_string())),
+                                                        Err(e) => error.set(Some(e)),
                                                     }
                                                     submitting.set(false);
                                                 });
@@ -426,4 +428,26 @@ pub fn template_wizard() -> Html {
             </div>
         </div>
     }
+}
+
+/* ============================================================================================== */
+/*                                             Helpers                                            */
+/* ============================================================================================== */
+
+/// Enqueue the provisioning job and poll it until the template exists or the job fails.
+async fn provision(req: &CreateTemplateRequest) -> Result<DatasetTemplate, String> {
+    let mut job = templates::create_template(req).await.map_err(|e| e.to_string())?;
+    loop {
+        match job.state.as_str() {
+            "completed" => {
+                let id = job.template_id.ok_or("job finished without a template")?;
+                return templates::get_template(&id).await.map_err(|e| e.to_string());
+            }
+            "failed" => return Err(job.error.unwrap_or_else(|| "unknown error".to_string())),
+            _ => {
+                gloo_timers::future::TimeoutFuture::new(1_000).await;
+                job = templates::get_job(&job.id).await.map_err(|e| e.to_string())?;
+            }
+        }
+    }
 }
\ No newline at end of file
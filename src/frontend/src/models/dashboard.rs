@@ -27,6 +27,31 @@ pub struct Panel {
     pub grid_h:       i32,
 }
 
+/// One user's or group's share of a dashboard, as returned by GET /api/dashboards/:id/shares
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DashboardShare {
+    pub user_id:    Option<String>,
+    pub username:   Option<String>,
+    pub group_id:   Option<String>,
+    pub group_name: Option<String>,
+    pub permission: String,    // "view" | "edit"
+}
+
+impl DashboardShare {
+    /// The user or group id — unique among a dashboard's shares.
+    pub fn grantee_id(&self) -> &str {
+        self.user_id.as_deref().or(self.group_id.as_deref()).unwrap_or_default()
+    }
+
+    pub fn label(&self) -> String {
+        match (&self.username, &self.group_name) {
+            (Some(username), _) => username.clone(),
+            (None, Some(group)) => format!("{group} (group)"),
+            (None, None)        => self.grantee_id().to_string(),
+        }
+    }
+}
+
 /// Returned by GET /api/dashboards/:slug
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct DashboardWithPanels {
@@ -34,6 +59,26 @@ pub struct DashboardWithPanels {
     pub panels:    Vec<Panel>,
 }
 
+/// A read-only link to a dashboard, as returned by GET /api/dashboards/:id/public-links
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PublicLink {
+    pub id:         String,
+    pub label:      String,
+    pub created_at: String,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub active:     bool,
+    pub url:        String,    // "/public/{token}"
+}
+
+/// Returned by GET /api/public/:token
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PublicDashboard {
+    pub dashboard:  Dashboard,
+    pub panels:     Vec<Panel>,
+    pub expires_at: String,
+}
+
 /* ============================================================================================== */
 /*                                          Request types                                         */
 /* ============================================================================================== */
@@ -57,6 +102,21 @@ pub struct UpdateDashboard {
     pub is_shared: Option<bool>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ShareGrant {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id:    Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id:   Option<String>,
+    pub permission: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatePublicLink {
+    pub label:            String,
+    pub expires_in_hours: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CreatePanel {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1,10 +1,81 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct FieldDefinition {
-    pub name:       String,
-    pub field_type: String,         // "number" | "text" | "date" | "select" 
-    pub unit:       Option<String>,
+    pub name:        String,
+    pub field_type:  String,         // one of FIELD_TYPES
+    pub unit:        Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options:     Vec<String>,    // select / multi_select choices
+    #[serde(default)]
+    pub required:    bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min:         Option<f64>,    // value bounds, or text length bounds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max:         Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default:     Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub time_axis:   bool,           // the date/datetime field that dates each row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone:    Option<String>, // IANA zone of the time axis, e.g. "Europe/Amsterdam"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart:       Option<ChartSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expression:  Option<String>, // formula of a computed field, e.g. "rolling_avg(weight, 7)"
+}
+
+/// Grafana panel settings of a charted field. Thresholds, colours and y-axis bounds are
+/// passed through untouched; the editor only sets the chart type and aggregation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChartSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chart_type:  Option<String>,    // one of CHART_TYPES; `None` = the type's default
+    #[serde(default = "raw")]
+    pub aggregation: String,            // one of AGGREGATIONS
+    #[serde(flatten)]
+    pub other:       serde_json::Map<String, serde_json::Value>,
+}
+
+fn raw() -> String {
+    "raw".into()
+}
+
+pub const CHART_TYPES: [&str; 5] = ["timeseries", "bar", "stat", "gauge", "histogram"];
+pub const AGGREGATIONS: [&str; 3] = ["raw", "daily_avg", "weekly_sum"];
+
+/// Field types the backend accepts, in the order the field editor offers them.
+pub const FIELD_TYPES: [&str; 15] = [
+    "number", "integer", "currency", "duration", "rating", "boolean", "text",
+    "long_text", "email", "url", "date", "datetime", "select", "multi_select", "computed",
+];
+
+pub fn is_temporal(field_type: &str) -> bool {
+    matches!(field_type, "date" | "datetime")
+}
+
+pub fn has_options(field_type: &str) -> bool {
+    matches!(field_type, "select" | "multi_select")
+}
+
+/// Field types that take `min`/`max`: numbers bound the value, text types its length.
+pub fn has_bounds(field_type: &str) -> bool {
+    matches!(field_type, "number" | "integer" | "currency" | "duration" | "rating"
+        | "text" | "long_text" | "email" | "url")
+}
+
+/// Whether the provisioned Grafana dashboard gets a panel for this field type.
+pub fn is_charted(field_type: &str) -> bool {
+    matches!(field_type, "number" | "integer" | "currency" | "duration" | "rating" | "boolean" | "computed")
+}
+
+/// Computed fields are evaluated from their expression and never entered.
+pub fn is_computed(field_type: &str) -> bool {
+    field_type == "computed"
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -24,4 +95,36 @@ pub struct CreateTemplateRequest {
     pub name:        String,
     pub description: Option<String>,
     pub fields:      Vec<FieldDefinition>,
+}
+
+/// Background provisioning job returned by `POST /api/templates`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ProvisioningJob {
+    pub id:          String,
+    pub name:        String,
+    pub state:       String,         // "unstarted" … "completed" | "failed"
+    pub template_id: Option<String>,
+    pub error:       Option<String>,
+}
+
+/// Returned by `POST /api/templates/{id}/import`, for dry runs and real imports alike.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportReport {
+    pub dry_run:          bool,
+    pub columns:          Vec<String>,
+    pub mapping:          BTreeMap<String, String>,   // CSV header → field name
+    pub unmapped_columns: Vec<String>,
+    pub total_rows:       usize,
+    pub valid_rows:       usize,
+    pub error_count:      usize,
+    pub errors:           Vec<ImportRowError>,
+    pub preview:          Vec<serde_json::Map<String, serde_json::Value>>,
+    pub inserted:         usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ImportRowError {
+    pub line:    usize,
+    pub column:  Option<String>,
+    pub message: String,
 }
\ No newline at end of file
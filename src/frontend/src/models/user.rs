@@ -34,4 +34,98 @@ pub struct User {
     pub username: String,
     pub email: Option<String>,
     pub role: Role,
-}
\ No newline at end of file
+    /// Only filled in by GET /api/users.
+    #[serde(default)]
+    pub totp_enabled: bool,
+    /// Only filled in by GET /api/users: set while failed sign-ins lock the user out.
+    #[serde(default)]
+    pub locked_until: Option<String>,
+}
+
+/// Returned by POST /api/auth/login: the user, or a challenge for the second step when
+/// two-factor authentication is on.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum LoginOutcome {
+    TwoFactorRequired { challenge: String },
+    SignedIn(User),
+}
+
+/// Returned by GET /api/users/directory
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct UserSummary {
+    pub id: String,
+    pub username: String,
+}
+
+/// Returned by GET /api/groups
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Group {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/* ============================================================================================== */
+/// Returned by GET /api/auth/tokens
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ApiToken {
+    pub id:           String,
+    pub name:         String,
+    pub scopes:       Vec<String>,   // "dashboards:read" | "records:write" | "admin"
+    pub expires_at:   String,
+    pub last_used_at: Option<String>,
+    pub created_at:   String,
+}
+
+/// Returned once by POST /api/auth/tokens; `secret` is never shown again.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CreatedApiToken {
+    #[serde(flatten)]
+    pub token:  ApiToken,
+    pub secret: String,
+}
+
+/// Request body for POST /api/auth/tokens
+#[derive(Debug, Serialize)]
+pub struct CreateApiToken {
+    pub name:            String,
+    pub scopes:          Vec<String>,
+    pub expires_in_days: i64,
+}
+
+/* ============================================================================================== */
+/// Returned by GET /api/auth/sessions
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SessionInfo {
+    pub id:           String,
+    pub user_agent:   Option<String>,
+    pub ip:           Option<String>,
+    pub created_at:   String,
+    pub last_seen_at: String,
+    pub expires_at:   String,
+    /// True for the session of this browser.
+    pub current:      bool,
+}
+
+/* ============================================================================================== */
+/// Returned by GET /api/auth/totp
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TotpStatus {
+    pub enabled:             bool,
+    pub recovery_codes_left: i64,
+}
+
+/// Returned by POST /api/auth/totp/setup
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TotpSetup {
+    pub secret: String,
+    pub uri:    String,
+    pub qr_svg: String,
+}
+
+/// Returned by POST /api/auth/totp/enable and /recovery-codes; shown once.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
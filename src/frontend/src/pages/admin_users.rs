@@ -82,6 +82,60 @@ pub fn admin_users_page() -> Html {
         })
     };
 
+    // ── Two-factor reset callback ───────────────────────────────────────────
+    let on_reset_totp = {
+        let user_list  = user_list.clone();
+        let show_toast = show_toast.clone();
+        Callback::from(move |id: String| {
+            let user_list  = user_list.clone();
+            let show_toast = show_toast.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match users::reset_user_totp(&id).await {
+                    Ok(()) => {
+                        user_list.set(
+                            (*user_list).iter().map(|u| {
+                                let mut u = u.clone();
+                                if u.id == id { u.totp_enabled = false; }
+                                u
+                            }).collect()
+                        );
+                        show_toast.emit(("Two-factor authentication turned off".to_string(), ToastKind::Success));
+                    }
+                    Err(e) => {
+                        show_toast.emit((format!("Failed: {e}"), ToastKind::Error));
+                    }
+                }
+            });
+        })
+    };
+
+    // ── Unlock callback ─────────────────────────────────────────────────────
+    let on_unlock = {
+        let user_list  = user_list.clone();
+        let show_toast = show_toast.clone();
+        Callback::from(move |id: String| {
+            let user_list  = user_list.clone();
+            let show_toast = show_toast.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match users::unlock_user(&id).await {
+                    Ok(()) => {
+                        user_list.set(
+                            (*user_list).iter().map(|u| {
+                                let mut u = u.clone();
+                                if u.id == id { u.locked_until = None; }
+                                u
+                            }).collect()
+                        );
+                        show_toast.emit(("User unlocked".to_string(), ToastKind::Success));
+                    }
+                    Err(e) => {
+                        show_toast.emit((format!("Failed: {e}"), ToastKind::Error));
+                    }
+                }
+            });
+        })
+    };
+
     // ── Create user callback ────────────────────────────────────────────────
     let on_create = {
         let new_username = new_username.clone();
@@ -159,6 +213,8 @@ pub fn admin_users_page() -> Html {
                                 <th class="px-6 py-3">{ "Username" }</th>
                                 <th class="px-6 py-3">{ "Email" }</th>
                                 <th class="px-6 py-3">{ "Role" }</th>
+                                <th class="px-6 py-3">{ "2FA" }</th>
+                                <th class="px-6 py-3">{ "Sign-in" }</th>
                             </tr>
                         </thead>
                         <tbody class="divide-y divide-stone-100 dark:divide-stone-700">
@@ -177,8 +233,23 @@ pub fn admin_users_page() -> Html {
                                         on_reset_password.emit(id.clone());
                                     })
                                 };
+                                let on_totp = {
+                                    let on_reset_totp = on_reset_totp.clone();
+                                    let id = u.id.clone();
+                                    Callback::from(move |_: ()| {
+                                        on_reset_totp.emit(id.clone());
+                                    })
+                                };
+                                let on_unlock = {
+                                    let on_unlock = on_unlock.clone();
+                                    let id = u.id.clone();
+                                    Callback::from(move |_: ()| {
+                                        on_unlock.emit(id.clone());
+                                    })
+                                };
                                 html! {
-                                    <UserRow key={u.id.clone()} user={u.clone()} on_role_change={on_role} on_reset_password={on_reset} />
+                                    <UserRow key={u.id.clone()} user={u.clone()} on_role_change={on_role}
+                                             on_reset_password={on_reset} on_reset_totp={on_totp} {on_unlock} />
                                 }
                             })}
                         </tbody>
@@ -271,6 +342,8 @@ struct UserRowProps {
     user: User,
     on_role_change: Callback<String>,
     on_reset_password: Callback<()>,
+    on_reset_totp: Callback<()>,
+    on_unlock: Callback<()>,
 }
 
 #[function_component(UserRow)]
@@ -301,6 +374,20 @@ fn user_row(props: &UserRowProps) -> Html {
         })
     };
 
+    let on_totp_click = {
+        let cb = props.on_reset_totp.clone();
+        Callback::from(move |_: MouseEvent| {
+            cb.emit(());
+        })
+    };
+
+    let on_unlock_click = {
+        let cb = props.on_unlock.clone();
+        Callback::from(move |_: MouseEvent| {
+            cb.emit(());
+        })
+    };
+
     let on_cancel = {
         let confirming = confirming.clone();
         Callback::from(move |_: MouseEvent| {
@@ -326,6 +413,36 @@ fn user_row(props: &UserRowProps) -> Html {
                     <option value="viewer" selected={props.user.role.to_string() == "viewer"}> { "Viewer" } </option>
                 </select>
             </td>
+            <td class="px-6 py-3">
+                if props.user.totp_enabled {
+                    <span class="inline-flex items-center gap-2">
+                        <span class="text-xs text-green-600 dark:text-green-400">{"On"}</span>
+                        <button onclick={on_totp_click}
+                            title="Turn off two-factor authentication, e.g. for a lost phone"
+                            class="text-xs text-stone-400 hover:text-amber-600 dark:hover:text-amber-300 transition-colors">
+                            {"Reset"}
+                        </button>
+                    </span>
+                } else {
+                    <span class="text-xs text-stone-400">{"Off"}</span>
+                }
+            </td>
+            <td class="px-6 py-3">
+                if let Some(until) = &props.user.locked_until {
+                    <span class="inline-flex items-center gap-2">
+                        <span class="text-xs text-red-500" title={until.clone()}>
+                            { format!("Locked until {}", until.get(11..16).unwrap_or(until)) }
+                        </span>
+                        <button onclick={on_unlock_click}
+                            title="Lift the lockout after failed sign-ins"
+                            class="text-xs text-stone-400 hover:text-amber-600 dark:hover:text-amber-300 transition-colors">
+                            {"Unlock"}
+                        </button>
+                    </span>
+                } else {
+                    <span class="text-xs text-stone-400">{"—"}</span>
+                }
+            </td>
             <td class="px-6 py-3">
                 if *confirming {
                     <span class="inline-flex items-center gap-2">
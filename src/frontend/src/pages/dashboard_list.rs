@@ -4,13 +4,18 @@ use yew::prelude::*;
 use yew_router::prelude::*;
 
 use crate::api::dashboards;
+use crate::components::dashboards::share_dialog::ShareDialog;
 use crate::hooks::use_api::use_api;
+use crate::hooks::use_auth::use_auth;
 use crate::models::dashboard::{CreateDashboard, Dashboard, UpdateDashboard};
+use crate::models::Role;
 use crate::router::Route;
 
 #[function_component(DashboardListPage)]
 pub fn dashboard_list_page() -> Html {
     let (state, reload) = use_api(|| dashboards::list_dashboards());
+    let auth = use_auth();
+    let sharing = use_state(|| Option::<Dashboard>::None);
 
     let show_form  = use_state(|| false);
     let new_title  = use_state(String::new);
@@ -157,13 +162,36 @@ pub fn dashboard_list_page() -> Html {
                             })
                         };
 
+                            // Only the owner and admins decide who else sees a dashboard.
+                            let can_share = auth.user.as_ref().is_some_and(|u| {
+                                u.role == Role::Admin
+                                    || (u.role == Role::Editor && d.owner_id.as_deref() == Some(u.id.as_str()))
+                            });
+                            let on_share = {
+                                let sharing = sharing.clone();
+                                let d = d.clone();
+                                Callback::from(move |_: ()| sharing.set(Some(d.clone())))
+                            };
+
                             html! {
-                                <DashboardCard dashboard={d.clone()} {on_delete} {on_rename}/>
+                                <DashboardCard dashboard={d.clone()} {can_share} {on_share} {on_delete} {on_rename}/>
                             }
                         })}
                     </div>
                 }
             }
+
+            /* ====== Share dialog ====== */
+            if let Some(d) = (*sharing).clone() {
+                <ShareDialog
+                    dashboard={d}
+                    on_close={Callback::from({
+                        let sharing = sharing.clone();
+                        move |_: ()| sharing.set(None)
+                    })}
+                    on_saved={reload.clone()}
+                />
+            }
         </div>
     }
 }
@@ -175,6 +203,8 @@ pub fn dashboard_list_page() -> Html {
 #[derive(Properties, PartialEq)]
 struct DashboardCardProps {
     dashboard: Dashboard,
+    can_share: bool,
+    on_share:  Callback<()>,
     on_rename: Callback<(String, String)>, // (id, new_title)
     on_delete: Callback<()>,
 }
@@ -194,6 +224,15 @@ fn dashboard_card(props: &DashboardCardProps) -> Html {
         })
     };
 
+    let on_share_click = {
+        let on_share = props.on_share.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            e.stop_propagation();
+            on_share.emit(());
+        })
+    };
+
     let on_edit_click = {
         let editing = editing.clone();
         let edit_title = edit_title.clone();
@@ -299,6 +338,21 @@ fn dashboard_card(props: &DashboardCardProps) -> Html {
                     }
                 </div>
                 if !*editing {
+                    if props.can_share {
+                        // Share button
+                        <button
+                            onclick={on_share_click}
+                            title="Share dashboard"
+                            class="opacity-0 group-hover:opacity-100 p-1.5 rounded-md
+                                   text-stone-400 hover:text-amber-600 hover:bg-amber-50
+                                   dark:text-stone-500 dark:hover:text-amber-300 dark:hover:bg-amber-900/30
+                                   transition-all shrink-0"
+                        >
+                            <svg xmlns="http://www.w3.org/2000/svg" class="w-4 h-4" viewBox="0 0 20 20" fill="currentColor">
+                                <path d="M15 8a3 3 0 10-2.977-2.63l-4.94 2.47a3 3 0 100 4.319l4.94 2.47a3 3 0 10.895-1.789l-4.94-2.47a3.027 3.027 0 000-.74l4.94-2.47C13.456 7.68 14.19 8 15 8z" />
+                            </svg>
+                        </button>
+                    }
                     // Edit button
                     <button
                         onclick={on_edit_click}
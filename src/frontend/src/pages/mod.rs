@@ -1,4 +1,5 @@
 pub mod dashboard_list;
 pub mod dashboard_view;
+pub mod public_dashboard;
 pub mod settings;
 pub mod admin_users;
\ No newline at end of file
@@ -0,0 +1,55 @@
+use yew::prelude::*;
+
+use crate::api::dashboards;
+use crate::components::grid::DashboardGrid;
+use crate::hooks::use_api::use_api;
+
+#[derive(Properties, PartialEq)]
+pub struct PublicDashboardProps {
+    pub token: String,
+}
+
+/// A dashboard opened through a public link: no shell, no session, no editing.
+#[function_component(PublicDashboardPage)]
+pub fn public_dashboard_page(props: &PublicDashboardProps) -> Html {
+    let token = props.token.clone();
+    let (state, _reload) = use_api(move || {
+        let token = token.clone();
+        async move { dashboards::get_public_dashboard(&token).await }
+    });
+
+    let Some(data) = state.data.as_ref() else {
+        return html! {
+            <div class="min-h-screen flex items-center justify-center bg-stone-50 dark:bg-stone-900">
+                <p class="text-stone-400 dark:text-stone-500 text-sm">
+                    { if state.loading { "Loading…" } else { "This link is invalid, expired or revoked." } }
+                </p>
+            </div>
+        };
+    };
+
+    html! {
+        <div class="min-h-screen bg-stone-50 dark:bg-stone-900 p-6 flex flex-col gap-4">
+
+            /* ====== Page header ====== */
+            <div class="flex items-center justify-between">
+                <h1 class="text-lg font-bold text-stone-900 dark:text-stone-100">{ &data.dashboard.title }</h1>
+                <span class="text-xs text-stone-400 dark:text-stone-500">
+                    { format!("Read-only · link expires {}", data.expires_at.get(..10).unwrap_or(&data.expires_at)) }
+                </span>
+            </div>
+
+            /* ====== Grid ====== */
+            if data.panels.is_empty() {
+                <p class="py-20 text-center text-stone-500 text-sm">{"This dashboard has no panels."}</p>
+            } else {
+                <DashboardGrid
+                    panels={data.panels.clone()}
+                    edit_mode={false}
+                    on_positions_change={Callback::noop()}
+                    on_delete_panel={Callback::noop()}
+                />
+            }
+        </div>
+    }
+}
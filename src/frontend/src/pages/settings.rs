@@ -1,5 +1,8 @@
 use yew::prelude::*;
 use crate::api::auth;
+use crate::components::auth::api_tokens::ApiTokens;
+use crate::components::auth::sessions::Sessions;
+use crate::components::auth::two_factor::TwoFactor;
 use crate::components::common::{use_toast, ToastKind};
 use crate::context::theme_context::{ThemeAction, ThemeContext};
 
@@ -55,7 +58,7 @@ pub fn settings_page() -> Html {
                         current_pw.set(String::new());
                         new_pw.set(String::new());
                         confirm_pw.set(String::new());
-                        show_toast.emit(("Password updated".to_string(), ToastKind::Success));
+                        show_toast.emit(("Password updated; other sessions signed out and API tokens revoked".to_string(), ToastKind::Success));
                     }
                     Err(e) => {
                         pw_error.set(Some(e.to_string()));
@@ -177,6 +180,30 @@ pub fn settings_page() -> Html {
                     </button>
                 </form>
             </section>
+
+            // ── Sessions ────────────────────────────────────────────────────────
+            <section class="bg-white dark:bg-stone-800 rounded-lg border border-stone-200 dark:border-stone-700 p-6">
+                <h2 class="text-xs font-semibold uppercase tracking-wider text-stone-500 dark:text-stone-400 mb-4">
+                    { "Sessions" }
+                </h2>
+                <Sessions />
+            </section>
+
+            // ── Two-factor authentication ───────────────────────────────────────
+            <section class="bg-white dark:bg-stone-800 rounded-lg border border-stone-200 dark:border-stone-700 p-6">
+                <h2 class="text-xs font-semibold uppercase tracking-wider text-stone-500 dark:text-stone-400 mb-4">
+                    { "Two-Factor Authentication" }
+                </h2>
+                <TwoFactor />
+            </section>
+
+            // ── API tokens ──────────────────────────────────────────────────────
+            <section class="bg-white dark:bg-stone-800 rounded-lg border border-stone-200 dark:border-stone-700 p-6">
+                <h2 class="text-xs font-semibold uppercase tracking-wider text-stone-500 dark:text-stone-400 mb-4">
+                    { "API Tokens" }
+                </h2>
+                <ApiTokens />
+            </section>
         </div>
     }
 }
@@ -16,6 +16,8 @@ pub enum Route {
     Settings,
     #[at("/admin/users")]
     AdminUsers,
+    #[at("/public/:token")]
+    PublicDashboard { token: String },
     #[at("/")]
     Home,
     #[not_found]